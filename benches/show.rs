@@ -1,6 +1,8 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use gitu::{cli::Commands, term::TermBackend};
 use ratatui::{backend::TestBackend, Terminal};
+use std::{env, fs, process::Command};
+use temp_dir::TempDir;
 
 fn show(c: &mut Criterion) {
     c.bench_function("show", |b| {
@@ -21,9 +23,50 @@ fn show(c: &mut Criterion) {
     });
 }
 
+/// The status of a repo with a change to a single 5 MB line, like in a minified bundle.
+fn long_line(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Bench",
+                "-c",
+                "user.email=bench@example.com",
+            ])
+            .args(args)
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    let line = "word ".repeat(1_000_000);
+    git(&["init", "--quiet"]);
+    fs::write(dir.child("bundle.min.js"), format!("{line}old\n")).unwrap();
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "--message", "Add bundle"]);
+    fs::write(dir.child("bundle.min.js"), format!("new {line}\n")).unwrap();
+    env::set_current_dir(dir.path()).unwrap();
+
+    c.bench_function("long_line", |b| {
+        let mut terminal = Terminal::new(TermBackend::Test(TestBackend::new(80, 1000))).unwrap();
+        b.iter(|| {
+            gitu::run(
+                &gitu::cli::Args {
+                    print: true,
+                    ..Default::default()
+                },
+                &mut terminal,
+            )
+            .unwrap();
+        })
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = show
+    targets = show, long_line
 }
 criterion_main!(benches);
@@ -0,0 +1,162 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Turns command output into styled lines, following its SGR escape codes (`ESC [ ... m`), like git's
+/// colors. Other escape sequences are dropped, and tabs are expanded to 4 spaces.
+pub(crate) fn parse(text: &str) -> Vec<Line<'static>> {
+    text.lines()
+        .map(|line| parse_line(line.trim_end_matches('\r')))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Line<'static> {
+    let mut spans = vec![];
+    let mut style = Style::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut end = None;
+
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        end = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+
+                if end == Some('m') {
+                    let next_style = apply_sgr(style, &params);
+                    if next_style != style && !current.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut current), style));
+                    }
+                    style = next_style;
+                }
+            }
+            '\x1b' => (),
+            '\t' => current.push_str("    "),
+            c if c.is_control() => (),
+            c => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        spans.push(Span::styled(current, style));
+    }
+
+    Line::from(spans)
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    // `ESC [ m` resets like `ESC [ 0 m`
+    if params.is_empty() {
+        return Style::new();
+    }
+
+    let mut codes = params
+        .split([';', ':'])
+        .map(|code| code.parse::<u8>().unwrap_or(0));
+
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed(code - 30)),
+            38 => match extended_color(&mut codes) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => style.fg(Color::Reset),
+            40..=47 => style.bg(Color::Indexed(code - 40)),
+            48 => match extended_color(&mut codes) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => style.bg(Color::Reset),
+            90..=97 => style.fg(Color::Indexed(code - 90 + 8)),
+            100..=107 => style.bg(Color::Indexed(code - 100 + 8)),
+            _ => style,
+        };
+    }
+
+    style
+}
+
+/// The rest of a `38;5;n` or `38;2;r;g;b` color code.
+fn extended_color(codes: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()?)),
+        2 => Some(Color::Rgb(codes.next()?, codes.next()?, codes.next()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use ratatui::{
+        style::{Color, Modifier, Style},
+        text::{Line, Span},
+    };
+
+    #[test]
+    fn colors() {
+        let lines =
+            parse("\x1b[33mcommit abc\x1b[m\n \x1b[1;32m+\x1b[0m\x1b[38;5;196mx\x1b[39m\tdone\r\n");
+
+        assert_eq!(
+            lines,
+            vec![
+                Line::from(Span::styled(
+                    "commit abc",
+                    Style::new().fg(Color::Indexed(3))
+                )),
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(
+                        "+",
+                        Style::new()
+                            .fg(Color::Indexed(2))
+                            .add_modifier(Modifier::BOLD)
+                    ),
+                    Span::styled("x", Style::new().fg(Color::Indexed(196))),
+                    Span::styled("    done", Style::new().fg(Color::Reset)),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn other_escapes_dropped() {
+        assert_eq!(
+            parse("\x1b[2Kprogress\x1b[1G\x07 done"),
+            vec![Line::raw("progress done")]
+        );
+        assert_eq!(
+            parse("\x1b[38;2;1;2;3mrgb"),
+            vec![Line::from(Span::styled(
+                "rgb",
+                Style::new().fg(Color::Rgb(1, 2, 3))
+            ))]
+        );
+    }
+}
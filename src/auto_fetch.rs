@@ -0,0 +1,101 @@
+use crate::{git, Res};
+use git2::Repository;
+use std::{
+    env,
+    process::{Child, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Runs `git fetch --quiet` in the background every `fetch.auto_interval` minutes,
+/// so that ahead/behind counts stay current in long-lived sessions.
+pub(crate) struct AutoFetch {
+    interval: Option<Duration>,
+    pub(crate) last_fetch: Instant,
+    child: Option<Child>,
+}
+
+impl AutoFetch {
+    pub(crate) fn new(minutes: u64) -> Self {
+        Self {
+            interval: (minutes > 0).then(|| Duration::from_secs(minutes * 60)),
+            last_fetch: Instant::now(),
+            child: None,
+        }
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.child.is_some()
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.interval
+            .is_some_and(|interval| !self.is_running() && now - self.last_fetch >= interval)
+    }
+
+    /// Starts a fetch if it's time for one, and the repo has a remote to fetch from.
+    pub(crate) fn start_if_due(&mut self, repo: &Repository) -> Res<()> {
+        let now = Instant::now();
+        if !self.is_due(now) {
+            return Ok(());
+        }
+
+        self.last_fetch = now;
+        if repo.remotes()?.is_empty() {
+            return Ok(());
+        }
+
+        let mut cmd = git::command();
+        cmd.args(["fetch", "--quiet"]);
+        cmd.current_dir(repo.workdir().expect("No workdir"));
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::null());
+
+        // Remotes that need credentials fail instead of prompting, and are tried again next time
+        cmd.env("GIT_TERMINAL_PROMPT", "0");
+        if env::var_os("GIT_SSH_COMMAND").is_none()
+            && repo.config()?.get_string("core.sshCommand").is_err()
+        {
+            cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+        }
+
+        log::debug!("Auto-fetching");
+        self.child = Some(cmd.spawn()?);
+        Ok(())
+    }
+
+    /// Checks on a running fetch without blocking. `Some(true)` once it succeeded.
+    pub(crate) fn poll(&mut self) -> Res<Option<bool>> {
+        let Some(child) = &mut self.child else {
+            return Ok(None);
+        };
+
+        let Some(status) = child.try_wait()? else {
+            return Ok(None);
+        };
+
+        log::debug!("Auto-fetch finished with {:?}", status);
+        self.child = None;
+        Ok(Some(status.success()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AutoFetch;
+    use std::time::Duration;
+
+    #[test]
+    fn due_after_interval() {
+        let auto_fetch = AutoFetch::new(5);
+        let start = auto_fetch.last_fetch;
+        assert!(!auto_fetch.is_due(start + Duration::from_secs(4 * 60)));
+        assert!(auto_fetch.is_due(start + Duration::from_secs(5 * 60)));
+    }
+
+    #[test]
+    fn disabled_with_zero() {
+        let auto_fetch = AutoFetch::new(0);
+        assert!(!auto_fetch.is_due(auto_fetch.last_fetch + Duration::from_secs(24 * 60 * 60)));
+    }
+}
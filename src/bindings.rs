@@ -40,6 +40,11 @@ impl Bindings {
             .filter(|binding| binding.keys.starts_with(events))
     }
 
+    /// Every binding, menu by menu, and op by op within them.
+    pub(crate) fn all(&self) -> impl Iterator<Item = &Binding> {
+        self.vec.iter()
+    }
+
     pub(crate) fn list<'a>(&'a self, pending: &Menu) -> impl Iterator<Item = &'a Binding> {
         let expected = if pending == &Menu::Help {
             Menu::Root
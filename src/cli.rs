@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Default, Debug, Parser)]
 #[command(name = "gitu")]
@@ -23,12 +24,52 @@ pub struct Args {
     #[clap(long, action)]
     pub log: bool,
 
+    /// Pass a `key=value` config override to every git command, like `git -c` (eg: `gitu -c user.email=me@example.com`).
+    #[clap(short = 'c', long = "config", value_name = "KEY=VALUE")]
+    pub git_config: Vec<String>,
+
+    /// Ignore the `.gitu.toml` in the root of the repository, using only the user config.
+    #[clap(long, action)]
+    pub no_local_config: bool,
+
+    /// Print the output of the subcommand (or the status) as JSON and exit, without the TUI.
+    #[clap(long, action)]
+    pub json: bool,
+
+    /// Log how long diffing and rendering take to 'gitu.log', with the sizes of the diffs.
+    #[cfg(any(debug_assertions, feature = "debug-timing"))]
+    #[clap(long, action)]
+    pub debug_timing: bool,
+
     #[clap(long, action)]
     /// Print version
     pub version: bool,
 }
 
+impl Args {
+    /// Whether `--debug-timing` was passed, only available in debug builds or with the `debug-timing` feature.
+    pub fn debug_timing(&self) -> bool {
+        #[cfg(any(debug_assertions, feature = "debug-timing"))]
+        return self.debug_timing;
+        #[cfg(not(any(debug_assertions, feature = "debug-timing")))]
+        false
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
-    Show { reference: String },
+    Show {
+        reference: String,
+    },
+    Status,
+    Diff {
+        /// Diff the index against HEAD, rather than the working tree against the index.
+        #[clap(long, action)]
+        staged: bool,
+        /// Open on the changes to only this file.
+        file: Option<PathBuf>,
+    },
+    Log {
+        reference: Option<String>,
+    },
 }
@@ -1,7 +1,6 @@
+use crate::ansi;
 use crate::config::Config;
 use itertools::Itertools;
-use ratatui::style::Style;
-use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Text;
 use std::borrow::Cow;
@@ -10,17 +9,25 @@ use std::process::Command;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+/// How many cleared entries are kept, dropping the oldest ones first.
+const MAX_HISTORY: usize = 100;
+
 pub(crate) struct CmdLog {
     pub(crate) entries: Vec<Arc<RwLock<CmdLogEntry>>>,
+    /// Entries that were cleared from view, kept to look back at what earlier commands output.
+    history: Vec<Arc<RwLock<CmdLogEntry>>>,
 }
 
 impl CmdLog {
     pub fn new() -> Self {
-        Self { entries: vec![] }
+        Self {
+            entries: vec![],
+            history: vec![],
+        }
     }
 
     pub fn push_cmd(&mut self, cmd: &Command) -> Arc<RwLock<CmdLogEntry>> {
-        let value = Arc::new(RwLock::new(CmdLogEntry::Cmd {
+        let value = Arc::new(RwLock::new(CmdLogEntry {
             args: command_args(cmd),
             out: None,
         }));
@@ -34,7 +41,7 @@ impl CmdLog {
         cmd: &Command,
         out: Cow<'static, str>,
     ) -> Arc<RwLock<CmdLogEntry>> {
-        let value = Arc::new(RwLock::new(CmdLogEntry::Cmd {
+        let value = Arc::new(RwLock::new(CmdLogEntry {
             args: command_args(cmd),
             out: Some(out),
         }));
@@ -43,12 +50,11 @@ impl CmdLog {
         value
     }
 
-    pub fn push(&mut self, entry: CmdLogEntry) {
-        self.entries.push(Arc::new(RwLock::new(entry)));
-    }
-
     pub fn clear(&mut self) {
-        self.entries.clear();
+        self.history.append(&mut self.entries);
+
+        let excess = self.history.len().saturating_sub(MAX_HISTORY);
+        self.history.drain(..excess);
     }
 
     pub(crate) fn is_empty(&self) -> bool {
@@ -63,6 +69,15 @@ impl CmdLog {
                 .collect::<Vec<_>>(),
         )
     }
+
+    /// Every command run so far with its output, oldest first.
+    pub(crate) fn format_history(&self, config: &Config) -> Vec<Line<'static>> {
+        self.history
+            .iter()
+            .chain(&self.entries)
+            .flat_map(|cmd| format_log_entry(config, cmd))
+            .collect()
+    }
 }
 
 pub(crate) fn command_args(cmd: &Command) -> Cow<'static, str> {
@@ -76,40 +91,24 @@ pub(crate) fn format_log_entry<'a>(
     config: &Config,
     log: &Arc<RwLock<CmdLogEntry>>,
 ) -> Vec<Line<'a>> {
-    match &*log.read().unwrap() {
-        CmdLogEntry::Cmd { args, out } => [Line::styled(
-            format!("{}{}", if out.is_some() { "$ " } else { "Running: " }, args),
-            &config.style.command,
-        )]
-        .into_iter()
-        .chain(out.iter().flat_map(|out| {
-            if out.is_empty() {
-                vec![]
-            } else {
-                Text::raw(out.to_string()).lines
-            }
-        }))
-        .collect::<Vec<_>>(),
-        CmdLogEntry::Error(err) => {
-            vec![Line::styled(
-                format!("! {}", err),
-                Style::new().red().bold(),
-            )]
-        }
-        CmdLogEntry::Info(msg) => {
-            vec![Line::styled(
-                format!("> {}", msg),
-                Style::new().green().bold(),
-            )]
+    let CmdLogEntry { args, out } = &*log.read().unwrap();
+
+    [Line::styled(
+        format!("{}{}", if out.is_some() { "$ " } else { "Running: " }, args),
+        &config.style.command,
+    )]
+    .into_iter()
+    .chain(out.iter().flat_map(|out| {
+        if out.is_empty() {
+            vec![]
+        } else {
+            ansi::parse(out)
         }
-    }
+    }))
+    .collect::<Vec<_>>()
 }
 
-pub(crate) enum CmdLogEntry {
-    Cmd {
-        args: Cow<'static, str>,
-        out: Option<Cow<'static, str>>,
-    },
-    Error(String),
-    Info(String),
+pub(crate) struct CmdLogEntry {
+    pub(crate) args: Cow<'static, str>,
+    pub(crate) out: Option<Cow<'static, str>>,
 }
@@ -1,21 +1,56 @@
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::{menu::Menu, ops::Op, Res};
+use crate::{git::diff_options::DiffAlgorithm, Res};
+#[cfg(feature = "tui")]
+use crate::{menu::Menu, ops::Op};
 use etcetera::{choose_base_strategy, BaseStrategy};
 use figment::{
     providers::{Format, Toml},
     Figment,
 };
 use ratatui::style::{Color, Modifier, Style};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 const DEFAULT_CONFIG: &str = include_str!("default_config.toml");
 
 #[derive(Default, Debug, Deserialize)]
 pub(crate) struct Config {
     pub general: GeneralConfig,
+    pub git: GitConfig,
+    #[serde(default)]
+    pub status: StatusConfig,
+    #[serde(default)]
+    pub diff: DiffConfig,
+    #[serde(default)]
+    pub commit: CommitConfig,
+    #[serde(default)]
+    pub forge: ForgeConfig,
+    #[serde(default)]
+    pub confirm: ConfirmConfig,
+    #[serde(default)]
+    pub fetch: FetchConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[cfg(feature = "tui")]
+    #[serde(default)]
+    pub hooks: BTreeMap<Op, String>,
     pub style: StyleConfig,
+    #[cfg(feature = "tui")]
     pub bindings: BTreeMap<Menu, BTreeMap<Op, Vec<String>>>,
+    /// Where `SavedState` is kept. Tests leave it unset, so that they don't touch it.
+    #[serde(skip)]
+    pub state_path: Option<PathBuf>,
+}
+
+/// Choices made while using Gitu that are kept for the next run, see `write_state`.
+#[derive(Default, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct SavedState {
+    /// Picked in the diff menu, used over `diff.algorithm`.
+    pub diff_algorithm: Option<DiffAlgorithm>,
 }
 
 #[derive(Default, Debug, Deserialize)]
@@ -23,6 +58,125 @@ pub struct GeneralConfig {
     pub always_show_help: BoolConfigEntry,
     pub confirm_quit: BoolConfigEntry,
     pub collapsed_sections: Vec<String>,
+    #[serde(default)]
+    pub compact_whole_file_diffs: BoolConfigEntry,
+    #[serde(default)]
+    pub stage_3way_fallback: BoolConfigEntry,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct GitConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub extra_config: Vec<String>,
+    #[serde(default)]
+    pub notes_refs: Vec<String>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct DiffConfig {
+    #[serde(default)]
+    pub wrap: BoolConfigEntry,
+    #[serde(default)]
+    pub algorithm: DiffAlgorithm,
+    pub max_line_bytes: usize,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct CommitConfig {
+    #[serde(default)]
+    pub co_authors: Vec<String>,
+    #[serde(default)]
+    pub signoff: BoolConfigEntry,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct StatusConfig {
+    #[serde(default)]
+    pub section_order: Vec<String>,
+    #[serde(default)]
+    pub expand_unstaged: BoolConfigEntry,
+    #[serde(default)]
+    pub expand_staged: BoolConfigEntry,
+    #[serde(default)]
+    pub expand_untracked: BoolConfigEntry,
+    #[serde(default)]
+    pub advance_after_stage: BoolConfigEntry,
+    #[serde(default)]
+    pub diffstat: BoolConfigEntry,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct FetchConfig {
+    #[serde(default)]
+    pub auto_interval: u64,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct UiConfig {
+    #[serde(default)]
+    pub scrolloff: usize,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct ForgeConfig {
+    #[serde(default)]
+    pub permalink: BTreeMap<String, String>,
+    #[serde(default)]
+    pub browse: BTreeMap<String, ForgePagesConfig>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct ForgePagesConfig {
+    pub repo: String,
+    pub branch: String,
+    pub file: String,
+    pub commit: String,
+}
+
+#[derive(Default, Debug, Deserialize)]
+pub struct ConfirmConfig {
+    #[serde(default)]
+    pub discard: Confirm,
+    #[serde(default)]
+    pub clean: Confirm,
+    #[serde(default)]
+    pub restore: Confirm,
+    #[serde(default)]
+    pub force_push: Confirm,
+    #[serde(default)]
+    pub hard_reset: Confirm,
+}
+
+/// When to ask before an action: `"always"`, `"never"`, or a number of files to ask above.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Confirm {
+    When(ConfirmWhen),
+    AboveFiles(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmWhen {
+    Always,
+    Never,
+}
+
+impl Default for Confirm {
+    fn default() -> Self {
+        Confirm::When(ConfirmWhen::Always)
+    }
+}
+
+impl Confirm {
+    pub(crate) fn asks(self, files: usize) -> bool {
+        match self {
+            Confirm::When(ConfirmWhen::Always) => true,
+            Confirm::When(ConfirmWhen::Never) => false,
+            Confirm::AboveFiles(threshold) => files > threshold,
+        }
+    }
 }
 
 #[derive(Default, Debug, Deserialize)]
@@ -47,15 +201,27 @@ pub struct StyleConfig {
     pub selection_line: StyleConfigEntry,
     pub selection_bar: SymbolStyleConfigEntry,
     pub selection_area: StyleConfigEntry,
+    #[serde(default)]
+    pub line_selection: StyleConfigEntry,
 
     pub hash: StyleConfigEntry,
     pub branch: StyleConfigEntry,
     pub remote: StyleConfigEntry,
     pub tag: StyleConfigEntry,
 
+    #[serde(default)]
+    pub commit_subject_overflow: StyleConfigEntry,
+    #[serde(default)]
+    pub commit_trailer: StyleConfigEntry,
+
     pub command: StyleConfigEntry,
     pub active_arg: StyleConfigEntry,
     pub hotkey: StyleConfigEntry,
+
+    #[serde(default)]
+    pub status_info: StyleConfigEntry,
+    #[serde(default)]
+    pub status_error: StyleConfigEntry,
 }
 
 #[derive(Default, Debug, Deserialize)]
@@ -152,9 +318,8 @@ impl From<&StyleConfigEntry> for Style {
         Style {
             fg: val.fg,
             bg: val.bg,
-            underline_color: None,
             add_modifier: val.mods.unwrap_or(Modifier::empty()),
-            sub_modifier: Modifier::empty(),
+            ..Style::default()
         }
     }
 }
@@ -164,14 +329,21 @@ impl From<&SymbolStyleConfigEntry> for Style {
         Style {
             fg: val.fg,
             bg: val.bg,
-            underline_color: None,
             add_modifier: val.mods.unwrap_or(Modifier::empty()),
-            sub_modifier: Modifier::empty(),
+            ..Style::default()
         }
     }
 }
 
+const REPO_CONFIG_FILE: &str = ".gitu.toml";
+
 pub(crate) fn init_config() -> Res<Config> {
+    let mut config: Config = user_figment()?.extract()?;
+    config.state_path = Some(state_path());
+    Ok(config)
+}
+
+fn user_figment() -> Res<Figment> {
     let config_path = config_path();
 
     if config_path.exists() {
@@ -180,12 +352,89 @@ pub(crate) fn init_config() -> Res<Config> {
         log::info!("No config file at {:?}", config_path);
     }
 
-    let config = Figment::new()
-        .merge(Toml::string(DEFAULT_CONFIG))
-        .merge(Toml::file(config_path))
-        .extract()?;
+    let defaults = Figment::new().merge(Toml::string(DEFAULT_CONFIG));
+    if config_path.exists() {
+        merge_file(defaults, &config_path, &mut vec![])
+    } else {
+        Ok(defaults)
+    }
+}
 
-    Ok(config)
+/// Merges the `.gitu.toml` in the root of the working tree on top of the user config.
+/// Its `[git]` settings are ignored, as those pick what gets run and shouldn't come with a clone.
+pub(crate) fn init_repo_config(config: Config, workdir: &Path) -> Res<Config> {
+    let path = workdir.join(REPO_CONFIG_FILE);
+    if !path.exists() {
+        return Ok(config);
+    }
+
+    log::info!("Loading repository config file at {:?}", path);
+    merge_repo_file(user_figment()?, config, &path).map_err(|err| {
+        format!(
+            "{}: {} (run with --no-local-config to ignore it)",
+            path.display(),
+            err
+        )
+        .into()
+    })
+}
+
+fn merge_repo_file(user: Figment, config: Config, path: &Path) -> Res<Config> {
+    if merge_file(Figment::new(), path, &mut vec![])?.contains("git") {
+        log::warn!("Ignoring the [git] settings of {:?}", path);
+    }
+
+    let mut repo_config: Config = merge_file(user, path, &mut vec![])?.extract()?;
+    repo_config.git = config.git;
+    repo_config.state_path = config.state_path;
+    Ok(repo_config)
+}
+
+#[derive(Default, Deserialize)]
+struct Includes {
+    #[serde(default)]
+    include: Vec<PathBuf>,
+}
+
+/// Merges a config file on top of `figment`, after the files it `include`s, in order.
+/// Includes are relative to the including file, which overrides them.
+/// `chain` holds the files currently being included, to detect cycles.
+fn merge_file(figment: Figment, path: &Path, chain: &mut Vec<PathBuf>) -> Res<Figment> {
+    let path = path
+        .canonicalize()
+        .map_err(|err| format!("Couldn't read config file {}: {}", path.display(), err))?;
+
+    if chain.contains(&path) {
+        let cycle = chain
+            .iter()
+            .skip_while(|&file| file != &path)
+            .chain([&path])
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>();
+
+        return Err(format!("Config files include each other: {}", cycle.join(" -> ")).into());
+    }
+
+    let Includes { include } = Figment::from(Toml::string(&fs::read_to_string(&path)?))
+        .extract()
+        .map_err(|err| format!("Invalid include in {}: {}", path.display(), err))?;
+
+    chain.push(path.clone());
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut figment = figment;
+    for included in include {
+        figment = merge_file(figment, &dir.join(expand_home(&included)), chain)?;
+    }
+    chain.pop();
+
+    Ok(figment.merge(Toml::file(path)))
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), choose_base_strategy()) {
+        (Ok(rest), Ok(strategy)) => strategy.home_dir().join(rest),
+        _ => path.to_path_buf(),
+    }
 }
 
 pub fn config_path() -> PathBuf {
@@ -195,6 +444,34 @@ pub fn config_path() -> PathBuf {
         .join("gitu/config.toml")
 }
 
+fn state_path() -> PathBuf {
+    choose_base_strategy()
+        .expect("Unable to find the data directory!")
+        .data_dir()
+        .join("gitu/state.toml")
+}
+
+/// What `write_state` saved, or the default if there's nothing (readable) there.
+pub(crate) fn read_state(path: &Path) -> SavedState {
+    let Ok(content) = fs::read_to_string(path) else {
+        return SavedState::default();
+    };
+
+    toml::from_str(&content).unwrap_or_else(|err| {
+        log::warn!("Ignoring invalid state file {:?}: {}", path, err);
+        SavedState::default()
+    })
+}
+
+pub(crate) fn write_state(path: &Path, state: &SavedState) -> Res<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, toml::to_string(state)?)
+        .map_err(|err| format!("Couldn't save {}: {}", path.display(), err).into())
+}
+
 #[cfg(test)]
 pub(crate) fn init_test_config() -> Res<Config> {
     let mut config: Config = Figment::new()
@@ -214,6 +491,8 @@ mod tests {
     use ratatui::style::Color;
 
     use super::{Config, DEFAULT_CONFIG};
+    use std::fs;
+    use temp_dir::TempDir;
 
     #[test]
     fn config_merges() {
@@ -231,4 +510,123 @@ mod tests {
         assert_eq!(config.style.hunk_header.bg, Some(Color::LightGreen));
         assert_eq!(config.style.hunk_header.fg, Some(Color::Blue));
     }
+
+    fn merge_files(dir: &TempDir, file: &str) -> crate::Res<Config> {
+        let defaults = Figment::new().merge(Toml::string(DEFAULT_CONFIG));
+        Ok(super::merge_file(defaults, &dir.child(file), &mut vec![])?.extract()?)
+    }
+
+    #[test]
+    fn config_includes() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.child("shared")).unwrap();
+        fs::write(
+            dir.child("config.toml"),
+            r#"
+            include = ["shared/base.toml", "shared/theme.toml"]
+
+            [style]
+            hunk_header.fg = "light green"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.child("shared/base.toml"),
+            r#"
+            [general]
+            confirm_quit.enabled = true
+
+            [style]
+            hunk_header.fg = "red"
+            hunk_header.bg = "red"
+            file_header.fg = "red"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.child("shared/theme.toml"),
+            r#"
+            [style]
+            file_header.fg = "cyan"
+            "#,
+        )
+        .unwrap();
+
+        let config = merge_files(&dir, "config.toml").unwrap();
+        assert!(config.general.confirm_quit.enabled);
+        assert_eq!(config.style.hunk_header.fg, Some(Color::LightGreen));
+        assert_eq!(config.style.hunk_header.bg, Some(Color::Red));
+        assert_eq!(config.style.file_header.fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn confirm_thresholds() {
+        let config: Config = Figment::new()
+            .merge(Toml::string(DEFAULT_CONFIG))
+            .merge(Toml::string(
+                r#"
+                [confirm]
+                discard = "never"
+                clean = 3
+                "#,
+            ))
+            .extract()
+            .unwrap();
+
+        assert!(!config.confirm.discard.asks(100));
+        assert!(!config.confirm.clean.asks(3));
+        assert!(config.confirm.clean.asks(4));
+        assert!(config.confirm.force_push.asks(1));
+    }
+
+    #[test]
+    fn config_include_cycle() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.child("config.toml"), r#"include = ["a.toml"]"#).unwrap();
+        fs::write(dir.child("a.toml"), r#"include = ["b.toml"]"#).unwrap();
+        fs::write(dir.child("b.toml"), r#"include = ["./a.toml"]"#).unwrap();
+
+        let dir_path = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            merge_files(&dir, "config.toml").unwrap_err().to_string(),
+            format!(
+                "Config files include each other: {0}/a.toml -> {0}/b.toml -> {0}/a.toml",
+                dir_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn config_include_missing() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.child("config.toml"), r#"include = ["nope.toml"]"#).unwrap();
+
+        assert!(merge_files(&dir, "config.toml")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Couldn't read config file "));
+    }
+
+    #[test]
+    fn repo_config_overrides_all_but_git() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.child(".gitu.toml"),
+            r#"
+            [git]
+            path = "/tmp/not-git"
+
+            [diff]
+            wrap.enabled = true
+            "#,
+        )
+        .unwrap();
+
+        let user = Figment::new().merge(Toml::string(DEFAULT_CONFIG));
+        let config: Config = user.clone().extract().unwrap();
+        let config = super::merge_repo_file(user, config, &dir.child(".gitu.toml")).unwrap();
+
+        assert!(config.diff.wrap.enabled);
+        assert_eq!(config.git.path.to_str(), Some("git"));
+    }
 }
@@ -0,0 +1,94 @@
+use std::{fs, path::Path};
+
+/// A `git am` that stopped at a patch, read from `rebase-apply` in the git dir.
+pub(crate) struct AmStatus {
+    /// The patch being applied, counting from 1.
+    pub current: usize,
+    pub total: usize,
+    pub subject: Option<String>,
+}
+
+impl AmStatus {
+    /// `rebase-apply` is also used by `git rebase --apply`, only `git am` leaves an `applying` file in it.
+    pub(crate) fn read(git_dir: &Path) -> Option<Self> {
+        let dir = git_dir.join("rebase-apply");
+        if !dir.join("applying").exists() {
+            return None;
+        }
+
+        let number = |file| {
+            fs::read_to_string(dir.join(file))
+                .ok()
+                .and_then(|content| content.trim().parse().ok())
+        };
+
+        Some(Self {
+            current: number("next")?,
+            total: number("last")?,
+            subject: fs::read_to_string(dir.join("info"))
+                .ok()
+                .and_then(|info| parse_subject(&info)),
+        })
+    }
+}
+
+/// The subject from the `info` file, which `git mailinfo` writes the headers of the patch to.
+fn parse_subject(info: &str) -> Option<String> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("Subject: "))
+        .map(|subject| subject.trim().to_string())
+        .filter(|subject| !subject.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AmStatus;
+    use std::fs;
+
+    fn write_state(files: &[(&str, &str)]) -> temp_dir::TempDir {
+        let git_dir = temp_dir::TempDir::new().unwrap();
+        fs::create_dir(git_dir.path().join("rebase-apply")).unwrap();
+        for (file, content) in files {
+            fs::write(git_dir.path().join("rebase-apply").join(file), content).unwrap();
+        }
+        git_dir
+    }
+
+    #[test]
+    fn am_in_progress() {
+        let git_dir = write_state(&[
+            ("applying", ""),
+            ("next", "2\n"),
+            ("last", "3\n"),
+            (
+                "info",
+                "Author: Ada\nEmail: ada@example.com\nSubject: Fix the thing\nDate: today\n\n",
+            ),
+        ]);
+
+        let status = AmStatus::read(git_dir.path()).unwrap();
+        assert_eq!(status.current, 2);
+        assert_eq!(status.total, 3);
+        assert_eq!(status.subject.as_deref(), Some("Fix the thing"));
+    }
+
+    #[test]
+    fn am_without_info() {
+        let git_dir = write_state(&[("applying", ""), ("next", "1\n"), ("last", "1\n")]);
+
+        let status = AmStatus::read(git_dir.path()).unwrap();
+        assert_eq!(status.subject, None);
+    }
+
+    #[test]
+    fn rebase_apply_is_not_am() {
+        let git_dir = write_state(&[("rebasing", ""), ("next", "1\n"), ("last", "2\n")]);
+        assert!(AmStatus::read(git_dir.path()).is_none());
+    }
+
+    #[test]
+    fn no_rebase_apply_dir() {
+        let git_dir = temp_dir::TempDir::new().unwrap();
+        assert!(AmStatus::read(git_dir.path()).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use itertools::Itertools;
+use std::fmt;
+
+/// Which hunks of a patch `git apply --reject --verbose` could apply, numbered from 1 within the file.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ApplyResult {
+    pub applied: Vec<usize>,
+    pub failed: Vec<usize>,
+    /// The last error git gave, e.g. `patch failed: src/main.rs:22`.
+    pub message: String,
+}
+
+impl ApplyResult {
+    pub(crate) fn parse(stderr: &str) -> Self {
+        let mut applied = vec![];
+        let mut failed = vec![];
+        let mut message = String::new();
+
+        for line in stderr.lines() {
+            if let Some(rest) = line.strip_prefix("Hunk #") {
+                let (number, outcome) = rest.split_once(' ').unwrap_or((rest, ""));
+                let Ok(number) = number.parse() else {
+                    continue;
+                };
+
+                // "applied cleanly." / "succeeded at 12 (offset 3 lines)." / patch(1) style "FAILED at 22."
+                if outcome.starts_with("FAILED") {
+                    failed.push(number);
+                } else {
+                    applied.push(number);
+                }
+            } else if let Some(number) = line
+                .strip_prefix("Rejected hunk #")
+                .and_then(|rest| rest.trim_end_matches('.').parse().ok())
+            {
+                failed.push(number);
+            } else if let Some(error) = line.strip_prefix("error: ") {
+                message = error.to_string();
+            }
+        }
+
+        Self {
+            applied,
+            failed,
+            message,
+        }
+    }
+}
+
+/// The files that `git apply --reject --verbose` left a `.rej` file next to, as it printed
+/// `Applying patch f.txt with 1 reject...`.
+pub(crate) fn rejected_files(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let (file, _) = line
+                .strip_prefix("Applying patch ")?
+                .rsplit_once(" with ")?;
+            Some(format!("{}.rej", file))
+        })
+        .collect()
+}
+
+impl fmt::Display for ApplyResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers = |hunks: &[usize]| hunks.iter().map(|n| format!("#{n}")).join(", ");
+
+        if self.failed.is_empty() {
+            return f.write_str(&self.message);
+        }
+
+        let (hunks, verb) = match self.failed.len() {
+            1 => ("Hunk", "doesn't"),
+            _ => ("Hunks", "don't"),
+        };
+        write!(f, "{} {} {} apply", hunks, numbers(&self.failed), verb)?;
+
+        if !self.applied.is_empty() {
+            let verb = if self.applied.len() == 1 {
+                "does"
+            } else {
+                "do"
+            };
+            write!(f, " ({} {})", numbers(&self.applied), verb)?;
+        }
+
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rejected_files, ApplyResult};
+
+    const REJECTED: &str = "\
+Checking patch f...
+error: while searching for:
+22
+23
+24
+25
+
+error: patch failed: f:22
+Applying patch f with 1 reject...
+Hunk #1 applied cleanly.
+Rejected hunk #2.
+";
+
+    #[test]
+    fn rejected_hunk() {
+        let result = ApplyResult::parse(REJECTED);
+        assert_eq!(
+            result,
+            ApplyResult {
+                applied: vec![1],
+                failed: vec![2],
+                message: "patch failed: f:22".into(),
+            }
+        );
+        assert_eq!(
+            result.to_string(),
+            "Hunk #2 doesn't apply (#1 does): patch failed: f:22"
+        );
+    }
+
+    #[test]
+    fn rejects_left() {
+        assert_eq!(rejected_files(REJECTED), vec!["f.rej"]);
+        assert!(rejected_files("Applied patch f cleanly.\n").is_empty());
+    }
+
+    #[test]
+    fn offset_and_failed_hunks() {
+        let result = ApplyResult::parse(
+            "Hunk #1 succeeded at 12 (offset 3 lines).\n\
+            Hunk #2 FAILED at 40.\n\
+            Hunk #3 FAILED at 52.\n",
+        );
+        assert_eq!(result.applied, vec![1]);
+        assert_eq!(result.failed, vec![2, 3]);
+        assert_eq!(result.to_string(), "Hunks #2, #3 don't apply (#1 does)");
+    }
+
+    #[test]
+    fn no_hunk_details() {
+        let result = ApplyResult::parse("error: f: does not exist in index\n");
+        assert!(result.failed.is_empty());
+        assert_eq!(result.to_string(), "f: does not exist in index");
+    }
+}
@@ -0,0 +1,4 @@
+pub(crate) struct BisectStatus {
+    pub start: String,
+    pub steps: Option<u32>,
+}
@@ -0,0 +1,89 @@
+#[derive(Debug, PartialEq)]
+pub(crate) struct Blame {
+    pub hash: String,
+    pub orig_line: u32,
+    pub path: String,
+    pub author: String,
+    pub summary: String,
+}
+
+impl Blame {
+    pub(crate) fn is_committed(&self) -> bool {
+        self.hash.chars().any(|c| c != '0')
+    }
+}
+
+/// Parses the first entry of `git blame --porcelain` output.
+pub(crate) fn parse_blame_porcelain(porcelain: &str) -> Option<Blame> {
+    let mut lines = porcelain.lines();
+    let mut header = lines.next()?.split(' ');
+    let hash = header.next()?.to_string();
+    let orig_line = header.next()?.parse().ok()?;
+
+    let mut author = None;
+    let mut summary = None;
+    let mut path = None;
+    for line in lines {
+        if line.starts_with('\t') {
+            break;
+        } else if let Some(value) = line.strip_prefix("author ") {
+            author = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("summary ") {
+            summary = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("filename ") {
+            path = Some(value.to_string());
+        }
+    }
+
+    Some(Blame {
+        hash,
+        orig_line,
+        path: path?,
+        author: author?,
+        summary: summary?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_committed() {
+        let porcelain = "c4d19d2ddef199b4b29dd9697e1045d67b759969 3 1 1\n\
+            author A U Thor\n\
+            author-mail <a@b>\n\
+            summary Fix the thing\n\
+            filename f\n\
+            \tcontent\n";
+
+        let blame = parse_blame_porcelain(porcelain).unwrap();
+        assert_eq!(
+            blame,
+            Blame {
+                hash: "c4d19d2ddef199b4b29dd9697e1045d67b759969".into(),
+                orig_line: 3,
+                path: "f".into(),
+                author: "A U Thor".into(),
+                summary: "Fix the thing".into(),
+            }
+        );
+        assert!(blame.is_committed());
+    }
+
+    #[test]
+    fn parse_uncommitted() {
+        let porcelain = "0000000000000000000000000000000000000000 2 2 1\n\
+            author Not Committed Yet\n\
+            summary Version of f from f\n\
+            filename f\n\
+            \tx\n";
+
+        assert!(!parse_blame_porcelain(porcelain).unwrap().is_committed());
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(parse_blame_porcelain(""), None);
+    }
+}
@@ -2,4 +2,233 @@
 pub(crate) struct Commit {
     pub hash: String,
     pub details: String,
+    pub message: String,
+}
+
+/// A commit message split into the parts that are shown differently.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CommitMessage {
+    pub subject: String,
+    /// The lines between the subject and any trailers, blank lines separate paragraphs.
+    pub body: Vec<String>,
+    /// `Key: value` lines ending the message, like `Signed-off-by: …` (see `git help interpret-trailers`).
+    pub trailers: Vec<String>,
+}
+
+impl CommitMessage {
+    pub(crate) fn parse(message: &str) -> Self {
+        let mut lines = message.trim_end().lines().map(str::trim_end);
+        let subject = lines.next().unwrap_or("").to_string();
+        let mut body = lines
+            .skip_while(|line| line.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        let last_paragraph = body
+            .iter()
+            .rposition(String::is_empty)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        let trailers = if is_trailer_block(&body[last_paragraph..]) {
+            let trailers = body.split_off(last_paragraph);
+            body.truncate(last_paragraph.saturating_sub(1));
+            trailers
+        } else {
+            vec![]
+        };
+
+        Self {
+            subject,
+            body,
+            trailers,
+        }
+    }
+
+    /// The trailers as keys and values. Values folded onto more lines are unfolded into one,
+    /// like `git interpret-trailers --parse` does.
+    pub(crate) fn trailers(&self) -> Vec<(String, String)> {
+        let mut trailers: Vec<(String, String)> = vec![];
+
+        for line in &self.trailers {
+            match (line.starts_with([' ', '\t']), trailers.last_mut()) {
+                (true, Some((_, value))) => {
+                    if !value.is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(line.trim());
+                }
+                _ => {
+                    let (key, value) = line.split_once(':').unwrap_or((line, ""));
+                    trailers.push((key.trim_end().to_string(), value.trim().to_string()));
+                }
+            }
+        }
+
+        trailers
+    }
+}
+
+/// Trailers may be folded onto lines starting with whitespace.
+fn is_trailer_block(lines: &[String]) -> bool {
+    lines.first().is_some_and(|line| is_trailer(line))
+        && lines
+            .iter()
+            .all(|line| is_trailer(line) || line.starts_with([' ', '\t']))
+}
+
+/// Git allows whitespace between the key and the colon.
+fn is_trailer(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim_end();
+        !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-')
+    })
+}
+
+/// The line that `git commit --cleanup=scissors` cuts the message off at, after the comment character.
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// A message for `git commit --cleanup=scissors` to edit: `message`, followed by a scissors line
+/// and `below` it, which git drops from the commit like it does with `--verbose`.
+pub(crate) fn scissors_template(message: &str, below: &str, comment_char: char) -> String {
+    format!(
+        "{message}\n\
+        {comment_char} {SCISSORS}\n\
+        {comment_char} Do not modify or remove the line above.\n\
+        {comment_char} Everything below it will be ignored.\n\
+        {below}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scissors_template, CommitMessage};
+
+    #[test]
+    fn body_and_trailers() {
+        let message = CommitMessage::parse(
+            "Fix the thing\n\
+            \n\
+            It was broken.\n\
+            Now it isn't.\n\
+            \n\
+            Really: it works.\n\
+            \n\
+            Signed-off-by: A U Thor <author@example.com>\n\
+            Co-authored-by: Some One\n\
+            \x20 <someone@example.com>\n",
+        );
+
+        assert_eq!(message.subject, "Fix the thing");
+        assert_eq!(
+            message.body,
+            vec!["It was broken.", "Now it isn't.", "", "Really: it works."]
+        );
+        assert_eq!(
+            message.trailers,
+            vec![
+                "Signed-off-by: A U Thor <author@example.com>",
+                "Co-authored-by: Some One",
+                "  <someone@example.com>"
+            ]
+        );
+    }
+
+    #[test]
+    fn not_trailers() {
+        let message = CommitMessage::parse("Subject\n\nSee https://example.com: it's down\n");
+        assert_eq!(message.body, vec!["See https://example.com: it's down"]);
+        assert!(message.trailers.is_empty());
+
+        let message = CommitMessage::parse("Subject: with a colon\n");
+        assert_eq!(message.subject, "Subject: with a colon");
+        assert!(message.body.is_empty());
+        assert!(message.trailers.is_empty());
+    }
+
+    #[test]
+    fn only_trailers() {
+        let message = CommitMessage::parse("Subject\n\nSigned-off-by: Me\n");
+        assert!(message.body.is_empty());
+        assert_eq!(message.trailers, vec!["Signed-off-by: Me"]);
+    }
+
+    #[test]
+    fn parsed_trailers() {
+        let message = CommitMessage::parse(
+            "mm: fix a race in the page cache\n\
+            \n\
+            Fixes: 1a2b3c4d5e6f (\"mm: add a page cache\")\n\
+            Reported-by: A Reporter <reporter@example.com>\n\
+            Link: https://lore.kernel.org/r/20240101000000.1234-1-someone@example.com\n\
+            Reviewed-by : A Reviewer <reviewer@example.com>\n\
+            Signed-off-by: A U Thor <author@example.com>\n",
+        );
+
+        assert_eq!(
+            message.trailers(),
+            vec![
+                (
+                    "Fixes".into(),
+                    "1a2b3c4d5e6f (\"mm: add a page cache\")".into()
+                ),
+                (
+                    "Reported-by".into(),
+                    "A Reporter <reporter@example.com>".into()
+                ),
+                (
+                    "Link".into(),
+                    "https://lore.kernel.org/r/20240101000000.1234-1-someone@example.com".into()
+                ),
+                (
+                    "Reviewed-by".into(),
+                    "A Reviewer <reviewer@example.com>".into()
+                ),
+                (
+                    "Signed-off-by".into(),
+                    "A U Thor <author@example.com>".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn folded_trailers() {
+        let message = CommitMessage::parse(
+            "Subject\n\
+            \n\
+            Co-authored-by: Some One\n\
+            \x20 <someone@example.com>\n\
+            Cc:\n\
+            \tA List <list@example.com>,\n\
+            \tAnother <another@example.com>\n",
+        );
+
+        assert_eq!(
+            message.trailers(),
+            vec![
+                (
+                    "Co-authored-by".into(),
+                    "Some One <someone@example.com>".into()
+                ),
+                (
+                    "Cc".into(),
+                    "A List <list@example.com>, Another <another@example.com>".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn scissors() {
+        assert_eq!(
+            scissors_template("Subject\n", "diff --git a/x b/x\n", ';'),
+            "Subject\n\
+            \n\
+            ; ------------------------ >8 ------------------------\n\
+            ; Do not modify or remove the line above.\n\
+            ; Everything below it will be ignored.\n\
+            diff --git a/x b/x\n"
+        );
+    }
 }
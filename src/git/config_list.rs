@@ -0,0 +1,112 @@
+/// A setting as `git config --list --show-scope --show-origin` lists it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ConfigEntry {
+    /// `system`, `global`, `local`, `worktree` or `command`.
+    pub scope: String,
+    /// Where it's set, like `file:.git/config` or `command line:`.
+    pub origin: String,
+    pub key: String,
+    /// A key given without `= value` is empty here, which git takes as true.
+    pub value: String,
+}
+
+/// Settings that git writes itself when creating a repository, and that it can't do without as they are.
+const READ_ONLY_KEYS: &[&str] = &["core.bare", "core.repositoryformatversion", "core.worktree"];
+
+impl ConfigEntry {
+    /// Why the entry can't be edited in gitu, if it can't.
+    pub(crate) fn read_only_reason(&self) -> Option<&'static str> {
+        if self.scope == "command" {
+            Some("it's set on the command line")
+        } else if is_read_only_key(&self.key) {
+            Some("it describes the repository")
+        } else if !self.origin.starts_with("file:") {
+            Some("it isn't set in a file")
+        } else {
+            None
+        }
+    }
+
+    /// The file the entry is set in, without the `file:` of its origin.
+    pub(crate) fn file(&self) -> &str {
+        self.origin.strip_prefix("file:").unwrap_or(&self.origin)
+    }
+}
+
+/// Whether gitu leaves `key` alone, as git relies on it describing the repository.
+pub(crate) fn is_read_only_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    READ_ONLY_KEYS.contains(&key.as_str()) || key.starts_with("extensions.")
+}
+
+/// Parses `git config --list --show-scope --show-origin -z`, where each entry is
+/// `<scope> NUL <origin> NUL <key> LF <value> NUL`.
+pub(crate) fn parse_config_list(out: &str) -> Vec<ConfigEntry> {
+    let mut fields = out.split('\0');
+    let mut entries = vec![];
+
+    while let (Some(scope), Some(origin), Some(key_value)) =
+        (fields.next(), fields.next(), fields.next())
+    {
+        let (key, value) = key_value.split_once('\n').unwrap_or((key_value, ""));
+
+        entries.push(ConfigEntry {
+            scope: scope.to_string(),
+            origin: origin.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_config_list, ConfigEntry};
+
+    #[test]
+    fn parse() {
+        let entries = parse_config_list(
+            "global\0file:/home/ada/.gitconfig\0user.email\nada@example.com\0\
+            local\0file:.git/config\0core.bare\nfalse\0\
+            local\0file:.git/config\0alias.st\nstatus\n--short\0\
+            command\0command line:\0rebase.autosquash\0",
+        );
+
+        assert_eq!(
+            entries,
+            vec![
+                ConfigEntry {
+                    scope: "global".into(),
+                    origin: "file:/home/ada/.gitconfig".into(),
+                    key: "user.email".into(),
+                    value: "ada@example.com".into(),
+                },
+                ConfigEntry {
+                    scope: "local".into(),
+                    origin: "file:.git/config".into(),
+                    key: "core.bare".into(),
+                    value: "false".into(),
+                },
+                ConfigEntry {
+                    scope: "local".into(),
+                    origin: "file:.git/config".into(),
+                    key: "alias.st".into(),
+                    value: "status\n--short".into(),
+                },
+                ConfigEntry {
+                    scope: "command".into(),
+                    origin: "command line:".into(),
+                    key: "rebase.autosquash".into(),
+                    value: "".into(),
+                },
+            ]
+        );
+
+        assert_eq!(entries[0].read_only_reason(), None);
+        assert_eq!(entries[0].file(), "/home/ada/.gitconfig");
+        assert!(entries[1].read_only_reason().is_some());
+        assert!(entries[3].read_only_reason().is_some());
+    }
+}
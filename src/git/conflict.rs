@@ -0,0 +1,203 @@
+use crate::Res;
+
+/// A `<<<<<<<` … `>>>>>>>` region of a conflicted file.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConflictHunk {
+    /// Index of the `<<<<<<<` line.
+    pub start: usize,
+    /// Index just past the `>>>>>>>` line.
+    pub end: usize,
+    pub ours_label: String,
+    pub ours: Vec<String>,
+    /// Only present with `merge.conflictStyle` set to `diff3` or `zdiff3`.
+    pub base: Option<(String, Vec<String>)>,
+    pub theirs_label: String,
+    pub theirs: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+enum Section {
+    Clean,
+    Ours,
+    Base,
+    Theirs,
+}
+
+impl ConflictHunk {
+    /// Finds all conflicts in the content of a file.
+    pub(crate) fn parse(content: &str) -> Res<Vec<ConflictHunk>> {
+        let mut hunks = vec![];
+        let mut section = Section::Clean;
+        let mut current = None;
+
+        for (i, line) in content.split_inclusive('\n').enumerate() {
+            match (&section, marker(line)) {
+                (Section::Clean, Some(("<<<<<<<", label))) => {
+                    section = Section::Ours;
+                    current = Some(ConflictHunk {
+                        start: i,
+                        end: i,
+                        ours_label: label.to_string(),
+                        ours: vec![],
+                        base: None,
+                        theirs_label: String::new(),
+                        theirs: vec![],
+                    });
+                }
+                (Section::Clean, _) => (),
+                (Section::Ours, Some(("|||||||", label))) => {
+                    section = Section::Base;
+                    current.as_mut().unwrap().base = Some((label.to_string(), vec![]));
+                }
+                (Section::Ours | Section::Base, Some(("=======", _))) => {
+                    section = Section::Theirs;
+                }
+                (Section::Theirs, Some((">>>>>>>", label))) => {
+                    section = Section::Clean;
+                    let mut hunk = current.take().unwrap();
+                    hunk.end = i + 1;
+                    hunk.theirs_label = label.to_string();
+                    hunks.push(hunk);
+                }
+                (_, Some((marker, _))) => {
+                    return Err(format!("Unexpected '{}' on line {}", marker, i + 1).into());
+                }
+                (Section::Ours, None) => current.as_mut().unwrap().ours.push(line.to_string()),
+                (Section::Base, None) => {
+                    let (_, base) = current.as_mut().unwrap().base.as_mut().unwrap();
+                    base.push(line.to_string());
+                }
+                (Section::Theirs, None) => current.as_mut().unwrap().theirs.push(line.to_string()),
+            }
+        }
+
+        if let Some(hunk) = current {
+            return Err(format!("Unterminated conflict on line {}", hunk.start + 1).into());
+        }
+
+        Ok(hunks)
+    }
+
+    /// The lines replacing this conflict, given a resolution.
+    pub(crate) fn resolved(&self, resolution: Resolution) -> Vec<String> {
+        match resolution {
+            Resolution::Ours => self.ours.clone(),
+            Resolution::Theirs => self.theirs.clone(),
+            Resolution::Both => self.ours.iter().chain(&self.theirs).cloned().collect(),
+        }
+    }
+}
+
+/// Replaces the lines of `hunk` within `content` with its resolved lines.
+pub(crate) fn resolve(content: &str, hunk: &ConflictHunk, resolution: Resolution) -> String {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+
+    lines[..hunk.start]
+        .iter()
+        .map(|line| line.to_string())
+        .chain(hunk.resolved(resolution))
+        .chain(lines[hunk.end..].iter().map(|line| line.to_string()))
+        .collect()
+}
+
+/// Splits a conflict marker line into the marker and its label.
+fn marker(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let marker = ["<<<<<<<", "|||||||", "=======", ">>>>>>>"]
+        .into_iter()
+        .find(|marker| line.starts_with(marker))?;
+
+    match &line[marker.len()..] {
+        "" => Some((marker, "")),
+        rest => rest.strip_prefix(' ').map(|label| (marker, label)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MERGE: &str = "start\n\
+        <<<<<<< HEAD\n\
+        ours\n\
+        =======\n\
+        theirs\n\
+        >>>>>>> feature\n\
+        end\n";
+
+    const DIFF3: &str = "<<<<<<< HEAD\n\
+        ours\n\
+        ||||||| base\n\
+        original\n\
+        =======\n\
+        theirs 1\n\
+        theirs 2\n\
+        >>>>>>> feature\n";
+
+    #[test]
+    fn parse_merge() {
+        assert_eq!(
+            ConflictHunk::parse(MERGE).unwrap(),
+            vec![ConflictHunk {
+                start: 1,
+                end: 6,
+                ours_label: "HEAD".into(),
+                ours: vec!["ours\n".into()],
+                base: None,
+                theirs_label: "feature".into(),
+                theirs: vec!["theirs\n".into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_diff3() {
+        let hunks = ConflictHunk::parse(DIFF3).unwrap();
+        assert_eq!(
+            hunks[0].base,
+            Some(("base".into(), vec!["original\n".into()]))
+        );
+        assert_eq!(hunks[0].theirs, vec!["theirs 1\n", "theirs 2\n"]);
+    }
+
+    #[test]
+    fn parse_unterminated() {
+        assert_eq!(
+            ConflictHunk::parse("<<<<<<< HEAD\nours\n=======\n")
+                .unwrap_err()
+                .to_string(),
+            "Unterminated conflict on line 1"
+        );
+    }
+
+    #[test]
+    fn parse_ignores_lookalikes() {
+        assert_eq!(
+            ConflictHunk::parse("=========\n<<<<<<<<\n").unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn resolve_sides() {
+        let hunks = ConflictHunk::parse(MERGE).unwrap();
+        assert_eq!(
+            resolve(MERGE, &hunks[0], Resolution::Ours),
+            "start\nours\nend\n"
+        );
+        assert_eq!(
+            resolve(MERGE, &hunks[0], Resolution::Theirs),
+            "start\ntheirs\nend\n"
+        );
+        assert_eq!(
+            resolve(MERGE, &hunks[0], Resolution::Both),
+            "start\nours\ntheirs\nend\n"
+        );
+    }
+}
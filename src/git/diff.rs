@@ -1,7 +1,11 @@
+use super::diff_options::DiffOptions;
+use super::function_context::{self, Lang};
+use super::status::SubmoduleState;
+use super::whitespace::WhitespaceRules;
 use crate::{
     config::Config,
     syntax_highlight::{self},
-    Res,
+    timing, Res,
 };
 use git2::Repository;
 use itertools::Itertools;
@@ -11,35 +15,100 @@ use ratatui::{
 };
 use similar::{Algorithm, DiffOp, DiffTag, DiffableStr, TextDiff};
 use std::{
-    fs,
+    error::Error,
+    fmt, fs,
     iter::{self},
     ops::Range,
-    path::PathBuf,
+    path::{Path, PathBuf},
     rc::Rc,
     str,
 };
 
 #[derive(Debug, Clone)]
-pub(crate) struct Diff {
+pub struct Diff {
     pub deltas: Vec<Delta>,
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Delta {
+pub struct Delta {
     pub file_header: String,
     pub old_file: PathBuf,
     pub new_file: PathBuf,
     pub hunks: Vec<Rc<Hunk>>,
     pub status: git2::Delta,
+    /// How similar a renamed or copied file is to its origin, from `similarity index 68%`.
+    pub similarity: Option<u8>,
+    /// How much of the file was rewritten, from `dissimilarity index 87%` (see `git diff -B`).
+    pub dissimilarity: Option<u8>,
+    /// From `old mode 100644` and `new mode 100755`, only there when the mode changed.
+    pub old_mode: Option<u32>,
+    pub new_mode: Option<u32>,
+    /// Header lines that Gitu doesn't know about (yet), kept so they can be reported.
+    pub unrecognized: Vec<String>,
+    pub(crate) new_version: NewVersion,
+    /// A thumbnail of a binary image's new version, drawn with colored cells.
+    pub(crate) preview: Vec<Line<'static>>,
+    /// Whether a sparse checkout left the file out of the working tree, so only the index has it.
+    pub(crate) sparse_excluded: bool,
+    /// For a submodule, what changed inside it. Only the working tree can have changes,
+    /// so in other diffs it stays the default.
+    pub(crate) submodule: Option<SubmoduleState>,
+}
+
+/// Where the new version of a file that was diffed can be read in full.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NewVersion {
+    Workdir,
+    Blob(git2::Oid),
+    /// Parsed from a patch, or the file was deleted.
+    Unknown,
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Hunk {
+pub struct Hunk {
     pub file_header: String,
     pub new_file: PathBuf,
+    pub old_start: u32,
+    pub old_lines: u32,
     pub new_start: u32,
-    pub header: String,
+    pub new_lines: u32,
     pub content: Text<'static>,
+    pub(crate) context: String,
+    /// Whether the content has bytes that aren't valid UTF-8, which are shown as `�`.
+    pub(crate) lossy: bool,
+}
+
+/// How many lines a file diff adds and removes, as `git diff --stat` counts them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DiffStats {
+    pub(crate) added: usize,
+    pub(crate) removed: usize,
+}
+
+impl DiffStats {
+    pub(crate) fn changes(&self) -> usize {
+        self.added + self.removed
+    }
+
+    /// The lengths of the added and removed parts of a `--stat` bar at most `width` wide,
+    /// scaled so that `max_changes` fills it. Each part that's there is at least one long.
+    pub(crate) fn bar(&self, max_changes: usize, width: usize) -> (usize, usize) {
+        let changes = self.changes();
+        if changes == 0 {
+            return (0, 0);
+        }
+
+        let len = match max_changes > width {
+            true => (changes * width).div_ceil(max_changes),
+            false => changes,
+        };
+        let added = match self.added {
+            0 => 0,
+            added => (added * len / changes).clamp(1, len - (self.removed > 0) as usize),
+        };
+
+        (added, len - added)
+    }
 }
 
 #[derive(Debug)]
@@ -49,8 +118,29 @@ pub(crate) enum PatchMode {
 }
 
 impl Hunk {
-    pub(crate) fn format_patch(&self) -> String {
-        format!("{}{}\n{}\n", &self.file_header, self.header, self.content)
+    /// Formats the `@@ -a,b +c,d @@` line like git does, omitting line counts of 1.
+    pub fn header(&self) -> String {
+        let ranges = format!(
+            "@@ -{} +{} @@",
+            format_range(self.old_start, self.old_lines),
+            format_range(self.new_start, self.new_lines)
+        );
+
+        if self.context.is_empty() {
+            ranges
+        } else {
+            format!("{} {}", ranges, self.context)
+        }
+    }
+
+    /// The function context after the `@@` (see `diff.<driver>.xfuncname` in `git help gitattributes`).
+    /// Only ever known for hunks read by `parse`, empty otherwise.
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    pub fn format_patch(&self) -> String {
+        format!("{}{}\n{}\n", &self.file_header, self.header(), self.content)
     }
 
     pub(crate) fn format_line_patch(&self, line_range: Range<usize>, mode: PatchMode) -> String {
@@ -76,20 +166,50 @@ impl Hunk {
                     Some(patch_line)
                 } else if patch_line.starts_with(add) {
                     None
+                } else if patch_line.starts_with('\\')
+                    && i > 0
+                    && !line_range.contains(&(i - 1))
+                    && self.line_marker(i - 1) == Some(add)
+                {
+                    // The "\ No newline at end of file" of a line left out
+                    None
                 } else if let Some(stripped) = patch_line.strip_prefix(remove) {
                     Some(format!(" {}", stripped))
                 } else {
                     Some(patch_line)
                 }
             })
-            .join("\n");
+            .collect::<Vec<_>>();
 
         format!(
             "{}{}\n{}\n",
-            &self.file_header, self.header, modified_content
+            &self.file_header,
+            self.header(),
+            move_missing_newlines(modified_content).join("\n")
         )
     }
 
+    /// Whether line `i` is added or removed, rather than context or a `\ No newline at end of file`.
+    pub(crate) fn is_change(&self, i: usize) -> bool {
+        matches!(self.line_marker(i), Some('+' | '-'))
+    }
+
+    /// Whether any of `lines` is added or removed.
+    pub(crate) fn has_change(&self, lines: Range<usize>) -> bool {
+        lines.into_iter().any(|i| self.is_change(i))
+    }
+
+    fn line_marker(&self, i: usize) -> Option<char> {
+        self.content
+            .lines
+            .get(i)?
+            .spans
+            .first()?
+            .content
+            .chars()
+            .next()
+    }
+
     pub(crate) fn first_diff_line(&self) -> u32 {
         self.content
             .lines
@@ -104,6 +224,216 @@ impl Hunk {
             .unwrap_or(0) as u32
             + self.new_start
     }
+
+    /// The old and new file line numbers of each line in `content`.
+    pub fn line_numbers(&self) -> Vec<(Option<u32>, Option<u32>)> {
+        let mut old = self.old_start;
+        let mut new = self.new_start;
+
+        self.content
+            .lines
+            .iter()
+            .map(
+                |line| match line.spans.first().and_then(|s| s.content.chars().next()) {
+                    Some('-') => {
+                        old += 1;
+                        (Some(old - 1), None)
+                    }
+                    Some('+') => {
+                        new += 1;
+                        (None, Some(new - 1))
+                    }
+                    Some('\\') => (None, None),
+                    _ => {
+                        old += 1;
+                        new += 1;
+                        (Some(old - 1), Some(new - 1))
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// The lines of `content` side by side, by their index: old on the left, new on the right.
+    /// Removed lines are paired with the lines added in their place, in order, and rows where one side
+    /// has no counterpart are `None` on that side. Lines of context are on both.
+    pub fn side_by_side_rows(&self) -> Vec<(Option<usize>, Option<usize>)> {
+        let mut rows = vec![];
+        let mut removed = vec![];
+        let mut added = vec![];
+
+        let flush = |rows: &mut Vec<_>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+            let len = removed.len().max(added.len());
+            rows.extend((0..len).map(|i| (removed.get(i).copied(), added.get(i).copied())));
+            removed.clear();
+            added.clear();
+        };
+
+        for (i, line) in self.content.lines.iter().enumerate() {
+            match line.spans.first().and_then(|s| s.content.chars().next()) {
+                // Added lines coming before any removed ones make a block of their own
+                Some('-') if !added.is_empty() => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    removed.push(i);
+                }
+                Some('-') => removed.push(i),
+                Some('+') => added.push(i),
+                Some('\\') => (),
+                _ => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push((Some(i), Some(i)));
+                }
+            }
+        }
+
+        flush(&mut rows, &mut removed, &mut added);
+        rows
+    }
+
+    /// The line shown beside line `i` of `content` in the other column, if any.
+    pub fn matching_line(&self, i: usize) -> Option<usize> {
+        self.side_by_side_rows()
+            .into_iter()
+            .find_map(|row| match row {
+                (Some(old), new) if old == i => new,
+                (old, Some(new)) if new == i => old,
+                _ => None,
+            })
+    }
+
+    /// The new version of the changed lines, without diff markers.
+    pub fn added_text(&self, include_context: bool) -> String {
+        self.text_without_markers('+', include_context)
+    }
+
+    /// The old version of the changed lines, without diff markers.
+    pub fn removed_text(&self, include_context: bool) -> String {
+        self.text_without_markers('-', include_context)
+    }
+
+    fn text_without_markers(&self, marker: char, include_context: bool) -> String {
+        let lines = self
+            .content
+            .lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let text = line
+                    .strip_prefix(marker)
+                    .or_else(|| line.strip_prefix(' ').filter(|_| include_context))?;
+
+                let no_newline = lines.get(i + 1).is_some_and(|next| next.starts_with('\\'));
+                Some(if no_newline {
+                    text.to_string()
+                } else {
+                    format!("{text}\n")
+                })
+            })
+            .collect()
+    }
+
+    /// The added lines that end in whitespace `git diff --check` would flag, by their index in `content`,
+    /// with where that whitespace is in them after the `+`.
+    pub(crate) fn whitespace_errors(&self, rules: WhitespaceRules) -> Vec<(usize, Range<usize>)> {
+        self.content
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let text = line.to_string();
+                let added = text.strip_prefix('+')?;
+                Some((i, rules.trailing_whitespace(added)?))
+            })
+            .collect()
+    }
+
+    /// Whether both hunks change the same file in the same way, regardless of where in the file.
+    pub fn content_eq(&self, other: &Hunk) -> bool {
+        self.new_file == other.new_file && self.content == other.content
+    }
+
+    /// Combines this hunk with the `next` one of the same file, when they touch or overlap in lines of
+    /// context. `None` if there are lines between them that neither has, or the two don't line up.
+    pub fn try_merge(&self, next: &Hunk) -> Option<Hunk> {
+        if self.file_header != next.file_header
+            || self.line_marker(self.content.lines.len().checked_sub(1)?) == Some('\\')
+        {
+            return None;
+        }
+
+        // Line numbers of where each hunk starts and ends, rather than the line before an empty one
+        let first = |start: u32, lines: u32| if lines == 0 { start + 1 } else { start };
+        let (old_first, new_first) = (
+            first(self.old_start, self.old_lines),
+            first(self.new_start, self.new_lines),
+        );
+        let old_end = old_first + self.old_lines;
+        let new_end = new_first + self.new_lines;
+        let next_old_first = first(next.old_start, next.old_lines);
+        let next_new_first = first(next.new_start, next.new_lines);
+
+        if next_old_first < old_first
+            || next_old_first > old_end
+            || i64::from(old_end) - i64::from(next_old_first)
+                != i64::from(new_end) - i64::from(next_new_first)
+        {
+            return None;
+        }
+
+        // The lines both have have to be the same context
+        let overlap = (old_end - next_old_first) as usize;
+        let tail = self.content.lines.len().checked_sub(overlap)?;
+        let shared = (0..overlap).all(|i| {
+            self.line_marker(tail + i) == Some(' ')
+                && next.content.lines.get(i) == self.content.lines.get(tail + i)
+        });
+        if !shared {
+            return None;
+        }
+
+        let lines = self
+            .content
+            .lines
+            .iter()
+            .chain(next.content.lines.iter().skip(overlap))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let old_lines = (next_old_first + next.old_lines).max(old_end) - old_first;
+        let new_lines = (next_new_first + next.new_lines).max(new_end) - new_first;
+        let start = |first: u32, lines: u32| if lines == 0 { first - 1 } else { first };
+
+        Some(Hunk {
+            file_header: self.file_header.clone(),
+            new_file: self.new_file.clone(),
+            old_start: start(old_first, old_lines),
+            old_lines,
+            new_start: start(new_first, new_lines),
+            new_lines,
+            content: Text::from(lines),
+            context: self.context.clone(),
+            lossy: self.lossy || next.lossy,
+        })
+    }
+
+    /// Returns `'+'` or `'-'` if the hunk consists solely of added or removed lines.
+    pub(crate) fn uniform_line_kind(&self) -> Option<char> {
+        let mut kinds = self
+            .content
+            .lines
+            .iter()
+            .map(|line| line.spans.first().and_then(|s| s.content.chars().next()));
+
+        match kinds.next()? {
+            Some(kind @ ('+' | '-')) if kinds.all(|k| k == Some(kind)) => Some(kind),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) fn convert_diff(
@@ -111,417 +441,2359 @@ pub(crate) fn convert_diff(
     repo: &Repository,
     diff: git2::Diff,
     workdir: bool,
+    opts: &DiffOptions,
+) -> Res<Diff> {
+    timing::measure(
+        "Diffing",
+        || convert_deltas(config, repo, diff, workdir, opts),
+        timing::diff_counts,
+    )
+}
+
+fn convert_deltas(
+    config: &Config,
+    repo: &Repository,
+    diff: git2::Diff,
+    workdir: bool,
+    opts: &DiffOptions,
 ) -> Res<Diff> {
     let mut deltas = vec![];
+    let sparse_excluded = super::sparse::excluded_paths(repo)?;
 
     // TODO Only need to "git2::DiffFormat::PatchHeader" here. But git2 seemed to have broken it in the new 0.19.0. https://github.com/rust-lang/git2-rs/issues/1064
     diff.print(git2::DiffFormat::Patch, |diffdelta, _maybe_hunk, line| {
-        if let Ok(line_content) = str::from_utf8(line.content()) {
-            let is_new_header = line_content.starts_with("diff")
-                && line.origin_value() == git2::DiffLineType::FileHeader;
-
-            if is_new_header {
-                let mut delta = Delta {
-                    file_header: line_content.to_string(),
-                    old_file: path(&diffdelta.old_file()),
-                    new_file: path(&diffdelta.new_file()),
-                    hunks: vec![],
-                    status: diffdelta.status(),
-                };
+        let line_content = &decode(line.content());
+        let is_new_header = line_content.starts_with("diff")
+            && line.origin_value() == git2::DiffLineType::FileHeader;
 
-                if let Ok(hunks) = diff_files(repo, diffdelta, workdir, config, &delta) {
-                    delta.hunks = hunks;
-                }
+        if is_new_header {
+            let mut delta = Delta {
+                file_header: line_content.to_string(),
+                old_file: path(&diffdelta.old_file()),
+                new_file: path(&diffdelta.new_file()),
+                hunks: vec![],
+                status: diffdelta.status(),
+                similarity: header_score(line_content, "similarity index "),
+                dissimilarity: header_score(line_content, "dissimilarity index "),
+                old_mode: header_mode(line_content, "old mode "),
+                new_mode: header_mode(line_content, "new mode "),
+                unrecognized: unrecognized_header_lines(line_content),
+                new_version: match diffdelta.new_file() {
+                    file if !file.exists() => NewVersion::Unknown,
+                    _ if workdir => NewVersion::Workdir,
+                    file => NewVersion::Blob(file.id()),
+                },
+                preview: vec![],
+                sparse_excluded: sparse_excluded.contains(&path(&diffdelta.new_file())),
+                submodule: (diffdelta.new_file().mode() == git2::FileMode::Commit)
+                    .then(SubmoduleState::default),
+            };
 
-                deltas.push(delta);
-            } else {
-                let delta = deltas.last_mut().unwrap();
-                delta.file_header.push_str(line_content);
+            #[cfg(feature = "image-preview")]
+            if diffdelta.flags().is_binary() {
+                delta.preview = image_preview(repo, &delta).unwrap_or_default();
             }
+
+            if let Ok(hunks) = diff_files(repo, diffdelta, workdir, opts, config, &delta) {
+                delta.hunks = hunks;
+            }
+
+            deltas.push(delta);
+        } else if matches!(
+            line.origin_value(),
+            git2::DiffLineType::FileHeader | git2::DiffLineType::Binary
+        ) {
+            let delta = deltas.last_mut().unwrap();
+            delta.file_header.push_str(line_content);
         }
 
         true
     })?;
 
-    Ok(Diff { deltas })
-}
+    // Like git, files a sparse checkout left out aren't compared to the (missing) working tree
+    if workdir {
+        deltas.retain(|delta| !delta.sparse_excluded);
 
-fn diff_files(
-    repo: &Repository,
-    diffdelta: git2::DiffDelta<'_>,
-    workdir: bool,
-    config: &Config,
-    delta: &Delta,
-) -> Res<Vec<Rc<Hunk>>> {
-    let old_content = read_blob(repo, &diffdelta.old_file())?.replace("\r\n", "\n");
-    let new_content = if workdir {
-        read_workdir(repo, &diffdelta.new_file())?
-    } else {
-        read_blob(repo, &diffdelta.new_file())?
+        if deltas.iter().any(|delta| delta.submodule.is_some()) {
+            add_submodule_states(repo, &mut deltas)?;
+        }
     }
-    .replace("\r\n", "\n");
 
-    diff_content(config, delta, &old_content, &new_content)
+    Ok(Diff { deltas })
 }
 
-fn diff_content(
-    config: &Config,
-    delta: &Delta,
-    old_content: &str,
-    new_content: &str,
-) -> Res<Vec<Rc<Hunk>>> {
-    let style = &config.style;
-    let old_lines = old_content.tokenize_lines();
-    let new_lines = new_content.tokenize_lines();
+/// Fills in what changed inside submodules, from the indicators of `git status`.
+fn add_submodule_states(repo: &Repository, deltas: &mut [Delta]) -> Res<()> {
+    let status = super::status(repo)?;
+    let states = status
+        .entries
+        .iter()
+        .filter_map(|entry| entry.submodule_state())
+        .collect::<Vec<_>>();
 
-    let old_line_indices = byte_ranges(&old_lines);
-    let new_line_indices = byte_ranges(&new_lines);
+    for delta in deltas {
+        if let Some((_, state)) = states
+            .iter()
+            .find(|(path, _)| Path::new(path) == delta.new_file)
+        {
+            delta.submodule = Some(*state);
+        }
+    }
 
-    let text_diff = TextDiff::configure()
-        .algorithm(Algorithm::Patience)
-        .diff_slices(&old_lines, &new_lines);
+    Ok(())
+}
 
-    let mut old_syntax_highlights = if config.style.syntax_highlight.enabled {
-        syntax_highlight::highlight(config, &delta.old_file, old_content)
-    } else {
-        vec![]
-    }
-    .into_iter()
-    .peekable();
+/// Parses a patch in the unified format, as printed by `git diff` or `git format-patch`.
+/// Lines outside of file diffs, like commit messages, are skipped.
+///
+/// ```
+/// let patch = "\
+/// diff --git a/hello.txt b/hello.txt
+/// --- a/hello.txt
+/// +++ b/hello.txt
+/// @@ -1,2 +1,2 @@
+///  Hello
+/// -world
+/// +there
+/// ";
+///
+/// let diff = gitu::diff::parse(patch).unwrap();
+/// for hunk in &diff.deltas[0].hunks {
+///     assert_eq!(hunk.header(), "@@ -1,2 +1,2 @@");
+///     assert_eq!(hunk.added_text(false), "there\n");
+/// }
+/// ```
+pub fn parse(patch: &str) -> Result<Diff, DiffParseError> {
+    timing::measure("Parsing diff", || parse_patch(patch), timing::diff_counts)
+}
 
-    let mut new_syntax_highlights = if config.style.syntax_highlight.enabled {
-        syntax_highlight::highlight(config, &delta.new_file, new_content)
-    } else {
-        vec![]
-    }
-    .into_iter()
-    .peekable();
+fn parse_patch(patch: &str) -> Result<Diff, DiffParseError> {
+    let mut deltas: Vec<Delta> = vec![];
+    let mut pending: Option<PendingHunk> = None;
+    let mut prefixes = ("a/", "b/");
 
-    Ok(text_diff
-        .unified_diff()
-        .iter_hunks()
-        .map(|hunk| {
-            let mut lines = vec![];
+    for line in patch.lines() {
+        if let Some(hunk) = pending.as_mut().filter(|hunk| hunk.expects(line)) {
+            hunk.push(line)?;
+            continue;
+        }
 
-            hunk.ops().iter().for_each(|op| {
-                let (line_tag, old_line, new_line) = op.as_tag_tuple();
+        if let Some(hunk) = pending.take() {
+            deltas
+                .last_mut()
+                .unwrap()
+                .hunks
+                .push(Rc::new(hunk.finish()));
+        }
 
-                let old_prefix = match line_tag {
-                    DiffTag::Equal => Span::raw(" "),
-                    _ => Span::styled("-", &style.diff_highlight.tag_old),
-                };
+        let in_header = deltas.last().is_some_and(|delta| delta.hunks.is_empty());
 
-                let old_lines_range = total_range(&old_line_indices[old_line.clone()]);
-                let old_words = old_content[old_lines_range.clone()].tokenize_unicode_words();
-                let old_word_indices = byte_ranges(&old_words);
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let [(old_prefix, old), (new_prefix, new)] = split_git_paths(paths);
+            prefixes = (old_prefix, new_prefix);
+            deltas.push(Delta::from_header(line, old, new));
+        } else if line.starts_with("@@") {
+            let delta = deltas
+                .last()
+                .ok_or_else(|| DiffParseError::new("Hunk outside of a file diff", line))?;
+            pending = Some(PendingHunk::new(delta, line)?);
+        } else if line.starts_with("--- ") && !in_header {
+            // A plain unified diff, without git's `diff --git` line
+            let old = line.trim_start_matches("--- ");
+            prefixes = ("a/", "b/");
+            deltas.push(Delta::from_header(line, old, old));
+            deltas.last_mut().unwrap().read_header_line(line, prefixes);
+        } else if in_header {
+            let delta = deltas.last_mut().unwrap();
+            delta.file_header.push_str(line);
+            delta.file_header.push('\n');
+            delta.read_header_line(line, prefixes);
+        }
+    }
 
-                let new_lines_range = total_range(&new_line_indices[new_line.clone()]);
-                let new_words = new_content[new_lines_range.clone()].tokenize_unicode_words();
-                let new_word_indices = byte_ranges(&new_words);
+    if let Some(hunk) = pending {
+        if hunk.old_left > 0 || hunk.new_left > 0 {
+            return Err(DiffParseError::new(
+                "Patch ends in the middle of a hunk",
+                &hunk.header,
+            ));
+        }
 
-                let word_diff = TextDiff::configure()
-                    .algorithm(Algorithm::Myers)
-                    .diff_slices(&old_words, &new_words);
+        deltas
+            .last_mut()
+            .unwrap()
+            .hunks
+            .push(Rc::new(hunk.finish()));
+    }
 
-                let mut old_diff_highlights = iter_token_tag_ranges(&word_diff)
-                    .map(|(word_tag, old_word_token_range, _)| (old_word_token_range, word_tag))
-                    .filter_map(|(word_token_range, word_tag)| {
-                        let words_range = total_range(&old_word_indices[word_token_range]);
-                        map_from_token_to_byte_range(&words_range, &old_lines_range, word_tag)
-                    })
-                    .flat_map(|style_range| {
-                        syntax_highlight::split_at_newlines(old_content, style_range)
-                    })
-                    .map(|(word_range, word_tag)| {
-                        let diff_style = match word_tag {
-                            DiffTag::Equal => Style::from(&style.diff_highlight.unchanged_old),
-                            DiffTag::Delete => Style::from(&style.diff_highlight.changed_old),
-                            DiffTag::Insert => unreachable!(),
-                            DiffTag::Replace => Style::from(&style.diff_highlight.changed_old),
-                        };
+    Ok(Diff { deltas })
+}
 
-                        (word_range.clone(), diff_style)
-                    })
-                    .peekable();
+impl Diff {
+    /// Every file's patch, like `git diff` prints them, to save or give to `git apply`.
+    pub fn format_patch(&self) -> String {
+        self.deltas.iter().map(Delta::format_patch).collect()
+    }
+}
 
-                create_lines(
-                    &old_line_indices[old_line.clone()],
-                    &mut old_syntax_highlights,
-                    &mut old_diff_highlights,
-                    old_prefix,
-                    old_content,
-                    &mut lines,
-                );
+impl Delta {
+    fn from_header(line: &str, old: &str, new: &str) -> Self {
+        Delta {
+            file_header: format!("{}\n", line),
+            old_file: PathBuf::from(old),
+            new_file: PathBuf::from(new),
+            hunks: vec![],
+            status: git2::Delta::Modified,
+            similarity: None,
+            dissimilarity: None,
+            old_mode: None,
+            new_mode: None,
+            unrecognized: vec![],
+            new_version: NewVersion::Unknown,
+            preview: vec![],
+            sparse_excluded: false,
+            submodule: None,
+        }
+    }
 
-                // Don't print both old/new if equal
-                if line_tag != DiffTag::Equal {
-                    let mut new_diff_highlights = iter_token_tag_ranges(&word_diff)
-                        .map(|(word_tag, _, new_word_token_range)| (new_word_token_range, word_tag))
-                        .filter_map(|(word_token_range, word_tag)| {
-                            let words_range = total_range(&new_word_indices[word_token_range]);
-                            map_from_token_to_byte_range(&words_range, &new_lines_range, word_tag)
-                        })
-                        .flat_map(|style_range| {
-                            syntax_highlight::split_at_newlines(new_content, style_range)
-                        })
-                        .map(|(word_range, word_tag)| {
-                            let diff_style = match word_tag {
-                                DiffTag::Equal => Style::from(&style.diff_highlight.unchanged_new),
-                                DiffTag::Delete => unreachable!(),
-                                DiffTag::Insert => Style::from(&style.diff_highlight.changed_new),
-                                DiffTag::Replace => Style::from(&style.diff_highlight.changed_new),
-                            };
+    pub(crate) fn stats(&self) -> DiffStats {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| (0..hunk.content.lines.len()).map(|i| hunk.line_marker(i)))
+            .fold(DiffStats::default(), |mut stats, marker| {
+                match marker {
+                    Some('+') => stats.added += 1,
+                    Some('-') => stats.removed += 1,
+                    _ => (),
+                }
+                stats
+            })
+    }
 
-                            (word_range.clone(), diff_style)
-                        })
-                        .peekable();
+    /// Whether the change is only to the executable bit: `Some(true)` if the file was made executable,
+    /// `Some(false)` if it no longer is.
+    pub(crate) fn executable_toggle(&self) -> Option<bool> {
+        match (self.old_mode?, self.new_mode?) {
+            (0o100644, 0o100755) => Some(true),
+            (0o100755, 0o100644) => Some(false),
+            _ => None,
+        }
+    }
 
-                    let new_prefix = Span::styled("+", &style.diff_highlight.tag_new);
+    /// The file header followed by every hunk, like `git diff` prints this file.
+    /// The header of a renamed file has its `rename from` and `rename to`, so applying it moves the file too.
+    pub fn format_patch(&self) -> String {
+        iter::once(self.file_header.clone())
+            .chain(
+                self.hunks
+                    .iter()
+                    .map(|hunk| format!("{}\n{}\n", hunk.header(), hunk.content)),
+            )
+            .collect()
+    }
 
-                    create_lines(
-                        &new_line_indices[new_line.clone()],
-                        &mut new_syntax_highlights,
-                        &mut new_diff_highlights,
-                        new_prefix,
-                        new_content,
-                        &mut lines,
-                    );
+    /// The whole new version of the file, as it was diffed.
+    pub(crate) fn read_new_version(&self, repo: &Repository) -> Res<String> {
+        Ok(decode(&self.read_new_bytes(repo)?))
+    }
+
+    /// Like `read_new_version`, but without decoding it, for binary files.
+    pub(crate) fn read_new_bytes(&self, repo: &Repository) -> Res<Vec<u8>> {
+        Ok(match &self.new_version {
+            NewVersion::Workdir => {
+                let path = repo.workdir().expect("No workdir").join(&self.new_file);
+                if fs::metadata(&path)?.len() > FULL_FILE_MAX_BYTES {
+                    return Err(self.too_large_error());
                 }
-            });
 
-            let formatted_hunk = Text::from(lines);
+                fs::read(path)?
+            }
+            NewVersion::Blob(id) => {
+                let blob = repo.find_blob(*id)?;
+                if blob.size() as u64 > FULL_FILE_MAX_BYTES {
+                    return Err(self.too_large_error());
+                }
 
-            let new_start = hunk
-                .header()
-                .to_string()
-                .strip_prefix("@@ -")
-                .unwrap()
-                .split([' ', ','])
-                .next()
-                .unwrap()
-                .parse()
-                .unwrap();
+                blob.content().to_vec()
+            }
+            NewVersion::Unknown => {
+                return Err(format!(
+                    "There's no new version of {} to show",
+                    self.new_file.display()
+                )
+                .into())
+            }
+        })
+    }
 
-            Rc::new(Hunk {
-                file_header: delta.file_header.clone(),
-                new_file: delta.new_file.clone(),
-                new_start,
-                header: format!("{}", hunk.header()),
-                content: formatted_hunk,
-            })
-        })
-        .collect::<Vec<_>>())
-}
+    fn too_large_error(&self) -> Box<dyn Error> {
+        format!(
+            "{} is too large to show in full (over {})",
+            self.new_file.display(),
+            super::lfs::format_size(FULL_FILE_MAX_BYTES)
+        )
+        .into()
+    }
 
-fn map_from_token_to_byte_range(
-    word_range: &Range<usize>,
-    old_lines_range: &Range<usize>,
-    word_tag: DiffTag,
-) -> Option<(Range<usize>, DiffTag)> {
-    (!word_range.is_empty()).then_some((
-        (old_lines_range.start + word_range.start)..(old_lines_range.start + word_range.end),
-        word_tag,
-    ))
+    /// Checks that a hand-edited `format_patch` still starts with this file's header and has a hunk.
+    pub(crate) fn check_edited_patch(&self, patch: &str) -> Res<()> {
+        if !patch.starts_with(&self.file_header) {
+            return Err(format!(
+                "The header of {} was changed, keep the lines before the first @@",
+                self.new_file.display()
+            )
+            .into());
+        }
+
+        if !patch[self.file_header.len()..]
+            .lines()
+            .any(|line| line.starts_with("@@"))
+        {
+            return Err("The patch has no hunks left".into());
+        }
+
+        Ok(())
+    }
+
+    fn read_header_line(&mut self, line: &str, (old_prefix, new_prefix): (&str, &str)) {
+        // The paths of `rename from` and such have no prefix that could be mistaken for a directory
+        let exact_paths = matches!(self.status, git2::Delta::Renamed | git2::Delta::Copied);
+        // Plain diffs may have a timestamp after the path
+        let strip_side = |path: &str, side| {
+            let path = path.split('\t').next().unwrap_or(path);
+            PathBuf::from(path.strip_prefix(side).unwrap_or(path))
+        };
+
+        if let Some(path) = line.strip_prefix("--- ") {
+            if path != "/dev/null" && !exact_paths {
+                self.old_file = strip_side(path, old_prefix);
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            if path != "/dev/null" && !exact_paths {
+                self.new_file = strip_side(path, new_prefix);
+            }
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            self.old_mode = parse_mode(mode);
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            self.new_mode = parse_mode(mode);
+        } else if line.starts_with("new file mode ") {
+            self.status = git2::Delta::Added;
+        } else if line.starts_with("deleted file mode ") {
+            self.status = git2::Delta::Deleted;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            self.status = git2::Delta::Renamed;
+            self.old_file = PathBuf::from(path);
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            self.new_file = PathBuf::from(path);
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            self.status = git2::Delta::Copied;
+            self.old_file = PathBuf::from(path);
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            self.new_file = PathBuf::from(path);
+        } else if line.starts_with("similarity index ") {
+            self.similarity = header_score(line, "similarity index ");
+        } else if line.starts_with("dissimilarity index ") {
+            self.dissimilarity = header_score(line, "dissimilarity index ");
+        } else {
+            self.unrecognized.extend(unrecognized_header_lines(line));
+        }
+    }
 }
 
-fn iter_token_tag_ranges<'a>(
-    word_diff: &'a TextDiff<'_, '_, '_, str>,
-) -> impl Iterator<Item = (DiffTag, Range<usize>, Range<usize>)> + 'a {
-    word_diff.ops().iter().map(DiffOp::as_tag_tuple)
+/// The prefixes and paths of the two sides of a `diff --git` line. The prefixes aren't always `a/`
+/// and `b/`, see `diff.srcPrefix`, `diff.mnemonicPrefix` and `diff.noPrefix` in `git help config`.
+/// Like git, this relies on both sides naming the same path. If the file was renamed or copied
+/// instead, the rest of the header has the paths.
+fn split_git_paths(paths: &str) -> [(&str, &str); 2] {
+    let mid = paths.len() / 2;
+    let halves =
+        (paths.len() % 2 == 1 && paths.is_char_boundary(mid) && paths[mid..].starts_with(' '))
+            .then(|| (&paths[..mid], &paths[mid + 1..]))
+            .filter(|(old, new)| split_prefix(old).1 == split_prefix(new).1);
+
+    let renamed = || {
+        paths
+            .match_indices(' ')
+            .map(|(i, _)| (&paths[..i], &paths[i + 1..]))
+            .find(|(old, new)| {
+                let (old_prefix, new_prefix) = (split_prefix(old).0, split_prefix(new).0);
+                !old_prefix.is_empty() && !new_prefix.contains(' ') && old_prefix != new_prefix
+            })
+    };
+
+    let (old, new) = halves.or_else(renamed).unwrap_or((paths, paths));
+    let (old_side, new_side) = (split_prefix(old), split_prefix(new));
+
+    // Git's prefixes differ, so the same first directory on both sides is part of the path
+    if old_side.0 == new_side.0 {
+        return [("", old), ("", new)];
+    }
+
+    [old_side, new_side]
 }
 
-fn byte_ranges(tokens: &[&str]) -> Vec<Range<usize>> {
-    tokens
-        .iter()
-        .scan(0, |count, x| {
-            let len = x.len();
-            let start = *count;
-            let end = start + len;
-            *count = end;
-            Some(start..end)
-        })
-        .collect::<Vec<_>>()
+/// Splits off the first directory of `path`.
+fn split_prefix(path: &str) -> (&str, &str) {
+    path.find('/').map_or(("", path), |i| path.split_at(i + 1))
 }
 
-fn total_range(lines: &[Range<usize>]) -> Range<usize> {
-    lines
-        .last()
-        .map(|last| lines[0].start..last.end)
-        .unwrap_or(0..0)
+/// A hunk being read by `parse`, counting down the lines its header says are left.
+struct PendingHunk {
+    header: String,
+    file_header: String,
+    new_file: PathBuf,
+    old: HunkRange,
+    new: HunkRange,
+    old_left: u32,
+    new_left: u32,
+    lines: Vec<Line<'static>>,
 }
 
-fn create_lines(
-    line_indices: &[Range<usize>],
-    syntax_highlights: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
-    diff_highlights: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
-    prefix: Span<'static>,
-    content: &str,
-    lines: &mut Vec<Line<'_>>,
-) {
-    for line in line_indices {
-        advance_to(syntax_highlights, line.start);
-        advance_to(diff_highlights, line.start);
+impl PendingHunk {
+    fn new(delta: &Delta, header: &str) -> Result<Self, DiffParseError> {
+        let (old, new) = parse_hunk_header(header)?;
 
-        let a = &mut syntax_highlights
-            .peeking_take_while(|(h_range, _)| h_range.start < line.end)
-            .peekable();
+        Ok(PendingHunk {
+            header: header.to_string(),
+            file_header: delta.file_header.clone(),
+            new_file: delta.new_file.clone(),
+            old,
+            new,
+            old_left: old.1,
+            new_left: new.1,
+            lines: vec![],
+        })
+    }
 
-        let b = &mut diff_highlights
-            .peeking_take_while(|(h_range, _)| h_range.start < line.end)
-            .peekable();
+    fn expects(&self, line: &str) -> bool {
+        self.old_left > 0 || self.new_left > 0 || line.starts_with('\\')
+    }
 
-        let spans = iter::once(prefix.clone())
-            .chain(
-                iter::from_fn(|| next_merged_style_range(a, b))
-                    .flatten()
-                    .map(|(h_range, h_style)| {
-                        (
-                            // clamp to line
-                            line.start.max(h_range.start)..line.end.min(h_range.end),
-                            h_style,
-                        )
-                    })
-                    .map(|(h_range, h_style)| {
-                        Span::styled(
-                            content[h_range]
-                                // TODO only need to do this for the last span
-                                .trim_end_matches(['\r', '\n'])
-                                .to_string(),
-                            h_style,
-                        )
-                    }),
-            )
-            .collect::<Vec<_>>();
+    fn push(&mut self, line: &str) -> Result<(), DiffParseError> {
+        // Some tools strip the trailing space off of empty context lines
+        let (marker, rest) = match line.chars().next() {
+            None => (' ', ""),
+            Some(marker) => (marker, &line[marker.len_utf8()..]),
+        };
 
-        lines.push(Line::from(spans));
+        let counters = match marker {
+            ' ' => [&mut self.old_left, &mut self.new_left]
+                .into_iter()
+                .collect(),
+            '-' => vec![&mut self.old_left],
+            '+' => vec![&mut self.new_left],
+            '\\' => vec![],
+            _ => return Err(DiffParseError::new("Unexpected line in hunk", line)),
+        };
 
-        if !content[line.clone()].ends_with('\n') {
-            lines.push(Line::from("\\ No newline at end of file"))
+        for left in counters {
+            *left = left
+                .checked_sub(1)
+                .ok_or_else(|| DiffParseError::new("Hunk is longer than its header says", line))?;
         }
+
+        self.lines.push(Line::from(vec![
+            Span::raw(marker.to_string()),
+            Span::raw(rest.to_string()),
+        ]));
+        Ok(())
     }
-}
 
-fn advance_to(iter: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>, to: usize) {
-    while let Some((range, _style)) = iter.peek() {
-        if range.end <= to {
-            iter.next();
-        } else {
-            break;
+    fn finish(self) -> Hunk {
+        Hunk {
+            lossy: self.lines.iter().any(has_escaped_bytes),
+            file_header: self.file_header,
+            new_file: self.new_file,
+            old_start: self.old.0,
+            old_lines: self.old.1,
+            new_start: self.new.0,
+            new_lines: self.new.1,
+            content: Text::from(self.lines),
+            context: hunk_context(&self.header).to_string(),
         }
     }
 }
 
-/// Merges overlapping style-ranges from two iterators.
-/// This should produce a continuous range, given that a and b are continuous.
-fn next_merged_style_range(
-    a: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
-    b: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
-) -> Option<Option<(Range<usize>, Style)>> {
-    let ((a_range, a_style), (b_range, b_style)) = match (a.peek(), b.peek()) {
-        (Some(a), Some(b)) => (a, b),
-        (Some(_), None) => {
-            return a.next().map(Some);
-        }
-        (None, Some(_)) => {
-            return b.next().map(Some);
-        }
-        (None, None) => {
-            return None;
-        }
-    };
+/// A hunk of a combined diff (`diff --cc`), comparing a merge result to each of its parents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombinedHunk {
+    /// The `(start, lines)` range of each parent.
+    pub parents: Vec<(u32, u32)>,
+    pub result: (u32, u32),
+    /// Each line starts with one column per parent, of either ` `, `+` or `-`.
+    pub lines: Vec<String>,
+}
 
-    if a_range.end == b_range.end {
-        let next = (
-            a_range.start.max(b_range.start)..a_range.end,
-            a_style.patch(*b_style),
-        );
-        a.next();
-        b.next();
-        Some(Some(next))
-    } else if a_range.contains(&b_range.start) {
-        if a_range.contains(&(b_range.end - 1)) {
-            // a: (       )
-            // b:   ( X )
-            let next = (b_range.start..b_range.end, a_style.patch(*b_style));
-            b.next();
-            Some(Some(next))
-        } else {
-            // a: ( X )
-            // b:   (   )
-            let next = (b_range.start..a_range.end, a_style.patch(*b_style));
-            a.next();
-            Some(Some(next))
-        }
-    } else if b_range.contains(&a_range.start) {
-        if b_range.contains(&(a_range.end - 1)) {
-            // a:   ( X )
-            // b: (       )
-            let next = (a_range.start..a_range.end, a_style.patch(*b_style));
-            a.next();
-            Some(Some(next))
-        } else {
-            // a:   (   )
-            // b: ( X )
-            let next = (a_range.start..b_range.end, a_style.patch(*b_style));
-            b.next();
-            Some(Some(next))
+impl CombinedHunk {
+    /// Parses the `@@@ -a,b -c,d +e,f @@@` hunks out of a combined diff.
+    pub fn parse(patch: &str) -> Result<Vec<CombinedHunk>, DiffParseError> {
+        let mut hunks: Vec<CombinedHunk> = vec![];
+        let mut in_hunk = false;
+
+        for line in patch.lines() {
+            if line.starts_with("@@@") {
+                hunks.push(Self::parse_header(line)?);
+                in_hunk = true;
+            } else if let Some(hunk) = hunks.last_mut().filter(|_| in_hunk) {
+                let is_content = line.starts_with('\\')
+                    || line.len() >= hunk.parents.len()
+                        && line
+                            .bytes()
+                            .take(hunk.parents.len())
+                            .all(|c| matches!(c, b' ' | b'+' | b'-'));
+
+                if is_content {
+                    hunk.lines.push(line.to_string());
+                } else {
+                    in_hunk = false;
+                }
+            }
         }
-    } else {
-        unreachable!("ranges are disjoint: a: {:?} b: {:?}", a_range, b_range);
+
+        Ok(hunks)
+    }
+
+    fn parse_header(header: &str) -> Result<CombinedHunk, DiffParseError> {
+        let marker_len = header.bytes().take_while(|&c| c == b'@').count();
+        let mut ranges = header[marker_len..].split_whitespace();
+
+        let parents = (1..marker_len)
+            .map(|_| {
+                ranges
+                    .next()
+                    .and_then(|range| range.strip_prefix('-'))
+                    .ok_or_else(|| {
+                        DiffParseError::new("Hunk header should have a parent range", header)
+                    })
+                    .and_then(parse_range)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let result = ranges
+            .next()
+            .and_then(|range| range.strip_prefix('+'))
+            .ok_or_else(|| DiffParseError::new("Hunk header should have a result range", header))
+            .and_then(parse_range)?;
+
+        Ok(CombinedHunk {
+            parents,
+            result,
+            lines: vec![],
+        })
+    }
+
+    /// The line number of each line in every parent, and in the result.
+    /// A `-` column means the line is only in that parent, a `+` that only that parent lacks it.
+    pub fn line_numbers(&self) -> Vec<(Vec<Option<u32>>, Option<u32>)> {
+        let mut parents = self
+            .parents
+            .iter()
+            .map(|(start, _)| *start)
+            .collect::<Vec<_>>();
+        let mut result = self.result.0;
+
+        self.lines
+            .iter()
+            .map(|line| {
+                if line.starts_with('\\') {
+                    return (vec![None; parents.len()], None);
+                }
+
+                let columns = &line.as_bytes()[..parents.len()];
+                let removed = columns.contains(&b'-');
+
+                let parent_lines = parents
+                    .iter_mut()
+                    .zip(columns)
+                    .map(|(next, &column)| {
+                        let in_parent = if removed {
+                            column == b'-'
+                        } else {
+                            column == b' '
+                        };
+
+                        in_parent.then(|| {
+                            *next += 1;
+                            *next - 1
+                        })
+                    })
+                    .collect();
+
+                let result_line = (!removed).then(|| {
+                    result += 1;
+                    result - 1
+                });
+
+                (parent_lines, result_line)
+            })
+            .collect()
     }
 }
 
-fn read_workdir(repo: &Repository, new_file: &git2::DiffFile<'_>) -> Res<String> {
-    Ok(fs::read_to_string(
-        repo.workdir()
-            .expect("No workdir")
-            .join(new_file.path().unwrap()),
-    )?)
+const KNOWN_HEADER_PREFIXES: [&str; 16] = [
+    "diff --git ",
+    "index ",
+    "--- ",
+    "+++ ",
+    "old mode ",
+    "new mode ",
+    "deleted file mode ",
+    "new file mode ",
+    "similarity index ",
+    "dissimilarity index ",
+    "rename from ",
+    "rename to ",
+    "copy from ",
+    "copy to ",
+    "Binary files ",
+    "GIT binary patch",
+];
+
+/// Reads a percentage like the `68` of `similarity index 68%` from a file header.
+fn header_score(header: &str, prefix: &str) -> Option<u8> {
+    header
+        .lines()
+        .find_map(|line| line.strip_prefix(prefix)?.strip_suffix('%')?.parse().ok())
 }
 
-fn read_blob(repo: &Repository, file: &git2::DiffFile<'_>) -> Res<String> {
-    let blob = repo.find_blob(file.id());
-    blob.map(|blob| Ok(String::from_utf8(blob.content().to_vec())?))
-        .unwrap_or(Ok("".to_string()))
+/// Reads a file mode like the `100755` of `new mode 100755` from a file header.
+fn header_mode(header: &str, prefix: &str) -> Option<u32> {
+    header
+        .lines()
+        .find_map(|line| parse_mode(line.strip_prefix(prefix)?))
 }
 
-fn path(file: &git2::DiffFile) -> PathBuf {
-    file.path().unwrap().to_path_buf()
+fn parse_mode(mode: &str) -> Option<u32> {
+    u32::from_str_radix(mode.trim(), 8).ok()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Delta;
-    use crate::config;
+/// Collects any lines of a file header that aren't part of git's known extended header format.
+/// Newer versions of git may add header lines, these shouldn't break anything.
+fn unrecognized_header_lines(header: &str) -> Vec<String> {
+    header
+        .lines()
+        .filter(|line| {
+            !KNOWN_HEADER_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .map(String::from)
+        .collect()
+}
 
-    #[test]
-    fn changed_line() {
-        let hunks = diff_content("old line\n", "new line\n");
-        insta::assert_snapshot!(hunks[0].format_patch());
+/// A thumbnail of a changed image, if the terminal can draw one.
+#[cfg(feature = "image-preview")]
+fn image_preview(repo: &Repository, delta: &Delta) -> Option<Vec<Line<'static>>> {
+    if !crate::image_preview::is_image(&delta.new_file)
+        || !crate::image_preview::terminal_supports_graphics()
+    {
+        return None;
     }
 
-    #[test]
-    fn changed_line_no_newline() {
-        let hunks = diff_content("old line", "new line");
-        insta::assert_snapshot!(hunks[0].format_patch());
-    }
+    crate::image_preview::thumbnail(&delta.read_new_bytes(repo).ok()?)
+}
 
-    #[test]
-    fn multiple_changed_lines() {
-        let hunks = diff_content("one\ntwo\nthree\n", "three\ntwo\none\n");
-        insta::assert_snapshot!(hunks[0].format_patch());
+fn diff_files(
+    repo: &Repository,
+    diffdelta: git2::DiffDelta<'_>,
+    workdir: bool,
+    opts: &DiffOptions,
+    config: &Config,
+    delta: &Delta,
+) -> Res<Vec<Rc<Hunk>>> {
+    if diffdelta.flags().is_binary() {
+        return Ok(vec![]);
     }
 
-    fn diff_content(old_content: &str, new_content: &str) -> Vec<std::rc::Rc<super::Hunk>> {
-        super::diff_content(
-            &config::init_test_config().unwrap(),
-            &Delta {
-                file_header: "header\n".into(),
-                new_file: "new_file".into(),
-                old_file: "old_file".into(),
-                hunks: vec![],
-                status: git2::Delta::Modified,
-            },
+    // Line endings are kept as they are in the index, for patches to apply to it
+    let old_content = read_blob(repo, &diffdelta.old_file())?;
+    let new_content = if workdir {
+        let content = read_workdir(repo, &diffdelta.new_file())?;
+        if converts_crlf(repo, &path(&diffdelta.new_file()), &old_content)? {
+            content.replace("\r\n", "\n")
+        } else {
+            content
+        }
+    } else {
+        read_blob(repo, &diffdelta.new_file())?
+    };
+
+    diff_content(config, delta, opts, &old_content, &new_content)
+}
+
+fn diff_content(
+    config: &Config,
+    delta: &Delta,
+    opts: &DiffOptions,
+    old_content: &str,
+    new_content: &str,
+) -> Res<Vec<Rc<Hunk>>> {
+    let style = &config.style;
+    // Lines end at `\n` only, like git's. `similar` would end them at a lone `\r` too.
+    let old_lines = old_content.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new_content.split_inclusive('\n').collect::<Vec<_>>();
+
+    let old_line_indices = byte_ranges(&old_lines);
+    let new_line_indices = byte_ranges(&new_lines);
+
+    // Like `git diff -w`, lines that differ only in whitespace are compared as equal,
+    // the hunks still show (and apply) their text as it is.
+    let (old_stripped, new_stripped);
+    let (old_compared, new_compared) = if opts.ignore_whitespace {
+        old_stripped = without_whitespace(&old_lines);
+        new_stripped = without_whitespace(&new_lines);
+        (as_strs(&old_stripped), as_strs(&new_stripped))
+    } else {
+        (old_lines.clone(), new_lines.clone())
+    };
+
+    let text_diff = TextDiff::configure()
+        .algorithm(opts.algorithm.similar())
+        .diff_slices(&old_compared, &new_compared);
+
+    let max_line_bytes = config.diff.max_line_bytes;
+    let has_long_line =
+        |lines: &[Range<usize>]| lines.iter().any(|line| line.len() > max_line_bytes);
+
+    let mut old_syntax_highlights =
+        if config.style.syntax_highlight.enabled && !has_long_line(&old_line_indices) {
+            syntax_highlight::highlight(config, &delta.old_file, old_content)
+        } else {
+            vec![]
+        }
+        .into_iter()
+        .peekable();
+
+    let mut new_syntax_highlights =
+        if config.style.syntax_highlight.enabled && !has_long_line(&new_line_indices) {
+            syntax_highlight::highlight(config, &delta.new_file, new_content)
+        } else {
+            vec![]
+        }
+        .into_iter()
+        .peekable();
+
+    text_diff
+        .unified_diff()
+        .context_radius(opts.context as usize)
+        .iter_hunks()
+        .map(|hunk| {
+            let mut lines = vec![];
+
+            hunk.ops().iter().for_each(|op| {
+                let (line_tag, old_line, new_line) = op.as_tag_tuple();
+
+                let old_prefix = match line_tag {
+                    DiffTag::Equal => Span::raw(" "),
+                    _ => Span::styled("-", &style.diff_highlight.tag_old),
+                };
+
+                let old_lines_range = total_range(&old_line_indices[old_line.clone()]);
+                let new_lines_range = total_range(&new_line_indices[new_line.clone()]);
+
+                // Comparing a long line word by word could take very long, so it's taken as one word
+                let is_long = has_long_line(&old_line_indices[old_line.clone()])
+                    || has_long_line(&new_line_indices[new_line.clone()]);
+                let old_words = diff_words(&old_content[old_lines_range.clone()], is_long);
+                let old_word_indices = byte_ranges(&old_words);
+
+                let new_words = diff_words(&new_content[new_lines_range.clone()], is_long);
+                let new_word_indices = byte_ranges(&new_words);
+
+                let word_ops = if is_long {
+                    vec![(DiffTag::Equal, 0..old_words.len(), 0..new_words.len())]
+                } else {
+                    let word_diff = TextDiff::configure()
+                        .algorithm(Algorithm::Myers)
+                        .diff_slices(&old_words, &new_words);
+                    iter_token_tag_ranges(&word_diff).collect()
+                };
+
+                let mut old_diff_highlights = word_ops
+                    .iter()
+                    .cloned()
+                    .map(|(word_tag, old_word_token_range, _)| (old_word_token_range, word_tag))
+                    .filter_map(|(word_token_range, word_tag)| {
+                        let words_range = total_range(&old_word_indices[word_token_range]);
+                        map_from_token_to_byte_range(&words_range, &old_lines_range, word_tag)
+                    })
+                    .flat_map(|style_range| {
+                        syntax_highlight::split_at_newlines(old_content, style_range)
+                    })
+                    .map(|(word_range, word_tag)| {
+                        let diff_style = match word_tag {
+                            DiffTag::Equal => Style::from(&style.diff_highlight.unchanged_old),
+                            DiffTag::Delete => Style::from(&style.diff_highlight.changed_old),
+                            DiffTag::Insert => unreachable!(),
+                            DiffTag::Replace => Style::from(&style.diff_highlight.changed_old),
+                        };
+
+                        (word_range.clone(), diff_style)
+                    })
+                    .peekable();
+
+                create_lines(
+                    &old_line_indices[old_line.clone()],
+                    &mut old_syntax_highlights,
+                    &mut old_diff_highlights,
+                    old_prefix,
+                    old_content,
+                    &mut lines,
+                );
+
+                // Don't print both old/new if equal
+                if line_tag != DiffTag::Equal {
+                    let mut new_diff_highlights = word_ops
+                        .iter()
+                        .cloned()
+                        .map(|(word_tag, _, new_word_token_range)| (new_word_token_range, word_tag))
+                        .filter_map(|(word_token_range, word_tag)| {
+                            let words_range = total_range(&new_word_indices[word_token_range]);
+                            map_from_token_to_byte_range(&words_range, &new_lines_range, word_tag)
+                        })
+                        .flat_map(|style_range| {
+                            syntax_highlight::split_at_newlines(new_content, style_range)
+                        })
+                        .map(|(word_range, word_tag)| {
+                            let diff_style = match word_tag {
+                                DiffTag::Equal => Style::from(&style.diff_highlight.unchanged_new),
+                                DiffTag::Delete => unreachable!(),
+                                DiffTag::Insert => Style::from(&style.diff_highlight.changed_new),
+                                DiffTag::Replace => Style::from(&style.diff_highlight.changed_new),
+                            };
+
+                            (word_range.clone(), diff_style)
+                        })
+                        .peekable();
+
+                    let new_prefix = Span::styled("+", &style.diff_highlight.tag_new);
+
+                    create_lines(
+                        &new_line_indices[new_line.clone()],
+                        &mut new_syntax_highlights,
+                        &mut new_diff_highlights,
+                        new_prefix,
+                        new_content,
+                        &mut lines,
+                    );
+                }
+            });
+
+            let lossy = lines.iter().any(has_escaped_bytes);
+            let formatted_hunk = Text::from(lines);
+
+            let ((old_start, old_lines), (new_start, new_lines)) =
+                parse_hunk_header(&hunk.header().to_string())?;
+
+            Ok(Rc::new(Hunk {
+                file_header: delta.file_header.clone(),
+                new_file: delta.new_file.clone(),
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                content: formatted_hunk,
+                // Unlike `git diff`, similar has no notion of function context
+                context: function_context::enclosing_context(
+                    old_content,
+                    old_start,
+                    Lang::from_path(&delta.old_file),
+                )
+                .unwrap_or_default(),
+                lossy,
+            }))
+        })
+        .collect::<Res<Vec<_>>>()
+}
+
+type HunkRange = (u32, u32);
+
+/// The output of `git range-diff`, pairing up the commits of two versions of a branch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDiff {
+    pub pairs: Vec<CommitPair>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitPair {
+    /// The position and abbreviated hash of the commit in the old range, if it's there.
+    pub old: Option<(u32, String)>,
+    pub new: Option<(u32, String)>,
+    pub status: PairStatus,
+    pub subject: String,
+    /// How the patch of the commit changed, if it did.
+    pub hunks: Vec<RangeDiffHunk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PairStatus {
+    /// `=`
+    Unchanged,
+    /// `!`
+    Changed,
+    /// `<`, only in the old range.
+    Removed,
+    /// `>`, only in the new range.
+    Added,
+}
+
+/// A diff of two patches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDiffHunk {
+    /// What follows `@@`, e.g. the file name or "Metadata".
+    pub header: String,
+    /// Each line starts with a ` `, `+` or `-` marker, followed by a line of either patch.
+    pub lines: Vec<String>,
+}
+
+impl RangeDiff {
+    /// Parses the output of `git range-diff --no-color`.
+    pub fn parse(output: &str) -> Result<RangeDiff, DiffParseError> {
+        let mut pairs: Vec<CommitPair> = vec![];
+
+        for line in output.lines() {
+            if let Some(content) = line.strip_prefix("    ") {
+                let pair = pairs.last_mut().ok_or_else(|| {
+                    DiffParseError::new("Patch diff outside of a commit pair", line)
+                })?;
+
+                if let Some(header) = content.strip_prefix("@@") {
+                    pair.hunks.push(RangeDiffHunk {
+                        header: header.trim().to_string(),
+                        lines: vec![],
+                    });
+                } else {
+                    pair.hunks
+                        .last_mut()
+                        .ok_or_else(|| {
+                            DiffParseError::new("Patch diff line outside of a hunk", line)
+                        })?
+                        .lines
+                        .push(content.to_string());
+                }
+            } else if !line.is_empty() {
+                pairs.push(Self::parse_pair(line)?);
+            }
+        }
+
+        Ok(RangeDiff { pairs })
+    }
+
+    /// Parses a line like `1:  b19f79c ! 1:  935d719 change b`.
+    fn parse_pair(line: &str) -> Result<CommitPair, DiffParseError> {
+        let error = || DiffParseError::new("Expected a commit pair", line);
+
+        let (old, rest) = parse_range_commit(line).ok_or_else(error)?;
+        let (status, rest) = rest.split_once(' ').ok_or_else(error)?;
+        let (new, subject) = parse_range_commit(rest).ok_or_else(error)?;
+
+        let status = match status {
+            "=" => PairStatus::Unchanged,
+            "!" => PairStatus::Changed,
+            "<" => PairStatus::Removed,
+            ">" => PairStatus::Added,
+            _ => return Err(error()),
+        };
+
+        Ok(CommitPair {
+            old,
+            new,
+            status,
+            subject: subject.to_string(),
+            hunks: vec![],
+        })
+    }
+}
+
+/// Parses `1:  b19f79c ` (or `-:  ------- ` for a missing commit) off the start of `line`.
+fn parse_range_commit(line: &str) -> Option<(Option<(u32, String)>, &str)> {
+    let (position, rest) = line.trim_start().split_once(':')?;
+    let (hash, rest) = rest.trim_start().split_once(' ')?;
+
+    if position == "-" {
+        return Some((None, rest));
+    }
+
+    Some((Some((position.parse().ok()?, hash.to_string())), rest))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DiffParseError {
+    pub message: &'static str,
+    pub token: String,
+}
+
+impl DiffParseError {
+    fn new(message: &'static str, token: &str) -> Self {
+        Self {
+            message,
+            token: token.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DiffParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: '{}'", self.message, self.token)
+    }
+}
+
+impl Error for DiffParseError {}
+
+/// Parses the old and new ranges out of a `@@ -a,b +c,d @@` hunk header.
+fn parse_hunk_header(header: &str) -> Result<(HunkRange, HunkRange), DiffParseError> {
+    let mut ranges = header
+        .strip_prefix("@@ ")
+        .ok_or_else(|| DiffParseError::new("Hunk header should start with '@@ '", header))?
+        .split(' ');
+
+    let old = ranges
+        .next()
+        .and_then(|range| range.strip_prefix('-'))
+        .ok_or_else(|| DiffParseError::new("Hunk header should have an old range", header))?;
+    let new = ranges
+        .next()
+        .and_then(|range| range.strip_prefix('+'))
+        .ok_or_else(|| DiffParseError::new("Hunk header should have a new range", header))?;
+
+    Ok((parse_range(old)?, parse_range(new)?))
+}
+
+/// Whatever follows the closing `@@` of a hunk header, verbatim.
+fn hunk_context(header: &str) -> &str {
+    header
+        .splitn(4, ' ')
+        .nth(3)
+        .and_then(|rest| rest.strip_prefix("@@"))
+        .map(|context| context.strip_prefix(' ').unwrap_or(context))
+        .unwrap_or("")
+}
+
+/// Parses a `start,lines` range. Git omits `lines` when it is 1, e.g. `@@ -5 +5,2 @@`.
+fn parse_range(range: &str) -> Result<HunkRange, DiffParseError> {
+    let (start, lines) = match range.split_once(',') {
+        Some((start, lines)) => (start, Some(lines)),
+        None => (range, None),
+    };
+
+    // `u32::from_str` is checked, so huge numbers are an error rather than wrapping around
+    let start = start
+        .parse()
+        .map_err(|_| DiffParseError::new("Error parsing range start", start))?;
+    let lines = match lines {
+        Some(lines) => lines
+            .parse()
+            .map_err(|_| DiffParseError::new("Error parsing range lines", lines))?,
+        None => 1,
+    };
+
+    Ok((start, lines))
+}
+
+fn format_range(start: u32, lines: u32) -> String {
+    if lines == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, lines)
+    }
+}
+
+fn map_from_token_to_byte_range(
+    word_range: &Range<usize>,
+    old_lines_range: &Range<usize>,
+    word_tag: DiffTag,
+) -> Option<(Range<usize>, DiffTag)> {
+    (!word_range.is_empty()).then_some((
+        (old_lines_range.start + word_range.start)..(old_lines_range.start + word_range.end),
+        word_tag,
+    ))
+}
+
+fn iter_token_tag_ranges<'a>(
+    word_diff: &'a TextDiff<'_, '_, '_, str>,
+) -> impl Iterator<Item = (DiffTag, Range<usize>, Range<usize>)> + 'a {
+    word_diff.ops().iter().map(DiffOp::as_tag_tuple)
+}
+
+fn diff_words(text: &str, whole: bool) -> Vec<&str> {
+    if whole {
+        vec![text]
+    } else {
+        text.tokenize_unicode_words()
+    }
+}
+
+/// The lines with all their whitespace left out, for comparing them like `git diff -w`.
+fn without_whitespace(lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+        .collect()
+}
+
+fn as_strs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(String::as_str).collect()
+}
+
+fn byte_ranges(tokens: &[&str]) -> Vec<Range<usize>> {
+    tokens
+        .iter()
+        .scan(0, |count, x| {
+            let len = x.len();
+            let start = *count;
+            let end = start + len;
+            *count = end;
+            Some(start..end)
+        })
+        .collect::<Vec<_>>()
+}
+
+fn total_range(lines: &[Range<usize>]) -> Range<usize> {
+    lines
+        .last()
+        .map(|last| lines[0].start..last.end)
+        .unwrap_or(0..0)
+}
+
+fn create_lines(
+    line_indices: &[Range<usize>],
+    syntax_highlights: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
+    diff_highlights: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
+    prefix: Span<'static>,
+    content: &str,
+    lines: &mut Vec<Line<'_>>,
+) {
+    for line in line_indices {
+        advance_to(syntax_highlights, line.start);
+        advance_to(diff_highlights, line.start);
+
+        let a = &mut syntax_highlights
+            .peeking_take_while(|(h_range, _)| h_range.start < line.end)
+            .peekable();
+
+        let b = &mut diff_highlights
+            .peeking_take_while(|(h_range, _)| h_range.start < line.end)
+            .peekable();
+
+        let spans = iter::once(prefix.clone())
+            .chain(
+                iter::from_fn(|| next_merged_style_range(a, b))
+                    .flatten()
+                    .map(|(h_range, h_style)| {
+                        (
+                            // clamp to line
+                            line.start.max(h_range.start)..line.end.min(h_range.end),
+                            h_style,
+                        )
+                    })
+                    .map(|(h_range, h_style)| {
+                        Span::styled(
+                            content[h_range]
+                                // TODO only need to do this for the last span
+                                .trim_end_matches('\n')
+                                .to_string(),
+                            h_style,
+                        )
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        lines.push(Line::from(spans));
+
+        if !content[line.clone()].ends_with('\n') {
+            lines.push(Line::from("\\ No newline at end of file"))
+        }
+    }
+}
+
+fn advance_to(iter: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>, to: usize) {
+    while let Some((range, _style)) = iter.peek() {
+        if range.end <= to {
+            iter.next();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Merges overlapping style-ranges from two iterators.
+/// This should produce a continuous range, given that a and b are continuous.
+fn next_merged_style_range(
+    a: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
+    b: &mut iter::Peekable<impl Iterator<Item = (Range<usize>, Style)>>,
+) -> Option<Option<(Range<usize>, Style)>> {
+    let ((a_range, a_style), (b_range, b_style)) = match (a.peek(), b.peek()) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(_), None) => {
+            return a.next().map(Some);
+        }
+        (None, Some(_)) => {
+            return b.next().map(Some);
+        }
+        (None, None) => {
+            return None;
+        }
+    };
+
+    if a_range.end == b_range.end {
+        let next = (
+            a_range.start.max(b_range.start)..a_range.end,
+            a_style.patch(*b_style),
+        );
+        a.next();
+        b.next();
+        Some(Some(next))
+    } else if a_range.contains(&b_range.start) {
+        if a_range.contains(&(b_range.end - 1)) {
+            // a: (       )
+            // b:   ( X )
+            let next = (b_range.start..b_range.end, a_style.patch(*b_style));
+            b.next();
+            Some(Some(next))
+        } else {
+            // a: ( X )
+            // b:   (   )
+            let next = (b_range.start..a_range.end, a_style.patch(*b_style));
+            a.next();
+            Some(Some(next))
+        }
+    } else if b_range.contains(&a_range.start) {
+        if b_range.contains(&(a_range.end - 1)) {
+            // a:   ( X )
+            // b: (       )
+            let next = (a_range.start..a_range.end, a_style.patch(*b_style));
+            a.next();
+            Some(Some(next))
+        } else {
+            // a:   (   )
+            // b: ( X )
+            let next = (a_range.start..b_range.end, a_style.patch(*b_style));
+            b.next();
+            Some(Some(next))
+        }
+    } else {
+        unreachable!("ranges are disjoint: a: {:?} b: {:?}", a_range, b_range);
+    }
+}
+
+fn read_workdir(repo: &Repository, new_file: &git2::DiffFile<'_>) -> Res<String> {
+    Ok(decode(&fs::read(
+        repo.workdir()
+            .expect("No workdir")
+            .join(new_file.path().unwrap()),
+    )?))
+}
+
+fn read_blob(repo: &Repository, file: &git2::DiffFile<'_>) -> Res<String> {
+    let blob = repo.find_blob(file.id());
+    Ok(blob.map(|blob| decode(blob.content())).unwrap_or_default())
+}
+
+/// Files larger than this aren't shown in full, rendering them would stall the UI.
+const FULL_FILE_MAX_BYTES: u64 = 2_000_000;
+
+/// Bytes that aren't valid UTF-8 are kept as the private use characters from here on, one per byte,
+/// so that a patch of e.g. a Latin-1 file can be written back out exactly.
+/// Real characters in this range would be mistaken for such bytes, but they're practically unused.
+const ESCAPED_BYTES: u32 = 0x10FF00;
+
+/// Decodes file content or git output, escaping any bytes that aren't valid UTF-8.
+/// NULs and other control bytes, like in a binary file diffed as text with `git diff --text`,
+/// are kept as they are, to be shown as symbols by `display_escaped`.
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        text.extend(
+            chunk
+                .invalid()
+                .iter()
+                .map(|&byte| char::from_u32(ESCAPED_BYTES + u32::from(byte)).unwrap()),
+        );
+    }
+
+    text
+}
+
+/// The exact bytes of text returned by `decode`, like a patch to give to `git apply`.
+pub(crate) fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+
+    for c in text.chars() {
+        match escaped_byte(c) {
+            Some(byte) => bytes.push(byte),
+            None => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    bytes
+}
+
+/// Shows the bytes escaped by `decode` as `�`.
+pub(crate) fn display_lossy(text: &str) -> String {
+    text.chars()
+        .map(|c| match escaped_byte(c) {
+            Some(_) => char::REPLACEMENT_CHARACTER,
+            None => c,
+        })
+        .collect()
+}
+
+/// Like `display_lossy`, but also expands tabs and shows other control characters as symbols like `␍`.
+/// A lone `\r` would otherwise return the cursor, and scramble the line it's in.
+pub(crate) fn display_escaped(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\t' => escaped.push_str("    "),
+            '\x7f' => escaped.push('\u{2421}'),
+            c if c < ' ' => escaped.push(char::from_u32(0x2400 + c as u32).unwrap()),
+            c if escaped_byte(c).is_some() => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn escaped_byte(c: char) -> Option<u8> {
+    (c as u32)
+        .checked_sub(ESCAPED_BYTES)
+        .and_then(|byte| u8::try_from(byte).ok())
+}
+
+fn has_escaped_bytes(line: &Line) -> bool {
+    line.spans
+        .iter()
+        .any(|span| span.content.chars().any(|c| escaped_byte(c).is_some()))
+}
+
+/// A `\\ No newline at end of file` only makes sense after the last line of the side(s) it's of.
+/// Leaving out some changes of a hunk may put other lines after it, like the one that replaces it,
+/// which then needs its newline. Otherwise git would join the lines.
+fn move_missing_newlines(lines: Vec<String>) -> Vec<String> {
+    let is_side = |line: &String, side: char| line.starts_with([side, ' ']);
+    let mut result = Vec::with_capacity(lines.len());
+
+    for (i, line) in lines.iter().enumerate() {
+        if !line.starts_with('\\') || i == 0 {
+            result.push(line.clone());
+            continue;
+        }
+
+        let rest = &lines[i + 1..];
+        let old_continues = rest.iter().any(|line| is_side(line, '-'));
+        let new_continues = rest.iter().any(|line| is_side(line, '+'));
+
+        let prev = &lines[i - 1];
+        match prev.chars().next() {
+            Some('-') if old_continues => (),
+            Some('+') if new_continues => (),
+            Some(' ') if old_continues && new_continues => (),
+            Some(' ') if new_continues => {
+                let text = &prev[1..];
+                result.pop();
+                result.extend([format!("-{text}"), line.clone(), format!("+{text}")]);
+            }
+            Some(' ') if old_continues => {
+                let text = &prev[1..];
+                result.pop();
+                result.extend([format!("-{text}"), format!("+{text}"), line.clone()]);
+            }
+            _ => result.push(line.clone()),
+        }
+    }
+
+    result
+}
+
+/// Whether `git add` would turn CRLF line endings of the working tree file into LF,
+/// see `core.autocrlf` and the `text` and `eol` attributes in `git help gitattributes`.
+fn converts_crlf(repo: &Repository, path: &Path, index_content: &str) -> Res<bool> {
+    let flags = git2::AttrCheckFlags::FILE_THEN_INDEX;
+    let text = git2::AttrValue::from_string(repo.get_attr(path, "text", flags)?);
+    let has_eol = repo.get_attr(path, "eol", flags)?.is_some();
+    let autocrlf = repo
+        .config()?
+        .get_string("core.autocrlf")
+        .is_ok_and(|value| matches!(value.as_str(), "true" | "input"));
+
+    // Files that were committed with CRLF are left alone, unless told explicitly
+    Ok(match text {
+        git2::AttrValue::True => true,
+        git2::AttrValue::False => false,
+        git2::AttrValue::String("auto") => !index_content.contains('\r'),
+        _ => (has_eol || autocrlf) && !index_content.contains('\r'),
+    })
+}
+
+fn path(file: &git2::DiffFile) -> PathBuf {
+    file.path().unwrap().to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delta, DiffStats};
+    use crate::config;
+
+    #[test]
+    fn delta_stats() {
+        let diff = super::parse(
+            "diff --git a/file b/file\n\
+            --- a/file\n\
+            +++ b/file\n\
+            @@ -1,3 +1,2 @@\n\
+            -one\n\
+            +uno\n\
+            \x20two\n\
+            -three\n\
+            @@ -9 +8,2 @@\n\
+            \x20nine\n\
+            +ten\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            diff.deltas[0].stats(),
+            DiffStats {
+                added: 2,
+                removed: 2
+            }
+        );
+    }
+
+    #[test]
+    fn stat_bars() {
+        let stats = |added, removed| DiffStats { added, removed };
+
+        assert_eq!(stats(3, 1).bar(10, 40), (3, 1));
+        assert_eq!(stats(60, 20).bar(80, 40), (30, 10));
+        assert_eq!(stats(1, 0).bar(1000, 40), (1, 0));
+        assert_eq!(stats(100, 1).bar(101, 40), (39, 1));
+        assert_eq!(stats(0, 0).bar(10, 40), (0, 0));
+    }
+
+    #[test]
+    fn changed_line() {
+        let hunks = diff_content("old line\n", "new line\n");
+        insta::assert_snapshot!(hunks[0].format_patch());
+    }
+
+    #[test]
+    fn changed_line_no_newline() {
+        let hunks = diff_content("old line", "new line");
+        insta::assert_snapshot!(hunks[0].format_patch());
+    }
+
+    #[test]
+    fn line_patch_no_newline() {
+        let hunks = diff_content("old line", "new line");
+        let hunk = &hunks[0];
+        assert!(hunk.is_change(0));
+        assert!(!hunk.is_change(1));
+
+        assert_eq!(
+            hunk.format_line_patch(0..1, super::PatchMode::Normal),
+            "header\n@@ -1 +1 @@\n-old line\n\\ No newline at end of file\n"
+        );
+
+        // The old line gets a newline when it's kept before the new one
+        assert_eq!(
+            hunk.format_line_patch(2..3, super::PatchMode::Normal),
+            "header\n@@ -1 +1 @@\n-old line\n\\ No newline at end of file\n\
+            +old line\n+new line\n\\ No newline at end of file\n"
+        );
+        assert_eq!(
+            hunk.format_line_patch(0..1, super::PatchMode::Reverse),
+            "header\n@@ -1 +1 @@\n-old line\n new line\n\\ No newline at end of file\n"
+        );
+        assert_eq!(
+            hunk.format_line_patch(2..3, super::PatchMode::Reverse),
+            "header\n@@ -1 +1 @@\n+new line\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn multiple_changed_lines() {
+        let hunks = diff_content("one\ntwo\nthree\n", "three\ntwo\none\n");
+        insta::assert_snapshot!(hunks[0].format_patch());
+    }
+
+    #[test]
+    fn single_line_hunk_header() {
+        let hunks = diff_content("one\n", "two\n");
+        let hunk = &hunks[0];
+
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 1));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 1));
+        assert_eq!(hunk.header(), "@@ -1 +1 @@");
+
+        let ((old_start, old_lines), (new_start, new_lines)) =
+            super::parse_hunk_header("@@ -5 +5,2 @@").unwrap();
+        assert_eq!((old_start, old_lines), (5, 1));
+        assert_eq!((new_start, new_lines), (5, 2));
+    }
+
+    #[test]
+    fn hunk_header_round_trip() {
+        for header in [
+            "@@ -5 +5,2 @@",
+            "@@ -1,3 +1 @@",
+            "@@ -0,0 +1 @@",
+            "@@ -7 +7 @@",
+        ] {
+            let ((old_start, old_lines), (new_start, new_lines)) =
+                super::parse_hunk_header(header).unwrap();
+            let formatted = format!(
+                "@@ -{} +{} @@",
+                super::format_range(old_start, old_lines),
+                super::format_range(new_start, new_lines)
+            );
+            assert_eq!(formatted, header);
+        }
+    }
+
+    #[test]
+    fn parse_git_patch() {
+        let patch = "From 1234 Mon Sep 17 00:00:00 2001\n\
+            Subject: [PATCH] Rename\n\
+            \n\
+            diff --git a/old.txt b/new.txt\n\
+            similarity index 80%\n\
+            rename from old.txt\n\
+            rename to new.txt\n\
+            --- a/old.txt\n\
+            +++ b/new.txt\n\
+            @@ -1,3 +1,3 @@\n\
+            \x20one\n\
+            -two\n\
+            +2\n\
+            \x20three\n\
+            diff --git a/added.txt b/added.txt\n\
+            new file mode 100644\n\
+            --- /dev/null\n\
+            +++ b/added.txt\n\
+            @@ -0,0 +1 @@\n\
+            +hello\n\
+            \\ No newline at end of file\n\
+            -- \n\
+            2.45.0\n";
+
+        let diff = super::parse(patch).unwrap();
+        assert_eq!(diff.deltas.len(), 2);
+
+        let renamed = &diff.deltas[0];
+        assert_eq!(renamed.status, git2::Delta::Renamed);
+        assert_eq!(renamed.old_file.to_str(), Some("old.txt"));
+        assert_eq!(renamed.new_file.to_str(), Some("new.txt"));
+        assert_eq!(renamed.hunks[0].line_numbers().len(), 4);
+        assert_eq!(renamed.hunks[0].removed_text(false), "two\n");
+
+        let added = &diff.deltas[1];
+        assert_eq!(added.status, git2::Delta::Added);
+        assert_eq!(added.hunks[0].added_text(false), "hello");
+        assert_eq!(
+            added.hunks[0].format_patch(),
+            "diff --git a/added.txt b/added.txt\n\
+            new file mode 100644\n\
+            --- /dev/null\n\
+            +++ b/added.txt\n\
+            @@ -0,0 +1 @@\n\
+            +hello\n\
+            \\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn format_patch_of_parsed_diff() {
+        let patch = "diff --git a/a.txt b/a.txt\n\
+            --- a/a.txt\n\
+            +++ b/a.txt\n\
+            @@ -1,2 +1,2 @@\n\
+            -one\n\
+            +1\n\
+            \x20two\n\
+            diff --git a/b.txt b/b.txt\n\
+            deleted file mode 100644\n\
+            --- a/b.txt\n\
+            +++ /dev/null\n\
+            @@ -1 +0,0 @@\n\
+            -gone\n";
+
+        assert_eq!(super::parse(patch).unwrap().format_patch(), patch);
+    }
+
+    #[test]
+    fn parse_custom_prefixes() {
+        // With `diff.mnemonicPrefix`, as `git diff` prints changes to the worktree
+        let patch = "diff --git i/src/my file.rs w/src/my file.rs\n\
+            --- i/src/my file.rs\n\
+            +++ w/src/my file.rs\n\
+            @@ -1 +1 @@\n\
+            -a\n\
+            +b\n\
+            diff --git i/old name.txt w/new.txt\n\
+            similarity index 80%\n\
+            rename from old name.txt\n\
+            rename to new.txt\n\
+            diff --git i/run.sh w/run.sh\n\
+            old mode 100644\n\
+            new mode 100755\n\
+            diff --git src/a.rs lib/a.rs\n\
+            similarity index 90%\n\
+            rename from src/a.rs\n\
+            rename to lib/a.rs\n\
+            --- src/a.rs\n\
+            +++ lib/a.rs\n\
+            @@ -1 +1 @@\n\
+            -a\n\
+            +b\n";
+
+        let diff = super::parse(patch).unwrap();
+        let paths = diff
+            .deltas
+            .iter()
+            .map(|delta| {
+                (
+                    delta.old_file.to_str().unwrap(),
+                    delta.new_file.to_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                ("src/my file.rs", "src/my file.rs"),
+                ("old name.txt", "new.txt"),
+                ("run.sh", "run.sh"),
+                // With `diff.noPrefix`
+                ("src/a.rs", "lib/a.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn split_git_paths() {
+        use super::split_git_paths;
+
+        assert_eq!(
+            split_git_paths("a/old.txt b/new.txt"),
+            [("a/", "old.txt"), ("b/", "new.txt")]
+        );
+        assert_eq!(
+            split_git_paths("a/my old.txt b/new.txt"),
+            [("a/", "my old.txt"), ("b/", "new.txt")]
+        );
+        // With `diff.noPrefix`
+        assert_eq!(
+            split_git_paths("src/f.rs src/f.rs"),
+            [("", "src/f.rs"), ("", "src/f.rs")]
+        );
+        assert_eq!(split_git_paths("f f"), [("", "f"), ("", "f")]);
+    }
+
+    #[test]
+    fn parse_plain_diff() {
+        let patch = "--- a.txt\t2024-01-01\n+++ b.txt\t2024-01-02\n@@ -1 +1 @@\n-a\n+b\n";
+
+        let diff = super::parse(patch).unwrap();
+        assert_eq!(diff.deltas[0].old_file.to_str(), Some("a.txt"));
+        assert_eq!(diff.deltas[0].new_file.to_str(), Some("b.txt"));
+        assert_eq!(diff.deltas[0].hunks[0].added_text(false), "b\n");
+    }
+
+    #[test]
+    fn delta_format_patch() {
+        let patch = "diff --git a/f b/f\n\
+            index 1234567..89abcde 100644\n\
+            --- a/f\n\
+            +++ b/f\n\
+            @@ -1,2 +1,2 @@\n\
+            -a\n\
+            +A\n\
+            \x20b\n\
+            @@ -10 +10 @@ fn main() {\n\
+            -y\n\
+            +Y\n";
+
+        let diff = super::parse(patch).unwrap();
+        let delta = &diff.deltas[0];
+        assert_eq!(delta.format_patch(), patch);
+
+        let edited = patch.replace("+A\n", "+A\n+more\n");
+        assert!(delta.check_edited_patch(&edited).is_ok());
+        assert_eq!(
+            delta
+                .check_edited_patch(&patch.replace("+++ b/f\n", ""))
+                .unwrap_err()
+                .to_string(),
+            "The header of f was changed, keep the lines before the first @@"
+        );
+        assert_eq!(
+            delta
+                .check_edited_patch(&delta.file_header)
+                .unwrap_err()
+                .to_string(),
+            "The patch has no hunks left"
+        );
+    }
+
+    #[test]
+    fn parse_truncated_hunk() {
+        use super::DiffParseError;
+
+        let patch = "diff --git a/f b/f\n--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n-a\n";
+        assert_eq!(
+            super::parse(patch).unwrap_err(),
+            DiffParseError {
+                message: "Patch ends in the middle of a hunk",
+                token: "@@ -1,2 +1,2 @@".into()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_forced_text_with_nul() {
+        // `git diff --text` of a binary file, with a NUL and a byte that isn't UTF-8
+        let patch = b"diff --git a/data.bin b/data.bin\n\
+            index 1234567..89abcde 100644\n\
+            --- a/data.bin\n\
+            +++ b/data.bin\n\
+            @@ -1,2 +1,2 @@\n\
+            \x20head\x00er\n\
+            -old\xff\x00\n\
+            +new\x00\x01\n";
+
+        let diff = super::parse(&super::decode(patch)).unwrap();
+        let hunk = &diff.deltas[0].hunks[0];
+        assert_eq!(hunk.header(), "@@ -1,2 +1,2 @@");
+        assert_eq!(hunk.added_text(false), "new\0\x01\n");
+        assert_eq!(super::encode(&diff.format_patch()), patch);
+        assert_eq!(
+            super::display_escaped(&hunk.content.lines[1].to_string()),
+            "-old\u{FFFD}\u{2400}"
+        );
+    }
+
+    #[test]
+    fn combined_line_numbers() {
+        use super::CombinedHunk;
+
+        // `git diff` of a conflicted merge, resolved by hand
+        let patch = "diff --cc f\n\
+            index f4ea702,685044e..0000000\n\
+            --- a/f\n\
+            +++ b/f\n\
+            @@@ -1,3 -1,4 +1,4 @@@\n\
+            \x20 a\n\
+            - B1\n\
+            \x20-B2\n\
+            ++X\n\
+            \x20 c\n\
+            + d\n";
+
+        let hunks = CombinedHunk::parse(patch).unwrap();
+        assert_eq!(hunks[0].parents, vec![(1, 3), (1, 4)]);
+        assert_eq!(hunks[0].result, (1, 4));
+        assert_eq!(
+            hunks[0].line_numbers(),
+            vec![
+                (vec![Some(1), Some(1)], Some(1)),
+                (vec![Some(2), None], None),
+                (vec![None, Some(2)], None),
+                (vec![None, None], Some(2)),
+                (vec![Some(3), Some(3)], Some(3)),
+                (vec![None, Some(4)], Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn combined_hunk_header_missing_range() {
+        use super::{CombinedHunk, DiffParseError};
+
+        assert_eq!(
+            CombinedHunk::parse("@@@ -1,3 +1,4 @@@\n"),
+            Err(DiffParseError {
+                message: "Hunk header should have a parent range",
+                token: "@@@ -1,3 +1,4 @@@".into()
+            })
+        );
+    }
+
+    // From `git range-diff main topic topic4`, after rebasing, amending one commit,
+    // dropping one and adding another.
+    const RANGE_DIFF: &str = "\
+1:  b19f79c = 1:  935d719 change b
+2:  04d55f8 ! 2:  ccef394 add g
+    @@ Metadata
+     Author: a <a@b>
+    \x20
+      ## Commit message ##
+    -    add g
+    +    add g up to 9
+    \x20
+      ## g (new) ##
+     @@
+    @@ g (new)
+     +6
+     +7
+     +8
+    ++9
+3:  fb21833 < -:  ------- add h
+-:  ------- > 3:  cfcaf69 add n
+";
+
+    #[test]
+    fn range_diff_pairs() {
+        use super::{PairStatus, RangeDiff};
+
+        let range_diff = RangeDiff::parse(RANGE_DIFF).unwrap();
+        let pairs = range_diff
+            .pairs
+            .iter()
+            .map(|pair| {
+                (
+                    pair.old.clone(),
+                    pair.status,
+                    pair.new.clone(),
+                    &pair.subject[..],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    Some((1, "b19f79c".into())),
+                    PairStatus::Unchanged,
+                    Some((1, "935d719".into())),
+                    "change b"
+                ),
+                (
+                    Some((2, "04d55f8".into())),
+                    PairStatus::Changed,
+                    Some((2, "ccef394".into())),
+                    "add g"
+                ),
+                (
+                    Some((3, "fb21833".into())),
+                    PairStatus::Removed,
+                    None,
+                    "add h"
+                ),
+                (
+                    None,
+                    PairStatus::Added,
+                    Some((3, "cfcaf69".into())),
+                    "add n"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn range_diff_hunks() {
+        use super::RangeDiff;
+
+        let range_diff = RangeDiff::parse(RANGE_DIFF).unwrap();
+        let hunks = &range_diff.pairs[1].hunks;
+
+        assert_eq!(hunks[0].header, "Metadata");
+        assert_eq!(hunks[0].lines[3..5], ["-    add g", "+    add g up to 9"]);
+        assert_eq!(hunks[1].header, "g (new)");
+        assert_eq!(hunks[1].lines, [" +6", " +7", " +8", "++9"]);
+        assert!(range_diff.pairs[0].hunks.is_empty());
+    }
+
+    #[test]
+    fn range_diff_garbled_pair() {
+        use super::{DiffParseError, RangeDiff};
+
+        assert_eq!(
+            RangeDiff::parse("1:  b19f79c ? 1:  935d719 change b\n"),
+            Err(DiffParseError {
+                message: "Expected a commit pair",
+                token: "1:  b19f79c ? 1:  935d719 change b".into()
+            })
+        );
+    }
+
+    // From `git diff`, with `*.py diff=python`, `*.rs diff=rust` and `*.md diff=markdown`
+    // in `.gitattributes`.
+    const USERDIFF: &str = "\
+diff --git a/a.md b/a.md
+index 185bd52..aefd7ec 100644
+--- a/a.md
++++ b/a.md
+@@ -8,5 +8,5 @@ ## Überblick — «section» 🚀
+ line 3
+ line 4
+ line 5
+-line 6
++line six
+ line 7
+diff --git a/a.py b/a.py
+index c16ed71..bc76a01 100644
+--- a/a.py
++++ b/a.py
+@@ -6,6 +6,6 @@ def naïve_размер(self, x: int) -> dict[str, 'Ü']:
+         a3 = 3
+         a4 = 4
+         a5 = 5
+-        a6 = 6
++        a6 = 66
+         a7 = 7
+         return {}
+diff --git a/a.rs b/a.rs
+index 6e33755..8e205ad 100644
+--- a/a.rs
++++ b/a.rs
+@@ -6,7 +6,7 @@ fn größe(&self) -> Option<&'a str> /* ✓ */ {
+         let a3 = 3;
+         let a4 = 4;
+         let a5 = 5;
+-        let a6 = 6;
++        let a6 = 66;
+         let a7 = 7;
+         None
+     }
+";
+
+    #[test]
+    fn hunk_context_from_userdiff_drivers() {
+        let diff = super::parse(USERDIFF).unwrap();
+        let contexts = diff
+            .deltas
+            .iter()
+            .map(|delta| delta.hunks[0].context())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            contexts,
+            [
+                "## Überblick — «section» 🚀",
+                "def naïve_размер(self, x: int) -> dict[str, 'Ü']:",
+                "fn größe(&self) -> Option<&'a str> /* ✓ */ {",
+            ]
+        );
+        assert_eq!(
+            diff.deltas[2].hunks[0].header(),
+            "@@ -6,7 +6,7 @@ fn größe(&self) -> Option<&'a str> /* ✓ */ {"
+        );
+    }
+
+    #[test]
+    fn hunk_context_verbatim() {
+        use super::hunk_context;
+
+        assert_eq!(hunk_context("@@ -1 +1 @@"), "");
+        assert_eq!(hunk_context("@@ -1 +1 @@ "), "");
+        assert_eq!(
+            hunk_context("@@ -1 +1 @@  indented(a,  b)"),
+            " indented(a,  b)"
+        );
+        assert_eq!(
+            hunk_context("@@ -1 +1 @@ s = \"@@ -2 +2 @@\""),
+            "s = \"@@ -2 +2 @@\""
+        );
+        assert_eq!(hunk_context("@@ -1,2"), "");
+    }
+
+    #[test]
+    fn garbled_hunk_header() {
+        use super::{parse_hunk_header, DiffParseError};
+
+        assert_eq!(
+            parse_hunk_header("@@ -1,2 +1,x @@"),
+            Err(DiffParseError {
+                message: "Error parsing range lines",
+                token: "x".into()
+            })
+        );
+        assert_eq!(
+            parse_hunk_header("@@ -99999999999 +1 @@"),
+            Err(DiffParseError {
+                message: "Error parsing range start",
+                token: "99999999999".into()
+            })
+        );
+        assert_eq!(
+            parse_hunk_header("@@ -1,2"),
+            Err(DiffParseError {
+                message: "Hunk header should have a new range",
+                token: "@@ -1,2".into()
+            })
+        );
+        assert!(parse_hunk_header("-1 +1").is_err());
+    }
+
+    #[test]
+    fn line_numbers() {
+        let hunks = diff_content("one\ntwo\nthree\n", "one\n2\nthree\n");
+
+        assert_eq!(
+            hunks[0].line_numbers(),
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (None, Some(2)),
+                (Some(3), Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn side_by_side_rows() {
+        let hunks = diff_content(
+            "one\ntwo\nthree\nfour\nfive\n",
+            "one\n2\nthree\n3.5\n3.75\nfive\n",
+        );
+        let hunk = &hunks[0];
+
+        // -two +2, then -four with +3.5 and +3.75 beside it
+        assert_eq!(
+            hunk.side_by_side_rows(),
+            vec![
+                (Some(0), Some(0)),
+                (Some(1), Some(2)),
+                (Some(3), Some(3)),
+                (Some(4), Some(5)),
+                (None, Some(6)),
+                (Some(7), Some(7)),
+            ]
+        );
+
+        assert_eq!(hunk.matching_line(1), Some(2));
+        assert_eq!(hunk.matching_line(2), Some(1));
+        assert_eq!(hunk.matching_line(6), None);
+        assert_eq!(hunk.matching_line(7), Some(7));
+    }
+
+    #[test]
+    fn side_by_side_rows_only_removed() {
+        let hunks = diff_content("one\ntwo\nthree\n", "one\n");
+        assert_eq!(
+            hunks[0].side_by_side_rows(),
+            vec![(Some(0), Some(0)), (Some(1), None), (Some(2), None)]
+        );
+        assert_eq!(hunks[0].matching_line(1), None);
+    }
+
+    #[test]
+    fn added_and_removed_text() {
+        let hunks = diff_content("one\ntwo\nthree\n", "one\n2\nthree\nfour");
+        let hunk = &hunks[0];
+
+        assert_eq!(hunk.added_text(false), "2\nfour");
+        assert_eq!(hunk.removed_text(false), "two\n");
+        assert_eq!(hunk.added_text(true), "one\n2\nthree\nfour");
+        assert_eq!(hunk.removed_text(true), "one\ntwo\nthree\n");
+    }
+
+    // From `git diff -B -M`, with `f` rewritten and `r` renamed to `s` and edited
+    const REWRITE: &str = "\
+diff --git a/f b/f
+dissimilarity index 100%
+index 43dbe33..81dc5a4 100644
+--- a/f
++++ b/f
+@@ -1,7 +1,7 @@
+-    let value_1 = compute_something_expensive(1, 2, \"label 1\");
+-    let value_2 = compute_something_expensive(2, 4, \"label 2\");
+-    let value_3 = compute_something_expensive(3, 6, \"label 3\");
+-    let value_4 = compute_something_expensive(4, 8, \"label 4\");
+-    let value_5 = compute_something_expensive(5, 10, \"label 5\");
+-    let value_6 = compute_something_expensive(6, 12, \"label 6\");
+-    let value_7 = compute_something_expensive(7, 14, \"label 7\");
++import sys
++print(sys.argv[1].upper(), 'argument number', 1, file=sys.stderr)
++print(sys.argv[2].upper(), 'argument number', 2, file=sys.stderr)
++print(sys.argv[3].upper(), 'argument number', 3, file=sys.stderr)
++print(sys.argv[4].upper(), 'argument number', 4, file=sys.stderr)
++print(sys.argv[5].upper(), 'argument number', 5, file=sys.stderr)
++print(sys.argv[6].upper(), 'argument number', 6, file=sys.stderr)
+diff --git a/r b/s
+similarity index 68%
+rename from r
+rename to s
+index 7a28df3..ea1263c 100644
+--- a/r
++++ b/s
+@@ -1,4 +1,4 @@
+ alpha
+ beta
+ gamma
+-delta
++epsilon
+";
+
+    #[test]
+    fn rewrite_and_rename_scores() {
+        let diff = super::parse(REWRITE).unwrap();
+
+        let rewrite = &diff.deltas[0];
+        assert_eq!(rewrite.dissimilarity, Some(100));
+        assert_eq!(rewrite.similarity, None);
+        assert_eq!(rewrite.status, git2::Delta::Modified);
+        assert!(rewrite.unrecognized.is_empty());
+
+        let rename = &diff.deltas[1];
+        assert_eq!(rename.similarity, Some(68));
+        assert_eq!(rename.dissimilarity, None);
+        assert_eq!(rename.status, git2::Delta::Renamed);
+    }
+
+    #[test]
+    fn executable_bit() {
+        let diff = super::parse(
+            "diff --git a/run.sh b/run.sh\n\
+            old mode 100644\n\
+            new mode 100755\n\
+            diff --git a/run.sh b/run.sh\n\
+            old mode 100755\n\
+            new mode 100644\n\
+            diff --git a/link b/link\n\
+            old mode 100644\n\
+            new mode 120000\n",
+        )
+        .unwrap();
+
+        assert_eq!(diff.deltas[0].old_mode, Some(0o100644));
+        assert_eq!(diff.deltas[0].executable_toggle(), Some(true));
+        assert_eq!(diff.deltas[1].executable_toggle(), Some(false));
+        assert_eq!(diff.deltas[2].new_mode, Some(0o120000));
+        assert_eq!(diff.deltas[2].executable_toggle(), None);
+    }
+
+    #[test]
+    fn garbled_score() {
+        assert_eq!(
+            super::header_score("similarity index 68", "similarity index "),
+            None
+        );
+        assert_eq!(
+            super::header_score("similarity index x%", "similarity index "),
+            None
+        );
+    }
+
+    #[test]
+    fn unrecognized_header_lines() {
+        let header = "diff --git a/file b/file\n\
+            old mode 100644\n\
+            new mode 100755\n\
+            some future header 42\n\
+            index 422c2b7..0f7bc76\n\
+            --- a/file\n\
+            +++ b/file\n";
+
+        assert_eq!(
+            super::unrecognized_header_lines(header),
+            vec!["some future header 42".to_string()]
+        );
+    }
+
+    #[test]
+    fn latin1_content() {
+        let old = super::decode(b"caf\xe9\n");
+        let new = super::decode(b"caf\xe9 au lait\n");
+        assert_eq!(super::encode(&old), b"caf\xe9\n");
+
+        let hunks = diff_content(&old, &new);
+        assert!(hunks[0].lossy);
+        assert_eq!(
+            super::encode(&hunks[0].format_patch()),
+            b"header\n@@ -1 +1 @@\n-caf\xe9\n+caf\xe9 au lait\n"
+        );
+        assert_eq!(
+            hunks[0].added_text(false),
+            super::decode(b"caf\xe9 au lait\n")
+        );
+        assert_eq!(super::display_lossy(&old), "caf\u{FFFD}\n");
+
+        assert!(!diff_content("café\n", "thé\n")[0].lossy);
+    }
+
+    #[test]
+    fn whitespace_changes_ignored() {
+        let hunks = super::diff_content(
+            &config::init_test_config().unwrap(),
+            &test_delta(),
+            &super::DiffOptions::new().ignore_whitespace(true),
+            "one\ntwo\nthree\n",
+            "one\n  two\nthree 3\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hunks[0].format_patch(),
+            "header\n@@ -1,3 +1,3 @@\n one\n two\n-three\n+three 3\n"
+        );
+    }
+
+    #[test]
+    fn control_characters_shown_as_symbols() {
+        assert_eq!(
+            super::display_escaped("a\rb\tc\x1b[2J\x7f"),
+            "a\u{240D}b    c\u{241B}[2J\u{2421}"
+        );
+        // Bytes that weren't UTF-8
+        assert_eq!(
+            super::display_escaped("caf\u{10FFE9}\n"),
+            "caf\u{FFFD}\u{240A}"
+        );
+
+        let hunks = diff_content("progress 10%\rprogress 20%\n", "done\n");
+        assert_eq!(
+            hunks[0].format_patch(),
+            "header\n@@ -1 +1 @@\n-progress 10%\rprogress 20%\n+done\n"
+        );
+    }
+
+    #[test]
+    fn huge_line_cut_off_without_word_highlights() {
+        let config = std::rc::Rc::new(config::init_test_config().unwrap());
+        let huge = "word ".repeat(1_000_000);
+        let hunks = diff_content(&format!("{huge}old\n"), &format!("new {huge}\n"));
+
+        let changed = [
+            ratatui::style::Style::from(&config.style.diff_highlight.changed_old),
+            ratatui::style::Style::from(&config.style.diff_highlight.changed_new),
+        ];
+        assert!(hunks[0]
+            .content
+            .lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .all(|span| !changed.contains(&span.style)));
+
+        let diff = super::Diff {
+            deltas: vec![Delta {
+                hunks,
+                ..test_delta()
+            }],
+        };
+        let lines = crate::items::create_diff_items(std::rc::Rc::clone(&config), &diff, &0, false)
+            .map(|item| item.display.to_string())
+            .collect::<Vec<_>>();
+
+        let max_line_bytes = config.diff.max_line_bytes;
+        assert!(lines.iter().all(|line| line.len() < max_line_bytes + 100));
+        assert!(lines[2].starts_with("-word word"));
+        assert!(lines[2].ends_with("more bytes)"));
+    }
+
+    fn two_hunks(patch: &str) -> (super::Hunk, super::Hunk) {
+        let diff = super::parse(patch).unwrap();
+        let [first, second] = &diff.deltas[0].hunks[..] else {
+            panic!("Expected two hunks");
+        };
+        (first.as_ref().clone(), second.as_ref().clone())
+    }
+
+    #[test]
+    fn merge_adjacent_hunks() {
+        let (first, second) = two_hunks(
+            "\
+diff --git a/file b/file
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+ a
+-b
++B
+@@ -3,2 +3,3 @@
+ c
++new
+ d
+",
+        );
+
+        let merged = first.try_merge(&second).unwrap();
+        assert_eq!(merged.header(), "@@ -1,4 +1,5 @@");
+        assert_eq!(merged.content.to_string(), " a\n-b\n+B\n c\n+new\n d");
+    }
+
+    #[test]
+    fn merge_overlapping_hunks() {
+        let (first, second) = two_hunks(
+            "\
+diff --git a/file b/file
+--- a/file
++++ b/file
+@@ -1,3 +1,2 @@
+-a
+ b
+ c
+@@ -2,3 +1,3 @@
+ b
+ c
+-d
++D
+",
+        );
+
+        let merged = first.try_merge(&second).unwrap();
+        assert_eq!(merged.header(), "@@ -1,4 +1,3 @@");
+        assert_eq!(merged.content.to_string(), "-a\n b\n c\n-d\n+D");
+    }
+
+    #[test]
+    fn no_merge_of_distant_hunks() {
+        let (first, second) = two_hunks(
+            "\
+diff --git a/file b/file
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+ a
+-b
++B
+@@ -10,2 +10,2 @@
+ j
+-k
++K
+",
+        );
+
+        assert!(first.try_merge(&second).is_none());
+        assert!(second.try_merge(&first).is_none());
+
+        let other_file = super::Hunk {
+            file_header: "diff --git a/other b/other\n".into(),
+            ..first.clone()
+        };
+        assert!(first.try_merge(&other_file).is_none());
+    }
+
+    fn test_delta() -> Delta {
+        Delta {
+            file_header: "header\n".into(),
+            new_file: "new_file".into(),
+            old_file: "old_file".into(),
+            hunks: vec![],
+            status: git2::Delta::Modified,
+            similarity: None,
+            dissimilarity: None,
+            old_mode: None,
+            new_mode: None,
+            unrecognized: vec![],
+            new_version: super::NewVersion::Unknown,
+            preview: vec![],
+            sparse_excluded: false,
+            submodule: None,
+        }
+    }
+
+    fn diff_content(old_content: &str, new_content: &str) -> Vec<std::rc::Rc<super::Hunk>> {
+        super::diff_content(
+            &config::init_test_config().unwrap(),
+            &test_delta(),
+            &super::DiffOptions::new(),
             old_content,
             new_content,
         )
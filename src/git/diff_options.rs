@@ -0,0 +1,213 @@
+use git2::DiffFindOptions;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::PathBuf};
+
+/// Like `git diff --diff-algorithm`. Gitu computes hunks with the `similar` crate,
+/// which has no histogram algorithm, so that one falls back to patience and is
+/// left out when switching algorithms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffAlgorithm {
+    Myers,
+    Minimal,
+    #[default]
+    Patience,
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    pub(crate) fn next(self) -> Self {
+        match self {
+            DiffAlgorithm::Myers => DiffAlgorithm::Minimal,
+            DiffAlgorithm::Minimal => DiffAlgorithm::Patience,
+            DiffAlgorithm::Patience | DiffAlgorithm::Histogram => DiffAlgorithm::Myers,
+        }
+    }
+
+    pub(crate) fn similar(self) -> similar::Algorithm {
+        match self {
+            DiffAlgorithm::Myers => similar::Algorithm::Myers,
+            DiffAlgorithm::Minimal => similar::Algorithm::Lcs,
+            DiffAlgorithm::Patience | DiffAlgorithm::Histogram => similar::Algorithm::Patience,
+        }
+    }
+}
+
+impl Display for DiffAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DiffAlgorithm::Myers => "myers",
+            DiffAlgorithm::Minimal => "minimal",
+            DiffAlgorithm::Patience => "patience",
+            DiffAlgorithm::Histogram => "histogram",
+        })
+    }
+}
+
+/// Settings for producing a `Diff`, these correspond to `git diff` flags.
+///
+/// Only the files affected are decided by libgit2, hunks are computed by Gitu itself.
+#[derive(Clone, Debug)]
+pub(crate) struct DiffOptions {
+    pub(crate) context: u32,
+    pub(crate) algorithm: DiffAlgorithm,
+    pub(crate) ignore_whitespace: bool,
+    pub(crate) renames: bool,
+    pub(crate) cached: bool,
+    pub(crate) pathspec: Option<PathBuf>,
+    /// Whether the status shows how many lines each file changes, like `git diff --stat`.
+    pub(crate) stat: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            context: 3,
+            algorithm: DiffAlgorithm::default(),
+            ignore_whitespace: false,
+            renames: false,
+            cached: false,
+            pathspec: None,
+            stat: false,
+        }
+    }
+}
+
+impl DiffOptions {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn context(mut self, lines: u32) -> Self {
+        self.context = lines;
+        self
+    }
+
+    pub(crate) fn algorithm(mut self, algorithm: DiffAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub(crate) fn ignore_whitespace(mut self, ignore: bool) -> Self {
+        self.ignore_whitespace = ignore;
+        self
+    }
+
+    pub(crate) fn renames(mut self, renames: bool) -> Self {
+        self.renames = renames;
+        self
+    }
+
+    /// Diff the index against `HEAD`, rather than the working tree against the index.
+    pub(crate) fn cached(mut self, cached: bool) -> Self {
+        self.cached = cached;
+        self
+    }
+
+    pub(crate) fn pathspec(mut self, path: PathBuf) -> Self {
+        self.pathspec = Some(path);
+        self
+    }
+
+    pub(crate) fn stat(mut self, stat: bool) -> Self {
+        self.stat = stat;
+        self
+    }
+
+    /// The algorithm to point out next to diffs, when it isn't the default one.
+    pub(crate) fn shown_algorithm(&self) -> Option<DiffAlgorithm> {
+        (self.algorithm != DiffAlgorithm::default()).then_some(self.algorithm)
+    }
+
+    /// The equivalent `git diff` invocation.
+    pub(crate) fn args(&self) -> Vec<String> {
+        let mut args = vec!["diff".to_string()];
+
+        if self.cached {
+            args.push("--cached".into());
+        }
+        if self.context != 3 {
+            args.push(format!("-U{}", self.context));
+        }
+        if self.algorithm != DiffAlgorithm::default() {
+            args.push(format!("--diff-algorithm={}", self.algorithm));
+        }
+        if self.ignore_whitespace {
+            args.push("-w".into());
+        }
+        if self.renames {
+            args.push("-M".into());
+        }
+        if self.stat {
+            args.push("--patch-with-stat".into());
+        }
+        if let Some(path) = &self.pathspec {
+            args.push("--".into());
+            args.push(path.to_string_lossy().to_string());
+        }
+
+        args
+    }
+
+    pub(crate) fn to_git2(&self) -> git2::DiffOptions {
+        let mut diff_options = git2::DiffOptions::new();
+        diff_options.patience(self.algorithm.similar() == similar::Algorithm::Patience);
+        diff_options.minimal(self.algorithm == DiffAlgorithm::Minimal);
+        // Patches are fed back to `git apply` (which defaults to `-p1`) when staging,
+        // so the prefixes can't follow configs like `diff.noprefix`.
+        diff_options.old_prefix("a/");
+        diff_options.new_prefix("b/");
+        diff_options.context_lines(self.context);
+        diff_options.ignore_whitespace(self.ignore_whitespace);
+
+        if let Some(path) = &self.pathspec {
+            diff_options.pathspec(path).disable_pathspec_match(true);
+        }
+
+        diff_options
+    }
+
+    pub(crate) fn find_similar(&self, diff: &mut git2::Diff) -> Result<(), git2::Error> {
+        if self.renames {
+            diff.find_similar(Some(&mut DiffFindOptions::new().renames(true)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffOptions;
+
+    #[test]
+    fn default_args() {
+        assert_eq!(DiffOptions::new().args(), vec!["diff"]);
+    }
+
+    #[test]
+    fn all_args() {
+        let opts = DiffOptions::new()
+            .context(5)
+            .algorithm(super::DiffAlgorithm::Histogram)
+            .ignore_whitespace(true)
+            .renames(true)
+            .cached(true)
+            .pathspec("src/main.rs".into())
+            .stat(true);
+
+        assert_eq!(
+            opts.args(),
+            vec![
+                "diff",
+                "--cached",
+                "-U5",
+                "--diff-algorithm=histogram",
+                "-w",
+                "-M",
+                "--patch-with-stat",
+                "--",
+                "src/main.rs"
+            ]
+        );
+    }
+}
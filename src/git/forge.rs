@@ -0,0 +1,124 @@
+use crate::Res;
+use git2::Repository;
+
+/// Where a repository lives on a forge, e.g. `github.com` and `org/repo`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ForgeRemote {
+    pub host: String,
+    pub repo: String,
+}
+
+/// Parses https, `ssh://` and scp-like (`git@host:org/repo.git`) remote URLs.
+pub(crate) fn parse_remote_url(url: &str) -> Option<ForgeRemote> {
+    let (host, repo) = if let Some((_scheme, rest)) = url.split_once("://") {
+        let (authority, repo) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        let host = host.split(':').next()?;
+        (host, repo)
+    } else {
+        let (authority, repo) = url.split_once(':')?;
+        (authority.rsplit('@').next()?, repo)
+    };
+
+    let repo = repo.trim_end_matches('/');
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+
+    if host.is_empty() || repo.is_empty() {
+        return None;
+    }
+
+    Some(ForgeRemote {
+        host: host.to_string(),
+        repo: repo.to_string(),
+    })
+}
+
+/// Where the `origin` remote lives.
+pub(crate) fn origin(repo: &Repository) -> Res<ForgeRemote> {
+    let url = super::remote_url(repo, "origin")?;
+    parse_remote_url(&url).ok_or_else(|| format!("Couldn't parse remote url '{}'", url).into())
+}
+
+/// Fills in the `{host}`, `{repo}`, `{rev}`, `{path}` and `{line}` placeholders of `template`.
+pub(crate) fn permalink(
+    template: &str,
+    remote: &ForgeRemote,
+    rev: &str,
+    path: &str,
+    line: u32,
+) -> String {
+    fill(
+        template,
+        remote,
+        &[("rev", rev), ("path", path), ("line", &line.to_string())],
+    )
+}
+
+/// Fills in the `{host}` and `{repo}` placeholders of `template`, and the named others.
+pub(crate) fn fill(template: &str, remote: &ForgeRemote, placeholders: &[(&str, &str)]) -> String {
+    placeholders.iter().fold(
+        template
+            .replace("{host}", &remote.host)
+            .replace("{repo}", &remote.repo),
+        |url, (name, value)| url.replace(&format!("{{{}}}", name), value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(host: &str, repo: &str) -> Option<ForgeRemote> {
+        Some(ForgeRemote {
+            host: host.into(),
+            repo: repo.into(),
+        })
+    }
+
+    #[test]
+    fn parse_scp_like() {
+        assert_eq!(
+            parse_remote_url("git@github.com:org/repo.git"),
+            remote("github.com", "org/repo")
+        );
+    }
+
+    #[test]
+    fn parse_ssh() {
+        assert_eq!(
+            parse_remote_url("ssh://git@gitlab.example.com:2222/group/sub/repo.git"),
+            remote("gitlab.example.com", "group/sub/repo")
+        );
+    }
+
+    #[test]
+    fn parse_https() {
+        assert_eq!(
+            parse_remote_url("https://user@github.com/org/repo"),
+            remote("github.com", "org/repo")
+        );
+        assert_eq!(
+            parse_remote_url("https://github.com/org/repo.git/"),
+            remote("github.com", "org/repo")
+        );
+    }
+
+    #[test]
+    fn parse_local_path() {
+        assert_eq!(parse_remote_url("/srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn fill_template() {
+        assert_eq!(
+            permalink(
+                "https://{host}/{repo}/blob/{rev}/{path}#L{line}",
+                &remote("github.com", "org/repo").unwrap(),
+                "abc123",
+                "src/main.rs",
+                42
+            ),
+            "https://github.com/org/repo/blob/abc123/src/main.rs#L42"
+        );
+    }
+}
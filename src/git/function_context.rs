@@ -0,0 +1,238 @@
+use regex::Regex;
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+/// Git cuts the function context of a hunk header off at this many bytes.
+const MAX_LEN: usize = 80;
+
+/// Languages that git has a built-in diff driver for, picked by file extension here.
+/// Without one, git takes any line starting with a letter, `_` or `$` as the context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Lang {
+    Default,
+    Cpp,
+    Go,
+    Java,
+    Markdown,
+    Python,
+    Ruby,
+    Rust,
+}
+
+/// The `xfuncname` patterns of git's `userdiff.c`, an entry starting with `!` rejects the lines it matches.
+/// If a pattern has a group, the function context is what that captured.
+const PATTERNS: &[(Lang, &[&str])] = &[
+    (
+        Lang::Cpp,
+        &[
+            r"!^[ \t]*[A-Za-z_][A-Za-z_0-9]*:[[:space:]]*($|/[/*])",
+            r"^((::[[:space:]]*)?[A-Za-z_].*)$",
+        ],
+    ),
+    (
+        Lang::Go,
+        &[
+            r"^[ \t]*(func[ \t]*.*(\{[ \t]*)?)$",
+            r"^[ \t]*(type[ \t].*(struct|interface)[ \t]*(\{[ \t]*)?)$",
+        ],
+    ),
+    (
+        Lang::Java,
+        &[
+            r"!^[ \t]*(catch|do|for|if|instanceof|new|return|switch|throw|while)",
+            r"^[ \t]*(([a-z]+[ \t]+)*(class|enum|interface|record)[ \t]+.*)$",
+            r"^[ \t]*(([A-Za-z_<>&\]\[][\]\[?&<>.,A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\([^;]*)$",
+        ],
+    ),
+    (Lang::Markdown, &[r"^ {0,3}#{1,6}[ \t].*"]),
+    (
+        Lang::Python,
+        &[r"^[ \t]*((class|(async[ \t]+)?def)[ \t].*)$"],
+    ),
+    (Lang::Ruby, &[r"^[ \t]*((class|module|def)[ \t].*)$"]),
+    (
+        Lang::Rust,
+        &[
+            r#"^[\t ]*((pub(\([^\)]+\))?[\t ]+)?((async|const|unsafe|extern([\t ]+"[^"]+"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[< \t]+[^;]*)$"#,
+        ],
+    ),
+];
+
+struct Pattern {
+    negate: bool,
+    regex: Regex,
+}
+
+impl Lang {
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp") => Lang::Cpp,
+            Some("go") => Lang::Go,
+            Some("java") => Lang::Java,
+            Some("md" | "markdown") => Lang::Markdown,
+            Some("py") => Lang::Python,
+            Some("rb") => Lang::Ruby,
+            Some("rs") => Lang::Rust,
+            _ => Lang::Default,
+        }
+    }
+
+    /// What of `line` to show as the function context, if it starts a function or the like.
+    fn function_name(self, line: &str) -> Option<&str> {
+        let Some(patterns) = patterns().get(&self) else {
+            return line
+                .starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+                .then_some(line);
+        };
+
+        for pattern in patterns {
+            let Some(captures) = pattern.regex.captures(line) else {
+                continue;
+            };
+
+            if pattern.negate {
+                return None;
+            }
+
+            let found = captures.get(1).or_else(|| captures.get(0))?;
+            return Some(found.as_str());
+        }
+
+        None
+    }
+}
+
+fn patterns() -> &'static HashMap<Lang, Vec<Pattern>> {
+    static PATTERNS_BY_LANG: OnceLock<HashMap<Lang, Vec<Pattern>>> = OnceLock::new();
+
+    PATTERNS_BY_LANG.get_or_init(|| {
+        PATTERNS
+            .iter()
+            .map(|&(lang, patterns)| {
+                let patterns = patterns
+                    .iter()
+                    .map(|pattern| {
+                        let (negate, pattern) = match pattern.strip_prefix('!') {
+                            Some(pattern) => (true, pattern),
+                            None => (false, *pattern),
+                        };
+                        let regex =
+                            Regex::new(pattern).expect("Function context patterns are valid");
+
+                        Pattern { negate, regex }
+                    })
+                    .collect();
+
+                (lang, patterns)
+            })
+            .collect()
+    })
+}
+
+/// Like git does for the `@@` line of a hunk starting at `line` (counting from 1),
+/// finds the closest line above it that starts a function, class or section.
+pub(crate) fn enclosing_context(file_content: &str, line: u32, lang: Lang) -> Option<String> {
+    let above = file_content
+        .lines()
+        .take((line as usize).saturating_sub(1))
+        .collect::<Vec<_>>();
+
+    above
+        .into_iter()
+        .rev()
+        .find_map(|line| lang.function_name(line.trim_end_matches('\r')))
+        .map(|name| truncate(name, MAX_LEN).trim_end().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn truncate(text: &str, max_len: usize) -> &str {
+    let end = (0..=max_len.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enclosing_context, Lang};
+    use std::path::Path;
+
+    const RUST: &str = "\
+use std::fmt;
+
+pub(crate) fn size(&self) -> usize {
+    let a = 1;
+    let b = 2;
+    a + b
+}
+";
+
+    #[test]
+    fn rust_function() {
+        assert_eq!(
+            enclosing_context(RUST, 5, Lang::Rust).as_deref(),
+            Some("pub(crate) fn size(&self) -> usize {")
+        );
+        assert_eq!(enclosing_context(RUST, 3, Lang::Rust), None);
+        assert_eq!(enclosing_context(RUST, 1, Lang::Rust), None);
+    }
+
+    #[test]
+    fn default_heuristic() {
+        // Like git without a diff driver, the closest line starting with a letter
+        assert_eq!(
+            enclosing_context(RUST, 5, Lang::Default).as_deref(),
+            Some("pub(crate) fn size(&self) -> usize {")
+        );
+        assert_eq!(
+            enclosing_context("  indented\n\nstart\n  x\n  y\n", 5, Lang::Default).as_deref(),
+            Some("start")
+        );
+    }
+
+    #[test]
+    fn negated_patterns() {
+        let cpp = "int main(void)\n{\nlabel:\n    return 0;\n}\n";
+        assert_eq!(
+            enclosing_context(cpp, 5, Lang::Cpp).as_deref(),
+            Some("int main(void)")
+        );
+
+        let java = "class Greeter {\n    void greet() {\n        if (x) {\n            y();\n";
+        assert_eq!(
+            enclosing_context(java, 4, Lang::Java).as_deref(),
+            Some("void greet() {")
+        );
+    }
+
+    #[test]
+    fn python_and_markdown() {
+        let python = "class A:\n    def f(self):\n        pass\n        return 1\n";
+        assert_eq!(
+            enclosing_context(python, 4, Lang::Python).as_deref(),
+            Some("def f(self):")
+        );
+
+        let markdown = "# Title\n\nText\n## Section\n\nMore text\n";
+        assert_eq!(
+            enclosing_context(markdown, 6, Lang::Markdown).as_deref(),
+            Some("## Section")
+        );
+    }
+
+    #[test]
+    fn truncated_like_git() {
+        let long = format!("fn {}() {{\n    x\n", "ü".repeat(50));
+        let context = enclosing_context(&long, 2, Lang::Rust).unwrap();
+        assert!(context.len() <= 80);
+        assert!(context.starts_with("fn üü"));
+    }
+
+    #[test]
+    fn lang_by_extension() {
+        assert_eq!(Lang::from_path(Path::new("src/main.rs")), Lang::Rust);
+        assert_eq!(Lang::from_path(Path::new("README.md")), Lang::Markdown);
+        assert_eq!(Lang::from_path(Path::new("Makefile")), Lang::Default);
+    }
+}
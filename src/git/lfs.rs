@@ -0,0 +1,112 @@
+/// What Git LFS commits in place of a large file (see https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md):
+///
+/// ```text
+/// version https://git-lfs.github.com/spec/v1
+/// oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+/// size 12345
+/// ```
+#[derive(Debug, PartialEq)]
+pub(crate) struct LfsPointer {
+    pub oid: String,
+    pub size: u64,
+}
+
+impl LfsPointer {
+    /// Returns `None` unless all of `text` is a pointer.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if !lines.next()?.starts_with("version https://git-lfs.") {
+            return None;
+        }
+
+        let mut oid = None;
+        let mut size = None;
+
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            if !key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+            {
+                return None;
+            }
+
+            match key {
+                "oid" => oid = Some(value.strip_prefix("sha256:")?.to_string()),
+                "size" => size = Some(value.parse().ok()?),
+                _ => (),
+            }
+        }
+
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+}
+
+/// Formats a size the way git-lfs does, e.g. `1.2 MB` or `130 MB`.
+pub(crate) fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+    if size < 1000 {
+        return format!("{} B", size);
+    }
+
+    let mut value = size as f64 / 1000.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next;
+    }
+
+    if value < 100.0 {
+        format!("{:.1} {}", value, unit)
+    } else {
+        format!("{:.0} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_size, LfsPointer};
+
+    #[test]
+    fn parse_pointer() {
+        let pointer = LfsPointer::parse(
+            "version https://git-lfs.github.com/spec/v1\n\
+            oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+            size 123000000\n",
+        );
+        assert_eq!(
+            pointer,
+            Some(LfsPointer {
+                oid: "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393".into(),
+                size: 123_000_000,
+            })
+        );
+
+        assert_eq!(LfsPointer::parse("version 1\nsize 12\n"), None);
+        assert_eq!(
+            LfsPointer::parse("version https://git-lfs.github.com/spec/v1\nsize 12\n"),
+            None
+        );
+        assert_eq!(
+            LfsPointer::parse(
+                "version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize 12\nSome text\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(1_234_567), "1.2 MB");
+        assert_eq!(format_size(130_000_000), "130 MB");
+        assert_eq!(format_size(2_500_000_000), "2.5 GB");
+    }
+}
@@ -1,30 +1,140 @@
-use git2::{DiffFindOptions, Repository};
-use itertools::Itertools;
+use git2::Repository;
 
-use self::{commit::Commit, diff::Diff, merge_status::MergeStatus, rebase_status::RebaseStatus};
-use crate::{config::Config, git2_opts, Res};
+use self::{
+    am_status::AmStatus,
+    apply::ApplyResult,
+    bisect_status::BisectStatus,
+    blame::Blame,
+    commit::Commit,
+    config_list::ConfigEntry,
+    diff::{Diff, RangeDiff},
+    diff_options::DiffOptions,
+    merge_status::MergeStatus,
+    rebase_status::RebaseStatus,
+    status::Status,
+    worktree::Worktree,
+};
+use crate::{config::Config, Res};
 use std::{
     fs,
-    path::Path,
-    process::Command,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
     str::{self},
+    sync::OnceLock,
 };
 
+pub(crate) mod am_status;
+pub(crate) mod apply;
+pub(crate) mod bisect_status;
+pub(crate) mod blame;
 pub(crate) mod commit;
-pub(crate) mod diff;
+pub(crate) mod config_list;
+pub(crate) mod conflict;
+pub mod diff;
+pub(crate) mod diff_options;
+pub(crate) mod forge;
+pub(crate) mod function_context;
+pub(crate) mod lfs;
 pub(crate) mod merge_status;
 pub(crate) mod rebase_status;
 pub(crate) mod remote;
+pub(crate) mod sparse;
+pub(crate) mod status;
+pub(crate) mod version;
+pub(crate) mod whitespace;
+pub(crate) mod worktree;
+
+static GIT_BINARY: OnceLock<PathBuf> = OnceLock::new();
+static GIT_CONFIG_OVERRIDES: OnceLock<Vec<String>> = OnceLock::new();
 
 // TODO Use only plumbing commands
 
+/// Sets the git executable spawned by `command()`, after checking that it runs.
+pub(crate) fn init_binary(path: PathBuf) -> Res<()> {
+    let out = Command::new(&path)
+        .arg("--version")
+        .output()
+        .map_err(|err| format!("Couldn't run git binary '{}': {}", path.display(), err))?;
+
+    if !out.status.success() {
+        return Err(format!("Couldn't run git binary '{}'", path.display()).into());
+    }
+
+    let version_output = str::from_utf8(&out.stdout)?;
+    log::info!("Using {} ({})", version_output.trim(), path.display());
+    version::init(version_output);
+
+    let _ = GIT_BINARY.set(path);
+    Ok(())
+}
+
+/// Sets `key=value` pairs that `command()` passes to git as `-c key=value`, for this session only.
+/// They don't affect what Gitu reads via libgit2, like its own diffs.
+pub(crate) fn init_config_overrides(overrides: Vec<String>) -> Res<()> {
+    for config_override in &overrides {
+        validate_config_override(config_override)?;
+    }
+
+    let _ = GIT_CONFIG_OVERRIDES.set(overrides);
+    Ok(())
+}
+
+fn validate_config_override(config_override: &str) -> Res<()> {
+    let is_key = |key: &str| {
+        key.rsplit_once('.').is_some_and(|(section, name)| {
+            !section.is_empty()
+                && name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    };
+
+    match config_override.split_once('=') {
+        Some((key, _)) if is_key(key) => Ok(()),
+        _ => Err(format!(
+            "Malformed git config override '{}', expected key=value (e.g. user.email=me@example.com)",
+            config_override
+        )
+        .into()),
+    }
+}
+
+/// A git `Command`, using the configured binary (or `git` found via `PATH`).
+pub(crate) fn command() -> Command {
+    let mut cmd = match GIT_BINARY.get() {
+        Some(path) => Command::new(path),
+        None => Command::new("git"),
+    };
+
+    for config_override in GIT_CONFIG_OVERRIDES.get().into_iter().flatten() {
+        cmd.args(["-c", config_override]);
+    }
+
+    cmd
+}
+
+/// Opens the repository that `git` run as `cmd` finds, with its working tree. Unlike assuming the working tree holds
+/// a `.git`, this also works when `cmd` has `GIT_DIR` and `GIT_WORK_TREE` set, like for dotfiles kept in a home dir.
+pub(crate) fn open_repo(mut cmd: Command) -> Res<Repository> {
+    version::show_toplevel_and_git_dir(&mut cmd);
+    let out = cmd.output()?;
+
+    let stdout = str::from_utf8(&out.stdout)?;
+    let (true, Some((work_tree, git_dir))) =
+        (out.status.success(), stdout.trim_end().split_once('\n'))
+    else {
+        return Err("No .git found in the current directory".into());
+    };
+
+    let repo = Repository::open(git_dir)?;
+    repo.set_workdir(Path::new(work_tree), false)?;
+    Ok(repo)
+}
+
 pub(crate) fn rebase_status(repo: &Repository) -> Res<Option<RebaseStatus>> {
     let dir = repo.workdir().expect("No workdir");
-    let mut rebase_onto_file = dir.to_path_buf();
-    rebase_onto_file.push(".git/rebase-merge/onto");
-
-    let mut rebase_head_name_file = dir.to_path_buf();
-    rebase_head_name_file.push(".git/rebase-merge/head-name");
+    let rebase_onto_file = repo.path().join("rebase-merge/onto");
+    let rebase_head_name_file = repo.path().join("rebase-merge/head-name");
 
     match fs::read_to_string(&rebase_onto_file) {
         Ok(content) => {
@@ -50,10 +160,13 @@ pub(crate) fn rebase_status(repo: &Repository) -> Res<Option<RebaseStatus>> {
     }
 }
 
+pub(crate) fn am_status(repo: &Repository) -> Option<AmStatus> {
+    AmStatus::read(repo.path())
+}
+
 pub(crate) fn merge_status(repo: &Repository) -> Res<Option<MergeStatus>> {
     let dir = repo.workdir().expect("No workdir");
-    let mut merge_head_file = dir.to_path_buf();
-    merge_head_file.push(".git/MERGE_HEAD");
+    let merge_head_file = repo.path().join("MERGE_HEAD");
 
     match fs::read_to_string(&merge_head_file) {
         Ok(content) => {
@@ -79,8 +192,7 @@ pub(crate) struct RevertStatus {
 
 pub(crate) fn revert_status(repo: &Repository) -> Res<Option<RevertStatus>> {
     let dir = repo.workdir().expect("No workdir");
-    let mut revert_head_file = dir.to_path_buf();
-    revert_head_file.push(".git/REVERT_HEAD");
+    let revert_head_file = repo.path().join("REVERT_HEAD");
 
     match fs::read_to_string(&revert_head_file) {
         Ok(content) => {
@@ -100,9 +212,374 @@ pub(crate) fn revert_status(repo: &Repository) -> Res<Option<RevertStatus>> {
     }
 }
 
+/// An operation that was started, but not yet concluded or aborted.
+pub(crate) struct InProgress {
+    pub name: &'static str,
+    pub abort_args: &'static [&'static str],
+}
+
+pub(crate) fn operation_in_progress(repo: &Repository) -> Option<InProgress> {
+    let dir = repo.path();
+
+    [
+        ("rebase-merge", "rebase", &["rebase", "--abort"][..]),
+        ("rebase-apply/applying", "git am", &["am", "--abort"]),
+        ("rebase-apply", "rebase", &["rebase", "--abort"]),
+        ("MERGE_HEAD", "merge", &["merge", "--abort"]),
+        (
+            "CHERRY_PICK_HEAD",
+            "cherry-pick",
+            &["cherry-pick", "--abort"],
+        ),
+        ("REVERT_HEAD", "revert", &["revert", "--abort"]),
+    ]
+    .into_iter()
+    .find(|(file, _, _)| dir.join(file).exists())
+    .map(|(_, name, abort_args)| InProgress { name, abort_args })
+}
+
+pub(crate) fn bisect_status(repo: &Repository) -> Res<Option<BisectStatus>> {
+    let dir = repo.workdir().expect("No workdir");
+    let bisect_start_file = repo.path().join("BISECT_START");
+
+    match fs::read_to_string(&bisect_start_file) {
+        Ok(content) => {
+            let start = content.trim().to_string();
+            Ok(Some(BisectStatus {
+                start: branch_name(dir, &start)?.unwrap_or(start),
+                steps: bisect_steps(dir)?,
+            }))
+        }
+        Err(err) => {
+            log::warn!(
+                "Couldn't read {}, due to {}",
+                bisect_start_file.to_string_lossy(),
+                err
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Estimated steps left, only known once both a good and a bad revision are marked.
+fn bisect_steps(dir: &Path) -> Res<Option<u32>> {
+    let out = command()
+        .args(["rev-list", "--bisect-vars", "--bisect"])
+        .current_dir(dir)
+        .output()?;
+
+    if !out.status.success() {
+        return Ok(None);
+    }
+
+    Ok(str::from_utf8(&out.stdout)?
+        .lines()
+        .find_map(|line| line.strip_prefix("bisect_steps="))
+        .and_then(|steps| steps.parse().ok()))
+}
+
+/// All settings git sees in `repo`, in the order git reads them: system, global, then local ones.
+pub(crate) fn config_list(repo: &Repository) -> Res<Vec<ConfigEntry>> {
+    version::require(version::Feature::ConfigShowScope)?;
+    let out = command()
+        .args(["config", "--list", "--show-scope", "--show-origin", "-z"])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    Ok(config_list::parse_config_list(str::from_utf8(&out.stdout)?))
+}
+
+pub(crate) fn worktrees(repo: &Repository) -> Res<Vec<Worktree>> {
+    let out = command()
+        .args(["worktree", "list", "--porcelain"])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?
+        .stdout;
+
+    Ok(worktree::parse_worktree_list(str::from_utf8(&out)?))
+}
+
+pub(crate) fn status(repo: &Repository) -> Res<Status> {
+    let out = command()
+        .args(["status", "--porcelain=v2", "--branch", "-z"])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    Ok(status::parse_status_porcelain_v2(str::from_utf8(
+        &out.stdout,
+    )?))
+}
+
+/// Runs `git range-diff` with `ranges` being e.g. `["main@{1}...main"]` or `["base", "old", "new"]`.
+pub(crate) fn range_diff(repo: &Repository, ranges: &[&str]) -> Res<RangeDiff> {
+    version::require(version::Feature::RangeDiff)?;
+    let out = command()
+        .args(["range-diff", "--no-color"])
+        .args(ranges)
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    Ok(RangeDiff::parse(str::from_utf8(&out.stdout)?)?)
+}
+
+/// Runs `git diff <from>..<to>`, comparing two commits regardless of the working tree.
+pub(crate) fn diff_refs(repo: &Repository, from: &str, to: &str) -> Res<Diff> {
+    let mut cmd = command();
+    cmd.args(["diff", "--no-color", "--no-ext-diff"]);
+    version::diff_paths_from_root(&mut cmd);
+
+    let out = cmd
+        .arg(format!("{from}..{to}"))
+        .arg("--")
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    Ok(diff::parse(&diff::decode(&out.stdout))?)
+}
+
+/// A remote-tracking branch that has `commit`, like `origin/main`, if it was pushed anywhere.
+pub(crate) fn pushed_to(repo: &Repository, commit: git2::Oid) -> Res<Option<String>> {
+    for reference in repo.references_glob("refs/remotes/*")? {
+        let reference = reference?;
+        let Some(tip) = reference.target() else {
+            continue;
+        };
+
+        if tip == commit || repo.graph_descendant_of(tip, commit)? {
+            return Ok(reference.shorthand().map(str::to_string));
+        }
+    }
+
+    Ok(None)
+}
+
+/// A path given on the command line, relative to the current directory, as one within the work tree.
+pub(crate) fn path_in_work_tree(repo: &Repository, path: &Path) -> Res<PathBuf> {
+    let workdir = repo.workdir().expect("No workdir").canonicalize()?;
+    let absolute = std::path::absolute(path)?;
+
+    // A deleted file can't be resolved, but the directory it was in can
+    let resolved = match (absolute.parent(), absolute.file_name()) {
+        (Some(dir), Some(name)) => dir.canonicalize().map(|dir| dir.join(name)),
+        _ => absolute.canonicalize(),
+    }
+    .map_err(|_| format!("No such file: {}", path.display()))?;
+
+    match resolved.strip_prefix(&workdir) {
+        Ok(relative) if !relative.as_os_str().is_empty() => Ok(relative.to_path_buf()),
+        _ => Err(format!("{} isn't a file in the repository", path.display()).into()),
+    }
+}
+
+/// The best common ancestor of `HEAD` and `target`, like `git merge-base HEAD <target>`.
+pub(crate) fn merge_base(repo: &Repository, target: &str) -> Res<git2::Oid> {
+    let target_commit = repo
+        .revparse_single(target)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| format!("No such ref: {target}"))?;
+    let head = repo.head()?.peel_to_commit()?;
+
+    repo.merge_base(head.id(), target_commit.id())
+        .map_err(|_| format!("HEAD has no merge base with {target}").into())
+}
+
+/// Runs `git stash show -p`, with what `--include-untracked` stashed as added files.
+/// The untracked files are kept in a third parent of the stash commit, if any were.
+/// Before git 2.32 `git stash show` can't show them, so they're left out.
+pub(crate) fn stash_show(repo: &Repository, stash: &str) -> Res<Diff> {
+    let has_untracked = repo
+        .revparse_single(stash)?
+        .peel_to_commit()?
+        .parent_count()
+        > 2;
+
+    let mut cmd = command();
+    cmd.args(["stash", "show", "--patch", "--no-color", "--no-ext-diff"]);
+    version::diff_paths_from_root(&mut cmd);
+    if has_untracked {
+        version::stash_show_untracked(&mut cmd);
+    }
+
+    let out = cmd
+        .arg(stash)
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    Ok(diff::parse(&diff::decode(&out.stdout))?)
+}
+
+/// Tries `git apply --cached --reject` on a scratch copy of the index, to find out which hunks of `patch` apply.
+/// The real index is left alone, and rejected hunks are written to a directory that's removed afterwards.
+pub(crate) fn check_apply_cached(
+    repo: &Repository,
+    patch: &[u8],
+    args: &[&str],
+) -> Res<ApplyResult> {
+    let scratch = repo.path().join("gitu-apply-check");
+    fs::create_dir_all(&scratch)?;
+
+    let result = (|| {
+        let index = scratch.join("index");
+        if repo.path().join("index").exists() {
+            fs::copy(repo.path().join("index"), &index)?;
+        }
+
+        let mut child = command()
+            .args(["apply", "--cached", "--reject", "--verbose"])
+            .args(args)
+            .env("GIT_DIR", repo.path())
+            .env("GIT_WORK_TREE", &scratch)
+            .env("GIT_INDEX_FILE", &index)
+            .current_dir(&scratch)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        child.stdin.take().unwrap().write_all(patch)?;
+        let out = child.wait_with_output()?;
+        Ok(ApplyResult::parse(&String::from_utf8_lossy(&out.stderr)))
+    })();
+
+    fs::remove_dir_all(&scratch)?;
+    result
+}
+
+/// Files with unresolved conflicts in the index.
+pub(crate) fn conflicted_files(repo: &Repository) -> Res<Vec<PathBuf>> {
+    let mut index = repo.index()?;
+    index.read(false)?;
+
+    let conflicted = index
+        .conflicts()?
+        .map(|conflict| {
+            let conflict = conflict?;
+            let entry = conflict
+                .our
+                .or(conflict.their)
+                .or(conflict.ancestor)
+                .ok_or("Conflict without index entries")?;
+            Ok(PathBuf::from(
+                String::from_utf8_lossy(&entry.path).into_owned(),
+            ))
+        })
+        .collect();
+
+    conflicted
+}
+
+/// The short names of all branches, remote branches and tags, to complete prompts with.
+pub(crate) fn ref_names(repo: &Repository) -> Res<Vec<String>> {
+    let mut names = vec!["HEAD".to_string()];
+
+    for reference in repo.references()? {
+        if let Some(name) = reference?.shorthand() {
+            names.push(name.to_string());
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Who made the last thousand commits, as `Name <email>`, the most active first.
+pub(crate) fn recent_contributors(repo: &Repository) -> Res<Vec<String>> {
+    if repo.head().is_err() {
+        return Ok(vec![]);
+    }
+
+    let out = command()
+        .args(["shortlog", "-sne", "--max-count=1000", "HEAD"])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?
+        .stdout;
+
+    Ok(str::from_utf8(&out)?
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(_count, contributor)| contributor.to_string())
+        .collect())
+}
+
+/// Blames a single line of `path`, either as it is in the working tree or at `rev`.
+pub(crate) fn blame_line(
+    repo: &Repository,
+    path: &Path,
+    line: u32,
+    rev: Option<&str>,
+) -> Res<Blame> {
+    let mut cmd = command();
+    cmd.args(["blame", "--porcelain", "-L", &format!("{line},{line}")]);
+    cmd.args(rev);
+    cmd.arg("--").arg(path);
+
+    let out = cmd
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+
+    blame::parse_blame_porcelain(str::from_utf8(&out.stdout)?)
+        .ok_or_else(|| "Couldn't parse git blame output".into())
+}
+
+pub(crate) fn remote_url(repo: &Repository, remote: &str) -> Res<String> {
+    let out = command()
+        .args(["remote", "get-url", remote])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err(format!("No remote named '{}'", remote).into());
+    }
+
+    Ok(str::from_utf8(&out.stdout)?.trim().to_string())
+}
+
 // TODO replace with libgit2
 fn branch_name(dir: &Path, hash: &str) -> Res<Option<String>> {
-    let out = Command::new("git")
+    let out = command()
         .args(["for-each-ref", "--format", "%(objectname) %(refname:short)"])
         .current_dir(dir)
         .output()?
@@ -114,22 +591,42 @@ fn branch_name(dir: &Path, hash: &str) -> Res<Option<String>> {
         .map(|line| line.split(' ').nth(1).unwrap().to_string()))
 }
 
-pub(crate) fn diff_unstaged(config: &Config, repo: &Repository) -> Res<Diff> {
-    let diff = repo.diff_index_to_workdir(None, Some(&mut git2_opts::diff(repo)?))?;
-    diff::convert_diff(config, repo, diff, true)
-}
+pub(crate) fn diff(config: &Config, repo: &Repository, opts: &DiffOptions) -> Res<Diff> {
+    log::debug!("Diffing like `git {}`", opts.args().join(" "));
 
-pub(crate) fn diff_staged(config: &Config, repo: &Repository) -> Res<Diff> {
-    let opts = &mut git2_opts::diff(repo)?;
+    let mut diff = if opts.cached {
+        let head_tree = match repo.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(_) => None,
+        };
 
-    let mut diff = match repo.head() {
-        Ok(head) => repo.diff_tree_to_index(Some(&head.peel_to_tree()?), None, Some(opts))?,
-        Err(_) => repo.diff_tree_to_index(None, None, Some(opts))?,
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts.to_git2()))?
+    } else {
+        repo.diff_index_to_workdir(None, Some(&mut opts.to_git2()))?
     };
 
-    diff.find_similar(Some(&mut DiffFindOptions::new().renames(true)))?;
+    opts.find_similar(&mut diff)?;
+    diff::convert_diff(config, repo, diff, !opts.cached, opts)
+}
 
-    diff::convert_diff(config, repo, diff, false)
+pub(crate) fn diff_unstaged(config: &Config, repo: &Repository, opts: &DiffOptions) -> Res<Diff> {
+    diff(config, repo, &opts.clone().cached(false))
+}
+
+pub(crate) fn diff_staged(config: &Config, repo: &Repository, opts: &DiffOptions) -> Res<Diff> {
+    diff(config, repo, &opts.clone().cached(true).renames(true))
+}
+
+/// The staged changes to a single file: what's in the index, compared to `HEAD`.
+pub(crate) fn diff_staged_file(config: &Config, repo: &Repository, path: &Path) -> Res<Diff> {
+    diff(
+        config,
+        repo,
+        &DiffOptions::new()
+            .cached(true)
+            .renames(true)
+            .pathspec(path.to_path_buf()),
+    )
 }
 
 pub(crate) fn show(config: &Config, repo: &Repository, reference: &str) -> Res<Diff> {
@@ -142,13 +639,11 @@ pub(crate) fn show(config: &Config, repo: &Repository, reference: &str) -> Res<D
         .next()
         .and_then(|parent| parent.tree().ok());
 
-    let diff = repo.diff_tree_to_tree(
-        parent_tree.as_ref(),
-        Some(&tree),
-        Some(&mut git2_opts::diff(repo)?),
-    )?;
+    let opts = DiffOptions::new();
+    let diff =
+        repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts.to_git2()))?;
 
-    diff::convert_diff(config, repo, diff, false)
+    diff::convert_diff(config, repo, diff, false, &opts)
 }
 
 pub(crate) fn show_summary(repo: &Repository, reference: &str) -> Res<Commit> {
@@ -163,14 +658,6 @@ pub(crate) fn show_summary(repo: &Repository, reference: &str) -> Res<Commit> {
         .map(|email| format!("<{}>", email))
         .unwrap_or("".to_string());
 
-    let message = commit
-        .message()
-        .unwrap_or("")
-        .to_string()
-        .lines()
-        .map(|line| format!("    {}", line))
-        .join("\n");
-
     let offset = chrono::FixedOffset::east_opt(author.when().offset_minutes() * 60).unwrap();
     let time = chrono::DateTime::with_timezone(
         &chrono::DateTime::from_timestamp(author.when().seconds(), 0).unwrap(),
@@ -178,18 +665,46 @@ pub(crate) fn show_summary(repo: &Repository, reference: &str) -> Res<Commit> {
     );
 
     let details = format!(
-        "Author: {}\nDate:   {}\n\n{}",
+        "Author: {}\nDate:   {}",
         [name, &email].join(" "),
         time.to_rfc2822(),
-        message
     );
 
     Ok(Commit {
         hash: commit.id().to_string(),
         details,
+        message: commit.message().unwrap_or("").to_string(),
     })
 }
 
+/// The editor git would run, from `GIT_EDITOR`, `core.editor`, `VISUAL` or `EDITOR`.
+pub(crate) fn editor(repo: &Repository) -> Res<String> {
+    let out = command()
+        .args(["var", "GIT_EDITOR"])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Err("No editor set, see `git var GIT_EDITOR`".into());
+    }
+
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_string())
+}
+
+/// The note on `hash` in `notes_ref`, as `git notes show` prints it. `None` if there isn't one.
+pub(crate) fn notes(repo: &Repository, notes_ref: &str, hash: &str) -> Res<Option<String>> {
+    let out = command()
+        .args(["notes", "--ref", notes_ref, "show", hash])
+        .current_dir(repo.workdir().expect("No workdir"))
+        .output()?;
+
+    if !out.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8(out.stdout)?))
+}
+
 pub(crate) fn get_head(repo: &git2::Repository) -> Res<String> {
     let head = repo.head()?;
     if head.is_branch() {
@@ -198,3 +713,43 @@ pub(crate) fn get_head(repo: &git2::Repository) -> Res<String> {
         Err("Head is not a branch".into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    #[test]
+    fn init_missing_binary() {
+        let result = super::init_binary(PathBuf::from("/nonexistent/git"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Couldn't run git binary '/nonexistent/git'"));
+    }
+
+    #[test]
+    fn config_overrides() {
+        for valid in [
+            "user.email=me@example.com",
+            "diff.renames=",
+            "remote.origin.url=git@host:a=b",
+        ] {
+            assert!(super::validate_config_override(valid).is_ok(), "{valid}");
+        }
+
+        for malformed in [
+            "user.email",
+            "email=me@example.com",
+            ".x=1",
+            "diff.=1",
+            "diff.a b=1",
+        ] {
+            assert_eq!(
+                super::validate_config_override(malformed)
+                    .unwrap_err()
+                    .to_string(),
+                format!("Malformed git config override '{malformed}', expected key=value (e.g. user.email=me@example.com)")
+            );
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::Res;
+use git2::{IndexEntryExtendedFlag, Repository};
+use std::{collections::HashSet, path::PathBuf};
+
+/// Paths that a sparse checkout left out of the working tree, which git marks "skip-worktree" in the index.
+/// Empty unless `core.sparseCheckout` is set, as by `git sparse-checkout set`.
+pub(crate) fn excluded_paths(repo: &Repository) -> Res<HashSet<PathBuf>> {
+    if !repo
+        .config()?
+        .get_bool("core.sparseCheckout")
+        .unwrap_or(false)
+    {
+        return Ok(HashSet::new());
+    }
+
+    Ok(repo
+        .index()?
+        .iter()
+        .filter(|entry| {
+            IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                .contains(IndexEntryExtendedFlag::SKIP_WORKTREE)
+        })
+        .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned()))
+        .collect())
+}
@@ -0,0 +1,333 @@
+use serde::Serialize;
+use std::fmt;
+
+/// The output of `git status --porcelain=v2 --branch -z`.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub(crate) struct Status {
+    pub branch: BranchHeader,
+    pub entries: Vec<StatusEntry>,
+}
+
+/// The `# branch.*` headers. `oid` and `head` are `None` for an initial commit and a detached head.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub(crate) struct BranchHeader {
+    pub oid: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: Option<u32>,
+    pub behind: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum StatusEntry {
+    Changed {
+        xy: String,
+        submodule: String,
+        mode_head: String,
+        mode_index: String,
+        mode_worktree: String,
+        hash_head: String,
+        hash_index: String,
+        path: String,
+    },
+    Renamed {
+        xy: String,
+        submodule: String,
+        mode_head: String,
+        mode_index: String,
+        mode_worktree: String,
+        hash_head: String,
+        hash_index: String,
+        /// `R` or `C` followed by the similarity percentage.
+        score: String,
+        path: String,
+        orig_path: String,
+    },
+    Unmerged {
+        xy: String,
+        submodule: String,
+        mode_stages: [String; 3],
+        mode_worktree: String,
+        hash_stages: [String; 3],
+        path: String,
+    },
+    Untracked {
+        path: String,
+    },
+    Ignored {
+        path: String,
+    },
+}
+
+/// What changed inside a submodule, from the `S<c><m><u>` field of a status entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SubmoduleState {
+    /// It's checked out at another commit than the one recorded in the parent repo.
+    pub new_commits: bool,
+    pub modified_content: bool,
+    pub untracked_content: bool,
+}
+
+impl SubmoduleState {
+    /// `None` for entries that aren't submodules, which have `N...`.
+    pub(crate) fn parse(field: &str) -> Option<Self> {
+        let flags = field.strip_prefix('S')?.as_bytes();
+        let [commit, modified, untracked] = flags else {
+            return None;
+        };
+
+        Some(Self {
+            new_commits: *commit == b'C',
+            modified_content: *modified == b'M',
+            untracked_content: *untracked == b'U',
+        })
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.new_commits || self.modified_content || self.untracked_content
+    }
+}
+
+/// Like the suffixes of `git status`: `new commits, modified content`.
+impl fmt::Display for SubmoduleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [
+            (self.new_commits, "new commits"),
+            (self.modified_content, "modified content"),
+            (self.untracked_content, "untracked content"),
+        ];
+
+        let names = parts
+            .iter()
+            .filter(|(is_set, _)| *is_set)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+
+        f.write_str(&names.join(", "))
+    }
+}
+
+impl StatusEntry {
+    /// The path, and the state of the submodule there if it is one.
+    pub(crate) fn submodule_state(&self) -> Option<(&str, SubmoduleState)> {
+        match self {
+            StatusEntry::Changed {
+                submodule, path, ..
+            }
+            | StatusEntry::Renamed {
+                submodule, path, ..
+            }
+            | StatusEntry::Unmerged {
+                submodule, path, ..
+            } => Some((path, SubmoduleState::parse(submodule)?)),
+            StatusEntry::Untracked { .. } | StatusEntry::Ignored { .. } => None,
+        }
+    }
+}
+
+/// Parses NUL-separated porcelain v2 status output, skipping records it doesn't recognize.
+pub(crate) fn parse_status_porcelain_v2(porcelain: &str) -> Status {
+    let mut status = Status::default();
+    let mut records = porcelain.split('\0').filter(|record| !record.is_empty());
+
+    while let Some(record) = records.next() {
+        let (kind, rest) = record.split_once(' ').unwrap_or((record, ""));
+
+        match kind {
+            "#" => parse_header(&mut status.branch, rest),
+            "1" => {
+                let fields = rest.splitn(8, ' ').collect::<Vec<_>>();
+                let [xy, submodule, mode_head, mode_index, mode_worktree, hash_head, hash_index, path] =
+                    fields[..]
+                else {
+                    continue;
+                };
+
+                status.entries.push(StatusEntry::Changed {
+                    xy: xy.into(),
+                    submodule: submodule.into(),
+                    mode_head: mode_head.into(),
+                    mode_index: mode_index.into(),
+                    mode_worktree: mode_worktree.into(),
+                    hash_head: hash_head.into(),
+                    hash_index: hash_index.into(),
+                    path: path.into(),
+                });
+            }
+            "2" => {
+                let orig_path = records.next().unwrap_or_default();
+                let fields = rest.splitn(9, ' ').collect::<Vec<_>>();
+                let [xy, submodule, mode_head, mode_index, mode_worktree, hash_head, hash_index, score, path] =
+                    fields[..]
+                else {
+                    continue;
+                };
+
+                status.entries.push(StatusEntry::Renamed {
+                    xy: xy.into(),
+                    submodule: submodule.into(),
+                    mode_head: mode_head.into(),
+                    mode_index: mode_index.into(),
+                    mode_worktree: mode_worktree.into(),
+                    hash_head: hash_head.into(),
+                    hash_index: hash_index.into(),
+                    score: score.into(),
+                    path: path.into(),
+                    orig_path: orig_path.into(),
+                });
+            }
+            "u" => {
+                let fields = rest.splitn(10, ' ').collect::<Vec<_>>();
+                let [xy, submodule, m1, m2, m3, mode_worktree, h1, h2, h3, path] = fields[..]
+                else {
+                    continue;
+                };
+
+                status.entries.push(StatusEntry::Unmerged {
+                    xy: xy.into(),
+                    submodule: submodule.into(),
+                    mode_stages: [m1.into(), m2.into(), m3.into()],
+                    mode_worktree: mode_worktree.into(),
+                    hash_stages: [h1.into(), h2.into(), h3.into()],
+                    path: path.into(),
+                });
+            }
+            "?" => status
+                .entries
+                .push(StatusEntry::Untracked { path: rest.into() }),
+            "!" => status
+                .entries
+                .push(StatusEntry::Ignored { path: rest.into() }),
+            _ => log::warn!("Unrecognized status record: {}", record),
+        }
+    }
+
+    status
+}
+
+fn parse_header(branch: &mut BranchHeader, header: &str) {
+    let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+
+    match key {
+        "branch.oid" if value != "(initial)" => branch.oid = Some(value.into()),
+        "branch.head" if value != "(detached)" => branch.head = Some(value.into()),
+        "branch.upstream" => branch.upstream = Some(value.into()),
+        "branch.ab" => {
+            let (ahead, behind) = value.split_once(' ').unwrap_or((value, ""));
+            branch.ahead = ahead.strip_prefix('+').and_then(|n| n.parse().ok());
+            branch.behind = behind.strip_prefix('-').and_then(|n| n.parse().ok());
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_branch_headers() {
+        let status = parse_status_porcelain_v2(
+            "# branch.oid 1234abcd\0# branch.head main\0\
+             # branch.upstream origin/main\0# branch.ab +1 -2\0",
+        );
+
+        assert_eq!(
+            status.branch,
+            BranchHeader {
+                oid: Some("1234abcd".into()),
+                head: Some("main".into()),
+                upstream: Some("origin/main".into()),
+                ahead: Some(1),
+                behind: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_initial_detached() {
+        let status =
+            parse_status_porcelain_v2("# branch.oid (initial)\0# branch.head (detached)\0");
+        assert_eq!(status.branch, BranchHeader::default());
+    }
+
+    #[test]
+    fn parse_entries() {
+        let status = parse_status_porcelain_v2(
+            "1 .M N... 100644 100644 100644 aaa aaa file with spaces\0\
+             2 R. N... 100644 100644 100644 bbb bbb R100 new\0old\0\
+             u UU N... 100644 100644 100644 100644 c1 c2 c3 conflicted\0\
+             ? untracked\0\
+             ! ignored\0",
+        );
+
+        assert_eq!(
+            status.entries,
+            vec![
+                StatusEntry::Changed {
+                    xy: ".M".into(),
+                    submodule: "N...".into(),
+                    mode_head: "100644".into(),
+                    mode_index: "100644".into(),
+                    mode_worktree: "100644".into(),
+                    hash_head: "aaa".into(),
+                    hash_index: "aaa".into(),
+                    path: "file with spaces".into(),
+                },
+                StatusEntry::Renamed {
+                    xy: "R.".into(),
+                    submodule: "N...".into(),
+                    mode_head: "100644".into(),
+                    mode_index: "100644".into(),
+                    mode_worktree: "100644".into(),
+                    hash_head: "bbb".into(),
+                    hash_index: "bbb".into(),
+                    score: "R100".into(),
+                    path: "new".into(),
+                    orig_path: "old".into(),
+                },
+                StatusEntry::Unmerged {
+                    xy: "UU".into(),
+                    submodule: "N...".into(),
+                    mode_stages: ["100644".into(), "100644".into(), "100644".into()],
+                    mode_worktree: "100644".into(),
+                    hash_stages: ["c1".into(), "c2".into(), "c3".into()],
+                    path: "conflicted".into(),
+                },
+                StatusEntry::Untracked {
+                    path: "untracked".into()
+                },
+                StatusEntry::Ignored {
+                    path: "ignored".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_submodule_states() {
+        assert_eq!(SubmoduleState::parse("N..."), None);
+        assert_eq!(
+            SubmoduleState::parse("S..."),
+            Some(SubmoduleState::default())
+        );
+
+        let state = SubmoduleState::parse("SC.U").unwrap();
+        assert!(state.new_commits && !state.modified_content && state.untracked_content);
+        assert_eq!(state.to_string(), "new commits, untracked content");
+
+        let status = parse_status_porcelain_v2(
+            "1 .M S.M. 160000 160000 160000 aaa aaa sub\0\
+             1 .M N... 100644 100644 100644 bbb bbb file\0",
+        );
+        let states = status
+            .entries
+            .iter()
+            .filter_map(StatusEntry::submodule_state)
+            .collect::<Vec<_>>();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].0, "sub");
+        assert_eq!(states[0].1.to_string(), "modified content");
+    }
+}
@@ -0,0 +1,406 @@
+use crate::Res;
+use std::{ffi::OsStr, fmt, process::Command, sync::OnceLock};
+
+static GIT_VERSION: OnceLock<GitVersion> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct GitVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl GitVersion {
+    pub(crate) const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the output of `git --version`, like `git version 2.39.2`, `git version 2.45.1.windows.1`
+    /// or `git version 2.24.3 (Apple Git-128)`.
+    pub(crate) fn parse(version_output: &str) -> Option<Self> {
+        let version = version_output
+            .trim()
+            .strip_prefix("git version ")?
+            .split_whitespace()
+            .next()?;
+
+        let mut numbers = version.split('.').map(|number| number.parse::<u32>().ok());
+        Some(Self::new(
+            numbers.next()??,
+            numbers.next().flatten().unwrap_or(0),
+            numbers.next().flatten().unwrap_or(0),
+        ))
+    }
+}
+
+impl fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Remembers the version `git --version` reported, see `super::init_binary`.
+pub(crate) fn init(version_output: &str) {
+    match GitVersion::parse(version_output) {
+        Some(version) => {
+            let _ = GIT_VERSION.set(version);
+        }
+        None => log::warn!(
+            "Couldn't parse git version from '{}'",
+            version_output.trim()
+        ),
+    }
+}
+
+/// The version of the git binary. When unknown, git is assumed to have every feature.
+pub(crate) fn current() -> Option<GitVersion> {
+    GIT_VERSION.get().copied()
+}
+
+/// What Gitu uses that only newer git versions have.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Feature {
+    /// `git restore`, otherwise done with `git reset` and `git checkout`
+    Restore,
+    /// `git rev-parse --path-format=absolute`, otherwise done with `--absolute-git-dir`
+    PathFormat,
+    /// `git config --fixed-value`, otherwise done by escaping the value as a regex
+    ConfigFixedValue,
+    /// `git config --show-scope`
+    ConfigShowScope,
+    /// `git range-diff`
+    RangeDiff,
+    /// `git stash push --staged`
+    StashStaged,
+    /// `git diff --no-relative`, older versions have no `diff.relative` to turn off
+    NoRelative,
+    /// `git stash show --include-untracked`
+    StashShowUntracked,
+}
+
+impl Feature {
+    fn min_version(self) -> GitVersion {
+        match self {
+            Feature::RangeDiff => GitVersion::new(2, 19, 0),
+            Feature::Restore => GitVersion::new(2, 23, 0),
+            Feature::ConfigShowScope => GitVersion::new(2, 26, 0),
+            Feature::NoRelative => GitVersion::new(2, 28, 0),
+            Feature::ConfigFixedValue => GitVersion::new(2, 30, 0),
+            Feature::PathFormat => GitVersion::new(2, 31, 0),
+            Feature::StashShowUntracked => GitVersion::new(2, 32, 0),
+            Feature::StashStaged => GitVersion::new(2, 35, 0),
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            Feature::Restore => "git restore",
+            Feature::PathFormat => "git rev-parse --path-format",
+            Feature::ConfigFixedValue => "git config --fixed-value",
+            Feature::ConfigShowScope => "git config --show-scope",
+            Feature::RangeDiff => "git range-diff",
+            Feature::StashStaged => "git stash push --staged",
+            Feature::NoRelative => "git diff --no-relative",
+            Feature::StashShowUntracked => "git stash show --include-untracked",
+        }
+    }
+}
+
+fn supported_by(version: Option<GitVersion>, feature: Feature) -> bool {
+    version.is_none_or(|version| version >= feature.min_version())
+}
+
+/// An error for features that can't be done at all with an older git.
+pub(crate) fn require(feature: Feature) -> Res<()> {
+    required_of(current(), feature)
+}
+
+fn required_of(version: Option<GitVersion>, feature: Feature) -> Res<()> {
+    match version {
+        Some(version) if !supported_by(Some(version), feature) => Err(format!(
+            "`{}` needs git {} or newer, but this is git {}",
+            feature.command(),
+            feature.min_version(),
+            version
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Adds the arguments that unstage `paths`, leaving the working tree as it is.
+pub(crate) fn unstage_paths<P: AsRef<OsStr>>(cmd: &mut Command, paths: &[P]) {
+    unstage_paths_with(current(), cmd, paths);
+}
+
+fn unstage_paths_with<P: AsRef<OsStr>>(
+    version: Option<GitVersion>,
+    cmd: &mut Command,
+    paths: &[P],
+) {
+    if supported_by(version, Feature::Restore) {
+        cmd.args(["restore", "--staged"]);
+    } else {
+        cmd.args(["reset", "--quiet", "HEAD", "--"]);
+    }
+    cmd.args(paths);
+}
+
+/// Adds the arguments that restore `paths` to how they are in HEAD, in both the index and working tree.
+pub(crate) fn restore_paths_to_head<P: AsRef<OsStr>>(cmd: &mut Command, paths: &[P]) {
+    restore_paths_to_head_with(current(), cmd, paths);
+}
+
+fn restore_paths_to_head_with<P: AsRef<OsStr>>(
+    version: Option<GitVersion>,
+    cmd: &mut Command,
+    paths: &[P],
+) {
+    if supported_by(version, Feature::Restore) {
+        cmd.args(["restore", "--source=HEAD", "--staged", "--worktree", "--"]);
+    } else {
+        cmd.args(["checkout", "HEAD", "--"]);
+    }
+    cmd.args(paths);
+}
+
+/// Adds the arguments that check out `paths` as they are in `source` into the working tree.
+/// Without `git restore`, they're staged like that too.
+pub(crate) fn restore_worktree_paths_from<P: AsRef<OsStr>>(
+    cmd: &mut Command,
+    source: &str,
+    paths: &[P],
+) {
+    restore_worktree_paths_from_with(current(), cmd, source, paths);
+}
+
+fn restore_worktree_paths_from_with<P: AsRef<OsStr>>(
+    version: Option<GitVersion>,
+    cmd: &mut Command,
+    source: &str,
+    paths: &[P],
+) {
+    if supported_by(version, Feature::Restore) {
+        cmd.args([
+            "restore",
+            &format!("--source={}", source),
+            "--worktree",
+            "--",
+        ]);
+    } else {
+        cmd.args(["checkout", source, "--"]);
+    }
+    cmd.args(paths);
+}
+
+/// Adds the argument that keeps the paths in a diff relative to the root of the work tree,
+/// regardless of `diff.relative`, as Gitu acts on them from there.
+pub(crate) fn diff_paths_from_root(cmd: &mut Command) {
+    diff_paths_from_root_with(current(), cmd);
+}
+
+fn diff_paths_from_root_with(version: Option<GitVersion>, cmd: &mut Command) {
+    if supported_by(version, Feature::NoRelative) {
+        cmd.arg("--no-relative");
+    }
+}
+
+/// Adds the argument that shows the untracked files of a stash too, older versions leave them out.
+pub(crate) fn stash_show_untracked(cmd: &mut Command) {
+    stash_show_untracked_with(current(), cmd);
+}
+
+fn stash_show_untracked_with(version: Option<GitVersion>, cmd: &mut Command) {
+    if supported_by(version, Feature::StashShowUntracked) {
+        cmd.arg("--include-untracked");
+    }
+}
+
+/// Adds the arguments that print the work tree and git dir, each on a line as an absolute path.
+pub(crate) fn show_toplevel_and_git_dir(cmd: &mut Command) {
+    show_toplevel_and_git_dir_with(current(), cmd);
+}
+
+fn show_toplevel_and_git_dir_with(version: Option<GitVersion>, cmd: &mut Command) {
+    if supported_by(version, Feature::PathFormat) {
+        cmd.args([
+            "rev-parse",
+            "--path-format=absolute",
+            "--show-toplevel",
+            "--git-dir",
+        ]);
+    } else {
+        cmd.args(["rev-parse", "--show-toplevel", "--absolute-git-dir"]);
+    }
+}
+
+/// Adds the arguments that set `key` to `value` in `file`, only where it's currently `old_value`.
+pub(crate) fn replace_config_value(
+    cmd: &mut Command,
+    file: &str,
+    key: &str,
+    value: &str,
+    old_value: &str,
+) {
+    replace_config_value_with(current(), cmd, file, key, value, old_value);
+}
+
+fn replace_config_value_with(
+    version: Option<GitVersion>,
+    cmd: &mut Command,
+    file: &str,
+    key: &str,
+    value: &str,
+    old_value: &str,
+) {
+    cmd.args(["config", "--file", file]);
+    if supported_by(version, Feature::ConfigFixedValue) {
+        cmd.args(["--fixed-value", key, value, old_value]);
+    } else {
+        cmd.args([key, value, &format!("^{}$", escape_regex(old_value))]);
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            let escape = "\\^$.|?*+()[]{}".contains(c);
+            escape.then_some('\\').into_iter().chain([c])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: Option<GitVersion> = Some(GitVersion::new(2, 17, 1));
+    const NEW: Option<GitVersion> = Some(GitVersion::new(2, 45, 0));
+
+    fn args(cmd: &Command) -> Vec<&str> {
+        cmd.get_args().map(|arg| arg.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(
+            GitVersion::parse("git version 2.39.2\n"),
+            Some(GitVersion::new(2, 39, 2))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.45.1.windows.1"),
+            Some(GitVersion::new(2, 45, 1))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.24.3 (Apple Git-128)"),
+            Some(GitVersion::new(2, 24, 3))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.50"),
+            Some(GitVersion::new(2, 50, 0))
+        );
+        assert_eq!(GitVersion::parse("hub version 2.14.2"), None);
+    }
+
+    #[test]
+    fn unknown_version_supports_everything() {
+        assert!(supported_by(None, Feature::StashStaged));
+        assert!(required_of(None, Feature::StashStaged).is_ok());
+    }
+
+    #[test]
+    fn old_version_is_refused() {
+        assert!(required_of(NEW, Feature::RangeDiff).is_ok());
+        assert_eq!(
+            required_of(OLD, Feature::RangeDiff)
+                .unwrap_err()
+                .to_string(),
+            "`git range-diff` needs git 2.19.0 or newer, but this is git 2.17.1"
+        );
+    }
+
+    #[test]
+    fn unstage_with_old_version() {
+        let mut cmd = Command::new("git");
+        unstage_paths_with(NEW, &mut cmd, &["file"]);
+        assert_eq!(args(&cmd), ["restore", "--staged", "file"]);
+
+        let mut cmd = Command::new("git");
+        unstage_paths_with(OLD, &mut cmd, &["file"]);
+        assert_eq!(args(&cmd), ["reset", "--quiet", "HEAD", "--", "file"]);
+    }
+
+    #[test]
+    fn restore_with_old_version() {
+        let mut cmd = Command::new("git");
+        restore_paths_to_head_with(OLD, &mut cmd, &["a", "b"]);
+        assert_eq!(args(&cmd), ["checkout", "HEAD", "--", "a", "b"]);
+    }
+
+    #[test]
+    fn restore_from_source_with_old_version() {
+        let mut cmd = Command::new("git");
+        restore_worktree_paths_from_with(NEW, &mut cmd, "stash@{0}", &["a"]);
+        assert_eq!(
+            args(&cmd),
+            ["restore", "--source=stash@{0}", "--worktree", "--", "a"]
+        );
+
+        let mut cmd = Command::new("git");
+        restore_worktree_paths_from_with(OLD, &mut cmd, "stash@{0}", &["a"]);
+        assert_eq!(args(&cmd), ["checkout", "stash@{0}", "--", "a"]);
+    }
+
+    #[test]
+    fn no_relative_with_old_version() {
+        let mut cmd = Command::new("git");
+        diff_paths_from_root_with(NEW, &mut cmd);
+        assert_eq!(args(&cmd), ["--no-relative"]);
+
+        let mut cmd = Command::new("git");
+        diff_paths_from_root_with(OLD, &mut cmd);
+        assert!(args(&cmd).is_empty());
+    }
+
+    #[test]
+    fn stash_show_untracked_with_old_version() {
+        let mut cmd = Command::new("git");
+        stash_show_untracked_with(NEW, &mut cmd);
+        assert_eq!(args(&cmd), ["--include-untracked"]);
+
+        let mut cmd = Command::new("git");
+        stash_show_untracked_with(OLD, &mut cmd);
+        assert!(args(&cmd).is_empty());
+    }
+
+    #[test]
+    fn rev_parse_with_old_version() {
+        let mut cmd = Command::new("git");
+        show_toplevel_and_git_dir_with(OLD, &mut cmd);
+        assert_eq!(
+            args(&cmd),
+            ["rev-parse", "--show-toplevel", "--absolute-git-dir"]
+        );
+    }
+
+    #[test]
+    fn replace_config_value_with_old_version() {
+        let mut cmd = Command::new("git");
+        replace_config_value_with(OLD, &mut cmd, ".git/config", "a.b", "new", "1.2 (x)");
+        assert_eq!(
+            args(&cmd),
+            [
+                "config",
+                "--file",
+                ".git/config",
+                "a.b",
+                "new",
+                "^1\\.2 \\(x\\)$"
+            ]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use super::diff::Delta;
+use ratatui::text::Line;
+use std::{fmt::Write as _, ops::Range};
+
+/// What of `core.whitespace` decides which trailing whitespace git flags (see `git help config`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct WhitespaceRules {
+    /// `blank-at-eol`, or `trailing-space`: whitespace at the end of a line.
+    pub blank_at_eol: bool,
+    /// `cr-at-eol`: a carriage return ending a line isn't counted.
+    pub cr_at_eol: bool,
+}
+
+impl Default for WhitespaceRules {
+    fn default() -> Self {
+        Self {
+            blank_at_eol: true,
+            cr_at_eol: false,
+        }
+    }
+}
+
+impl WhitespaceRules {
+    /// Reads a comma-separated `core.whitespace`, where `-` in front of a rule turns it off.
+    /// Rules that aren't about the end of a line are left out.
+    pub(crate) fn parse(core_whitespace: Option<&str>) -> Self {
+        let mut rules = Self::default();
+
+        for rule in core_whitespace.unwrap_or("").split(',').map(str::trim) {
+            let (enabled, name) = match rule.strip_prefix('-') {
+                Some(name) => (false, name),
+                None => (true, rule),
+            };
+
+            match name {
+                "blank-at-eol" | "trailing-space" => rules.blank_at_eol = enabled,
+                "cr-at-eol" => rules.cr_at_eol = enabled,
+                _ => (),
+            }
+        }
+
+        rules
+    }
+
+    /// Where the trailing whitespace that git would flag is in `line`, which has no newline.
+    /// With `cr-at-eol`, a carriage return ending the line is kept out of it.
+    pub(crate) fn trailing_whitespace(self, line: &str) -> Option<Range<usize>> {
+        if !self.blank_at_eol {
+            return None;
+        }
+
+        let line = match self.cr_at_eol {
+            true => line.strip_suffix('\r').unwrap_or(line),
+            false => line,
+        };
+        let trimmed = line.trim_end_matches(|c: char| c.is_ascii_whitespace());
+
+        (trimmed.len() < line.len()).then_some(trimmed.len()..line.len())
+    }
+}
+
+/// A patch of the new version of `delta` that removes the trailing whitespace its added lines have,
+/// with a hunk for each. Returns the patch and how many lines it cleans, if any.
+pub(crate) fn cleanup_patch(delta: &Delta, rules: WhitespaceRules) -> Option<(String, usize)> {
+    let path = delta.new_file.to_string_lossy();
+    let mut patch = format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n");
+    let mut cleaned = 0;
+
+    for hunk in &delta.hunks {
+        let lines = hunk
+            .content
+            .lines
+            .iter()
+            .map(Line::to_string)
+            .collect::<Vec<_>>();
+        let line_numbers = hunk.line_numbers();
+
+        for (i, range) in hunk.whitespace_errors(rules) {
+            let line = &lines[i][1..];
+            let Some(new_line) = line_numbers[i].1 else {
+                continue;
+            };
+
+            let marker = match lines.get(i + 1).is_some_and(|next| next.starts_with('\\')) {
+                true => "\n\\ No newline at end of file",
+                false => "",
+            };
+
+            let fixed = format!("{}{}", &line[..range.start], &line[range.end..]);
+            writeln!(
+                patch,
+                "@@ -{new_line} +{new_line} @@\n-{line}{marker}\n+{fixed}{marker}"
+            )
+            .unwrap();
+            cleaned += 1;
+        }
+    }
+
+    (cleaned > 0).then_some((patch, cleaned))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WhitespaceRules;
+
+    #[test]
+    fn core_whitespace() {
+        assert_eq!(WhitespaceRules::parse(None), WhitespaceRules::default());
+        assert_eq!(
+            WhitespaceRules::parse(Some("-trailing-space,space-before-tab")),
+            WhitespaceRules {
+                blank_at_eol: false,
+                cr_at_eol: false,
+            }
+        );
+        assert_eq!(
+            WhitespaceRules::parse(Some("cr-at-eol")),
+            WhitespaceRules {
+                blank_at_eol: true,
+                cr_at_eol: true,
+            }
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        let rules = WhitespaceRules::default();
+        assert_eq!(rules.trailing_whitespace("code  \t"), Some(4..7));
+        assert_eq!(rules.trailing_whitespace("code"), None);
+        assert_eq!(rules.trailing_whitespace("   "), Some(0..3));
+        assert_eq!(rules.trailing_whitespace("code\r"), Some(4..5));
+
+        let cr_at_eol = WhitespaceRules::parse(Some("cr-at-eol"));
+        assert_eq!(cr_at_eol.trailing_whitespace("code\r"), None);
+        assert_eq!(cr_at_eol.trailing_whitespace("code \r"), Some(4..5));
+
+        let off = WhitespaceRules::parse(Some("-blank-at-eol"));
+        assert_eq!(off.trailing_whitespace("code  "), None);
+    }
+}
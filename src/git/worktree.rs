@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Worktree {
+    pub path: PathBuf,
+    pub head: Option<String>,
+    pub branch: Option<String>,
+    pub is_main: bool,
+    pub is_bare: bool,
+    pub is_locked: bool,
+}
+
+/// Parses the output of `git worktree list --porcelain`. The main worktree is always listed first.
+pub(crate) fn parse_worktree_list(porcelain: &str) -> Vec<Worktree> {
+    porcelain
+        .split("\n\n")
+        .filter_map(|entry| {
+            let mut lines = entry.lines();
+            let path = lines.next()?.strip_prefix("worktree ")?;
+
+            let mut worktree = Worktree {
+                path: PathBuf::from(path),
+                head: None,
+                branch: None,
+                is_main: false,
+                is_bare: false,
+                is_locked: false,
+            };
+
+            for line in lines {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "HEAD" => worktree.head = Some(value.to_string()),
+                    "branch" => {
+                        worktree.branch = Some(
+                            value
+                                .strip_prefix("refs/heads/")
+                                .unwrap_or(value)
+                                .to_string(),
+                        )
+                    }
+                    "bare" => worktree.is_bare = true,
+                    "locked" => worktree.is_locked = true,
+                    _ => (),
+                }
+            }
+
+            Some(worktree)
+        })
+        .enumerate()
+        .map(|(i, worktree)| Worktree {
+            is_main: i == 0,
+            ..worktree
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_main_and_linked() {
+        let porcelain = "worktree /tmp/repo\n\
+            HEAD c4d19d2ddef199b4b29dd9697e1045d67b759969\n\
+            branch refs/heads/main\n\
+            \n\
+            worktree /tmp/repo-wt\n\
+            HEAD 9b10db1ff1fe97478b9ec8354f94f315da821a26\n\
+            detached\n\
+            locked reason\n\
+            \n";
+
+        let worktrees = parse_worktree_list(porcelain);
+
+        assert_eq!(
+            worktrees,
+            vec![
+                Worktree {
+                    path: "/tmp/repo".into(),
+                    head: Some("c4d19d2ddef199b4b29dd9697e1045d67b759969".into()),
+                    branch: Some("main".into()),
+                    is_main: true,
+                    is_bare: false,
+                    is_locked: false,
+                },
+                Worktree {
+                    path: "/tmp/repo-wt".into(),
+                    head: Some("9b10db1ff1fe97478b9ec8354f94f315da821a26".into()),
+                    branch: None,
+                    is_main: false,
+                    is_bare: false,
+                    is_locked: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_bare() {
+        let worktrees = parse_worktree_list("worktree /tmp/repo.git\nbare\n\n");
+        assert!(worktrees[0].is_main && worktrees[0].is_bare);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{cmd_log::CmdLog, config::Config, ops::Op, pending_cmd::PendingCmd, Res};
+use git2::Repository;
+use std::process::{Command, Stdio};
+
+/// Runs the `[hooks]` commands of the config: the shell command set for an action,
+/// in the background once the action and whatever it started finished without errors.
+pub(crate) struct Hooks {
+    queued: Option<Op>,
+    running: Vec<(String, PendingCmd)>,
+}
+
+impl Hooks {
+    pub(crate) fn new() -> Self {
+        Self {
+            queued: None,
+            running: vec![],
+        }
+    }
+
+    /// Has the hook of `op` run once it's done, if there is one.
+    pub(crate) fn queue(&mut self, config: &Config, op: &Op) {
+        if config.hooks.contains_key(op) {
+            self.queued = Some(op.clone());
+        }
+    }
+
+    /// Drops the queued hook, after its action failed or was aborted.
+    pub(crate) fn cancel(&mut self) {
+        self.queued = None;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Starts the queued hook, logging it to `cmd_log`. What the action left behind is passed in
+    /// `GITU_ACTION`, `GITU_HEAD` and `GITU_STAGED_PATHS` (one per line).
+    pub(crate) fn start_queued(
+        &mut self,
+        config: &Config,
+        repo: &Repository,
+        cmd_log: &mut CmdLog,
+    ) -> Res<()> {
+        let Some(op) = self.queued.take() else {
+            return Ok(());
+        };
+        let Some(command) = config.hooks.get(&op) else {
+            return Ok(());
+        };
+
+        let name = op_name(&op)?;
+        let mut cmd = shell_command(command);
+        cmd.current_dir(repo.workdir().expect("No workdir"));
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        cmd.env("GITU_ACTION", &name);
+        if let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) {
+            cmd.env("GITU_HEAD", head.id().to_string());
+        }
+        cmd.env("GITU_STAGED_PATHS", staged_paths(repo)?.join("\n"));
+
+        let log_entry = cmd_log.push_cmd(&cmd);
+        let child = cmd
+            .spawn()
+            .map_err(|error| format!("Couldn't run the {} hook: {}", name, error))?;
+
+        log::debug!("Running the {} hook", name);
+        self.running.push((name, PendingCmd::new(child, log_entry)));
+        Ok(())
+    }
+
+    /// Checks on running hooks without blocking, writing the output of finished ones to their log entry.
+    /// `Ok(true)` if any finished, or the error of one that failed.
+    pub(crate) fn poll(&mut self) -> Res<bool> {
+        let mut finished = vec![];
+        for (i, (_, pending)) in self.running.iter_mut().enumerate() {
+            if let Some(status) = pending.child.try_wait()? {
+                finished.push((i, status));
+            }
+        }
+
+        let mut result = Ok(!finished.is_empty());
+        for (i, status) in finished.into_iter().rev() {
+            let (name, mut pending) = self.running.remove(i);
+            let out = String::from_utf8_lossy(&pending.take_output()?).into_owned();
+            pending.log_entry.write().unwrap().out = Some(out.into());
+
+            if !status.success() {
+                result = Err(format!(
+                    "The {} hook failed: exited with code: {}",
+                    name,
+                    status.code().map(|c| c.to_string()).unwrap_or_default()
+                )
+                .into());
+            }
+        }
+
+        result
+    }
+}
+
+/// The name of `op` in the config, like `commit` or `stage`.
+fn op_name(op: &Op) -> Res<String> {
+    Ok(serde_json::to_string(op)?.trim_matches('"').to_string())
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// The paths that differ between HEAD and the index.
+fn staged_paths(repo: &Repository) -> Res<Vec<String>> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
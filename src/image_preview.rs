@@ -0,0 +1,139 @@
+use image::imageops::FilterType;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::env;
+use std::path::Path;
+
+/// Thumbnails fit in this many cells. Each cell shows two pixels, one above the other.
+const MAX_COLUMNS: u32 = 32;
+const MAX_ROWS: u32 = 8;
+
+/// Whether the terminal is one of those with a graphics protocol (Kitty, iTerm2 or Sixel),
+/// which also draw the 24-bit colors thumbnails are made of.
+pub(crate) fn terminal_supports_graphics() -> bool {
+    supports_graphics(|name| env::var(name).ok())
+}
+
+fn supports_graphics(var: impl Fn(&str) -> Option<String>) -> bool {
+    if var("KITTY_WINDOW_ID").is_some() {
+        return true;
+    }
+
+    let term = var("TERM").unwrap_or_default();
+    if ["kitty", "sixel", "foot", "mlterm"]
+        .iter()
+        .any(|name| term.contains(name))
+    {
+        return true;
+    }
+
+    matches!(
+        var("TERM_PROGRAM").as_deref(),
+        Some("iTerm.app" | "WezTerm" | "ghostty")
+    )
+}
+
+/// Whether the file is named like an image that can be decoded, before reading it.
+pub(crate) fn is_image(path: &Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// Decodes an image and scales it down into lines of `▀`, the top pixel as the foreground color
+/// and the bottom one as the background. `None` if it can't be decoded.
+pub(crate) fn thumbnail(bytes: &[u8]) -> Option<Vec<Line<'static>>> {
+    let mut image = image::load_from_memory(bytes).ok()?;
+    if image.width() > MAX_COLUMNS || image.height() > MAX_ROWS * 2 {
+        image = image.resize(MAX_COLUMNS, MAX_ROWS * 2, FilterType::Triangle);
+    }
+    let image = image.to_rgb8();
+
+    let color = |x, y| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        Color::Rgb(r, g, b)
+    };
+
+    let lines = (0..image.height())
+        .step_by(2)
+        .map(|y| {
+            Line::from(
+                (0..image.width())
+                    .map(|x| {
+                        let style = Style::new().fg(color(x, y));
+                        let style = if y + 1 < image.height() {
+                            style.bg(color(x, y + 1))
+                        } else {
+                            style
+                        };
+
+                        Span::styled("▀", style)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn graphics_terminals() {
+        assert!(supports_graphics(env(&[("KITTY_WINDOW_ID", "1")])));
+        assert!(supports_graphics(env(&[("TERM", "xterm-kitty")])));
+        assert!(supports_graphics(env(&[("TERM_PROGRAM", "iTerm.app")])));
+        assert!(!supports_graphics(env(&[("TERM", "xterm-256color")])));
+        assert!(!supports_graphics(env(&[])));
+    }
+
+    #[test]
+    fn image_names() {
+        assert!(is_image(Path::new("logo.png")));
+        assert!(is_image(Path::new("docs/photo.JPG")));
+        assert!(!is_image(Path::new("archive.zip")));
+    }
+
+    #[test]
+    fn thumbnail_of_png() {
+        let mut image = RgbImage::new(4, 2);
+        image.put_pixel(0, 0, Rgb([255, 0, 0]));
+        image.put_pixel(0, 1, Rgb([0, 0, 255]));
+
+        let mut png = vec![];
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let lines = thumbnail(&png).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans.len(), 4);
+        assert_eq!(lines[0].spans[0].content, "▀");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(lines[0].spans[0].style.bg, Some(Color::Rgb(0, 0, 255)));
+        assert!(thumbnail(b"not an image").is_none());
+    }
+
+    #[test]
+    fn thumbnail_of_large_png_fits() {
+        let mut png = vec![];
+        RgbImage::new(640, 160)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let lines = thumbnail(&png).unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].spans.len(), 32);
+    }
+}
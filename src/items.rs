@@ -1,19 +1,26 @@
 use crate::config::Config;
+use crate::git::config_list::ConfigEntry;
+use crate::git::diff;
 use crate::git::diff::Delta;
 use crate::git::diff::Diff;
 use crate::git::diff::Hunk;
+use crate::git::lfs::{self, LfsPointer};
 use crate::Res;
 use git2::Commit;
 use git2::Oid;
 use git2::Repository;
+use itertools::Itertools;
 use ratatui::style::Style;
+use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use regex::Regex;
 use std::borrow::Cow;
 use std::iter;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::rc::Rc;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Default, Clone, Debug)]
 pub(crate) struct Item {
@@ -23,6 +30,10 @@ pub(crate) struct Item {
     pub(crate) default_collapsed: bool,
     pub(crate) depth: usize,
     pub(crate) unselectable: bool,
+    /// Prose, like a commit message, that the screen wraps at word boundaries to its width.
+    pub(crate) word_wrap: bool,
+    /// Output, like of a command, that's soft-wrapped along with hunk lines when wrapping is on.
+    pub(crate) soft_wrap: bool,
     pub(crate) target_data: Option<TargetData>,
 }
 
@@ -33,11 +44,22 @@ pub(crate) enum TargetData {
     AllUntracked(Vec<PathBuf>),
     Branch(String),
     Commit(String),
+    ConfigEntry(ConfigEntry),
+    ConfigScope(String),
+    Conflict(PathBuf, usize),
     Delta(Delta),
     File(PathBuf),
     Hunk(Rc<Hunk>),
     HunkLine(Rc<Hunk>, usize),
-    Stash { commit: String, id: usize },
+    /// Lines of a hunk selected together, as a range of its line indices.
+    HunkLines(Rc<Hunk>, Range<usize>),
+    Stash {
+        commit: String,
+        id: usize,
+    },
+    /// A commit message trailer, as its key and unfolded value.
+    Trailer(String, String),
+    Worktree(PathBuf),
 }
 
 pub(crate) fn create_diff_items<'a>(
@@ -50,64 +72,250 @@ pub(crate) fn create_diff_items<'a>(
         let target_data = TargetData::Delta(delta.clone());
         let config = Rc::clone(&config);
 
+        let mut display = Line::styled(
+            format!(
+                "{:8}   {}",
+                format!("{:?}", delta.status).to_lowercase(),
+                match delta.status {
+                    git2::Delta::Renamed => format!(
+                        "{} -> {}",
+                        delta.old_file.to_string_lossy(),
+                        delta.new_file.to_string_lossy()
+                    ),
+                    _ => delta.new_file.to_string_lossy().to_string(),
+                }
+            ),
+            &config.style.file_header,
+        );
+
+        if let Some(dissimilarity) = delta.dissimilarity {
+            display.push_span(Span::styled(
+                format!(" ({dissimilarity}% rewritten)"),
+                Style::new().dim(),
+            ));
+        }
+
+        match delta.executable_toggle() {
+            Some(true) => {
+                display.push_span(Span::styled(" (made executable)", Style::new().bold()))
+            }
+            Some(false) => {
+                display.push_span(Span::styled(" (made non-executable)", Style::new().bold()))
+            }
+            None => (),
+        }
+
+        if delta.sparse_excluded {
+            display.push_span(Span::styled(" (not checked out)", Style::new().dim()));
+        }
+
+        if let Some(submodule) = delta.submodule.filter(|submodule| submodule.is_dirty()) {
+            display.push_span(Span::styled(
+                format!(" ({})", submodule),
+                Style::new().bold(),
+            ));
+        }
+
+        if !delta.unrecognized.is_empty() {
+            display.push_span(Span::styled(" (?)", Style::new().dim()));
+        }
+
         iter::once(Item {
             id: delta.file_header.to_string().into(),
-            display: Line::styled(
-                format!(
-                    "{:8}   {}",
-                    format!("{:?}", delta.status).to_lowercase(),
-                    match delta.status {
-                        git2::Delta::Renamed => format!(
-                            "{} -> {}",
-                            delta.old_file.to_string_lossy(),
-                            delta.new_file.to_string_lossy()
-                        ),
-                        _ => delta.new_file.to_string_lossy().to_string(),
-                    }
-                ),
-                &config.style.file_header,
-            ),
+            display,
             section: true,
             default_collapsed,
             depth: *depth,
             target_data: Some(target_data),
             ..Default::default()
         })
-        .chain(
-            delta
-                .hunks
-                .iter()
-                .cloned()
-                .flat_map(move |hunk| create_hunk_items(Rc::clone(&config), hunk, *depth + 1)),
-        )
+        .chain(binary_items(delta, *depth + 1))
+        .chain(delta.hunks.iter().cloned().flat_map(move |hunk| {
+            let summary = lfs_summary(&hunk).or_else(|| compact_summary(&config, delta));
+            create_hunk_items(Rc::clone(&config), hunk, *depth + 1, summary)
+        }))
     })
 }
 
+/// A thumbnail of a binary file if there is one, otherwise git's "Binary files ... differ".
+fn binary_items(delta: &Delta, depth: usize) -> Vec<Item> {
+    let item = |n: usize, display| Item {
+        id: format!("{}binary:{n}", delta.file_header).into(),
+        display,
+        depth,
+        unselectable: true,
+        ..Default::default()
+    };
+
+    if !delta.preview.is_empty() {
+        return delta
+            .preview
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(n, line)| item(n, line))
+            .collect();
+    }
+
+    delta
+        .file_header
+        .lines()
+        .filter(|line| line.starts_with("Binary files "))
+        .map(|line| item(0, Line::styled(line.to_string(), Style::new().dim())))
+        .collect()
+}
+
+/// The whole new version of a file, with its hunks shown where they are and the lines between them dimmed.
+pub(crate) fn create_full_file_items(
+    config: Rc<Config>,
+    delta: &Delta,
+    content: &str,
+) -> Vec<Item> {
+    let lines = content.lines().collect::<Vec<_>>();
+    let mut items = vec![];
+    let mut next_line = 1;
+
+    for hunk in &delta.hunks {
+        // A hunk that only removes lines starts after the line it's at
+        let start = match hunk.new_lines {
+            0 => hunk.new_start + 1,
+            _ => hunk.new_start,
+        } as usize;
+
+        items.extend(unchanged_line_items(&config, &lines, next_line..start));
+        items.extend(create_hunk_items(
+            Rc::clone(&config),
+            Rc::clone(hunk),
+            1,
+            None,
+        ));
+        next_line = start + hunk.new_lines as usize;
+    }
+
+    items.extend(unchanged_line_items(
+        &config,
+        &lines,
+        next_line..lines.len() + 1,
+    ));
+    items
+}
+
+/// The lines of `lines` numbered from 1 in `range`.
+fn unchanged_line_items<'a>(
+    config: &'a Config,
+    lines: &'a [&str],
+    range: Range<usize>,
+) -> impl Iterator<Item = Item> + 'a {
+    let range = range.start.saturating_sub(1)..range.end.saturating_sub(1).min(lines.len());
+
+    lines
+        .get(range)
+        .unwrap_or_default()
+        .iter()
+        .map(|line| Item {
+            display: display_hunk_line(
+                Line::styled(
+                    format!(" {}", line),
+                    &config.style.diff_highlight.unchanged_new,
+                ),
+                config.diff.max_line_bytes,
+            ),
+            depth: 1,
+            unselectable: true,
+            ..Default::default()
+        })
+}
+
+/// Summarizes a whole-file addition or deletion, if compacting these is enabled.
+fn compact_summary(config: &Config, delta: &Delta) -> Option<String> {
+    if !config.general.compact_whole_file_diffs.enabled {
+        return None;
+    }
+
+    let [hunk] = &delta.hunks[..] else {
+        return None;
+    };
+
+    let kind = match hunk.uniform_line_kind()? {
+        '+' => "new file",
+        _ => "deleted file",
+    };
+
+    let lines = hunk.content.lines.len();
+    Some(format!(
+        "{kind}, {lines} line{}",
+        if lines == 1 { "" } else { "s" }
+    ))
+}
+
+/// Summarizes a change to a Git LFS pointer by the sizes of the objects, if the hunk is one.
+fn lfs_summary(hunk: &Hunk) -> Option<String> {
+    let pointer = |text: String| match text.as_str() {
+        "" => Some(None),
+        text => LfsPointer::parse(text).map(Some),
+    };
+
+    match (
+        pointer(hunk.removed_text(true))?,
+        pointer(hunk.added_text(true))?,
+    ) {
+        (Some(old), Some(new)) => Some(format!(
+            "LFS object changed ({} → {})",
+            lfs::format_size(old.size),
+            lfs::format_size(new.size)
+        )),
+        (None, Some(new)) => Some(format!("LFS object added ({})", lfs::format_size(new.size))),
+        (Some(old), None) => Some(format!(
+            "LFS object removed ({})",
+            lfs::format_size(old.size)
+        )),
+        (None, None) => None,
+    }
+}
+
 fn create_hunk_items(
     config: Rc<Config>,
     hunk: Rc<Hunk>,
     depth: usize,
+    summary: Option<String>,
 ) -> impl Iterator<Item = Item> {
     let target_data = TargetData::Hunk(Rc::clone(&hunk));
+    let collapsed = summary.is_some();
+    let mut display = Line::styled(
+        summary.unwrap_or_else(|| hunk.header()),
+        &config.style.hunk_header,
+    );
+
+    if hunk.lossy {
+        display.push_span(Span::styled(
+            " (not UTF-8, shown with \u{FFFD})",
+            Style::new().dim(),
+        ));
+    }
 
     iter::once(Item {
         id: hunk.format_patch().into(),
-        display: Line::styled(hunk.header.clone(), &config.style.hunk_header),
+        default_collapsed: collapsed,
+        display,
         section: true,
         depth,
         target_data: Some(target_data),
         ..Default::default()
     })
-    .chain(format_diff_hunk_items(depth + 1, hunk))
+    .chain(format_diff_hunk_items(
+        depth + 1,
+        hunk,
+        config.diff.max_line_bytes,
+    ))
 }
 
-fn format_diff_hunk_items(depth: usize, hunk: Rc<Hunk>) -> Vec<Item> {
+fn format_diff_hunk_items(depth: usize, hunk: Rc<Hunk>, max_line_bytes: usize) -> Vec<Item> {
     hunk.content
         .lines
         .iter()
         .enumerate()
         .map(|(i, line)| Item {
-            display: replace_tabs_with_spaces(line.clone()),
+            display: display_hunk_line(line.clone(), max_line_bytes),
             unselectable: line
                 .spans
                 .first()
@@ -119,17 +327,261 @@ fn format_diff_hunk_items(depth: usize, hunk: Rc<Hunk>) -> Vec<Item> {
         .collect()
 }
 
-fn replace_tabs_with_spaces(line: Line<'_>) -> Line<'_> {
-    let spans = line
+/// Expands tabs, drops the `\r` of CRLF line endings, shows other control characters as symbols
+/// and bytes that aren't valid UTF-8 as `�`. The hunk's content is left as is, for patches.
+/// Lines longer than `max_bytes` are cut off, as rendering or wrapping them all would take long.
+fn display_hunk_line(line: Line<'_>, max_bytes: usize) -> Line<'_> {
+    let mut remaining = max_bytes;
+    let mut cut_off = 0;
+
+    let mut spans = line
         .spans
         .iter()
-        .cloned()
-        .map(|span| Span::styled(span.content.replace('\t', "    "), span.style))
+        .filter_map(|span| {
+            let content = span.content.trim_end_matches('\r');
+            let shown = truncate(content, remaining);
+            remaining -= shown.len();
+            cut_off += content.len() - shown.len();
+
+            (!shown.is_empty() || content.is_empty())
+                .then(|| Span::styled(diff::display_escaped(shown), span.style))
+        })
         .collect::<Vec<_>>();
 
+    if cut_off > 0 {
+        spans.push(Span::styled(
+            format!(" … ({} more bytes)", cut_off),
+            Style::new().dim(),
+        ));
+    }
+
     Line { spans, ..line }
 }
 
+fn truncate(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+
+    &text[..end]
+}
+
+/// Replaces the rendered diff with the literal patch text, unstyled, as `Hunk::format_patch` would print it.
+pub(crate) fn raw_patch(items: Vec<Item>) -> Vec<Item> {
+    items
+        .into_iter()
+        .flat_map(|item| match &item.target_data {
+            Some(TargetData::Delta(delta)) => {
+                let mut header = delta.file_header.lines().map(|line| line.to_string());
+                let depth = item.depth;
+
+                iter::once(Item {
+                    display: Line::raw(header.next().unwrap_or_default()),
+                    ..item.clone()
+                })
+                .chain(header.map(|line| Item {
+                    display: Line::raw(line),
+                    unselectable: true,
+                    depth: depth + 1,
+                    ..Default::default()
+                }))
+                .collect()
+            }
+            Some(TargetData::Hunk(hunk)) => vec![Item {
+                display: Line::raw(hunk.header()),
+                ..item.clone()
+            }],
+            Some(TargetData::HunkLine(..)) => vec![Item {
+                display: Line::raw(item.display.to_string()),
+                ..item
+            }],
+            _ => vec![item],
+        })
+        .collect()
+}
+
+/// Soft-wraps hunk lines, and items marked `soft_wrap`, wider than `width` onto unselectable continuation lines.
+/// These start with a marker in place of the `+`/`-`, keeping the content aligned.
+pub(crate) fn wrap_hunk_lines(items: Vec<Item>, width: usize) -> Vec<Item> {
+    items
+        .into_iter()
+        .flat_map(|item| {
+            let wraps =
+                item.soft_wrap || matches!(item.target_data, Some(TargetData::HunkLine(..)));
+            if !wraps || item.display.width() <= width {
+                return vec![item];
+            }
+
+            let marker_style = item
+                .display
+                .spans
+                .first()
+                .map(|span| span.style)
+                .unwrap_or_default();
+
+            let mut segments = split_line(&item.display, width).into_iter();
+            let first = segments.next().unwrap_or_default();
+            let depth = item.depth;
+
+            iter::once(Item {
+                display: first,
+                ..item
+            })
+            .chain(segments.map(|mut segment| {
+                segment
+                    .spans
+                    .insert(0, Span::styled(WRAP_MARKER, marker_style));
+
+                Item {
+                    display: segment,
+                    depth: depth + 1,
+                    unselectable: true,
+                    ..Default::default()
+                }
+            }))
+            .collect()
+        })
+        .collect()
+}
+
+const WRAP_MARKER: &str = "↪";
+
+/// Splits `line` into lines no wider than `width`, except the first, all leave room for the `WRAP_MARKER`.
+fn split_line(line: &Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut spans = vec![];
+    let mut current = String::new();
+    let mut current_style = Style::new();
+    let mut line_width = 0;
+
+    let chars = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)));
+
+    for (c, style) in chars {
+        let char_width = c.width().unwrap_or(0);
+        let limit = if lines.is_empty() {
+            width
+        } else {
+            width.saturating_sub(WRAP_MARKER.width()).max(1)
+        };
+
+        if line_width + char_width > limit && line_width > 0 {
+            spans.push(Span::styled(std::mem::take(&mut current), current_style));
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            line_width = 0;
+        }
+
+        if style != current_style && !current.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut current), current_style));
+        }
+
+        current_style = style;
+        current.push(c);
+        line_width += char_width;
+    }
+
+    if !current.is_empty() {
+        spans.push(Span::styled(current, current_style));
+    }
+
+    if !spans.is_empty() {
+        lines.push(Line::from(spans));
+    }
+
+    lines
+}
+
+/// Wraps items marked `word_wrap` to `width` at spaces, indenting continuation lines like the first.
+pub(crate) fn wrap_words(items: Vec<Item>, width: usize) -> Vec<Item> {
+    items
+        .into_iter()
+        .flat_map(|item| {
+            if !item.word_wrap || item.display.width() <= width {
+                return vec![item];
+            }
+
+            let mut lines = split_words(&item.display, width).into_iter();
+            let first = lines.next().unwrap_or_default();
+            let (id, depth) = (item.id.clone(), item.depth);
+
+            iter::once(Item {
+                display: first,
+                ..item
+            })
+            .chain(lines.map(|line| Item {
+                id: id.clone(),
+                display: line,
+                depth,
+                unselectable: true,
+                word_wrap: true,
+                ..Default::default()
+            }))
+            .collect()
+        })
+        .collect()
+}
+
+fn split_words(line: &Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let chars = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect::<Vec<_>>();
+
+    let indent = chars.iter().take_while(|(c, _)| *c == ' ').count();
+    let mut lines = vec![];
+    let mut rest = &chars[..];
+    let mut line_indent = 0;
+
+    while !rest.is_empty() {
+        let limit = width.saturating_sub(line_indent);
+        let mut line_width = 0;
+        let fits = rest
+            .iter()
+            .position(|(c, _)| {
+                line_width += c.width().unwrap_or(0);
+                line_width > limit
+            })
+            .unwrap_or(rest.len());
+
+        let end = if fits == rest.len() {
+            fits
+        } else {
+            // Words longer than a line are cut wherever
+            let words_from = if lines.is_empty() { indent } else { 0 };
+            rest[..=fits]
+                .iter()
+                .rposition(|(c, _)| *c == ' ')
+                .filter(|&i| i > words_from)
+                .unwrap_or(fits.max(1))
+        };
+
+        let mut spans = vec![Span::raw(" ".repeat(line_indent))];
+        for (style, chunk) in &rest[..end].iter().chunk_by(|(_, style)| *style) {
+            spans.push(Span::styled(
+                chunk.map(|(c, _)| c).collect::<String>(),
+                style,
+            ));
+        }
+        lines.push(Line::from(spans));
+
+        rest = &rest[end..];
+        while rest.first().is_some_and(|(c, _)| *c == ' ') {
+            rest = &rest[1..];
+        }
+        line_indent = indent;
+    }
+
+    lines
+}
+
 pub(crate) fn stash_list(config: &Config, repo: &Repository, limit: usize) -> Res<Vec<Item>> {
     let style = &config.style;
 
@@ -171,12 +623,77 @@ pub(crate) fn stash_list(config: &Config, repo: &Repository, limit: usize) -> Re
         .collect::<Vec<_>>())
 }
 
+/// Conditions that commits in a log must all match.
+#[derive(Default, Clone, Debug)]
+pub(crate) struct LogFilter {
+    pub message: Option<Regex>,
+    pub author: Option<Regex>,
+    pub path: Option<PathBuf>,
+}
+
+impl LogFilter {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.message.is_none() && self.author.is_none() && self.path.is_none()
+    }
+
+    /// e.g. "author /Jane/, path src/main.rs"
+    pub(crate) fn describe(&self) -> String {
+        self.author
+            .iter()
+            .map(|re| format!("author /{}/", re))
+            .chain(
+                self.path
+                    .iter()
+                    .map(|path| format!("path {}", path.to_string_lossy())),
+            )
+            .chain(self.message.iter().map(|re| format!("message /{}/", re)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn matches(&self, repo: &Repository, commit: &Commit) -> Res<bool> {
+        if let Some(re) = &self.message {
+            if !re.is_match(commit.message().unwrap_or("")) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(re) = &self.author {
+            let author = commit.author();
+            let name_email = format!(
+                "{} <{}>",
+                author.name().unwrap_or(""),
+                author.email().unwrap_or("")
+            );
+
+            if !re.is_match(&name_email) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(path) = &self.path {
+            let parent_tree = commit.parents().next().map(|p| p.tree()).transpose()?;
+            let diff = repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&commit.tree()?),
+                Some(git2::DiffOptions::new().pathspec(path)),
+            )?;
+
+            if diff.deltas().len() == 0 {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
 pub(crate) fn log(
     config: &Config,
     repo: &Repository,
     limit: usize,
     rev: Option<Oid>,
-    msg_regex: Option<Regex>,
+    filter: &LogFilter,
 ) -> Res<Vec<Item>> {
     let style = &config.style;
     let mut revwalk = repo.revwalk()?;
@@ -231,10 +748,8 @@ pub(crate) fn log(
             )
             .collect::<Vec<_>>();
 
-            if let Some(re) = &msg_regex {
-                if !re.is_match(commit.message().unwrap_or("")) {
-                    return Ok(None);
-                }
+            if !filter.matches(repo, &commit)? {
+                return Ok(None);
             }
 
             Ok(Some(Item {
@@ -0,0 +1,139 @@
+use crate::{
+    cli::Commands,
+    config::Config,
+    git::{
+        self,
+        diff::{Delta, Diff, Hunk},
+        diff_options::DiffOptions,
+    },
+    Res,
+};
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Serialize)]
+struct DiffRecord<'a> {
+    deltas: Vec<DeltaRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct DeltaRecord<'a> {
+    status: String,
+    old_file: &'a Path,
+    new_file: &'a Path,
+    similarity: Option<u8>,
+    dissimilarity: Option<u8>,
+    hunks: Vec<HunkRecord>,
+}
+
+#[derive(Serialize)]
+struct HunkRecord {
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    /// Including the leading `+`, `-`, ` ` or `\` marker.
+    lines: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CommitRecord {
+    hash: String,
+    author: String,
+    email: String,
+    time: String,
+    summary: String,
+}
+
+/// Prints the output of a subcommand as JSON, instead of showing it in the TUI.
+pub(crate) fn print(config: &Config, repo: &Repository, command: Option<&Commands>) -> Res<()> {
+    let json = match command {
+        None | Some(Commands::Status) => serde_json::to_string(&git::status(repo)?)?,
+        Some(Commands::Diff { staged, file }) => {
+            let mut opts = DiffOptions::new().algorithm(config.diff.algorithm);
+            if let Some(file) = file {
+                opts = opts.pathspec(git::path_in_work_tree(repo, file)?);
+            }
+
+            let diff = if *staged {
+                git::diff_staged(config, repo, &opts)?
+            } else {
+                git::diff_unstaged(config, repo, &opts)?
+            };
+            serde_json::to_string(&diff_record(&diff))?
+        }
+        Some(Commands::Show { reference }) => {
+            serde_json::to_string(&diff_record(&git::show(config, repo, reference)?))?
+        }
+        Some(Commands::Log { reference }) => {
+            serde_json::to_string(&log_records(repo, reference.as_deref())?)?
+        }
+    };
+
+    println!("{}", json);
+    Ok(())
+}
+
+fn diff_record(diff: &Diff) -> DiffRecord<'_> {
+    DiffRecord {
+        deltas: diff.deltas.iter().map(delta_record).collect(),
+    }
+}
+
+fn delta_record(delta: &Delta) -> DeltaRecord<'_> {
+    DeltaRecord {
+        status: format!("{:?}", delta.status).to_lowercase(),
+        old_file: &delta.old_file,
+        new_file: &delta.new_file,
+        similarity: delta.similarity,
+        dissimilarity: delta.dissimilarity,
+        hunks: delta.hunks.iter().map(|hunk| hunk_record(hunk)).collect(),
+    }
+}
+
+fn hunk_record(hunk: &Hunk) -> HunkRecord {
+    HunkRecord {
+        header: hunk.header(),
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+        lines: hunk
+            .content
+            .lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect(),
+    }
+}
+
+fn log_records(repo: &Repository, reference: Option<&str>) -> Res<Vec<CommitRecord>> {
+    let mut revwalk = repo.revwalk()?;
+    match reference {
+        Some(reference) => revwalk.push(repo.revparse_single(reference)?.peel_to_commit()?.id())?,
+        None => revwalk.push_head()?,
+    }
+
+    revwalk
+        .map(|oid| {
+            let commit = repo.find_commit(oid?)?;
+            let author = commit.author();
+            let time = chrono::DateTime::from_timestamp(author.when().seconds(), 0)
+                .ok_or("Invalid commit time")?
+                .with_timezone(
+                    &chrono::FixedOffset::east_opt(author.when().offset_minutes() * 60)
+                        .ok_or("Invalid commit time zone")?,
+                );
+
+            Ok(CommitRecord {
+                hash: commit.id().to_string(),
+                author: author.name().unwrap_or("").to_string(),
+                email: author.email().unwrap_or("").to_string(),
+                time: time.to_rfc3339(),
+                summary: commit.summary().unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
@@ -1,28 +1,68 @@
+// Without the TUI, most of the git and config code is only there for the diff model
+#![cfg_attr(not(feature = "tui"), allow(dead_code))]
+
+#[cfg(feature = "tui")]
+mod ansi;
+#[cfg(feature = "tui")]
+mod auto_fetch;
+#[cfg(feature = "tui")]
 mod bindings;
+#[cfg(feature = "tui")]
 pub mod cli;
+#[cfg(feature = "tui")]
 mod cmd_log;
 pub mod config;
 mod git;
+#[cfg(feature = "tui")]
 mod git2_opts;
+#[cfg(feature = "tui")]
+mod hooks;
+#[cfg(feature = "image-preview")]
+mod image_preview;
+#[cfg(feature = "tui")]
 mod items;
+#[cfg(feature = "tui")]
+mod json;
+#[cfg(feature = "tui")]
 mod key_parser;
+#[cfg(feature = "tui")]
+mod macros;
+#[cfg(feature = "tui")]
 mod menu;
+#[cfg(feature = "tui")]
 mod ops;
+#[cfg(feature = "tui")]
+mod pending_cmd;
+#[cfg(feature = "tui")]
 mod prompt;
+#[cfg(feature = "tui")]
 mod screen;
+#[cfg(feature = "tui")]
 pub mod state;
+#[cfg(feature = "tui")]
+mod status_line;
 mod syntax_highlight;
+#[cfg(feature = "tui")]
 pub mod term;
-#[cfg(test)]
+#[cfg(all(test, feature = "tui"))]
 mod tests;
+mod timing;
+#[cfg(feature = "tui")]
 mod ui;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventState, KeyModifiers};
-use git2::Repository;
-use items::Item;
-use ops::Action;
-use std::{error::Error, path::PathBuf, process::Command, rc::Rc, time::Duration};
-use term::Term;
+/// Gitu's diff model, usable without the TUI (with `default-features = false`), see `diff::parse`.
+pub use git::diff;
+
+use std::error::Error;
+#[cfg(feature = "tui")]
+use {
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventState, KeyModifiers},
+    git2::Repository,
+    items::Item,
+    ops::Action,
+    std::{path::PathBuf, rc::Rc, time::Duration},
+    term::Term,
+};
 
 //                                An overview of Gitu's ui and terminology:
 //
@@ -63,24 +103,16 @@ use term::Term;
 
 pub type Res<T> = Result<T, Box<dyn Error>>;
 
-pub fn run(args: &cli::Args, term: &mut Term) -> Res<()> {
-    log::debug!("Finding git dir");
-    let dir = PathBuf::from(
-        String::from_utf8(
-            Command::new("git")
-                .args(["rev-parse", "--show-toplevel"])
-                .output()?
-                .stdout,
-        )?
-        .trim_end(),
-    );
+#[cfg(feature = "tui")]
+const MAX_EVENTS_PER_FRAME: usize = 100;
 
-    log::debug!("Opening repo");
-    let repo = open_repo_from_env()?;
-    repo.set_workdir(&dir, false)?;
+#[cfg(feature = "tui")]
+pub fn run(args: &cli::Args, term: &mut Term) -> Res<()> {
+    if args.debug_timing() {
+        timing::enable();
+    }
 
-    log::debug!("Initializing config");
-    let config = config::init_config()?;
+    let (config, repo) = init(args)?;
 
     log::debug!("Creating initial state");
     let mut state = state::State::create(Rc::new(repo), term.size()?, args, Rc::new(config), true)?;
@@ -100,11 +132,15 @@ pub fn run(args: &cli::Args, term: &mut Term) -> Res<()> {
     }
 
     while !state.quit {
-        let events = if event::poll(Duration::from_millis(100))? {
-            vec![event::read()?]
-        } else {
-            vec![]
-        };
+        let mut events = vec![];
+        if event::poll(Duration::from_millis(100))? {
+            events.push(event::read()?);
+
+            // Handle whatever queued up meanwhile (e.g. a held down key) before rendering again
+            while events.len() < MAX_EVENTS_PER_FRAME && event::poll(Duration::ZERO)? {
+                events.push(event::read()?);
+            }
+        }
 
         state.update(term, &events)?;
     }
@@ -112,16 +148,47 @@ pub fn run(args: &cli::Args, term: &mut Term) -> Res<()> {
     Ok(())
 }
 
-fn open_repo_from_env() -> Res<Repository> {
-    match Repository::open_from_env() {
-        Ok(repo) => Ok(repo),
-        Err(err) if err.code() == git2::ErrorCode::NotFound => {
-            Err("No .git found in the current directory".into())
-        }
-        Err(err) => Err(Box::new(err)),
-    }
+#[cfg(feature = "tui")]
+/// Prints the output of `args.command` as JSON, see `cli::Args::json`.
+pub fn print_json(args: &cli::Args) -> Res<()> {
+    let (config, repo) = init(args)?;
+    json::print(&config, &repo, args.command.as_ref())
+}
+
+#[cfg(feature = "tui")]
+fn init(args: &cli::Args) -> Res<(config::Config, Repository)> {
+    log::debug!("Initializing config");
+    let config = config::init_config()?;
+
+    log::debug!("Checking git binary");
+    git::init_binary(
+        std::env::var_os("GITU_GIT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| config.git.path.clone()),
+    )?;
+    git::init_config_overrides(
+        config
+            .git
+            .extra_config
+            .iter()
+            .chain(&args.git_config)
+            .cloned()
+            .collect(),
+    )?;
+
+    log::debug!("Opening repo");
+    let repo = git::open_repo(git::command())?;
+
+    let config = if args.no_local_config {
+        config
+    } else {
+        config::init_repo_config(config, repo.workdir().expect("No workdir"))?
+    };
+
+    Ok((config, repo))
 }
 
+#[cfg(feature = "tui")]
 fn handle_initial_send_keys(
     keys: &[(KeyModifiers, KeyCode)],
     state: &mut state::State,
@@ -0,0 +1,138 @@
+use crate::Res;
+use crossterm::event::KeyEvent;
+use std::collections::{BTreeMap, VecDeque};
+
+/// A key pressed while recording a macro, and whether it went to a prompt.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RecordedKey {
+    pub key: KeyEvent,
+    pub in_prompt: bool,
+}
+
+/// Key presses recorded into named registers, to be replayed like Vim's `q` and `@`.
+#[derive(Default)]
+pub(crate) struct Macros {
+    registers: BTreeMap<String, Vec<RecordedKey>>,
+    recording: Option<(String, Vec<RecordedKey>)>,
+    replaying: VecDeque<RecordedKey>,
+    last_register: Option<String>,
+}
+
+impl Macros {
+    pub(crate) fn recording(&self) -> Option<&str> {
+        self.recording
+            .as_ref()
+            .map(|(register, _)| register.as_str())
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        !self.replaying.is_empty()
+    }
+
+    pub(crate) fn start_recording(&mut self, register: String) {
+        self.recording = Some((register, vec![]));
+    }
+
+    /// Saves the recorded keys, except for the last `binding_len` ones that stopped the recording.
+    /// Returns the register and how many keys it has.
+    pub(crate) fn stop_recording(&mut self, binding_len: usize) -> Option<(String, usize)> {
+        let (register, mut keys) = self.recording.take()?;
+        keys.truncate(keys.len().saturating_sub(binding_len));
+
+        let len = keys.len();
+        self.registers.insert(register.clone(), keys);
+        self.last_register = Some(register.clone());
+        Some((register, len))
+    }
+
+    pub(crate) fn record(&mut self, key: KeyEvent, in_prompt: bool) {
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push(RecordedKey { key, in_prompt });
+        }
+    }
+
+    pub(crate) fn registers(&self) -> Vec<String> {
+        self.registers.keys().cloned().collect()
+    }
+
+    pub(crate) fn last_register(&self) -> Option<String> {
+        self.last_register.clone()
+    }
+
+    /// Queues the keys of `register` to be handled `times` over.
+    pub(crate) fn replay(&mut self, register: &str, times: usize) -> Res<()> {
+        let Some(keys) = self.registers.get(register) else {
+            return Err(format!("No macro recorded in register '{}'", register).into());
+        };
+
+        for _ in 0..times {
+            self.replaying.extend(keys.iter().copied());
+        }
+
+        self.last_register = Some(register.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn next_replayed(&mut self) -> Option<RecordedKey> {
+        self.replaying.pop_front()
+    }
+
+    pub(crate) fn stop_replaying(&mut self) {
+        self.replaying.clear();
+    }
+}
+
+/// Reads what to replay: a register, optionally preceded by how many times, like `3a`.
+pub(crate) fn parse_replay(input: &str) -> Res<(usize, &str)> {
+    let input = input.trim();
+    let register = input.trim_start_matches(|c: char| c.is_ascii_digit());
+    let times = match &input[..input.len() - register.len()] {
+        "" => 1,
+        digits => digits
+            .parse()
+            .ok()
+            .filter(|&times| times > 0)
+            .ok_or_else(|| format!("Can't replay a macro {} times", digits))?,
+    };
+
+    if register.is_empty() {
+        return Err("No register given".into());
+    }
+
+    Ok((times, register))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_replay, Macros};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn replay_input() {
+        assert_eq!(parse_replay("a").unwrap(), (1, "a"));
+        assert_eq!(parse_replay(" 12stage ").unwrap(), (12, "stage"));
+        assert!(parse_replay("3").is_err());
+        assert!(parse_replay("0a").is_err());
+        assert!(parse_replay("").is_err());
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let mut macros = Macros::default();
+
+        macros.start_recording("a".into());
+        macros.record(key('j'), false);
+        macros.record(key('s'), false);
+        macros.record(key('M'), false);
+        assert_eq!(macros.stop_recording(1), Some(("a".into(), 2)));
+
+        assert!(macros.replay("b", 1).is_err());
+        macros.replay("a", 2).unwrap();
+        let replayed = std::iter::from_fn(|| macros.next_replayed())
+            .map(|recorded| recorded.key.code)
+            .collect::<Vec<_>>();
+        assert_eq!(replayed, [KeyCode::Char('j'), KeyCode::Char('s')].repeat(2));
+        assert!(!macros.is_replaying());
+    }
+}
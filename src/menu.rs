@@ -2,8 +2,10 @@ use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::ffi::OsString;
 
+use git2::Repository;
 use serde::{Deserialize, Serialize};
 
+use crate::config::Config;
 use crate::ops;
 
 pub(crate) mod arg;
@@ -13,16 +15,28 @@ pub(crate) mod arg;
 pub(crate) enum Menu {
     #[serde(rename = "root")]
     Root,
+    #[serde(rename = "bisect_menu")]
+    Bisect,
     #[serde(rename = "branch_menu")]
     Branch,
+    #[serde(rename = "browse_menu")]
+    Browse,
+    #[serde(rename = "clone_menu")]
+    Clone,
     #[serde(rename = "commit_menu")]
     Commit,
+    #[serde(rename = "config_menu")]
+    Config,
+    #[serde(rename = "diff_menu")]
+    Diff,
     #[serde(rename = "fetch_menu")]
     Fetch,
     #[serde(rename = "help_menu")]
     Help,
     #[serde(rename = "log_menu")]
     Log,
+    #[serde(rename = "patch_menu")]
+    Patch,
     #[serde(rename = "pull_menu")]
     Pull,
     #[serde(rename = "push_menu")]
@@ -35,6 +49,28 @@ pub(crate) enum Menu {
     Revert,
     #[serde(rename = "stash_menu")]
     Stash,
+    #[serde(rename = "worktree_menu")]
+    Worktree,
+}
+
+impl Menu {
+    /// The git subcommand that the arguments of this menu are passed to.
+    fn git_subcommand(self) -> Option<&'static str> {
+        match self {
+            Menu::Branch => Some("checkout"),
+            Menu::Clone => Some("clone"),
+            Menu::Commit => Some("commit"),
+            Menu::Fetch => Some("fetch"),
+            Menu::Patch => Some("apply"),
+            Menu::Pull => Some("pull"),
+            Menu::Push => Some("push"),
+            Menu::Rebase => Some("rebase"),
+            Menu::Reset => Some("reset"),
+            Menu::Revert => Some("revert"),
+            Menu::Stash => Some("stash"),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) struct PendingMenu {
@@ -44,23 +80,30 @@ pub(crate) struct PendingMenu {
 }
 
 impl PendingMenu {
-    pub fn init(menu: Menu) -> Self {
+    pub fn init(menu: Menu, config: &Config, repo: &Repository) -> Self {
         Self {
             menu,
             is_hidden: false,
             args: match menu {
                 Menu::Root => vec![],
+                Menu::Bisect => vec![],
                 Menu::Branch => ops::checkout::init_args(),
-                Menu::Commit => ops::commit::init_args(),
+                Menu::Browse => vec![],
+                Menu::Clone => ops::clone::init_args(),
+                Menu::Commit => ops::commit::init_args(config, repo),
+                Menu::Config => vec![],
+                Menu::Diff => vec![],
                 Menu::Fetch => ops::fetch::init_args(),
                 Menu::Help => vec![],
                 Menu::Log => ops::log::init_args(),
+                Menu::Patch => ops::patch::init_args(),
                 Menu::Pull => ops::pull::init_args(),
                 Menu::Push => ops::push::init_args(),
                 Menu::Rebase => ops::rebase::init_args(),
                 Menu::Reset => ops::reset::init_args(),
                 Menu::Revert => ops::revert::init_args(),
                 Menu::Stash => ops::stash::init_args(),
+                Menu::Worktree => vec![],
             }
             .into_iter()
             .map(|arg| (Cow::from(arg.arg), arg))
@@ -75,4 +118,22 @@ impl PendingMenu {
             .map(|(_, v)| v.get_cli_token().into())
             .collect()
     }
+
+    /// What the active arguments amount to, e.g. `git push --force-with-lease`.
+    /// The command run also gets whatever the chosen action adds, like a remote.
+    pub fn command_preview(&self) -> Option<String> {
+        let subcommand = self.menu.git_subcommand()?;
+        let args = self
+            .args
+            .values()
+            .filter(|arg| arg.is_active())
+            .map(|arg| arg.get_cli_token())
+            .collect::<Vec<_>>();
+
+        if args.is_empty() {
+            return None;
+        }
+
+        Some(format!("git {} {}", subcommand, args.join(" ")))
+    }
 }
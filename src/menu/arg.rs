@@ -172,6 +172,14 @@ pub fn positive_number(s: &str) -> Res<u32> {
     Err("Value must be a number greater than 0".into())
 }
 
+pub fn any_string(s: &str) -> Res<String> {
+    if s.is_empty() {
+        return Err("Value must not be empty".into());
+    }
+
+    Ok(s.to_string())
+}
+
 pub fn any_regex(s: &str) -> Res<Regex> {
     Ok(Regex::try_from(s)?)
 }
@@ -0,0 +1,146 @@
+use super::{create_prompt_with_default, selected_rev, set_prompt, Action, OpTrait};
+use crate::{cmd_log::CmdLogEntry, git, items::TargetData, screen, state::State, term::Term, Res};
+use std::rc::Rc;
+
+pub(crate) struct BisectStart;
+impl OpTrait for BisectStart {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(create_prompt_with_default(
+            "Bisect bad revision",
+            bisect_start,
+            selected_rev,
+            true,
+        ))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "start".into()
+    }
+}
+
+fn bisect_start(state: &mut State, _term: &mut Term, bad: &str) -> Res<()> {
+    let bad = bad.to_string();
+    state.close_menu();
+
+    set_prompt(
+        state,
+        "Bisect good revision",
+        Box::new(move |state, term, good| {
+            let mut cmd = git::command();
+            cmd.args(["bisect", "start", &bad, good]);
+
+            state.run_cmd(term, &[], cmd)?;
+            show_first_bad_commit(state, term)
+        }),
+        Box::new(|_| None),
+        false,
+    );
+
+    Ok(())
+}
+
+pub(crate) struct BisectGood;
+impl OpTrait for BisectGood {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(bisect_mark("good"))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "good".into()
+    }
+}
+
+pub(crate) struct BisectBad;
+impl OpTrait for BisectBad {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(bisect_mark("bad"))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "bad".into()
+    }
+}
+
+pub(crate) struct BisectSkip;
+impl OpTrait for BisectSkip {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(bisect_mark("skip"))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "skip".into()
+    }
+}
+
+fn bisect_mark(term_arg: &'static str) -> Action {
+    Rc::new(move |state: &mut State, term: &mut Term| {
+        let mut cmd = git::command();
+        cmd.args(["bisect", term_arg]);
+
+        state.close_menu();
+        state.run_cmd(term, &[], cmd)?;
+        show_first_bad_commit(state, term)
+    })
+}
+
+pub(crate) struct BisectReset;
+impl OpTrait for BisectReset {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            let mut cmd = git::command();
+            cmd.args(["bisect", "reset"]);
+
+            state.close_menu();
+            state.run_cmd(term, &[], cmd)
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "reset".into()
+    }
+}
+
+/// Opens the culprit commit once the last `git bisect` command has narrowed it down.
+fn show_first_bad_commit(state: &mut State, term: &mut Term) -> Res<()> {
+    let commit = state.current_cmd_log.entries.last().and_then(|entry| {
+        let CmdLogEntry { out, .. } = &*entry.read().unwrap();
+        out.as_deref().and_then(first_bad_commit).map(String::from)
+    });
+
+    if let Some(commit) = commit {
+        state.screens.push(screen::show::create(
+            Rc::clone(&state.config),
+            Rc::clone(&state.repo),
+            term.size()?,
+            commit,
+        )?);
+    }
+
+    Ok(())
+}
+
+fn first_bad_commit(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.strip_suffix(" is the first bad commit"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::first_bad_commit;
+
+    #[test]
+    fn parse_first_bad_commit() {
+        let output = "3933d9f49b5d77a80e9128082d0f8f4545a3e1db is the first bad commit\n\
+            commit 3933d9f49b5d77a80e9128082d0f8f4545a3e1db\n";
+
+        assert_eq!(
+            first_bad_commit(output),
+            Some("3933d9f49b5d77a80e9128082d0f8f4545a3e1db")
+        );
+        assert_eq!(
+            first_bad_commit("Bisecting: 3 revisions left to test after this (roughly 2 steps)\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use super::{Action, OpTrait};
+use crate::{
+    git::{self, diff::Hunk},
+    items::TargetData,
+    state::State,
+};
+use git2::Repository;
+use std::{path::PathBuf, rc::Rc};
+
+pub(crate) struct BlameLine;
+impl OpTrait for BlameLine {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let Some(TargetData::HunkLine(hunk, i)) = target else {
+            return None;
+        };
+
+        let (line, rev) = blamed_line(hunk, *i)?;
+        let path = hunk.new_file.clone();
+        let text = hunk.content.lines.get(*i)?.to_string();
+
+        Some(Rc::new(move |state, _term| {
+            state.close_menu();
+
+            let head = head_id(&state.repo);
+            let key = (path.clone(), line, text.clone(), head);
+            let summary = match state.blame_cache.get(&key) {
+                Some(summary) => summary.clone(),
+                None => {
+                    let summary = describe(git::blame_line(&state.repo, &path, line, rev)?);
+                    state.blame_cache.insert(key, summary.clone());
+                    summary
+                }
+            };
+
+            state.display_info(summary);
+            Ok(())
+        }))
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Blame line".into()
+    }
+}
+
+/// The line number to blame a hunk line at, and the revision to blame it in.
+/// Removed lines no longer exist in the working tree, so they're blamed as of `HEAD`.
+pub(crate) fn blamed_line(hunk: &Hunk, i: usize) -> Option<(u32, Option<&'static str>)> {
+    match hunk.line_numbers().get(i)? {
+        (_, Some(new)) => Some((*new, None)),
+        (Some(old), None) => Some((*old, Some("HEAD"))),
+        (None, None) => None,
+    }
+}
+
+pub(crate) type BlameKey = (PathBuf, u32, String, Option<git2::Oid>);
+
+fn head_id(repo: &Repository) -> Option<git2::Oid> {
+    repo.head().ok().and_then(|head| head.target())
+}
+
+fn describe(blame: git::blame::Blame) -> String {
+    if !blame.is_committed() {
+        return "Not committed yet".into();
+    }
+
+    format!("{} {}: {}", &blame.hash[..7], blame.author, blame.summary)
+}
@@ -0,0 +1,142 @@
+use super::{Action, OpTrait};
+use crate::{
+    config::ForgePagesConfig,
+    git::{forge, remote},
+    items::TargetData,
+    state::State,
+    Res,
+};
+use std::{env, path::PathBuf, rc::Rc};
+
+pub(crate) struct BrowseRepo;
+impl OpTrait for BrowseRepo {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            browse(state, |pages| &pages.repo, &[])
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Repository".into()
+    }
+}
+
+pub(crate) struct BrowseBranch;
+impl OpTrait for BrowseBranch {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            let branch = forge_branch(state)?;
+            browse(state, |pages| &pages.branch, &[("branch", &branch)])
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Current branch".into()
+    }
+}
+
+/// The branch as it's named on `origin`, if it tracks one there.
+fn forge_branch(state: &State) -> Res<String> {
+    if let Ok(Some((remote, branch))) = remote::get_upstream_components(&state.repo) {
+        if remote == "origin" {
+            return Ok(branch);
+        }
+    }
+
+    let head = state.repo.head()?;
+    if !head.is_branch() {
+        return Err("Head is not a branch".into());
+    }
+
+    Ok(head.shorthand().ok_or("Branch name not utf-8")?.to_string())
+}
+
+pub(crate) struct BrowseFile;
+impl OpTrait for BrowseFile {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let path = match target {
+            Some(TargetData::Delta(d)) if d.status != git2::Delta::Deleted => d.new_file.clone(),
+            Some(TargetData::Hunk(h) | TargetData::HunkLine(h, _)) => h.new_file.clone(),
+            Some(TargetData::File(f)) => f.clone(),
+            _ => return None,
+        };
+
+        Some(browse_file(path))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "File at HEAD".into()
+    }
+}
+
+fn browse_file(path: PathBuf) -> Action {
+    Rc::new(move |state, _term| {
+        let rev = state.repo.head()?.peel_to_commit()?.id().to_string();
+        let path = path.to_string_lossy().to_string();
+        browse(
+            state,
+            |pages| &pages.file,
+            &[("rev", &rev), ("path", &path)],
+        )
+    })
+}
+
+pub(crate) struct BrowseCommit;
+impl OpTrait for BrowseCommit {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let Some(TargetData::Commit(rev)) = target else {
+            return None;
+        };
+
+        let rev = rev.clone();
+        Some(Rc::new(move |state, _term| {
+            browse(state, |pages| &pages.commit, &[("rev", &rev)])
+        }))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Commit".into()
+    }
+}
+
+/// Opens one of the pages of `origin`, as configured in `[forge.browse]` for its host.
+fn browse(
+    state: &mut State,
+    page: fn(&ForgePagesConfig) -> &String,
+    placeholders: &[(&str, &str)],
+) -> Res<()> {
+    state.close_menu();
+
+    let remote = forge::origin(&state.repo)?;
+    let pages = state
+        .config
+        .forge
+        .browse
+        .get(&remote.host)
+        .ok_or_else(|| format!("No forge pages for '{}' in [forge.browse]", remote.host))?;
+
+    let url = forge::fill(page(pages), &remote, placeholders);
+    open_in_browser(&url)?;
+    state.display_info(format!("Opened {}", url));
+    Ok(())
+}
+
+/// Opens `url` with `$BROWSER` if it's set, otherwise with whatever the system opens links with.
+fn open_in_browser(url: &str) -> Res<()> {
+    match env::var("BROWSER") {
+        // Like `sensible-browser`, the first of a `:`-separated list is used
+        Ok(browser) if !browser.is_empty() => {
+            open::with_detached(url, browser.split(':').next().unwrap_or(&browser))
+        }
+        _ => open::that_detached(url),
+    }
+    .map_err(|error| format!("Couldn't open a browser: {}", error).into())
+}
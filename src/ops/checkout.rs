@@ -1,6 +1,8 @@
 use super::{create_prompt_with_default, selected_rev, Action, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, prompt::PromptData, state::State, term::Term, Res};
-use std::{process::Command, rc::Rc};
+use crate::{
+    git, items::TargetData, menu::arg::Arg, prompt::PromptData, state::State, term::Term, Res,
+};
+use std::rc::Rc;
 use tui_prompts::State as _;
 
 pub(crate) fn init_args() -> Vec<Arg> {
@@ -24,7 +26,7 @@ impl OpTrait for Checkout {
 }
 
 fn checkout(state: &mut State, term: &mut Term, rev: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["checkout"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.arg(rev);
@@ -57,7 +59,7 @@ fn checkout_new_branch_prompt_update(state: &mut State, term: &mut Term) -> Res<
         let name = state.prompt.state.value().to_string();
         state.prompt.reset(term)?;
 
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["checkout", "-b", &name]);
 
         state.run_cmd(term, &[], cmd)?;
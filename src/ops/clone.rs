@@ -0,0 +1,118 @@
+use super::{create_prompt, set_prompt, Action, OpTrait};
+use crate::{
+    git,
+    items::TargetData,
+    menu::arg::{positive_number, Arg},
+    state::State,
+    term::Term,
+    Res,
+};
+use std::{env, ffi::OsString, fs, path::Path};
+
+pub(crate) fn init_args() -> Vec<Arg> {
+    vec![
+        Arg::new_arg(
+            "--depth",
+            "Only fetch this many recent commits",
+            None,
+            positive_number,
+        ),
+        Arg::new_flag("--recurse-submodules", "Clone submodules too", false),
+    ]
+}
+
+pub(crate) struct CloneRepo;
+impl OpTrait for CloneRepo {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(create_prompt("Clone from", clone_from, true))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Clone".into()
+    }
+}
+
+fn clone_from(state: &mut State, _term: &mut Term, url: &str) -> Res<()> {
+    validate_url(url)?;
+
+    let url = url.to_string();
+    let args = state.pending_menu.as_ref().unwrap().args();
+    let default_dir = default_dir(&url);
+    state.close_menu();
+
+    set_prompt(
+        state,
+        "Clone into",
+        Box::new(move |state, term, dir| clone_into(state, term, &url, &args, dir)),
+        Box::new(move |_| default_dir.clone()),
+        false,
+    );
+
+    Ok(())
+}
+
+/// Clones with the terminal handed over to git, which shows its progress and may ask for credentials.
+/// Gitu then continues in the new repository.
+fn clone_into(
+    state: &mut State,
+    term: &mut Term,
+    url: &str,
+    args: &[OsString],
+    dir: &str,
+) -> Res<()> {
+    if dir.is_empty() {
+        return Err("Clone directory can't be empty".into());
+    }
+
+    // Relative to where gitu was started, rather than inside the current repository
+    let dir = env::current_dir()?.join(dir);
+    if dir.exists() && fs::read_dir(&dir)?.next().is_some() {
+        return Err(format!("{} already exists and isn't empty", dir.display()).into());
+    }
+
+    let mut cmd = git::command();
+    cmd.arg("clone");
+    cmd.args(args);
+    cmd.args(["--", url]);
+    cmd.arg(&dir);
+
+    state.run_cmd_suspended(term, cmd)?;
+    state.open_repo(term, &dir)
+}
+
+/// Accepts what `git clone` does: a URL like `https://host/repo`, the scp-like `host:repo`, or a local path.
+fn validate_url(url: &str) -> Res<()> {
+    if url.is_empty() {
+        return Err("Repository URL can't be empty".into());
+    }
+
+    let is_url = url.split_once("://").is_some_and(|(scheme, rest)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+            && !rest.is_empty()
+    });
+
+    let is_scp_like = url
+        .split_once(':')
+        .is_some_and(|(host, path)| !host.is_empty() && !host.contains('/') && !path.is_empty());
+
+    if is_url || is_scp_like || Path::new(url).exists() {
+        Ok(())
+    } else {
+        Err(format!("{} isn't a URL or a path to a repository", url).into())
+    }
+}
+
+/// Like git, names the clone after the last part of the URL, without any `.git`.
+fn default_dir(url: &str) -> Option<String> {
+    let path = url.trim_end_matches('/');
+    let path = path.strip_suffix("/.git").unwrap_or(path);
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    path.rsplit(['/', ':'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
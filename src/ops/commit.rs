@@ -1,16 +1,44 @@
-use super::{Action, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term};
+use super::{set_prompt, Action, OpTrait};
+use crate::{
+    config::Config,
+    git::{
+        self,
+        diff_options::DiffOptions,
+        whitespace::{self, WhitespaceRules},
+    },
+    items::TargetData,
+    menu::arg::Arg,
+    screen,
+    state::State,
+    term::Term,
+    Res,
+};
+use git2::Repository;
+use itertools::Itertools;
 use std::{
     ffi::{OsStr, OsString},
+    fs, io,
+    path::PathBuf,
     process::Command,
     rc::Rc,
 };
 
-pub(crate) fn init_args() -> Vec<Arg> {
+pub(crate) fn init_args(config: &Config, repo: &Repository) -> Vec<Arg> {
+    let signoff = config.commit.signoff.enabled;
+    let scissors = repo
+        .config()
+        .and_then(|config| config.get_string("commit.cleanup"))
+        .is_ok_and(|cleanup| cleanup == "scissors");
+
     vec![
         Arg::new_flag("--all", "Stage all modified and deleted files", false),
         Arg::new_flag("--allow-empty", "Allow empty commit", false),
         Arg::new_flag("--verbose", "Show diff of changes to be committed", false),
+        Arg::new_flag(
+            "--cleanup=scissors",
+            "Show staged diff below scissors line",
+            scissors,
+        ),
         Arg::new_flag("--no-verify", "Disable hooks", false),
         Arg::new_flag(
             "--reset-author",
@@ -18,7 +46,7 @@ pub(crate) fn init_args() -> Vec<Arg> {
             false,
         ),
         // TODO -A Override the author (--author=)
-        Arg::new_flag("--signoff", "Add Signed-off-by line", false),
+        Arg::new_flag("--signoff", "Add Signed-off-by line", signoff),
         // TODO -C Reuse commit message (--reuse-message=)
     ]
 }
@@ -27,13 +55,10 @@ pub(crate) struct Commit;
 impl OpTrait for Commit {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
-            cmd.args(["commit"]);
-            cmd.args(state.pending_menu.as_ref().unwrap().args());
+            let cmd = commit_cmd(state, false)?;
 
             state.close_menu();
-            state.run_cmd_interactive(term, cmd)?;
-            Ok(())
+            run_commit_interactive(state, term, cmd)
         }))
     }
 
@@ -42,25 +67,367 @@ impl OpTrait for Commit {
     }
 }
 
+pub(crate) struct CommitQuick;
+impl OpTrait for CommitQuick {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            set_prompt(
+                state,
+                "Message (\\n for a new line, ctrl+o for the editor)",
+                Box::new(|state, term, input| {
+                    let mut cmd = commit_cmd(state, false)?;
+                    cmd.args(["--message", &quick_message(input)]);
+
+                    state.close_menu();
+                    let result = state.run_cmd(term, &[], cmd);
+                    remove_scissors_template(state);
+                    result
+                }),
+                Box::new(|_| None),
+                true,
+            );
+
+            // The editor starts out with what was typed as the subject
+            state.prompt.editor_fn = Some(Rc::new(|state, term, input| {
+                let mut cmd = commit_cmd(state, false)?;
+                cmd.args(["--edit", "--message", &quick_message(input)]);
+
+                run_commit_interactive(state, term, cmd)?;
+                state.close_menu();
+                Ok(())
+            }));
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Commit with a message".into()
+    }
+}
+
+/// A message typed into a single line, with each literal `\n` as a line break.
+fn quick_message(input: &str) -> String {
+    input.replace("\\n", "\n")
+}
+
 pub(crate) struct CommitAmend;
 impl OpTrait for CommitAmend {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
-            cmd.args(["commit", "--amend"]);
-            cmd.args(state.pending_menu.as_ref().unwrap().args());
+            let cmd = commit_cmd(state, true)?;
 
             state.close_menu();
-            state.run_cmd_interactive(term, cmd)?;
-            Ok(())
+            run_commit_interactive(state, term, cmd)
         }))
     }
 
+    fn display(&self, state: &State) -> String {
+        let resets_author = state
+            .pending_menu
+            .as_ref()
+            .is_some_and(|menu| menu.args().iter().any(|arg| arg == "--reset-author"));
+
+        if resets_author {
+            return "amend, claiming authorship".into();
+        }
+
+        // Like `git commit --amend`, the author stays and only the committer is updated
+        match head_author(&state.repo) {
+            Some(author) => format!("amend, keeping author {}", author),
+            None => "amend".into(),
+        }
+    }
+}
+
+pub(crate) struct CommitWhitespaceCleaned;
+impl OpTrait for CommitWhitespaceCleaned {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            let (patch, cleaned) = whitespace_cleanup_patch(state)?;
+            let cmd = commit_cmd(state, false)?;
+            state.close_menu();
+
+            if cleaned == 0 {
+                return run_commit_interactive(state, term, cmd);
+            }
+
+            state.screens.push(screen::preview_patch::create(
+                Rc::clone(&state.config),
+                term.size()?,
+                patch.clone(),
+            )?);
+
+            let mut cmd = Some(cmd);
+            let commit = Rc::new(move |state: &mut State, term: &mut Term| {
+                state.screens.pop();
+
+                // Only the index is patched, the working tree keeps its whitespace
+                let mut apply = git::command();
+                apply.args(["apply", "--cached", "--unidiff-zero"]);
+                state.run_cmd(term, patch.as_bytes(), apply)?;
+
+                match cmd.take() {
+                    Some(cmd) => run_commit_interactive(state, term, cmd),
+                    None => Ok(()),
+                }
+            });
+
+            let mut prompt = super::create_y_n_prompt(
+                commit,
+                format!(
+                    "Remove trailing whitespace from {} staged line{} and commit?",
+                    cleaned,
+                    if cleaned == 1 { "" } else { "s" }
+                ),
+            );
+            Rc::get_mut(&mut prompt).unwrap()(state, term)
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Commit, trim whitespace".into()
+    }
+}
+
+/// A patch of the index that removes the trailing whitespace the staged changes add,
+/// as `core.whitespace` has it, and how many lines it cleans.
+fn whitespace_cleanup_patch(state: &State) -> Res<(String, usize)> {
+    let core_whitespace = state
+        .repo
+        .config()
+        .and_then(|config| config.get_string("core.whitespace"))
+        .ok();
+    let rules = WhitespaceRules::parse(core_whitespace.as_deref());
+
+    let staged = git::diff_staged(
+        &state.config,
+        &state.repo,
+        &DiffOptions::new().algorithm(state.config.diff.algorithm),
+    )?;
+
+    Ok(staged
+        .deltas
+        .iter()
+        .filter_map(|delta| whitespace::cleanup_patch(delta, rules))
+        .fold((String::new(), 0), |(patch, cleaned), (delta_patch, n)| {
+            (patch + &delta_patch, cleaned + n)
+        }))
+}
+
+/// Runs a command from `commit_cmd` in the editor, removing the scissors template it may use once it's done.
+fn run_commit_interactive(state: &mut State, term: &mut Term, cmd: Command) -> Res<()> {
+    let result = state.run_cmd_interactive(term, cmd);
+    remove_scissors_template(state);
+    result
+}
+
+fn commit_cmd(state: &State, amend: bool) -> Res<Command> {
+    let args = state.pending_menu.as_ref().unwrap().args();
+
+    let mut cmd = git::command();
+    cmd.arg("commit");
+    if amend {
+        cmd.arg("--amend");
+    }
+    cmd.args(&args);
+
+    if args.iter().any(|arg| arg == "--cleanup=scissors") {
+        let template = write_scissors_template(state, amend)?;
+        // Git's own status and scissors line would end up after this one
+        cmd.args([OsStr::new("--no-status"), OsStr::new("--edit")]);
+        cmd.arg(OsString::from_iter([
+            OsStr::new("--file="),
+            template.as_os_str(),
+        ]));
+    }
+
+    Ok(cmd)
+}
+
+/// Like `git commit --verbose`, but with the staged changes as gitu shows them below the scissors line.
+fn write_scissors_template(state: &State, amend: bool) -> Res<PathBuf> {
+    let message = if amend {
+        state
+            .repo
+            .head()?
+            .peel_to_commit()?
+            .message()
+            .unwrap_or("")
+            .to_string()
+    } else {
+        String::new()
+    };
+
+    let staged = git::diff_staged(
+        &state.config,
+        &state.repo,
+        &DiffOptions::new().algorithm(state.config.diff.algorithm),
+    )?;
+    let diff = staged
+        .deltas
+        .iter()
+        .map(|delta| delta.format_patch())
+        .join("");
+
+    let comment_char = state
+        .repo
+        .config()
+        .and_then(|config| config.get_string("core.commentChar"))
+        .ok()
+        .and_then(|comment| comment.chars().exactly_one().ok())
+        .unwrap_or('#');
+
+    let path = scissors_template_path(state);
+    fs::write(
+        &path,
+        git::commit::scissors_template(&message, &diff, comment_char),
+    )?;
+    Ok(path)
+}
+
+fn scissors_template_path(state: &State) -> PathBuf {
+    state.repo.path().join("GITU_COMMIT_TEMPLATE")
+}
+
+/// A template left behind doesn't break anything, so failing to remove it is only logged.
+fn remove_scissors_template(state: &State) {
+    match fs::remove_file(scissors_template_path(state)) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            log::warn!("Couldn't remove the commit template: {}", error);
+        }
+        _ => (),
+    }
+}
+
+fn head_author(repo: &Repository) -> Option<String> {
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    let author = commit.author();
+    author.name().map(str::to_string)
+}
+
+pub(crate) struct CommitCoAuthored;
+impl OpTrait for CommitCoAuthored {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(co_authors_prompt(false))
+    }
+
     fn display(&self, _state: &State) -> String {
-        "amend".into()
+        "Commit with co-authors".into()
     }
 }
 
+pub(crate) struct CommitAmendCoAuthored;
+impl OpTrait for CommitAmendCoAuthored {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(co_authors_prompt(true))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "amend with co-authors".into()
+    }
+}
+
+fn co_authors_prompt(amend: bool) -> Action {
+    Rc::new(move |state: &mut State, _term: &mut Term| {
+        let candidates = co_author_candidates(state)?;
+        let completions = candidates.clone();
+
+        set_prompt(
+            state,
+            "Co-authors (e.g. ada, grace)",
+            Box::new(move |state, term, input| {
+                let co_authors = pick_co_authors(&candidates, input)?;
+                let args = state.pending_menu.as_ref().unwrap().args();
+
+                let mut cmd = git::command();
+                // Co-authors that the message already has, like when amending, aren't added again
+                cmd.args(["-c", "trailer.ifexists=addIfDifferent", "commit"]);
+                if amend {
+                    cmd.arg("--amend");
+                }
+                cmd.args(args);
+                cmd.args(
+                    co_authors
+                        .iter()
+                        .map(|co_author| format!("--trailer=Co-authored-by: {}", co_author)),
+                );
+
+                state.close_menu();
+                state.run_cmd_interactive(term, cmd)
+            }),
+            Box::new(|_| None),
+            true,
+        );
+        state.prompt.completions = completions;
+        Ok(())
+    })
+}
+
+/// The configured co-authors, followed by recent contributors other than oneself.
+fn co_author_candidates(state: &State) -> Res<Vec<String>> {
+    let own_email = state
+        .repo
+        .config()
+        .and_then(|config| config.get_string("user.email"))
+        .ok()
+        .map(|email| format!("<{}>", email));
+
+    Ok(state
+        .config
+        .commit
+        .co_authors
+        .iter()
+        .cloned()
+        .chain(
+            git::recent_contributors(&state.repo)?
+                .into_iter()
+                .filter(|contributor| {
+                    !own_email.as_ref().is_some_and(|e| contributor.ends_with(e))
+                }),
+        )
+        .unique()
+        .collect())
+}
+
+/// Picks co-authors by any part of their name or email, or takes them as given in the `Name <email>`
+/// form that GitHub recognizes in `Co-authored-by:` trailers.
+fn pick_co_authors(candidates: &[String], input: &str) -> Res<Vec<String>> {
+    let picked = input
+        .split(',')
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            if is_name_and_email(term) {
+                return Ok(term.to_string());
+            }
+
+            let lowercase = term.to_lowercase();
+            let matches = candidates
+                .iter()
+                .filter(|candidate| candidate.to_lowercase().contains(&lowercase))
+                .collect::<Vec<_>>();
+
+            match matches[..] {
+                [co_author] => Ok(co_author.clone()),
+                [] => Err(format!("No co-author matches '{}', use 'Name <email>'", term).into()),
+                _ => Err(format!("'{}' matches {}", term, matches.iter().join(", ")).into()),
+            }
+        })
+        .collect::<Res<Vec<_>>>()?;
+
+    if picked.is_empty() {
+        return Err("No co-authors given".into());
+    }
+
+    Ok(picked.into_iter().unique().collect())
+}
+
+fn is_name_and_email(text: &str) -> bool {
+    text.strip_suffix('>')
+        .and_then(|text| text.split_once(" <"))
+        .is_some_and(|(name, email)| !name.trim().is_empty() && email.contains('@'))
+}
+
 pub(crate) struct CommitFixup;
 impl OpTrait for CommitFixup {
     fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
@@ -72,7 +439,7 @@ impl OpTrait for CommitFixup {
                     let args = state.pending_menu.as_ref().unwrap().args();
 
                     state.close_menu();
-                    state.run_cmd_interactive(term, commit_fixup_cmd(&args, &rev))
+                    state.run_cmd_interactive(term, commit_targeting_cmd(&args, "--fixup", &rev))
                 }))
             }
             _ => None,
@@ -88,9 +455,37 @@ impl OpTrait for CommitFixup {
     }
 }
 
-fn commit_fixup_cmd(args: &[OsString], rev: &OsStr) -> Command {
-    let mut cmd = Command::new("git");
-    cmd.args(["commit", "--fixup"]);
+pub(crate) struct CommitSquash;
+impl OpTrait for CommitSquash {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Commit(r)) => {
+                let rev = OsString::from(r);
+
+                Some(Rc::new(move |state: &mut State, term: &mut Term| {
+                    let args = state.pending_menu.as_ref().unwrap().args();
+
+                    state.close_menu();
+                    state.run_cmd_interactive(term, commit_targeting_cmd(&args, "--squash", &rev))
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "squash".into()
+    }
+}
+
+/// Creates a `fixup!` or `squash!` commit, which `git rebase --autosquash` will later apply to `rev`.
+fn commit_targeting_cmd(args: &[OsString], flag: &str, rev: &OsStr) -> Command {
+    let mut cmd = git::command();
+    cmd.args(["commit", flag]);
     cmd.arg(rev);
     cmd.args(args);
     cmd
@@ -108,7 +503,7 @@ impl OpTrait for CommitInstantFixup {
 
                     state.close_menu();
 
-                    state.run_cmd(term, &[], commit_fixup_cmd(&args, &rev))?;
+                    state.run_cmd(term, &[], commit_targeting_cmd(&args, "--fixup", &rev))?;
                     state.run_cmd(term, &[], rebase_autosquash_cmd(&rev))
                 }))
             }
@@ -125,8 +520,39 @@ impl OpTrait for CommitInstantFixup {
     }
 }
 
+pub(crate) struct CommitInstantSquash;
+impl OpTrait for CommitInstantSquash {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Commit(r)) => {
+                let rev = OsString::from(r);
+
+                Some(Rc::new(move |state: &mut State, term: &mut Term| {
+                    let args = state.pending_menu.as_ref().unwrap().args();
+
+                    state.close_menu();
+
+                    // Both of these open an editor: first for the squash message, then for the combined one.
+                    state
+                        .run_cmd_interactive(term, commit_targeting_cmd(&args, "--squash", &rev))?;
+                    state.run_cmd_interactive(term, rebase_autosquash_cmd(&rev))
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "instant squash".into()
+    }
+}
+
 fn rebase_autosquash_cmd(rev: &OsStr) -> Command {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args([
         "rebase",
         "-i",
@@ -145,3 +571,27 @@ fn parent(reference: &OsStr) -> OsString {
     parent.push("^");
     parent
 }
+
+#[cfg(test)]
+mod tests {
+    use super::pick_co_authors;
+
+    #[test]
+    fn pick_by_name_email_or_as_given() {
+        let candidates = ["Ada Lovelace <ada@example.com>".to_string()];
+
+        assert_eq!(
+            pick_co_authors(
+                &candidates,
+                "lovelace, Grace Hopper <grace@example.com>, ADA@"
+            )
+            .unwrap(),
+            vec![
+                "Ada Lovelace <ada@example.com>",
+                "Grace Hopper <grace@example.com>"
+            ]
+        );
+        assert!(pick_co_authors(&candidates, "grace").is_err());
+        assert!(pick_co_authors(&candidates, " , ").is_err());
+    }
+}
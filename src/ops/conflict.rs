@@ -0,0 +1,121 @@
+use super::{Action, OpTrait};
+use crate::{
+    git::{
+        self,
+        conflict::{self, ConflictHunk, Resolution},
+    },
+    items::TargetData,
+    screen,
+    state::State,
+    term::Term,
+    Res,
+};
+use std::{fs, path::Path, rc::Rc};
+
+pub(crate) struct ShowConflicts;
+impl OpTrait for ShowConflicts {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let Some(TargetData::File(path)) = target else {
+            return None;
+        };
+
+        let path = path.clone();
+        Some(Rc::new(move |state: &mut State, term: &mut Term| {
+            state.close_menu();
+            state.screens.push(screen::conflicts::create(
+                Rc::clone(&state.config),
+                Rc::clone(&state.repo),
+                term.size()?,
+                path.clone(),
+            )?);
+            Ok(())
+        }))
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Resolve conflicts".into()
+    }
+}
+
+pub(crate) struct ConflictAcceptOurs;
+impl OpTrait for ConflictAcceptOurs {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        accept(target, Resolution::Ours)
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Accept ours".into()
+    }
+}
+
+pub(crate) struct ConflictAcceptTheirs;
+impl OpTrait for ConflictAcceptTheirs {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        accept(target, Resolution::Theirs)
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Accept theirs".into()
+    }
+}
+
+pub(crate) struct ConflictAcceptBoth;
+impl OpTrait for ConflictAcceptBoth {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        accept(target, Resolution::Both)
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Accept both".into()
+    }
+}
+
+fn accept(target: Option<&TargetData>, resolution: Resolution) -> Option<Action> {
+    let Some(TargetData::Conflict(path, i)) = target else {
+        return None;
+    };
+
+    let (path, i) = (path.clone(), *i);
+    Some(Rc::new(move |state: &mut State, term: &mut Term| {
+        state.close_menu();
+        resolve_conflict(state, term, &path, i, resolution)
+    }))
+}
+
+/// Writes the resolved file, and stages it once no conflicts are left.
+fn resolve_conflict(
+    state: &mut State,
+    term: &mut Term,
+    path: &Path,
+    i: usize,
+    resolution: Resolution,
+) -> Res<()> {
+    let file = state.repo.workdir().expect("No workdir").join(path);
+    let content = fs::read_to_string(&file)?;
+    let hunks = ConflictHunk::parse(&content)?;
+    let hunk = hunks.get(i).ok_or("Conflict no longer exists")?;
+
+    fs::write(&file, conflict::resolve(&content, hunk, resolution))?;
+
+    if hunks.len() == 1 {
+        let mut cmd = git::command();
+        cmd.args(["add", "--"]).arg(path);
+        state.run_cmd(term, &[], cmd)?;
+    } else {
+        state.screen_mut().update()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use super::{Action, OpTrait};
+use crate::{git::diff, items::TargetData, state::State};
+use std::rc::Rc;
+
+pub(crate) struct CopyAddedText;
+impl OpTrait for CopyAddedText {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Hunk(h)) => copy_text(h.added_text(false), "Added lines"),
+            _ => None,
+        }
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Copy new version".into()
+    }
+}
+
+pub(crate) struct CopyRemovedText;
+impl OpTrait for CopyRemovedText {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Hunk(h)) => copy_text(h.removed_text(false), "Removed lines"),
+            _ => None,
+        }
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Copy old version".into()
+    }
+}
+
+/// Copies a commit message trailer as `Key: value`, unfolded onto one line.
+pub(crate) struct CopyTrailer;
+impl OpTrait for CopyTrailer {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Trailer(key, value)) => {
+                copy_text(format!("{}: {}", key, value), "Trailer")
+            }
+            _ => None,
+        }
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Copy trailer".into()
+    }
+}
+
+fn copy_text(text: String, what: &'static str) -> Option<Action> {
+    Some(Rc::new(move |state, _term| {
+        state.close_menu();
+        match &mut state.clipboard {
+            Some(cb) => {
+                cb.set_text(diff::display_lossy(&text))?;
+                state.display_info(format!("{} copied to clipboard", what));
+            }
+            None => state.display_error("Clipboard not available".to_owned()),
+        }
+        Ok(())
+    }))
+}
@@ -0,0 +1,268 @@
+use super::{create_prompt_with_default, set_prompt, Action, OpTrait};
+use crate::{config, git, items::TargetData, screen, state::State, term::Term, Res};
+use std::rc::Rc;
+
+pub(crate) struct DiffMoreContext;
+impl OpTrait for DiffMoreContext {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            let opts = state.diff_options.borrow().clone();
+            let context = opts.context + 1;
+            *state.diff_options.borrow_mut() = opts.context(context);
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        format!("More context ({})", state.diff_options.borrow().context)
+    }
+}
+
+pub(crate) struct DiffLessContext;
+impl OpTrait for DiffLessContext {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            let opts = state.diff_options.borrow().clone();
+            // Hunks without context can't be staged without `git apply --unidiff-zero`
+            let context = opts.context.saturating_sub(1).max(1);
+            *state.diff_options.borrow_mut() = opts.context(context);
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Less context".into()
+    }
+}
+
+pub(crate) struct DiffToggleIgnoreWhitespace;
+impl OpTrait for DiffToggleIgnoreWhitespace {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            let opts = state.diff_options.borrow().clone();
+            let ignore = !opts.ignore_whitespace;
+            *state.diff_options.borrow_mut() = opts.ignore_whitespace(ignore);
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        if state.diff_options.borrow().ignore_whitespace {
+            "Show whitespace changes".into()
+        } else {
+            "Ignore whitespace changes".into()
+        }
+    }
+}
+
+pub(crate) struct DiffNextAlgorithm;
+impl OpTrait for DiffNextAlgorithm {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            let opts = state.diff_options.borrow().clone();
+            let algorithm = opts.algorithm.next();
+            *state.diff_options.borrow_mut() = opts.algorithm(algorithm);
+
+            if let Some(path) = &state.config.state_path {
+                let mut saved = config::read_state(path);
+                saved.diff_algorithm = Some(algorithm);
+                config::write_state(path, &saved)?;
+            }
+
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        format!("Algorithm ({})", state.diff_options.borrow().algorithm)
+    }
+}
+
+pub(crate) struct DiffToggleRawPatch;
+impl OpTrait for DiffToggleRawPatch {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            state.screen_mut().toggle_raw_patch()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        if state.screen().is_raw_patch() {
+            "Show rendered diff".into()
+        } else {
+            "Show raw patch".into()
+        }
+    }
+}
+
+pub(crate) struct DiffToggleWrap;
+impl OpTrait for DiffToggleWrap {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            state.screen_mut().toggle_wrap()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        if state.screen().is_wrapped() {
+            "Cut off long lines".into()
+        } else {
+            "Wrap long lines".into()
+        }
+    }
+}
+
+pub(crate) struct DiffToggleStat;
+impl OpTrait for DiffToggleStat {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            let opts = state.diff_options.borrow().clone();
+            let stat = !opts.stat;
+            *state.diff_options.borrow_mut() = opts.stat(stat);
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        if state.diff_options.borrow().stat {
+            "Hide diffstat".into()
+        } else {
+            "Show diffstat".into()
+        }
+    }
+}
+
+pub(crate) struct DiffRefs;
+impl OpTrait for DiffRefs {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            set_prompt(
+                state,
+                "Diff (e.g. main..feature)",
+                Box::new(diff_refs),
+                Box::new(|_| None),
+                true,
+            );
+            state.prompt.completions = git::ref_names(&state.repo)?;
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Diff refs".into()
+    }
+}
+
+fn diff_refs(state: &mut State, _term: &mut Term, input: &str) -> Res<()> {
+    let refs = match input.split_once("..") {
+        Some((from, to)) => vec![from.trim(), to.trim()],
+        None => input.split_whitespace().collect(),
+    };
+
+    let [from, to] = refs[..] else {
+        return Err("Expected two refs, e.g. `main feature` or `main..feature`".into());
+    };
+
+    for reference in [from, to] {
+        state
+            .repo
+            .revparse_single(reference)
+            .map_err(|_| format!("No such ref: {reference}"))?;
+    }
+
+    let size = state.screens.last().unwrap().size;
+
+    state.close_menu();
+    state.screens.push(screen::diff_refs::create(
+        Rc::clone(&state.config),
+        Rc::clone(&state.repo),
+        size,
+        from.to_string(),
+        to.to_string(),
+    )?);
+
+    Ok(())
+}
+
+pub(crate) struct DiffMergeBase;
+impl OpTrait for DiffMergeBase {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            set_prompt(
+                state,
+                "Diff against merge base with",
+                Box::new(diff_merge_base),
+                Box::new(|_| None),
+                true,
+            );
+            state.prompt.completions = git::ref_names(&state.repo)?;
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Diff against merge base".into()
+    }
+}
+
+fn diff_merge_base(state: &mut State, _term: &mut Term, input: &str) -> Res<()> {
+    let target = input.trim();
+    if target.is_empty() {
+        return Err("No branch given".into());
+    }
+
+    // Fails early, rather than with an empty screen
+    git::merge_base(&state.repo, target)?;
+    let size = state.screens.last().unwrap().size;
+
+    state.close_menu();
+    state.screens.push(screen::diff_refs::create_merge_base(
+        Rc::clone(&state.config),
+        Rc::clone(&state.repo),
+        size,
+        target.to_string(),
+    )?);
+
+    Ok(())
+}
+
+pub(crate) struct DiffRange;
+impl OpTrait for DiffRange {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(create_prompt_with_default(
+            "Range diff (e.g. main@{1}...main)",
+            range_diff,
+            |state| {
+                state
+                    .repo
+                    .revparse_single("ORIG_HEAD")
+                    .ok()
+                    .map(|_| "ORIG_HEAD...HEAD".into())
+            },
+            true,
+        ))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Range diff".into()
+    }
+}
+
+fn range_diff(state: &mut State, _term: &mut Term, input: &str) -> Res<()> {
+    let ranges = input.split_whitespace().collect::<Vec<_>>();
+    if ranges.is_empty() {
+        return Err("No range given".into());
+    }
+
+    let range_diff = git::range_diff(&state.repo, &ranges)?;
+    let size = state.screens.last().unwrap().size;
+
+    state.close_menu();
+    state.screens.push(screen::range_diff::create(
+        Rc::clone(&state.config),
+        size,
+        range_diff,
+    )?);
+
+    Ok(())
+}
@@ -1,23 +1,44 @@
 use super::{Action, OpTrait};
-use crate::{git::diff::Hunk, items::TargetData, state::State};
-use std::{path::PathBuf, process::Command, rc::Rc};
+use crate::{
+    config::{Confirm, ConfirmConfig},
+    git,
+    git::diff::{self, Hunk},
+    items::TargetData,
+    state::State,
+};
+use std::{path::PathBuf, rc::Rc};
 
 pub(crate) struct Discard;
 impl OpTrait for Discard {
     fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
-        let action = match target.cloned() {
-            Some(TargetData::Branch(branch)) => discard_branch(branch),
-            Some(TargetData::File(file)) => clean_file(file),
-            Some(TargetData::Delta(d)) => match d.status {
-                git2::Delta::Added => remove_file(d.new_file),
-                git2::Delta::Renamed => rename_file(d.new_file, d.old_file),
-                _ => checkout_file(d.old_file),
-            },
-            Some(TargetData::Hunk(h)) => discard_unstaged_patch(h),
+        let (action, setting, files): (_, fn(&ConfirmConfig) -> Confirm, _) = match target.cloned()
+        {
+            Some(TargetData::Branch(branch)) => (discard_branch(branch), |c| c.discard, 1),
+            Some(TargetData::AllUntracked(files)) => {
+                let count = files.len();
+                (clean_files(files), |c| c.clean, count)
+            }
+            Some(TargetData::File(file)) => (clean_files(vec![file]), |c| c.clean, 1),
+            // Outside of a sparse checkout, there's no file to discard changes of
+            Some(TargetData::Delta(d)) if d.sparse_excluded => return None,
+            Some(TargetData::Delta(d)) => {
+                let action = match d.status {
+                    git2::Delta::Added => remove_file(d.new_file),
+                    git2::Delta::Renamed => rename_file(d.new_file, d.old_file),
+                    _ => checkout_file(d.old_file),
+                };
+                (action, |c| c.discard, 1)
+            }
+            Some(TargetData::Hunk(h)) => (discard_unstaged_patch(h), |c| c.discard, 1),
             _ => return None,
         };
 
-        Some(super::create_y_n_prompt(action, "Really discard?"))
+        Some(super::create_confirm_prompt(
+            action,
+            setting,
+            files,
+            "Really discard?",
+        ))
     }
 
     fn is_target_op(&self) -> bool {
@@ -29,9 +50,44 @@ impl OpTrait for Discard {
     }
 }
 
+pub(crate) struct RestoreFile;
+impl OpTrait for RestoreFile {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let paths = match target.cloned() {
+            Some(TargetData::Delta(d)) if d.sparse_excluded => return None,
+            Some(TargetData::Delta(d)) if d.old_file != d.new_file => vec![d.old_file, d.new_file],
+            Some(TargetData::Delta(d)) => vec![d.new_file],
+            _ => return None,
+        };
+
+        let action = Rc::new(move |state: &mut State, term: &mut _| {
+            let mut cmd = git::command();
+            git::version::restore_paths_to_head(&mut cmd, &paths);
+
+            state.close_menu();
+            state.run_cmd(term, &[], cmd)
+        });
+
+        Some(super::create_confirm_prompt(
+            action,
+            |c| c.restore,
+            1,
+            "Really restore to HEAD?",
+        ))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Restore to HEAD".into()
+    }
+}
+
 fn discard_branch(branch: String) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["branch", "-d"]);
         cmd.arg(&branch);
 
@@ -40,11 +96,11 @@ fn discard_branch(branch: String) -> Action {
     })
 }
 
-fn clean_file(file: PathBuf) -> Action {
+fn clean_files(files: Vec<PathBuf>) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["clean", "--force"]);
-        cmd.arg(&file);
+        let mut cmd = git::command();
+        cmd.args(["clean", "--force", "--"]);
+        cmd.args(&files);
 
         state.close_menu();
         state.run_cmd(term, &[], cmd)
@@ -53,7 +109,7 @@ fn clean_file(file: PathBuf) -> Action {
 
 fn rename_file(src: PathBuf, dest: PathBuf) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["mv", "--force"]);
         cmd.arg(&src);
         cmd.arg(&dest);
@@ -65,7 +121,7 @@ fn rename_file(src: PathBuf, dest: PathBuf) -> Action {
 
 fn remove_file(file: PathBuf) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["rm", "--force"]);
         cmd.arg(&file);
 
@@ -76,7 +132,7 @@ fn remove_file(file: PathBuf) -> Action {
 
 fn checkout_file(file: PathBuf) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["checkout", "HEAD", "--"]);
         cmd.arg(&file);
 
@@ -87,10 +143,10 @@ fn checkout_file(file: PathBuf) -> Action {
 
 fn discard_unstaged_patch(h: Rc<Hunk>) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["apply", "--reverse"]);
 
         state.close_menu();
-        state.run_cmd(term, &h.format_patch().into_bytes(), cmd)
+        state.run_cmd(term, &diff::encode(&h.format_patch()), cmd)
     })
 }
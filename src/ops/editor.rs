@@ -1,12 +1,16 @@
 use super::{set_prompt, Action, OpTrait};
 use crate::{
+    git::{self, InProgress},
     items::TargetData,
     menu::PendingMenu,
+    prompt::PromptData,
     screen::NavMode,
     state::{root_menu, State},
     term::Term,
+    Res,
 };
-use std::rc::Rc;
+use std::{path::Path, rc::Rc};
+use tui_prompts::State as _;
 
 pub(crate) struct Quit;
 impl OpTrait for Quit {
@@ -18,17 +22,24 @@ impl OpTrait for Quit {
                 .map(|pending_menu| pending_menu.menu);
 
             if menu == root_menu(&state.config) {
+                if state.screen_mut().clear_line_selection() {
+                    return Ok(());
+                }
+
                 if state.screens.len() == 1 {
                     let quit = Rc::new(|state: &mut State, _term: &mut Term| {
                         state.quit = true;
                         Ok(())
                     });
 
-                    let mut action = if state.config.general.confirm_quit.enabled {
-                        super::create_y_n_prompt(quit, "Really quit?")
-                    } else {
-                        quit
-                    };
+                    let mut action =
+                        if let Some(in_progress) = git::operation_in_progress(&state.repo) {
+                            in_progress_prompt(in_progress)
+                        } else if state.config.general.confirm_quit.enabled {
+                            super::create_y_n_prompt(quit, "Really quit?")
+                        } else {
+                            quit
+                        };
 
                     Rc::get_mut(&mut action).unwrap()(state, term)?;
                 } else {
@@ -51,12 +62,66 @@ impl OpTrait for Quit {
     }
 }
 
+/// Asks whether to abort an in-progress operation before quitting, or leave it as is.
+fn in_progress_prompt(in_progress: InProgress) -> Action {
+    let update_fn = Rc::new(move |state: &mut State, term: &mut Term| {
+        if state.prompt.state.status().is_pending() {
+            match state.prompt.state.value() {
+                "a" => {
+                    state.prompt.reset(term)?;
+                    let mut cmd = git::command();
+                    cmd.args(in_progress.abort_args);
+                    state.run_cmd(term, &[], cmd)?;
+                    state.quit = true;
+                }
+                "q" => {
+                    state.prompt.reset(term)?;
+                    state.quit = true;
+                }
+                "" => (),
+                _ => {
+                    state.display_info("Cancelled".to_string());
+                    state.prompt.reset(term)?;
+                }
+            }
+        }
+        Ok(())
+    });
+
+    Rc::new(move |state: &mut State, _term: &mut Term| {
+        state.prompt.set(PromptData {
+            prompt_text: format!(
+                "A {} is in progress. (a)bort it and quit, (q)uit leaving it, or cancel",
+                in_progress.name
+            )
+            .into(),
+            update_fn: update_fn.clone(),
+        });
+
+        Ok(())
+    })
+}
+
+pub(crate) struct ForceQuit;
+impl OpTrait for ForceQuit {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.quit = true;
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Quit without confirming".into()
+    }
+}
+
 pub(crate) struct OpenMenu(pub crate::menu::Menu);
 impl OpTrait for OpenMenu {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         let submenu = self.0;
         Some(Rc::new(move |state, _term| {
-            state.pending_menu = Some(PendingMenu::init(submenu));
+            state.pending_menu = Some(PendingMenu::init(submenu, &state.config, &state.repo));
             Ok(())
         }))
     }
@@ -153,6 +218,51 @@ impl OpTrait for ToggleSection {
     }
 }
 
+pub(crate) struct DismissStatus;
+impl OpTrait for DismissStatus {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.status_line.dismiss();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Dismiss message".into()
+    }
+}
+
+pub(crate) struct ExpandAll;
+impl OpTrait for ExpandAll {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().expand_all();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Expand all".into()
+    }
+}
+
+pub(crate) struct CollapseAll;
+impl OpTrait for CollapseAll {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().collapse_all();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Collapse all".into()
+    }
+}
+
 pub(crate) struct MoveUp;
 impl OpTrait for MoveUp {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
@@ -215,6 +325,20 @@ impl OpTrait for MoveUpLine {
     }
 }
 
+pub(crate) struct ToggleLineSelection;
+impl OpTrait for ToggleLineSelection {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().toggle_line_selection()
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Select lines".into()
+    }
+}
+
 pub(crate) struct MoveNextSection;
 impl OpTrait for MoveNextSection {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
@@ -267,6 +391,66 @@ impl OpTrait for MoveParentSection {
     }
 }
 
+pub(crate) struct ScrollLeft;
+impl OpTrait for ScrollLeft {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().scroll_left();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Scroll left".into()
+    }
+}
+
+pub(crate) struct ScrollRight;
+impl OpTrait for ScrollRight {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().scroll_right();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Scroll right".into()
+    }
+}
+
+pub(crate) struct ScrollLineStart;
+impl OpTrait for ScrollLineStart {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().scroll_line_start();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Scroll to line start".into()
+    }
+}
+
+pub(crate) struct ScrollLineEnd;
+impl OpTrait for ScrollLineEnd {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            state.close_menu();
+            state.screen_mut().scroll_line_end();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Scroll to line end".into()
+    }
+}
+
 pub(crate) struct HalfPageUp;
 impl OpTrait for HalfPageUp {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
@@ -296,3 +480,113 @@ impl OpTrait for HalfPageDown {
         "Half page down".into()
     }
 }
+
+pub(crate) struct GoToLine;
+impl OpTrait for GoToLine {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        // Line numbers are of the selected file, otherwise the first file that has the line
+        let file = match target {
+            Some(TargetData::Delta(delta)) => Some(delta.new_file.clone()),
+            Some(TargetData::Hunk(hunk) | TargetData::HunkLine(hunk, _)) => {
+                Some(hunk.new_file.clone())
+            }
+            _ => None,
+        };
+
+        Some(Rc::new(move |state, _term| {
+            let file = file.clone();
+            set_prompt(
+                state,
+                "Go to line",
+                Box::new(move |state, _term, input| go_to_line(state, file.as_deref(), input)),
+                Box::new(|_| None),
+                true,
+            );
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Go to line".into()
+    }
+}
+
+fn go_to_line(state: &mut State, file: Option<&Path>, input: &str) -> Res<()> {
+    let line = input
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|&line| line > 0)
+        .ok_or_else(|| format!("{} isn't a line number", input))?;
+
+    state.close_menu();
+    match state.screen_mut().select_new_line(file, line) {
+        None => Err("There are no diff lines to go to".into()),
+        Some(selected) if selected != line => {
+            state.display_info(format!(
+                "Line {} is unchanged, went to the nearest change at line {}",
+                line, selected
+            ));
+            Ok(())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+pub(crate) struct Search;
+impl OpTrait for Search {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| {
+            set_prompt(
+                state,
+                "Search",
+                Box::new(|state, _term, input| {
+                    state.last_search = Some(input.to_string());
+                    search(state, false)
+                }),
+                Box::new(|state| state.last_search.clone()),
+                true,
+            );
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Search".into()
+    }
+}
+
+pub(crate) struct SearchNext;
+impl OpTrait for SearchNext {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| search(state, false)))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Next search match".into()
+    }
+}
+
+pub(crate) struct SearchPrevious;
+impl OpTrait for SearchPrevious {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state, _term| search(state, true)))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Previous search match".into()
+    }
+}
+
+fn search(state: &mut State, backwards: bool) -> Res<()> {
+    let Some(query) = state.last_search.clone().filter(|query| !query.is_empty()) else {
+        return Err("Nothing searched for yet".into());
+    };
+
+    state.close_menu();
+    if !state.screen_mut().search(&query, backwards) {
+        return Err(format!("No match for '{}'", query).into());
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use super::{create_prompt, Action, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
-use std::{process::Command, rc::Rc};
+use crate::{git, items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
+use std::rc::Rc;
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![
@@ -13,7 +13,7 @@ pub(crate) struct FetchAll;
 impl OpTrait for FetchAll {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state, term| {
-            let mut cmd = Command::new("git");
+            let mut cmd = git::command();
             cmd.args(["fetch", "--all", "--jobs", "10"]);
             cmd.args(state.pending_menu.as_ref().unwrap().args());
 
@@ -40,7 +40,7 @@ impl OpTrait for FetchElsewhere {
 }
 
 fn push_elsewhere(state: &mut State, term: &mut Term, remote: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["fetch"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.arg(remote);
@@ -0,0 +1,69 @@
+use super::{Action, OpTrait};
+use crate::{
+    git::diff::{Delta, Hunk},
+    items::TargetData,
+    screen,
+    state::State,
+    term::Term,
+    Res,
+};
+use std::rc::Rc;
+
+pub(crate) struct ShowFullFile;
+impl OpTrait for ShowFullFile {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Delta(d)) if d.status != git2::Delta::Deleted => {
+                let delta = d.clone();
+                Some(Rc::new(move |state, term| {
+                    goto_full_file_screen(state, term, delta.clone(), None)
+                }))
+            }
+            Some(TargetData::Hunk(h)) => goto_hunk(Rc::clone(h), None),
+            Some(TargetData::HunkLine(h, i)) => goto_hunk(Rc::clone(h), Some(*i)),
+            _ => None,
+        }
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Show full file".into()
+    }
+}
+
+/// Opens the file that the hunk is in, at the hunk or at line `i` of it.
+fn goto_hunk(hunk: Rc<Hunk>, i: Option<usize>) -> Option<Action> {
+    Some(Rc::new(move |state, term| {
+        let delta = state
+            .screen()
+            .delta_of(&hunk)
+            .ok_or("Couldn't find the file of this hunk")?
+            .clone();
+
+        goto_full_file_screen(state, term, delta, Some((&hunk, i)))
+    }))
+}
+
+fn goto_full_file_screen(
+    state: &mut State,
+    term: &mut Term,
+    delta: Delta,
+    selected: Option<(&Rc<Hunk>, Option<usize>)>,
+) -> Res<()> {
+    state.close_menu();
+    let mut screen = screen::full_file::create(
+        Rc::clone(&state.config),
+        Rc::clone(&state.repo),
+        term.size()?,
+        delta,
+    )?;
+
+    if let Some((hunk, i)) = selected {
+        screen.select_hunk(hunk, i);
+    }
+
+    state.screens.push(screen);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use super::{set_prompt, Action, OpTrait};
+use crate::{
+    git::{
+        self,
+        config_list::{self, ConfigEntry},
+    },
+    items::TargetData,
+    screen,
+    state::State,
+    term::Term,
+    Res,
+};
+use std::rc::Rc;
+
+pub(crate) struct ShowGitConfig;
+impl OpTrait for ShowGitConfig {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            state.close_menu();
+            state.screens.push(screen::git_config::create(
+                Rc::clone(&state.config),
+                Rc::clone(&state.repo),
+                term.size()?,
+            )?);
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "list".into()
+    }
+}
+
+pub(crate) struct ConfigSet;
+impl OpTrait for ConfigSet {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::ConfigEntry(entry)) => {
+                let entry = entry.clone();
+                Some(Rc::new(move |state: &mut State, _term: &mut Term| {
+                    state.close_menu();
+
+                    if let Some(reason) = entry.read_only_reason() {
+                        return Err(format!("{} can't be edited, as {}", entry.key, reason).into());
+                    }
+
+                    let entry = entry.clone();
+                    let current = entry.value.clone();
+                    set_prompt(
+                        state,
+                        format!("Set {} in {}", entry.key, entry.file()),
+                        Box::new(move |state, term, value| set_entry(state, term, &entry, value)),
+                        Box::new(move |_| Some(current.clone())),
+                        false,
+                    );
+                    Ok(())
+                }))
+            }
+            Some(TargetData::ConfigScope(scope)) if scope != "command" => {
+                let scope = scope.clone();
+                Some(Rc::new(move |state: &mut State, _term: &mut Term| {
+                    state.close_menu();
+
+                    let scope = scope.clone();
+                    set_prompt(
+                        state,
+                        format!("Set in {} config (key=value)", scope),
+                        Box::new(move |state, term, input| {
+                            set_in_scope(state, term, &scope, input)
+                        }),
+                        Box::new(|_| None),
+                        false,
+                    );
+                    Ok(())
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "set".into()
+    }
+}
+
+/// Replaces the value of `entry` in the file it's set in.
+fn set_entry(state: &mut State, term: &mut Term, entry: &ConfigEntry, value: &str) -> Res<()> {
+    if value == entry.value {
+        state.display_info(format!("{} is already {}", entry.key, value));
+        return Ok(());
+    }
+
+    if is_bool(&entry.value) && !(is_bool(value) || value == "1" || value == "0") {
+        return Err(format!("{} takes a boolean, like true or false", entry.key).into());
+    }
+
+    // Only the value shown is replaced, should the key have several
+    let mut cmd = git::command();
+    git::version::replace_config_value(&mut cmd, entry.file(), &entry.key, value, &entry.value);
+
+    state.run_cmd(term, &[], cmd)
+}
+
+/// Sets a `key=value` given in the prompt, with `git config --<scope>`.
+fn set_in_scope(state: &mut State, term: &mut Term, scope: &str, input: &str) -> Res<()> {
+    let Some((key, value)) = input.split_once('=') else {
+        return Err("Expected key=value, like user.email=ada@example.com".into());
+    };
+
+    let key = key.trim();
+    if config_list::is_read_only_key(key) {
+        return Err(format!("{} can't be edited, as it describes the repository", key).into());
+    }
+
+    let mut cmd = git::command();
+    cmd.args(["config", &format!("--{}", scope), key, value]);
+
+    state.run_cmd(term, &[], cmd)
+}
+
+/// Whether git reads `value` as a boolean, leaving out `1` and `0` which could as well be numbers.
+fn is_bool(value: &str) -> bool {
+    ["true", "false", "yes", "no", "on", "off"]
+        .iter()
+        .any(|bool| value.eq_ignore_ascii_case(bool))
+}
@@ -0,0 +1,54 @@
+use super::{Action, Op, OpTrait};
+use crate::{
+    items::TargetData,
+    screen::{self, keybindings::KeybindingEntry},
+    state::State,
+    term::Term,
+};
+use itertools::Itertools;
+use std::rc::Rc;
+
+pub(crate) struct ShowKeybindings;
+impl OpTrait for ShowKeybindings {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            state.close_menu();
+
+            // From the bindings as configured, so that remapped keys are listed where they are
+            let menus = state
+                .bindings
+                .all()
+                .chunk_by(|bind| bind.menu)
+                .into_iter()
+                .map(|(menu, binds)| {
+                    let entries = binds
+                        .chunk_by(|bind| &bind.op)
+                        .into_iter()
+                        .map(|(op, binds)| KeybindingEntry {
+                            keys: binds.map(|bind| &bind.raw).join("/"),
+                            description: match op {
+                                Op::ToggleArg(name) => name.clone(),
+                                Op::OpenMenu(menu) => format!("{menu} menu"),
+                                op => op.clone().implementation().display(state),
+                            },
+                        })
+                        .collect();
+
+                    (menu, entries)
+                })
+                .collect();
+
+            let size = state.screens.last().unwrap().size;
+            state.screens.push(screen::keybindings::create(
+                Rc::clone(&state.config),
+                size,
+                menus,
+            )?);
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Show keybindings".into()
+    }
+}
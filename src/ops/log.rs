@@ -1,7 +1,8 @@
 use super::{create_prompt_with_default, selected_rev, Action, OpTrait};
 use crate::{
+    items::LogFilter,
     items::TargetData,
-    menu::arg::{any_regex, positive_number, Arg},
+    menu::arg::{any_regex, any_string, positive_number, Arg},
     screen,
     state::State,
     term::Term,
@@ -9,7 +10,7 @@ use crate::{
 };
 use git2::Oid;
 use regex::Regex;
-use std::rc::Rc;
+use std::{path::PathBuf, rc::Rc};
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![
@@ -20,6 +21,8 @@ pub(crate) fn init_args() -> Vec<Arg> {
             positive_number,
         ),
         Arg::new_arg("--grep", "Search messages", None, any_regex),
+        Arg::new_arg("--author", "Limit to author", None, any_regex),
+        Arg::new_arg("--", "Limit to files", None, any_string),
         // Arg::new_str("-S", "Search occurences"), // TOOD: Implement search
     ]
 }
@@ -80,12 +83,12 @@ fn goto_log_screen(state: &mut State, rev: Option<Oid>) {
         .and_then(|arg| arg.value_as::<u32>())
         .unwrap_or(&u32::MAX);
 
-    let msg_regex_menu = state
-        .pending_menu
-        .as_ref()
-        .and_then(|m| m.args.get("--grep"));
-
-    let msg_regex = msg_regex_menu.and_then(|arg| arg.value_as::<Regex>().cloned());
+    let arg = |name| state.pending_menu.as_ref().and_then(|m| m.args.get(name));
+    *state.log_filter.borrow_mut() = LogFilter {
+        message: arg("--grep").and_then(|arg| arg.value_as::<Regex>().cloned()),
+        author: arg("--author").and_then(|arg| arg.value_as::<Regex>().cloned()),
+        path: arg("--").and_then(|arg| arg.value_as::<String>().map(PathBuf::from)),
+    };
 
     state.close_menu();
 
@@ -96,8 +99,23 @@ fn goto_log_screen(state: &mut State, rev: Option<Oid>) {
             size,
             limit as usize,
             rev,
-            msg_regex,
+            Rc::clone(&state.log_filter),
         )
         .expect("Couldn't create screen"),
     );
 }
+
+pub(crate) struct LogClearFilters;
+impl OpTrait for LogClearFilters {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            state.close_menu();
+            *state.log_filter.borrow_mut() = LogFilter::default();
+            state.screen_mut().update()
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "clear filters".into()
+    }
+}
@@ -0,0 +1,83 @@
+use super::{set_prompt, Action, OpTrait};
+use crate::{items::TargetData, macros, state::State, term::Term, Res};
+use std::rc::Rc;
+
+pub(crate) struct RecordMacro;
+impl OpTrait for RecordMacro {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            if state.macros.recording().is_some() {
+                let binding_len = state.pending_keys.len();
+                if let Some((register, len)) = state.macros.stop_recording(binding_len) {
+                    state.display_info(format!("Recorded {} keys into macro {}", len, register));
+                }
+                return Ok(());
+            }
+
+            if state.macros.is_replaying() {
+                return Err("Can't record a macro while replaying one".into());
+            }
+
+            set_prompt(
+                state,
+                "Record macro into register",
+                Box::new(start_recording),
+                Box::new(|_| None),
+                false,
+            );
+            Ok(())
+        }))
+    }
+
+    fn display(&self, state: &State) -> String {
+        match state.macros.recording() {
+            Some(register) => format!("Stop recording macro {}", register),
+            None => "Record macro".into(),
+        }
+    }
+}
+
+fn start_recording(state: &mut State, _term: &mut Term, register: &str) -> Res<()> {
+    let register = register.trim();
+    if register.is_empty() || register.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err("Macro registers are named like 'a', without leading digits".into());
+    }
+
+    state.macros.start_recording(register.to_string());
+    state.display_info(format!("Recording macro {}", register));
+    Ok(())
+}
+
+pub(crate) struct PlayMacro;
+impl OpTrait for PlayMacro {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            // A macro replaying itself would never end
+            if state.macros.recording().is_some() {
+                return Err("Can't replay a macro while recording one".into());
+            }
+
+            set_prompt(
+                state,
+                "Replay macro (e.g. a, or 3a for 3 times)",
+                Box::new(replay),
+                Box::new(|state| state.macros.last_register()),
+                false,
+            );
+            state.prompt.completions = state.macros.registers();
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Replay macro".into()
+    }
+}
+
+fn replay(state: &mut State, _term: &mut Term, input: &str) -> Res<()> {
+    let (times, register) = macros::parse_replay(input)?;
+    state.macros.replay(register, times)?;
+    // So that an error left from before doesn't stop the replay right away
+    state.status_line.dismiss();
+    Ok(())
+}
@@ -2,28 +2,50 @@ use serde::{Deserialize, Serialize};
 use tui_prompts::State as _;
 
 use crate::{
-    cmd_log::CmdLogEntry, items::TargetData, menu::Menu, prompt::PromptData, state::State,
-    term::Term, Res,
+    config::{Confirm, ConfirmConfig},
+    items::TargetData,
+    menu::Menu,
+    prompt::PromptData,
+    state::State,
+    term::Term,
+    Res,
 };
-use std::{fmt::Display, rc::Rc};
+use std::{borrow::Cow, fmt::Display, rc::Rc};
 
+pub(crate) mod bisect;
+pub(crate) mod blame;
+pub(crate) mod browse;
 pub(crate) mod checkout;
+pub(crate) mod clone;
 pub(crate) mod commit;
+pub(crate) mod conflict;
 pub(crate) mod copy_hash;
+pub(crate) mod copy_text;
+pub(crate) mod diff;
 pub(crate) mod discard;
 pub(crate) mod editor;
 pub(crate) mod fetch;
+pub(crate) mod full_file;
+pub(crate) mod git_config;
+pub(crate) mod keybindings;
 pub(crate) mod log;
+pub(crate) mod macros;
+pub(crate) mod notes;
+pub(crate) mod output;
+pub(crate) mod patch;
+pub(crate) mod permalink;
 pub(crate) mod pull;
 pub(crate) mod push;
 pub(crate) mod rebase;
 pub(crate) mod reset;
 pub(crate) mod revert;
 pub(crate) mod show;
+pub(crate) mod show_index;
 pub(crate) mod show_refs;
 pub(crate) mod stage;
 pub(crate) mod stash;
 pub(crate) mod unstage;
+pub(crate) mod worktree;
 
 pub(crate) type Action = Rc<dyn FnMut(&mut State, &mut Term) -> Res<()>>;
 
@@ -44,13 +66,46 @@ pub(crate) trait OpTrait {
 #[derive(Clone, PartialOrd, Ord, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum Op {
+    BisectStart,
+    BisectGood,
+    BisectBad,
+    BisectSkip,
+    BisectReset,
+    BrowseRepo,
+    BrowseBranch,
+    BrowseFile,
+    BrowseCommit,
     Checkout,
     CheckoutNewBranch,
+    Clone,
     Commit,
+    CommitQuick,
     CommitAmend,
+    CommitWhitespaceCleaned,
+    CommitCoAuthored,
+    CommitAmendCoAuthored,
+    ShowGitConfig,
+    ConfigSet,
+    DiffMoreContext,
+    DiffLessContext,
+    DiffToggleIgnoreWhitespace,
+    DiffToggleRawPatch,
+    DiffToggleWrap,
+    DiffToggleStat,
+    DiffNextAlgorithm,
+    DiffRefs,
+    DiffMergeBase,
+    DiffRange,
     FetchAll,
     FetchElsewhere,
     LogCurrent,
+    LogClearFilters,
+    NotesEdit,
+    PatchSave,
+    PatchApply,
+    AmContinue,
+    AmSkip,
+    AmAbort,
     PullFromPushRemote,
     PullFromUpstream,
     PullFromElsewhere,
@@ -61,6 +116,9 @@ pub(crate) enum Op {
     RebaseContinue,
     RebaseElsewhere,
     ShowRefs,
+    ShowKeybindings,
+    ShowCmdLog,
+    ShowGitOutput,
     Stash,
     StashApply,
     StashIndex,
@@ -68,37 +126,77 @@ pub(crate) enum Op {
     StashKeepIndex,
     StashPop,
     StashDrop,
+    StashRestoreFile,
     CommitFixup,
     CommitInstantFixup,
+    CommitSquash,
+    CommitInstantSquash,
     LogOther,
     RebaseAutosquash,
     RebaseInteractive,
+    RebaseSquash,
     ResetSoft,
     ResetMixed,
     ResetHard,
     RevertAbort,
     RevertContinue,
     RevertCommit,
+    ShowWorktrees,
+    WorktreeAdd,
+    WorktreeRemove,
 
     Stage,
+    StageModified,
+    StageAll,
+    EditAndStage,
+    EditAndUnstage,
+    PreviewPatch,
     Unstage,
     Show,
+    ShowIndex,
+    ShowFullFile,
     Discard,
+    RestoreFile,
     CopyHash,
+    CopyAddedText,
+    CopyRemovedText,
+    CopyTrailer,
+    BlameLine,
+    CopyPermalink,
+    ShowConflicts,
+    ConflictAcceptOurs,
+    ConflictAcceptTheirs,
+    ConflictAcceptBoth,
 
     ToggleSection,
+    DismissStatus,
+    ExpandAll,
+    CollapseAll,
     MoveUp,
     MoveDown,
     MoveUpLine,
     MoveDownLine,
+    ToggleLineSelection,
     MovePrevSection,
     MoveNextSection,
     MoveParentSection,
+    ScrollLeft,
+    ScrollRight,
+    ScrollLineStart,
+    ScrollLineEnd,
     HalfPageUp,
     HalfPageDown,
+    GoToLine,
+    Search,
+    SearchNext,
+    SearchPrevious,
+
+    RecordMacro,
+    PlayMacro,
 
     Refresh,
     Quit,
+    ForceQuit,
 
     #[serde(untagged)]
     OpenMenu(Menu),
@@ -110,27 +208,75 @@ impl Op {
     pub fn implementation(self) -> Box<dyn OpTrait> {
         match self {
             Op::Quit => Box::new(editor::Quit),
+            Op::ForceQuit => Box::new(editor::ForceQuit),
+            Op::RecordMacro => Box::new(macros::RecordMacro),
+            Op::PlayMacro => Box::new(macros::PlayMacro),
             Op::OpenMenu(menu) => Box::new(editor::OpenMenu(menu)),
             Op::Refresh => Box::new(editor::Refresh),
             Op::ToggleArg(name) => Box::new(editor::ToggleArg(name)),
             Op::ToggleSection => Box::new(editor::ToggleSection),
+            Op::DismissStatus => Box::new(editor::DismissStatus),
+            Op::ExpandAll => Box::new(editor::ExpandAll),
+            Op::CollapseAll => Box::new(editor::CollapseAll),
             Op::MoveDown => Box::new(editor::MoveDown),
             Op::MoveUp => Box::new(editor::MoveUp),
             Op::MoveDownLine => Box::new(editor::MoveDownLine),
             Op::MoveUpLine => Box::new(editor::MoveUpLine),
+            Op::ToggleLineSelection => Box::new(editor::ToggleLineSelection),
             Op::MoveNextSection => Box::new(editor::MoveNextSection),
             Op::MovePrevSection => Box::new(editor::MovePrevSection),
             Op::MoveParentSection => Box::new(editor::MoveParentSection),
+            Op::ScrollLeft => Box::new(editor::ScrollLeft),
+            Op::ScrollRight => Box::new(editor::ScrollRight),
+            Op::ScrollLineStart => Box::new(editor::ScrollLineStart),
+            Op::ScrollLineEnd => Box::new(editor::ScrollLineEnd),
             Op::HalfPageUp => Box::new(editor::HalfPageUp),
             Op::HalfPageDown => Box::new(editor::HalfPageDown),
+            Op::GoToLine => Box::new(editor::GoToLine),
+            Op::Search => Box::new(editor::Search),
+            Op::SearchNext => Box::new(editor::SearchNext),
+            Op::SearchPrevious => Box::new(editor::SearchPrevious),
 
+            Op::BisectStart => Box::new(bisect::BisectStart),
+            Op::BisectGood => Box::new(bisect::BisectGood),
+            Op::BisectBad => Box::new(bisect::BisectBad),
+            Op::BisectSkip => Box::new(bisect::BisectSkip),
+            Op::BisectReset => Box::new(bisect::BisectReset),
+            Op::BrowseRepo => Box::new(browse::BrowseRepo),
+            Op::BrowseBranch => Box::new(browse::BrowseBranch),
+            Op::BrowseFile => Box::new(browse::BrowseFile),
+            Op::BrowseCommit => Box::new(browse::BrowseCommit),
             Op::Checkout => Box::new(checkout::Checkout),
             Op::CheckoutNewBranch => Box::new(checkout::CheckoutNewBranch),
+            Op::Clone => Box::new(clone::CloneRepo),
             Op::Commit => Box::new(commit::Commit),
+            Op::CommitQuick => Box::new(commit::CommitQuick),
             Op::CommitAmend => Box::new(commit::CommitAmend),
+            Op::CommitWhitespaceCleaned => Box::new(commit::CommitWhitespaceCleaned),
+            Op::CommitCoAuthored => Box::new(commit::CommitCoAuthored),
+            Op::CommitAmendCoAuthored => Box::new(commit::CommitAmendCoAuthored),
+            Op::ShowGitConfig => Box::new(git_config::ShowGitConfig),
+            Op::ConfigSet => Box::new(git_config::ConfigSet),
+            Op::DiffMoreContext => Box::new(diff::DiffMoreContext),
+            Op::DiffLessContext => Box::new(diff::DiffLessContext),
+            Op::DiffToggleIgnoreWhitespace => Box::new(diff::DiffToggleIgnoreWhitespace),
+            Op::DiffToggleRawPatch => Box::new(diff::DiffToggleRawPatch),
+            Op::DiffToggleWrap => Box::new(diff::DiffToggleWrap),
+            Op::DiffToggleStat => Box::new(diff::DiffToggleStat),
+            Op::DiffNextAlgorithm => Box::new(diff::DiffNextAlgorithm),
+            Op::DiffRefs => Box::new(diff::DiffRefs),
+            Op::DiffMergeBase => Box::new(diff::DiffMergeBase),
+            Op::DiffRange => Box::new(diff::DiffRange),
             Op::FetchAll => Box::new(fetch::FetchAll),
             Op::FetchElsewhere => Box::new(fetch::FetchElsewhere),
             Op::LogCurrent => Box::new(log::LogCurrent),
+            Op::LogClearFilters => Box::new(log::LogClearFilters),
+            Op::NotesEdit => Box::new(notes::NotesEdit),
+            Op::PatchSave => Box::new(patch::PatchSave),
+            Op::PatchApply => Box::new(patch::PatchApply),
+            Op::AmContinue => Box::new(patch::AmContinue),
+            Op::AmSkip => Box::new(patch::AmSkip),
+            Op::AmAbort => Box::new(patch::AmAbort),
             Op::PullFromPushRemote => Box::new(pull::PullFromPushRemote),
             Op::PullFromUpstream => Box::new(pull::PullFromUpstream),
             Op::PullFromElsewhere => Box::new(pull::PullFromElsewhere),
@@ -141,6 +287,9 @@ impl Op {
             Op::RebaseContinue => Box::new(rebase::RebaseContinue),
             Op::RebaseElsewhere => Box::new(rebase::RebaseElsewhere),
             Op::ShowRefs => Box::new(show_refs::ShowRefs),
+            Op::ShowKeybindings => Box::new(keybindings::ShowKeybindings),
+            Op::ShowCmdLog => Box::new(output::ShowCmdLog),
+            Op::ShowGitOutput => Box::new(output::ShowGitOutput),
             Op::Stash => Box::new(stash::Stash),
             Op::StashApply => Box::new(stash::StashApply),
             Op::StashIndex => Box::new(stash::StashIndex),
@@ -148,47 +297,98 @@ impl Op {
             Op::StashKeepIndex => Box::new(stash::StashKeepIndex),
             Op::StashPop => Box::new(stash::StashPop),
             Op::StashDrop => Box::new(stash::StashDrop),
+            Op::StashRestoreFile => Box::new(stash::StashRestoreFile),
 
             Op::CommitFixup => Box::new(commit::CommitFixup),
             Op::CommitInstantFixup => Box::new(commit::CommitInstantFixup),
+            Op::CommitSquash => Box::new(commit::CommitSquash),
+            Op::CommitInstantSquash => Box::new(commit::CommitInstantSquash),
             Op::Discard => Box::new(discard::Discard),
+            Op::RestoreFile => Box::new(discard::RestoreFile),
             Op::LogOther => Box::new(log::LogOther),
             Op::RebaseAutosquash => Box::new(rebase::RebaseAutosquash),
             Op::RebaseInteractive => Box::new(rebase::RebaseInteractive),
+            Op::RebaseSquash => Box::new(rebase::RebaseSquash),
             Op::ResetSoft => Box::new(reset::ResetSoft),
             Op::ResetMixed => Box::new(reset::ResetMixed),
             Op::ResetHard => Box::new(reset::ResetHard),
             Op::RevertAbort => Box::new(revert::RevertAbort),
             Op::RevertContinue => Box::new(revert::RevertContinue),
             Op::RevertCommit => Box::new(revert::RevertCommit),
+            Op::ShowWorktrees => Box::new(worktree::ShowWorktrees),
+            Op::WorktreeAdd => Box::new(worktree::WorktreeAdd),
+            Op::WorktreeRemove => Box::new(worktree::WorktreeRemove),
             Op::Show => Box::new(show::Show),
+            Op::ShowIndex => Box::new(show_index::ShowIndex),
+            Op::ShowFullFile => Box::new(full_file::ShowFullFile),
             Op::Stage => Box::new(stage::Stage),
+            Op::StageModified => Box::new(stage::StageModified),
+            Op::StageAll => Box::new(stage::StageAll),
+            Op::EditAndStage => Box::new(stage::EditAndStage),
+            Op::EditAndUnstage => Box::new(unstage::EditAndUnstage),
+            Op::PreviewPatch => Box::new(stage::PreviewPatch),
             Op::Unstage => Box::new(unstage::Unstage),
             Op::CopyHash => Box::new(copy_hash::CopyHash),
+            Op::CopyAddedText => Box::new(copy_text::CopyAddedText),
+            Op::CopyRemovedText => Box::new(copy_text::CopyRemovedText),
+            Op::CopyTrailer => Box::new(copy_text::CopyTrailer),
+            Op::BlameLine => Box::new(blame::BlameLine),
+            Op::CopyPermalink => Box::new(permalink::CopyPermalink),
+            Op::ShowConflicts => Box::new(conflict::ShowConflicts),
+            Op::ConflictAcceptOurs => Box::new(conflict::ConflictAcceptOurs),
+            Op::ConflictAcceptTheirs => Box::new(conflict::ConflictAcceptTheirs),
+            Op::ConflictAcceptBoth => Box::new(conflict::ConflictAcceptBoth),
         }
     }
+
+    /// Whether this applies the selected change to the index or working tree.
+    pub(crate) fn modifies_worktree(&self) -> bool {
+        matches!(
+            self,
+            Op::Stage
+                | Op::StageModified
+                | Op::StageAll
+                | Op::EditAndStage
+                | Op::EditAndUnstage
+                | Op::Unstage
+                | Op::Discard
+                | Op::RestoreFile
+                | Op::StashRestoreFile
+        )
+    }
 }
 
 impl Display for Menu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
             Menu::Root => "Root",
+            Menu::Bisect => "Bisect",
             Menu::Branch => "Branch",
+            Menu::Browse => "Browse",
+            Menu::Clone => "Clone",
             Menu::Commit => "Commit",
+            Menu::Config => "Config",
+            Menu::Diff => "Diff",
             Menu::Fetch => "Fetch",
             Menu::Help => "Help",
             Menu::Log => "Log",
+            Menu::Patch => "Patch",
             Menu::Pull => "Pull",
             Menu::Push => "Push",
             Menu::Rebase => "Rebase",
             Menu::Reset => "Reset",
             Menu::Revert => "Revert",
             Menu::Stash => "Stash",
+            Menu::Worktree => "Worktree",
         })
     }
 }
 
-pub(crate) fn create_y_n_prompt(mut action: Action, prompt: &'static str) -> Action {
+pub(crate) fn create_y_n_prompt(
+    mut action: Action,
+    prompt: impl Into<Cow<'static, str>>,
+) -> Action {
+    let prompt = prompt.into();
     let update_fn = Rc::new(move |state: &mut State, term: &mut Term| {
         if state.prompt.state.status().is_pending() {
             match state.prompt.state.value() {
@@ -198,9 +398,7 @@ pub(crate) fn create_y_n_prompt(mut action: Action, prompt: &'static str) -> Act
                 }
                 "" => (),
                 _ => {
-                    state
-                        .current_cmd_log
-                        .push(CmdLogEntry::Error("Aborted".to_string()));
+                    state.display_info("Aborted".to_string());
                     state.prompt.reset(term)?;
                 }
             }
@@ -218,6 +416,31 @@ pub(crate) fn create_y_n_prompt(mut action: Action, prompt: &'static str) -> Act
     })
 }
 
+/// Asks like `create_y_n_prompt`, unless the `[confirm]` setting says not to for this many files.
+pub(crate) fn create_confirm_prompt(
+    action: Action,
+    setting: fn(&ConfirmConfig) -> Confirm,
+    files: usize,
+    prompt: impl Into<Cow<'static, str>>,
+) -> Action {
+    let prompt = prompt.into();
+    let mut action = Some(action);
+
+    Rc::new(move |state: &mut State, term: &mut Term| {
+        let Some(action) = action.take() else {
+            return Ok(());
+        };
+
+        let mut action = if setting(&state.config.confirm).asks(files) {
+            create_y_n_prompt(action, prompt.clone())
+        } else {
+            action
+        };
+
+        Rc::get_mut(&mut action).unwrap()(state, term)
+    })
+}
+
 pub(crate) fn create_prompt(
     prompt: &'static str,
     on_success: fn(&mut State, &mut Term, &str) -> Res<()>,
@@ -249,11 +472,12 @@ type PromptAction = Box<dyn Fn(&mut State, &mut Term, &str) -> Res<()>>;
 
 pub(crate) fn set_prompt(
     state: &mut State,
-    prompt: &'static str,
+    prompt: impl Into<Cow<'static, str>>,
     on_success: PromptAction,
     default_fn: DefaultFn,
     hide_menu: bool,
 ) {
+    let prompt = prompt.into();
     let prompt_text = if let Some(default) = default_fn(state) {
         format!("{} (default {}):", prompt, default).into()
     } else {
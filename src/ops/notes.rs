@@ -0,0 +1,56 @@
+use super::{set_prompt, Action, OpTrait};
+use crate::{git, items::TargetData, state::State, term::Term, Res};
+use std::rc::Rc;
+
+pub(crate) struct NotesEdit;
+impl OpTrait for NotesEdit {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let Some(TargetData::Commit(hash)) = target else {
+            return None;
+        };
+
+        let hash = hash.clone();
+        Some(Rc::new(move |state: &mut State, term: &mut Term| {
+            state.close_menu();
+
+            let notes_refs = state.config.git.notes_refs.clone();
+            match &notes_refs[..] {
+                [] => Err("No notes refs to edit, set some in git.notes_refs".into()),
+                [notes_ref] => edit_notes(state, term, notes_ref, &hash),
+                [first, ..] => {
+                    let hash = hash.clone();
+                    let first = first.clone();
+                    set_prompt(
+                        state,
+                        format!("Edit notes in ({})", notes_refs.join(", ")),
+                        Box::new(move |state, term, notes_ref| {
+                            edit_notes(state, term, notes_ref, &hash)
+                        }),
+                        Box::new(move |_| Some(first.clone())),
+                        false,
+                    );
+                    Ok(())
+                }
+            }
+        }))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Edit notes".into()
+    }
+}
+
+/// Opens the note in the editor with `git notes edit`, which creates it if there's none yet.
+fn edit_notes(state: &mut State, term: &mut Term, notes_ref: &str, hash: &str) -> Res<()> {
+    if notes_ref.is_empty() {
+        return Err("Notes ref can't be empty".into());
+    }
+
+    let mut cmd = git::command();
+    cmd.args(["notes", "--ref", notes_ref, "edit", hash]);
+    state.run_cmd_interactive(term, cmd)
+}
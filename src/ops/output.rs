@@ -0,0 +1,156 @@
+use super::{set_prompt, Action, OpTrait};
+use crate::{ansi, git, items::TargetData, screen, state::State, term::Term, Res};
+use itertools::Itertools;
+use ratatui::text::Line;
+use std::{borrow::Cow, rc::Rc};
+
+pub(crate) struct ShowCmdLog;
+impl OpTrait for ShowCmdLog {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            let lines = state.current_cmd_log.format_history(&state.config);
+            if lines.is_empty() {
+                return Err("No commands were run yet".into());
+            }
+
+            state.close_menu();
+            state.screens.push(screen::output::create(
+                Rc::clone(&state.config),
+                term.size()?,
+                lines,
+            )?);
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Show command output".into()
+    }
+}
+
+pub(crate) struct ShowGitOutput;
+impl OpTrait for ShowGitOutput {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, _term: &mut Term| {
+            set_prompt(
+                state,
+                "Show output of git (e.g. log --stat)",
+                Box::new(show_git_output),
+                Box::new(|_| None),
+                true,
+            );
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Show output of git command".into()
+    }
+}
+
+/// Runs git with the arguments given, split like a shell would, and pages what it outputs in color.
+fn show_git_output(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
+    let args = split_args(input)?;
+    if args.is_empty() {
+        return Err("No git command given".into());
+    }
+
+    let mut cmd = git::command();
+    cmd.args(["-c", "color.ui=always", "--no-pager"]);
+    cmd.args(&args);
+    cmd.current_dir(state.repo.workdir().expect("No workdir"));
+    let out = cmd.output()?;
+
+    let title = format!("$ git {}", args.iter().map(|arg| quote_arg(arg)).join(" "));
+    let lines = std::iter::once(Line::styled(title, &state.config.style.command))
+        .chain(ansi::parse(&String::from_utf8_lossy(&out.stdout)))
+        .chain(ansi::parse(&String::from_utf8_lossy(&out.stderr)))
+        .collect();
+
+    state.close_menu();
+    state.screens.push(screen::output::create(
+        Rc::clone(&state.config),
+        term.size()?,
+        lines,
+    )?);
+
+    if !out.status.success() {
+        return Err(format!(
+            "git {} exited with code: {}",
+            args[0],
+            out.status.code().map(|c| c.to_string()).unwrap_or_default()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Splits `input` at whitespace into arguments, keeping what's in quotes (or escaped) together.
+fn split_args(input: &str) -> Res<Vec<String>> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                let quoted = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some('\\') if c == '"' => quoted.extend(chars.next()),
+                        Some(other) => quoted.push(other),
+                        None => return Err(format!("Missing closing {} in git command", c).into()),
+                    }
+                }
+            }
+            '\\' => arg.get_or_insert_with(String::new).extend(chars.next()),
+            c if c.is_whitespace() => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(arg);
+    Ok(args)
+}
+
+/// Quotes `arg` for showing it in a command line, if it wouldn't split back into the same argument.
+fn quote_arg(arg: &str) -> Cow<'_, str> {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"\\".contains(c)) {
+        return arg.into();
+    }
+
+    format!("'{}'", arg.replace('\'', "'\\''")).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{quote_arg, split_args};
+
+    #[test]
+    fn split_args_at_whitespace() {
+        assert_eq!(split_args("  log   --stat ").unwrap(), ["log", "--stat"]);
+    }
+
+    #[test]
+    fn split_args_keeps_quoted_together() {
+        assert_eq!(
+            split_args(r#"log "--format=%h %s" -S'a b' c\ d "say \"hi\"" ''"#).unwrap(),
+            ["log", "--format=%h %s", "-Sa b", "c d", "say \"hi\"", ""]
+        );
+    }
+
+    #[test]
+    fn split_args_unclosed_quote() {
+        assert!(split_args("log \"--format=%s").is_err());
+    }
+
+    #[test]
+    fn quote_args_that_would_split() {
+        assert_eq!(quote_arg("--stat"), "--stat");
+        assert_eq!(quote_arg("--format=%h %s"), "'--format=%h %s'");
+        assert_eq!(quote_arg("it's"), "'it'\\''s'");
+        assert_eq!(quote_arg(""), "''");
+    }
+}
@@ -0,0 +1,298 @@
+use super::{
+    create_prompt_with_default, create_y_n_prompt, set_prompt,
+    stage::{no_change, no_selected_change},
+    Action, OpTrait,
+};
+use crate::{
+    git::{
+        self,
+        apply::{self, ApplyResult},
+        diff::{self, PatchMode},
+    },
+    items::TargetData,
+    menu::arg::Arg,
+    state::State,
+    term::Term,
+    Res,
+};
+use std::{fs, path::Path, rc::Rc, time::SystemTime};
+
+pub(crate) fn init_args() -> Vec<Arg> {
+    vec![Arg::new_flag("--index", "Also apply to the index", false)]
+}
+
+/// What a saved patch is made of.
+#[derive(Clone)]
+enum PatchSource {
+    Unstaged,
+    Staged,
+    /// A commit, as `git format-patch` prints it: with its author, date and message.
+    Commit(String),
+    Text(String),
+}
+
+pub(crate) struct PatchSave;
+impl OpTrait for PatchSave {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let (source, default_name) = match target {
+            Some(TargetData::AllUnstaged) => (PatchSource::Unstaged, "unstaged.patch".into()),
+            Some(TargetData::AllStaged) => (PatchSource::Staged, "staged.patch".into()),
+            Some(TargetData::Commit(rev)) => (
+                PatchSource::Commit(rev.clone()),
+                format!("{}.patch", &rev[..rev.len().min(7)]),
+            ),
+            Some(TargetData::Delta(delta)) => (
+                PatchSource::Text(delta.format_patch()),
+                file_patch_name(&delta.new_file),
+            ),
+            Some(TargetData::Hunk(hunk)) => (
+                PatchSource::Text(hunk.format_patch()),
+                file_patch_name(&hunk.new_file),
+            ),
+            Some(TargetData::HunkLine(hunk, line_i)) => {
+                if !hunk.is_change(*line_i) {
+                    return Some(no_change("save"));
+                }
+
+                (
+                    PatchSource::Text(
+                        hunk.format_line_patch(*line_i..(*line_i + 1), PatchMode::Normal),
+                    ),
+                    file_patch_name(&hunk.new_file),
+                )
+            }
+            Some(TargetData::HunkLines(hunk, lines)) => {
+                if !hunk.has_change(lines.clone()) {
+                    return Some(no_selected_change("save"));
+                }
+
+                (
+                    PatchSource::Text(hunk.format_line_patch(lines.clone(), PatchMode::Normal)),
+                    file_patch_name(&hunk.new_file),
+                )
+            }
+            _ => return None,
+        };
+
+        Some(Rc::new(move |state: &mut State, _term: &mut Term| {
+            state.close_menu();
+
+            let patch = read_patch(state, &source)?;
+            if patch.is_empty() {
+                return Err("Nothing to save, there are no changes".into());
+            }
+
+            let default_name = default_name.clone();
+            set_prompt(
+                state,
+                "Save patch as",
+                Box::new(move |state, term, path| save_patch(state, term, path, patch.clone())),
+                Box::new(move |_| Some(default_name.clone())),
+                false,
+            );
+            Ok(())
+        }))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "save as patch".into()
+    }
+}
+
+fn file_patch_name(file: &Path) -> String {
+    let name = file.file_name().unwrap_or(file.as_os_str());
+    format!("{}.patch", name.to_string_lossy())
+}
+
+fn read_patch(state: &State, source: &PatchSource) -> Res<Vec<u8>> {
+    let patch = match source {
+        PatchSource::Unstaged => {
+            git::diff_unstaged(&state.config, &state.repo, &state.diff_options.borrow())?
+                .format_patch()
+        }
+        PatchSource::Staged => {
+            git::diff_staged(&state.config, &state.repo, &state.diff_options.borrow())?
+                .format_patch()
+        }
+        PatchSource::Commit(rev) => {
+            let out = git::command()
+                .args(["format-patch", "-1", "--stdout", rev])
+                .current_dir(state.repo.workdir().expect("No workdir"))
+                .output()?;
+
+            if !out.status.success() {
+                return Err(String::from_utf8_lossy(&out.stderr)
+                    .trim()
+                    .to_string()
+                    .into());
+            }
+
+            return Ok(out.stdout);
+        }
+        PatchSource::Text(text) => text.clone(),
+    };
+
+    Ok(diff::encode(&patch))
+}
+
+/// Writes `patch` to `path` within the work tree, asking before overwriting a file.
+fn save_patch(state: &mut State, term: &mut Term, path: &str, patch: Vec<u8>) -> Res<()> {
+    if path.is_empty() {
+        return Err("Patch file name can't be empty".into());
+    }
+
+    let file = state.repo.workdir().expect("No workdir").join(path);
+    let exists = file.exists();
+
+    let saved = format!("Saved patch to {}", path);
+    let write = Rc::new(move |state: &mut State, _term: &mut Term| {
+        fs::write(&file, &patch)?;
+        state.screen_mut().update()?;
+        state.display_info(saved.clone());
+        Ok(())
+    });
+
+    let mut action = match exists {
+        true => create_y_n_prompt(write, format!("{} exists, overwrite?", path)),
+        false => write,
+    };
+    Rc::get_mut(&mut action).unwrap()(state, term)
+}
+
+pub(crate) struct PatchApply;
+impl OpTrait for PatchApply {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(create_prompt_with_default(
+            "Apply patch file",
+            patch_apply,
+            newest_patch_file,
+            true,
+        ))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "apply patch file".into()
+    }
+}
+
+/// The most recently changed `.patch` file in the work tree, the likeliest to be applied.
+fn newest_patch_file(state: &State) -> Option<String> {
+    fs::read_dir(state.repo.workdir()?)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "patch"))
+        .max_by_key(|entry| {
+            entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+}
+
+/// Checks that the patch applies, then applies it. If some hunks don't, offers to apply the rest
+/// with `--reject`, which leaves those that don't in `.rej` files.
+fn patch_apply(state: &mut State, term: &mut Term, path: &str) -> Res<()> {
+    let args = state.pending_menu.as_ref().unwrap().args();
+    state.close_menu();
+
+    let dir = state.repo.workdir().expect("No workdir").to_path_buf();
+    if !dir.join(path).is_file() {
+        return Err(format!("No patch file at '{}'", path).into());
+    }
+
+    let check = git::command()
+        .args(["apply", "--check"])
+        .args(&args)
+        .arg(path)
+        .current_dir(&dir)
+        .output()?;
+
+    if check.status.success() {
+        let mut cmd = git::command();
+        cmd.arg("apply").args(&args).arg(path);
+        return state.run_cmd(term, &[], cmd);
+    }
+
+    let error = ApplyResult::parse(&String::from_utf8_lossy(&check.stderr)).message;
+    if !error.ends_with("patch does not apply") {
+        return Err(error.into());
+    }
+
+    let path = path.to_string();
+    let apply_rest = Rc::new(move |state: &mut State, _term: &mut Term| {
+        let out = git::command()
+            .args(["apply", "--reject", "--verbose"])
+            .args(&args)
+            .arg(&path)
+            .current_dir(&dir)
+            .output()?;
+
+        state.screen_mut().update()?;
+
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let rejected = apply::rejected_files(&stderr);
+        if rejected.is_empty() {
+            return match out.status.success() {
+                true => Ok(()),
+                false => Err(ApplyResult::parse(&stderr).message.into()),
+            };
+        }
+
+        Err(format!("Applied with rejects left in {}", rejected.join(", ")).into())
+    });
+
+    let mut prompt = create_y_n_prompt(apply_rest, format!("{}. Apply the rest?", error));
+    Rc::get_mut(&mut prompt).unwrap()(state, term)
+}
+
+pub(crate) struct AmContinue;
+impl OpTrait for AmContinue {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(am_action(&["am", "--continue"]))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "continue git am".into()
+    }
+}
+
+pub(crate) struct AmSkip;
+impl OpTrait for AmSkip {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(am_action(&["am", "--skip"]))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "skip patch".into()
+    }
+}
+
+pub(crate) struct AmAbort;
+impl OpTrait for AmAbort {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(am_action(&["am", "--abort"]))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "abort git am".into()
+    }
+}
+
+fn am_action(args: &'static [&'static str]) -> Action {
+    Rc::new(move |state: &mut State, term: &mut Term| {
+        state.close_menu();
+
+        if git::am_status(&state.repo).is_none() {
+            return Err("No git am in progress".into());
+        }
+
+        let mut cmd = git::command();
+        cmd.args(args);
+        state.run_cmd(term, &[], cmd)
+    })
+}
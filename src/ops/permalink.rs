@@ -0,0 +1,56 @@
+use super::{blame::blamed_line, Action, OpTrait};
+use crate::{
+    git::{self, forge},
+    items::TargetData,
+    state::State,
+};
+use std::rc::Rc;
+
+/// Links to the line as of the commit that last touched it, so the link stays valid.
+pub(crate) struct CopyPermalink;
+impl OpTrait for CopyPermalink {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let Some(TargetData::HunkLine(hunk, i)) = target else {
+            return None;
+        };
+
+        let (line, rev) = blamed_line(hunk, *i)?;
+        let path = hunk.new_file.clone();
+
+        Some(Rc::new(move |state, _term| {
+            state.close_menu();
+
+            let blame = git::blame_line(&state.repo, &path, line, rev)?;
+            if !blame.is_committed() {
+                return Err("Line isn't committed yet".into());
+            }
+
+            let remote = forge::origin(&state.repo)?;
+            let template = state
+                .config
+                .forge
+                .permalink
+                .get(&remote.host)
+                .ok_or_else(|| format!("No permalink template for '{}'", remote.host))?;
+
+            let link =
+                forge::permalink(template, &remote, &blame.hash, &blame.path, blame.orig_line);
+
+            match &mut state.clipboard {
+                Some(cb) => {
+                    cb.set_text(link.clone())?;
+                    state.display_info(format!("Copied {}", link));
+                }
+                None => state.display_error("Clipboard not available".to_owned()),
+            }
+            Ok(())
+        }))
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Copy permalink".into()
+    }
+}
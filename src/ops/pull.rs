@@ -10,7 +10,7 @@ use crate::{
     term::Term,
     Res,
 };
-use std::{process::Command, rc::Rc};
+use std::rc::Rc;
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![Arg::new_flag("--rebase", "Rebase local commits", false)]
@@ -84,7 +84,7 @@ impl OpTrait for PullFromUpstream {
 }
 
 fn set_upstream_and_pull(state: &mut State, term: &mut Term, upstream_name: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["branch", "--set-upstream-to", upstream_name]);
     state.run_cmd(term, &[], cmd)?;
 
@@ -112,7 +112,7 @@ fn pull_elsewhere(state: &mut State, term: &mut Term, remote: &str) -> Res<()> {
 }
 
 fn pull(state: &mut State, term: &mut Term, extra_args: &[&str]) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["pull"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.args(extra_args);
@@ -1,10 +1,12 @@
-use super::{create_prompt, Action, OpTrait};
+use super::{
+    create_confirm_prompt, create_prompt, create_prompt_with_default, set_prompt, Action, OpTrait,
+};
 use crate::git;
 use crate::git::remote::{
     get_push_remote, get_upstream_components, get_upstream_shortname, set_push_remote,
 };
 use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
-use std::{process::Command, rc::Rc};
+use std::{ffi::OsString, rc::Rc};
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![
@@ -12,6 +14,8 @@ pub(crate) fn init_args() -> Vec<Arg> {
         Arg::new_flag("--force", "Force", false),
         Arg::new_flag("--no-verify", "Disable hooks", false),
         Arg::new_flag("--dry-run", "Dry run", false),
+        Arg::new_flag("--set-upstream", "Set upstream", false),
+        Arg::new_flag("--tags", "Include tags", false),
     ]
 }
 
@@ -82,7 +86,7 @@ impl OpTrait for PushToUpstream {
 }
 
 fn set_upstream_and_push(state: &mut State, term: &mut Term, upstream_name: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["branch", "--set-upstream-to", upstream_name]);
     state.run_cmd(term, &[], cmd)?;
 
@@ -96,7 +100,12 @@ fn set_upstream_and_push(state: &mut State, term: &mut Term, upstream_name: &str
 pub(crate) struct PushToElsewhere;
 impl OpTrait for PushToElsewhere {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
-        Some(create_prompt("Select remote", push_elsewhere, true))
+        Some(create_prompt_with_default(
+            "Select remote",
+            push_elsewhere,
+            default_remote,
+            true,
+        ))
     }
 
     fn display(&self, _state: &State) -> String {
@@ -104,8 +113,48 @@ impl OpTrait for PushToElsewhere {
     }
 }
 
-fn push_elsewhere(state: &mut State, term: &mut Term, remote: &str) -> Res<()> {
-    push(state, term, &[remote])
+fn default_remote(state: &State) -> Option<String> {
+    get_push_remote(&state.repo)
+        .ok()
+        .flatten()
+        .or_else(|| Some(get_upstream_components(&state.repo).ok()??.0))
+        .filter(|remote| remote != ".")
+}
+
+fn push_elsewhere(state: &mut State, _term: &mut Term, remote: &str) -> Res<()> {
+    if state.repo.find_remote(remote).is_err() {
+        return Err(format!("No remote named '{}'", remote).into());
+    }
+
+    let remote = remote.to_string();
+    let args = state.pending_menu.as_ref().unwrap().args();
+    state.close_menu();
+
+    // Pushing to `refs/heads/<branch>` creates the remote branch if it doesn't exist yet
+    set_prompt(
+        state,
+        "Select branch",
+        Box::new(move |state, term, branch| {
+            let head_ref = git::get_head(&state.repo)?;
+            let mut args = args.clone();
+            args.extend([
+                remote.clone().into(),
+                format!("{}:refs/heads/{}", head_ref, branch).into(),
+            ]);
+
+            run_push(state, term, args)
+        }),
+        Box::new(|state| {
+            state
+                .repo
+                .head()
+                .ok()
+                .and_then(|head| head.shorthand().map(String::from))
+        }),
+        false,
+    );
+
+    Ok(())
 }
 
 fn push_head_to(state: &mut State, term: &mut Term, remote: &str, branch: &str) -> Res<()> {
@@ -115,12 +164,34 @@ fn push_head_to(state: &mut State, term: &mut Term, remote: &str, branch: &str)
 }
 
 fn push(state: &mut State, term: &mut Term, extra_args: &[&str]) -> Res<()> {
-    let mut cmd = Command::new("git");
-    cmd.args(["push"]);
-    cmd.args(state.pending_menu.as_ref().unwrap().args());
-    cmd.args(extra_args);
+    let mut args = state.pending_menu.as_ref().unwrap().args();
+    args.extend(extra_args.iter().map(OsString::from));
 
     state.close_menu();
-    state.run_cmd_async(term, &[], cmd)?;
-    Ok(())
+    run_push(state, term, args)
+}
+
+/// Asks first when force pushing, as `confirm.force_push` is set to by default.
+fn run_push(state: &mut State, term: &mut Term, args: Vec<OsString>) -> Res<()> {
+    let is_force = args.iter().any(|arg| {
+        arg.to_str()
+            .is_some_and(|arg| arg == "--force" || arg.starts_with("--force-with-lease"))
+    });
+
+    let push = Rc::new(move |state: &mut State, term: &mut Term| {
+        let mut cmd = git::command();
+        cmd.args(["push"]);
+        cmd.args(&args);
+
+        state.run_cmd_async(term, &[], cmd)?;
+        Ok(())
+    });
+
+    let mut action = if is_force {
+        create_confirm_prompt(push, |c| c.force_push, 1, "Really force push?")
+    } else {
+        push
+    };
+
+    Rc::get_mut(&mut action).unwrap()(state, term)
 }
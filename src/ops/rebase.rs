@@ -1,7 +1,9 @@
 use super::{create_prompt_with_default, selected_rev, Action, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
+use crate::{git, items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
+use git2::{Oid, Repository};
 use std::{
     ffi::{OsStr, OsString},
+    fs,
     process::Command,
     rc::Rc,
 };
@@ -26,7 +28,7 @@ pub(crate) struct RebaseContinue;
 impl OpTrait for RebaseContinue {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
+            let mut cmd = git::command();
             cmd.args(["rebase", "--continue"]);
 
             state.close_menu();
@@ -44,7 +46,7 @@ pub(crate) struct RebaseAbort;
 impl OpTrait for RebaseAbort {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
+            let mut cmd = git::command();
             cmd.args(["rebase", "--abort"]);
 
             state.close_menu();
@@ -75,7 +77,7 @@ impl OpTrait for RebaseElsewhere {
 }
 
 fn rebase_elsewhere(state: &mut State, term: &mut Term, rev: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.arg("rebase");
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.arg(rev);
@@ -106,13 +108,23 @@ impl OpTrait for RebaseInteractive {
         true
     }
 
-    fn display(&self, _state: &State) -> String {
-        "interactively".into()
+    fn display(&self, state: &State) -> String {
+        let autosquash = state
+            .repo
+            .config()
+            .and_then(|config| config.get_bool("rebase.autosquash"))
+            .unwrap_or(false);
+
+        if autosquash {
+            "interactively (autosquash)".into()
+        } else {
+            "interactively".into()
+        }
     }
 }
 
 fn rebase_interactive_cmd(args: &[OsString], rev: &OsStr) -> Command {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["rebase", "-i"]);
     cmd.args(args);
     cmd.arg(parent(rev));
@@ -152,9 +164,134 @@ impl OpTrait for RebaseAutosquash {
 }
 
 fn rebase_autosquash_cmd(args: &[OsString], rev: &OsStr) -> Command {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["rebase", "-i", "--autosquash", "--keep-empty"]);
     cmd.args(args);
     cmd.arg(rev);
     cmd
 }
+
+pub(crate) struct RebaseSquash;
+impl OpTrait for RebaseSquash {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let action = match target {
+            Some(TargetData::Commit(r)) => {
+                let rev = r.clone();
+                Rc::new(move |state: &mut State, term: &mut Term| {
+                    let args = state.pending_menu.as_ref().unwrap().args();
+                    state.close_menu();
+
+                    SquashTodo::new(&state.repo, &rev)?.rebase(state, term, &args)
+                })
+            }
+            _ => return None,
+        };
+
+        Some(action)
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "squash into parent".into()
+    }
+}
+
+/// A rebase todo that squashes a commit into its parent, keeping the commits after it as they are.
+struct SquashTodo {
+    /// What the rebase starts from, `None` when the parent is the root commit.
+    onto: Option<Oid>,
+    todo: String,
+}
+
+impl SquashTodo {
+    fn new(repo: &Repository, rev: &str) -> Res<Self> {
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+        let short = short_id(&commit)?;
+
+        if commit.parent_count() != 1 {
+            return Err(
+                format!("Can't squash {}, it isn't a commit with one parent", short).into(),
+            );
+        }
+
+        let parent = commit.parent(0)?;
+        if parent.parent_count() > 1 {
+            return Err(format!("Can't squash {} into a merge commit", short).into());
+        }
+
+        let head = repo.head()?.peel_to_commit()?;
+        if head.id() != commit.id() && !repo.graph_descendant_of(head.id(), commit.id())? {
+            return Err(format!("Can't squash {}, it isn't on the current branch", short).into());
+        }
+
+        if let Some(remote) = git::pushed_to(repo, parent.id())? {
+            return Err(format!(
+                "Won't squash {} into {}, it's already pushed to {}",
+                short,
+                short_id(&parent)?,
+                remote
+            )
+            .into());
+        }
+
+        let mut walk = repo.revwalk()?;
+        walk.push(head.id())?;
+        walk.hide(commit.id())?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        let later = walk.collect::<Result<Vec<_>, _>>()?;
+
+        let mut todo = format!("pick {}\nsquash {}\n", parent.id(), commit.id());
+        for id in later {
+            if repo.find_commit(id)?.parent_count() > 1 {
+                return Err(
+                    format!("Can't squash {}, there's a merge commit after it", short).into(),
+                );
+            }
+            todo.push_str(&format!("pick {}\n", id));
+        }
+
+        Ok(Self {
+            onto: parent.parent_ids().next(),
+            todo,
+        })
+    }
+
+    /// Runs a `git rebase -i` that takes this todo in place of opening an editor for it.
+    /// Opens an editor for the combined message, a conflict stops the rebase as usual.
+    fn rebase(&self, state: &mut State, term: &mut Term, args: &[OsString]) -> Res<()> {
+        let path = state.repo.path().join("GITU_SQUASH_TODO");
+        fs::write(&path, &self.todo)?;
+
+        let mut cmd = git::command();
+        cmd.args(["rebase", "-i"]);
+        cmd.args(args);
+        match self.onto {
+            Some(onto) => cmd.arg(onto.to_string()),
+            None => cmd.arg("--root"),
+        };
+        // Git runs this through a shell, with the path of its todo appended
+        cmd.env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp '{}'", path.to_string_lossy().replace('\'', r"'\''")),
+        );
+
+        // Git has copied the todo by the time it stops, whether done or at a conflict
+        let result = state.run_cmd_interactive(term, cmd);
+        // What the rebase did matters more than a todo left behind
+        if let Err(error) = fs::remove_file(&path) {
+            log::warn!("Couldn't remove the squash todo: {}", error);
+        }
+        result
+    }
+}
+
+fn short_id(commit: &git2::Commit) -> Res<String> {
+    Ok(commit
+        .as_object()
+        .short_id()?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
@@ -1,6 +1,7 @@
-use super::{create_prompt_with_default, selected_rev, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Action, Res};
-use std::process::Command;
+use super::{create_confirm_prompt, create_prompt_with_default, selected_rev, OpTrait};
+use crate::{git, items::TargetData, menu::arg::Arg, state::State, term::Term, Action, Res};
+use git2::{Repository, Status, StatusOptions};
+use std::rc::Rc;
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![]
@@ -23,13 +24,7 @@ impl OpTrait for ResetSoft {
 }
 
 fn reset_soft(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
-    cmd.args(["reset", "--soft"]);
-    cmd.args(state.pending_menu.as_ref().unwrap().args());
-    cmd.arg(input);
-
-    state.close_menu();
-    state.run_cmd(term, &[], cmd)
+    reset(state, term, "--soft", input)
 }
 
 pub(crate) struct ResetMixed;
@@ -49,13 +44,7 @@ impl OpTrait for ResetMixed {
 }
 
 fn reset_mixed(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
-    cmd.args(["reset", "--mixed"]);
-    cmd.args(state.pending_menu.as_ref().unwrap().args());
-    cmd.arg(input);
-
-    state.close_menu();
-    state.run_cmd(term, &[], cmd)
+    reset(state, term, "--mixed", input)
 }
 
 pub(crate) struct ResetHard;
@@ -74,12 +63,88 @@ impl OpTrait for ResetHard {
     }
 }
 
+/// Asks first, telling how many files have changes that would be lost.
 fn reset_hard(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
-    cmd.args(["reset", "--hard"]);
+    check_not_past_initial_commit(&state.repo, input)?;
+
+    // Commits selected in a log are given by their full hash
+    let shown = match state.repo.revparse_single(input) {
+        Ok(object) if object.id().to_string() == input => {
+            object.short_id()?.as_str().unwrap_or(input).to_string()
+        }
+        _ => input.to_string(),
+    };
+
+    let changed = changed_tracked_files(&state.repo)?;
+    let prompt = match changed {
+        0 => format!("Hard reset to {}?", shown),
+        1 => format!("Hard reset to {}, discarding the changes to 1 file?", shown),
+        n => format!(
+            "Hard reset to {}, discarding the changes to {} files?",
+            shown, n
+        ),
+    };
+
+    let rev = input.to_string();
+    let action =
+        Rc::new(move |state: &mut State, term: &mut Term| reset(state, term, "--hard", &rev));
+
+    let mut action = create_confirm_prompt(action, |c| c.hard_reset, changed, prompt);
+    Rc::get_mut(&mut action).unwrap()(state, term)
+}
+
+fn reset(state: &mut State, term: &mut Term, mode: &str, input: &str) -> Res<()> {
+    check_not_past_initial_commit(&state.repo, input)?;
+
+    let mut cmd = git::command();
+    cmd.args(["reset", mode]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.arg(input);
 
     state.close_menu();
     state.run_cmd(term, &[], cmd)
 }
+
+/// Refuses first-parent ancestors like `HEAD~` beyond the initial commit, which git only tells apart
+/// from a typo with "ambiguous argument".
+fn check_not_past_initial_commit(repo: &Repository, rev: &str) -> Res<()> {
+    if rev.is_empty() || repo.revparse_single(rev).is_ok() {
+        return Ok(());
+    }
+
+    let Some(at) = rev.rfind(['^', '~']) else {
+        return Ok(());
+    };
+
+    let (base, suffix) = (&rev[..at], &rev[at + 1..]);
+    let first_parent = match &rev[at..at + 1] {
+        "~" => suffix.chars().all(|c| c.is_ascii_digit()),
+        _ => suffix.is_empty() || suffix == "1",
+    };
+
+    if first_parent
+        && repo
+            .revparse_single(base)
+            .and_then(|base| base.peel_to_commit())
+            .is_ok()
+    {
+        return Err(format!("Can't reset to {}, it's past the initial commit", rev).into());
+    }
+
+    Ok(())
+}
+
+/// Files with staged or unstaged changes, which a hard reset throws away. Untracked ones are left alone.
+fn changed_tracked_files(repo: &Repository) -> Res<usize> {
+    let statuses = repo.statuses(Some(
+        StatusOptions::new()
+            .include_untracked(false)
+            .include_ignored(false),
+    ))?;
+
+    Ok(statuses
+        .iter()
+        .filter(|entry| !entry.status().intersects(Status::WT_NEW | Status::IGNORED))
+        .filter(|entry| !entry.status().is_empty())
+        .count())
+}
@@ -1,6 +1,6 @@
-use std::{process::Command, rc::Rc};
+use std::rc::Rc;
 
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
+use crate::{git, items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
 
 use super::{create_prompt_with_default, selected_rev, Action, OpTrait};
 
@@ -17,7 +17,7 @@ pub(crate) struct RevertAbort;
 impl OpTrait for RevertAbort {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
+            let mut cmd = git::command();
             cmd.args(["revert", "--abort"]);
 
             state.close_menu();
@@ -35,7 +35,7 @@ pub(crate) struct RevertContinue;
 impl OpTrait for RevertContinue {
     fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
         Some(Rc::new(|state: &mut State, term: &mut Term| {
-            let mut cmd = Command::new("git");
+            let mut cmd = git::command();
             cmd.args(["revert", "--continue"]);
 
             state.close_menu();
@@ -66,7 +66,7 @@ impl OpTrait for RevertCommit {
 }
 
 fn revert_commit(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["revert"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     cmd.arg(input);
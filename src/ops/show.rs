@@ -1,6 +1,11 @@
-use super::OpTrait;
-use crate::{items::TargetData, screen, state::State, Action};
-use std::{path::Path, process::Command, rc::Rc};
+use super::{worktree, OpTrait};
+use crate::{git, items::TargetData, screen, state::State, term::Term, Action, Res};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+    rc::Rc,
+};
 
 pub(crate) struct Show;
 impl OpTrait for Show {
@@ -8,9 +13,14 @@ impl OpTrait for Show {
         match target {
             Some(TargetData::Commit(r) | TargetData::Branch(r)) => goto_show_screen(r.clone()),
             Some(TargetData::File(u)) => editor(u.as_path(), None),
+            Some(TargetData::Delta(d)) if d.sparse_excluded => None,
+            Some(TargetData::Delta(d)) if d.submodule.is_some() => {
+                open_submodule(d.new_file.clone())
+            }
             Some(TargetData::Delta(d)) => editor(d.new_file.as_path(), None),
             Some(TargetData::Hunk(h)) => editor(h.new_file.as_path(), Some(h.first_diff_line())),
-            Some(TargetData::Stash { id: _, commit }) => goto_show_screen(commit.clone()),
+            Some(TargetData::Stash { id, commit }) => goto_show_stash_screen(*id, commit.clone()),
+            Some(TargetData::Worktree(path)) => worktree::open_worktree(path.clone()),
             _ => None,
         }
     }
@@ -39,57 +49,98 @@ fn goto_show_screen(r: String) -> Option<Action> {
     }))
 }
 
-fn editor(file: &Path, maybe_line: Option<u32>) -> Option<Action> {
-    let file = file.to_str().unwrap().to_string();
+fn goto_show_stash_screen(id: usize, commit: String) -> Option<Action> {
+    Some(Rc::new(move |state, term| {
+        state.close_menu();
+        state.screens.push(screen::show::create_stash(
+            Rc::clone(&state.config),
+            Rc::clone(&state.repo),
+            term.size()?,
+            id,
+            commit.clone(),
+        )?);
+        Ok(())
+    }))
+}
 
+/// Runs another Gitu in the submodule, coming back here once it's quit.
+fn open_submodule(path: PathBuf) -> Option<Action> {
     Some(Rc::new(move |state, term| {
-        const EDITOR_VARS: [&str; 3] = ["VISUAL", "EDITOR", "GIT_EDITOR"];
-        let configured_editor = EDITOR_VARS
-            .into_iter()
-            .find_map(|var| std::env::var(var).ok());
-
-        let Some(editor) = configured_editor else {
-            return Err(format!(
-                "No editor environment variable set ({})",
-                EDITOR_VARS.join(", ")
-            )
-            .into());
-        };
+        state.close_menu();
 
-        let cmd = parse_editor_command(&editor, &file, maybe_line);
+        let dir = state.repo.workdir().expect("No workdir").join(&path);
+        if !dir.join(".git").exists() {
+            return Err(format!("Submodule {} isn't checked out", path.display()).into());
+        }
 
-        state.close_menu();
-        state
-            .run_cmd_interactive(term, cmd)
-            .map_err(|err| format!("Couldn't open editor {} due to: {}", editor, err))?;
+        let mut cmd = Command::new(env::current_exe()?);
+        cmd.current_dir(dir);
+        state.run_cmd_interactive(term, cmd)
+    }))
+}
 
+fn editor(file: &Path, maybe_line: Option<u32>) -> Option<Action> {
+    let file = file.to_str().unwrap().to_string();
+
+    Some(Rc::new(move |state, term| {
+        state.close_menu();
+        open_in_editor(state, term, &file, maybe_line)?;
         state.screen_mut().update()
     }))
 }
 
+/// Opens `file` in the user's editor and waits for it to exit.
+/// The editor is the one git would run, see `git var GIT_EDITOR`.
+pub(crate) fn open_in_editor(
+    state: &mut State,
+    term: &mut Term,
+    file: &str,
+    maybe_line: Option<u32>,
+) -> Res<()> {
+    let editor = git::editor(&state.repo)?;
+    let cmd = parse_editor_command(&editor, file, maybe_line);
+
+    state
+        .run_cmd_interactive(term, cmd)
+        .map_err(|err| format!("Couldn't open editor {} due to: {}", editor, err).into())
+}
+
+/// Like git, runs the editor through the shell if it's more than a command and its arguments.
 fn parse_editor_command(editor: &str, file: &str, maybe_line: Option<u32>) -> Command {
     let args = &editor.split_whitespace().collect::<Vec<_>>();
-    let mut cmd = Command::new(args[0]);
-    cmd.args(&args[1..]);
-
     let lower = args[0].to_lowercase();
 
-    if let Some(line) = maybe_line {
-        if lower.ends_with("vi")
-            || lower.ends_with("vim")
-            || lower.ends_with("nvim")
-            || lower.ends_with("nano")
+    let file_args = match maybe_line {
+        Some(line)
+            if lower.ends_with("vi")
+                || lower.ends_with("vim")
+                || lower.ends_with("nvim")
+                || lower.ends_with("nano") =>
         {
-            cmd.args([&format!("+{}", line), file]);
-        } else {
-            cmd.args([&format!("{}:{}", file, line)]);
+            vec![format!("+{}", line), file.to_string()]
         }
-    } else {
-        cmd.args([file.to_string()]);
+        Some(line) => vec![format!("{}:{}", file, line)],
+        None => vec![file.to_string()],
+    };
+
+    if editor.contains(SHELL_METACHARACTERS) {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &format!("{} \"$@\"", editor), editor]);
+        cmd.args(file_args);
+        return cmd;
     }
+
+    let mut cmd = Command::new(args[0]);
+    cmd.args(&args[1..]);
+    cmd.args(file_args);
     cmd
 }
 
+/// What git looks for in the editor to tell whether it needs a shell, leaving out whitespace.
+const SHELL_METACHARACTERS: &[char] = &[
+    '|', '&', ';', '<', '>', '(', ')', '$', '`', '\\', '"', '\'', '*', '?', '[', '#', '~', '=', '%',
+];
+
 #[cfg(test)]
 mod tests {
     use std::ffi::OsStr;
@@ -103,4 +154,14 @@ mod tests {
             &["-f", "+42", "README.md"]
         );
     }
+
+    #[test]
+    fn parse_editor_command_with_redirect() {
+        let cmd = super::parse_editor_command("echo edited >", "README.md", None);
+        assert_eq!(cmd.get_program(), OsStr::new("sh"));
+        assert_eq!(
+            &cmd.get_args().collect::<Vec<_>>(),
+            &["-c", "echo edited > \"$@\"", "echo edited >", "README.md"]
+        );
+    }
 }
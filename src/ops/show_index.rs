@@ -0,0 +1,34 @@
+use super::{Action, OpTrait};
+use crate::{items::TargetData, screen, state::State, term::Term};
+use std::{path::PathBuf, rc::Rc};
+
+pub(crate) struct ShowIndex;
+impl OpTrait for ShowIndex {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Delta(d)) => goto_index_screen(d.new_file.clone()),
+            Some(TargetData::File(f)) => goto_index_screen(f.clone()),
+            _ => None,
+        }
+    }
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Show index version".into()
+    }
+}
+
+fn goto_index_screen(path: PathBuf) -> Option<Action> {
+    Some(Rc::new(move |state: &mut State, term: &mut Term| {
+        state.close_menu();
+        state.screens.push(screen::show_index::create(
+            Rc::clone(&state.config),
+            Rc::clone(&state.repo),
+            term.size()?,
+            path.clone(),
+        )?);
+        Ok(())
+    }))
+}
@@ -1,12 +1,16 @@
-use super::OpTrait;
+use super::{show, OpTrait};
+use crate::git;
 use crate::{
-    git::diff::{Hunk, PatchMode},
+    git::diff::{self, Delta, Hunk, PatchMode},
     items::TargetData,
+    prompt::PromptData,
+    screen,
     state::State,
     term::Term,
-    Action,
+    Action, Res,
 };
-use std::{ffi::OsString, process::Command, rc::Rc};
+use std::{error::Error, ffi::OsString, fs, ops::Range, rc::Rc};
+use tui_prompts::State as _;
 
 pub(crate) struct Stage;
 impl OpTrait for Stage {
@@ -14,10 +18,19 @@ impl OpTrait for Stage {
         let action = match target.cloned() {
             Some(TargetData::AllUnstaged) => stage_unstaged(),
             Some(TargetData::AllUntracked(untracked)) => stage_untracked(untracked),
-            Some(TargetData::File(u)) => stage_file(u.into()),
-            Some(TargetData::Delta(d)) => stage_file(d.new_file.into()),
-            Some(TargetData::Hunk(h)) => stage_patch(h),
-            Some(TargetData::HunkLine(h, i)) => stage_line(h, i),
+            Some(TargetData::File(u)) => advancing(stage_file(u.into())),
+            // The patch's `rename from` and `rename to` stage the move along with the changes
+            Some(TargetData::Delta(d)) if d.status == git2::Delta::Renamed => {
+                advancing(stage_delta(d))
+            }
+            Some(TargetData::Delta(d)) => advancing(stage_file(d.new_file.into())),
+            Some(TargetData::Hunk(h)) => advancing(stage_patch(h)),
+            Some(TargetData::HunkLine(h, i)) if !h.is_change(i) => no_change("stage"),
+            Some(TargetData::HunkLine(h, i)) => stage_lines(h, i..(i + 1)),
+            Some(TargetData::HunkLines(h, lines)) if !h.has_change(lines.clone()) => {
+                no_selected_change("stage")
+            }
+            Some(TargetData::HunkLines(h, lines)) => stage_lines(h, lines),
             _ => return None,
         };
 
@@ -33,9 +46,51 @@ impl OpTrait for Stage {
     }
 }
 
+/// Stages every change to tracked files, leaving untracked ones be.
+pub(crate) struct StageModified;
+impl OpTrait for StageModified {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(stage_unstaged())
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Stage modified (git add -u)".into()
+    }
+}
+
+/// Stages every change, untracked files included.
+pub(crate) struct StageAll;
+impl OpTrait for StageAll {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            let mut cmd = git::command();
+            cmd.args(["add", "-A"]);
+
+            state.close_menu();
+            state.run_cmd(term, &[], cmd)
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Stage all, untracked too (git add -A)".into()
+    }
+}
+
+/// Has the selection move on to the next hunk or file once `action` staged the selected one,
+/// with `status.advance_after_stage` enabled.
+fn advancing(mut action: Action) -> Action {
+    Rc::new(move |state, term| {
+        if state.config.status.advance_after_stage.enabled {
+            state.screen_mut().advance_on_next_update();
+        }
+
+        Rc::get_mut(&mut action).unwrap()(state, term)
+    })
+}
+
 fn stage_unstaged() -> Action {
     Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["add", "-u", "."]);
 
         state.close_menu();
@@ -45,7 +100,7 @@ fn stage_unstaged() -> Action {
 
 fn stage_untracked(untracked: Vec<std::path::PathBuf>) -> Action {
     Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.arg("add");
         cmd.args(untracked.clone());
 
@@ -56,7 +111,7 @@ fn stage_untracked(untracked: Vec<std::path::PathBuf>) -> Action {
 
 fn stage_file(file: OsString) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["add"]);
         cmd.arg(&file);
 
@@ -65,26 +120,245 @@ fn stage_file(file: OsString) -> Action {
     })
 }
 
-fn stage_patch(h: Rc<Hunk>) -> Action {
+fn stage_delta(delta: Delta) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["apply", "--cached"]);
+        state.close_menu();
+        apply_cached(state, term, diff::encode(&delta.format_patch()), &[])
+    })
+}
 
+fn stage_patch(h: Rc<Hunk>) -> Action {
+    Rc::new(move |state, term| {
         state.close_menu();
-        state.run_cmd(term, &h.format_patch().into_bytes(), cmd)
+        apply_cached(state, term, diff::encode(&h.format_patch()), &[])
     })
 }
 
-fn stage_line(h: Rc<Hunk>, i: usize) -> Action {
+fn stage_lines(h: Rc<Hunk>, lines: Range<usize>) -> Action {
     Rc::new(move |state, term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["apply", "--cached", "--recount"]);
+        let input = diff::encode(&h.format_line_patch(lines.clone(), PatchMode::Normal));
+
+        state.close_menu();
+        apply_cached(state, term, input, &["--recount"])
+    })
+}
+
+/// Runs `git apply --cached`. If the patch doesn't apply, tells which hunks don't and offers to retry with `--3way`,
+/// or retries right away with `general.stage_3way_fallback` enabled.
+fn apply_cached(
+    state: &mut State,
+    term: &mut Term,
+    patch: Vec<u8>,
+    args: &'static [&'static str],
+) -> Res<()> {
+    let mut cmd = git::command();
+    cmd.args(["apply", "--cached"]).args(args);
+
+    let Err(error) = state.run_cmd(term, &patch, cmd) else {
+        return Ok(());
+    };
+
+    if state.config.general.stage_3way_fallback.enabled {
+        return apply_cached_3way(state, term, &patch, args);
+    }
+
+    let error = explain_apply_error(state, &patch, args, error);
+    let retry = Rc::new(move |state: &mut State, term: &mut Term| {
+        apply_cached_3way(state, term, &patch, args)
+    });
+
+    let mut prompt = super::create_y_n_prompt(retry, format!("{}. Retry with --3way?", error));
+    Rc::get_mut(&mut prompt).unwrap()(state, term)
+}
+
+/// Stages with `git apply --3way`, which merges the file as a whole if the context doesn't match.
+/// Reports the files it left conflicts in.
+fn apply_cached_3way(state: &mut State, term: &mut Term, patch: &[u8], args: &[&str]) -> Res<()> {
+    let conflicted_before = git::conflicted_files(&state.repo)?;
+
+    let mut cmd = git::command();
+    cmd.args(["apply", "--cached", "--3way"]).args(args);
+    let result = state.run_cmd(term, patch, cmd);
+
+    let new_conflicts = git::conflicted_files(&state.repo)?
+        .into_iter()
+        .filter(|file| !conflicted_before.contains(file))
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>();
+
+    if !new_conflicts.is_empty() {
+        return Err(format!(
+            "Staged with conflicts in {}, resolve them before committing",
+            new_conflicts.join(", ")
+        )
+        .into());
+    }
+
+    result
+}
+
+/// Replaces the error of a failed `git apply --cached` with which hunks didn't apply, if git can tell.
+pub(crate) fn explain_apply_error(
+    state: &State,
+    patch: &[u8],
+    args: &[&str],
+    error: Box<dyn Error>,
+) -> Box<dyn Error> {
+    match git::check_apply_cached(&state.repo, patch, args) {
+        Ok(result) if !result.failed.is_empty() => result.to_string().into(),
+        _ => error,
+    }
+}
 
-        let input = h
-            .format_line_patch(i..(i + 1), PatchMode::Normal)
-            .into_bytes();
+/// For a line of context, which there's nothing to do with by itself.
+pub(crate) fn no_change(verb: &'static str) -> Action {
+    Rc::new(move |state, _term| {
+        state.close_menu();
+        state.display_info(format!("No change on this line to {}", verb));
+        Ok(())
+    })
+}
 
+/// For selected lines that are all context.
+pub(crate) fn no_selected_change(verb: &'static str) -> Action {
+    Rc::new(move |state, _term| {
         state.close_menu();
-        state.run_cmd(term, &input, cmd)
+        state.display_info(format!("No change in the selected lines to {}", verb));
+        Ok(())
     })
 }
+
+/// Of line `i` alone, to apply to the index as it is: staged with `Normal`, unstaged with `Reverse` and `--reverse`.
+pub(crate) fn line_patch(h: &Hunk, i: usize, mode: PatchMode) -> String {
+    h.format_line_patch(i..(i + 1), mode)
+}
+
+pub(crate) struct EditAndStage;
+impl OpTrait for EditAndStage {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let delta = match target {
+            Some(TargetData::Delta(d)) => d.clone(),
+            _ => return None,
+        };
+
+        Some(Rc::new(move |state, term| {
+            if delta.hunks.is_empty() {
+                return Err(format!("No hunks to edit in {}", delta.new_file.display()).into());
+            }
+
+            state.close_menu();
+            edit_patch(state, term, &delta, delta.format_patch(), false)
+        }))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Edit patch and stage".into()
+    }
+}
+
+/// Lets the user edit a patch of `delta`, like `git add -e`, then stages the result, or unstages it
+/// when `reverse`: the patch is of staged changes then, and applied to the index backwards.
+/// Asks to edit it again if it doesn't apply.
+pub(crate) fn edit_patch(
+    state: &mut State,
+    term: &mut Term,
+    delta: &Delta,
+    patch: String,
+    reverse: bool,
+) -> Res<()> {
+    let path = state.repo.path().join("ADD_EDIT.patch");
+    fs::write(&path, diff::encode(&patch))?;
+    // The editor runs in the work tree, where `.git/ADD_EDIT.patch` is shorter to show
+    let shown_path = path
+        .strip_prefix(state.repo.workdir().expect("No workdir"))
+        .unwrap_or(&path)
+        .to_string_lossy()
+        .to_string();
+    let edited = show::open_in_editor(state, term, &shown_path, None)
+        .and_then(|()| Ok(diff::decode(&fs::read(&path)?)));
+    fs::remove_file(&path)?;
+    let edited = edited?;
+
+    if edited.trim().is_empty() {
+        let info = match reverse {
+            true => "Patch is empty, nothing unstaged",
+            false => "Patch is empty, nothing staged",
+        };
+        state.display_info(info.into());
+        return Ok(());
+    }
+
+    let args: &[&str] = match reverse {
+        true => &["--reverse", "--recount"],
+        false => &["--recount"],
+    };
+    let result = delta.check_edited_patch(&edited).and_then(|()| {
+        let input = diff::encode(&edited);
+        let mut cmd = git::command();
+        cmd.args(["apply", "--cached"]).args(args);
+        state
+            .run_cmd(term, &input, cmd)
+            .map_err(|error| explain_apply_error(state, &input, args, error))
+    });
+
+    if let Err(error) = result {
+        let delta = delta.clone();
+        state.prompt.set(PromptData {
+            prompt_text: format!("{}. Edit again? (y or n)", error).into(),
+            update_fn: Rc::new(move |state, term| {
+                if state.prompt.state.status().is_pending() {
+                    match state.prompt.state.value() {
+                        "y" => {
+                            state.prompt.reset(term)?;
+                            edit_patch(state, term, &delta, edited.clone(), reverse)?;
+                        }
+                        "" => (),
+                        _ => {
+                            state.display_info("Aborted".to_string());
+                            state.prompt.reset(term)?;
+                        }
+                    }
+                }
+                Ok(())
+            }),
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) struct PreviewPatch;
+impl OpTrait for PreviewPatch {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        let patch = match target {
+            Some(TargetData::Hunk(h)) => h.format_patch(),
+            Some(TargetData::HunkLine(h, i)) => line_patch(h, *i, PatchMode::Normal),
+            Some(TargetData::HunkLines(h, lines)) => {
+                h.format_line_patch(lines.clone(), PatchMode::Normal)
+            }
+            _ => return None,
+        };
+
+        Some(Rc::new(move |state, term| {
+            state.close_menu();
+            state.screens.push(screen::preview_patch::create(
+                Rc::clone(&state.config),
+                term.size()?,
+                patch.clone(),
+            )?);
+            Ok(())
+        }))
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Preview patch".into()
+    }
+}
@@ -1,7 +1,9 @@
-use super::{create_prompt, create_prompt_with_default, Action, OpTrait};
-use crate::{items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
+use super::{
+    create_confirm_prompt, create_prompt, create_prompt_with_default, set_prompt, Action, OpTrait,
+};
+use crate::{git, items::TargetData, menu::arg::Arg, state::State, term::Term, Res};
 use git2::{Repository, Status, StatusOptions};
-use std::{process::Command, rc::Rc};
+use std::{path::Path, rc::Rc};
 
 pub(crate) fn init_args() -> Vec<Arg> {
     vec![
@@ -22,7 +24,7 @@ impl OpTrait for Stash {
 }
 
 fn stash_push(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "push"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     if !input.is_empty() {
@@ -46,7 +48,8 @@ impl OpTrait for StashIndex {
 }
 
 fn stash_push_index(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    git::version::require(git::version::Feature::StashStaged)?;
+    let mut cmd = git::command();
     // --all / --unclude-untracked are not allowed together with --staged
     cmd.args(["stash", "push", "--staged"]);
     if !input.is_empty() {
@@ -100,15 +103,18 @@ fn is_working_tree_empty(repo: &Repository) -> Res<bool> {
 
 fn stash_worktree(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
     let need_to_stash_index = is_something_staged(&state.repo)?;
+    if need_to_stash_index {
+        git::version::require(git::version::Feature::StashStaged)?;
+    }
 
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "push"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
 
     state.close_menu();
 
     if need_to_stash_index {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["stash", "push", "--staged"]);
         state.run_cmd(term, &[], cmd)?;
     }
@@ -119,7 +125,7 @@ fn stash_worktree(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
     state.run_cmd(term, &[], cmd)?;
 
     if need_to_stash_index {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["stash", "pop", "-q", "1"]);
         state.run_cmd(term, &[], cmd)?;
     }
@@ -157,7 +163,7 @@ impl OpTrait for StashKeepIndex {
 }
 
 fn stash_push_keep_index(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "push", "--keep-index"]);
     cmd.args(state.pending_menu.as_ref().unwrap().args());
     if !input.is_empty() {
@@ -186,7 +192,7 @@ impl OpTrait for StashPop {
 }
 
 fn stash_pop(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "pop", "-q"]);
     cmd.arg(input);
 
@@ -212,7 +218,7 @@ impl OpTrait for StashApply {
 }
 
 fn stash_apply(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "apply", "-q"]);
     cmd.arg(input);
 
@@ -238,7 +244,7 @@ impl OpTrait for StashDrop {
 }
 
 fn stash_drop(state: &mut State, term: &mut Term, input: &str) -> Res<()> {
-    let mut cmd = Command::new("git");
+    let mut cmd = git::command();
     cmd.args(["stash", "drop"]);
     cmd.arg(input);
 
@@ -253,3 +259,104 @@ fn selected_stash(state: &State) -> Option<String> {
         _ => Some("0".to_string()),
     }
 }
+
+/// Checks out a single file of a stash into the working tree: the one selected in the view of a stash,
+/// or one picked from those the selected stash has.
+pub(crate) struct StashRestoreFile;
+impl OpTrait for StashRestoreFile {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Delta(d)) if d.status != git2::Delta::Deleted => {
+                let file = d.new_file.clone();
+                Some(Rc::new(move |state, term| {
+                    let id = state
+                        .screen()
+                        .shown_stash()
+                        .ok_or("Files can be restored from the view of a stash")?;
+                    restore_from_stash(state, term, id, &file)
+                }))
+            }
+            Some(TargetData::Stash { id, commit }) => {
+                let (id, commit) = (*id, commit.clone());
+                Some(Rc::new(move |state, _term| {
+                    // Files deleted in the stash have nothing to restore
+                    let files = git::stash_show(&state.repo, &commit)?
+                        .deltas
+                        .into_iter()
+                        .filter(|delta| delta.status != git2::Delta::Deleted)
+                        .map(|delta| delta.new_file.to_string_lossy().into_owned())
+                        .collect::<Vec<_>>();
+
+                    set_prompt(
+                        state,
+                        format!("Restore file from stash@{{{}}}", id),
+                        Box::new(move |state, term, input| {
+                            restore_from_stash(state, term, id, Path::new(input))
+                        }),
+                        Box::new(|_| None),
+                        true,
+                    );
+                    state.prompt.completions = files;
+                    Ok(())
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "restore file".into()
+    }
+}
+
+/// Checks out `file` from stash `id`, asking first if that would overwrite changes to it.
+fn restore_from_stash(state: &mut State, term: &mut Term, id: usize, file: &Path) -> Res<()> {
+    let stash = format!("stash@{{{}}}", id);
+    let source = stash_source(&state.repo, &stash, file)?;
+    let file = file.to_path_buf();
+
+    let modified = state.repo.status_file(&file).is_ok_and(|status| {
+        status.intersects(
+            Status::WT_NEW
+                | Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE,
+        )
+    });
+    let prompt = format!("Overwrite the changes to {}?", file.display());
+
+    let action: Action = Rc::new(move |state: &mut State, term: &mut Term| {
+        let mut cmd = git::command();
+        git::version::restore_worktree_paths_from(&mut cmd, &source, &[&file]);
+
+        state.close_menu();
+        state.run_cmd(term, &[], cmd)
+    });
+
+    let mut action = if modified {
+        create_confirm_prompt(action, |c| c.discard, 1, prompt)
+    } else {
+        action
+    };
+    Rc::get_mut(&mut action).unwrap()(state, term)
+}
+
+/// What to check `file` out of: the stash itself, or its third parent holding the untracked files it saved.
+fn stash_source(repo: &Repository, stash: &str, file: &Path) -> Res<String> {
+    let commit = repo.revparse_single(stash)?.peel_to_commit()?;
+    if commit.tree()?.get_path(file).is_ok() {
+        return Ok(stash.to_string());
+    }
+
+    let untracked = commit.parent(2).ok().and_then(|parent| parent.tree().ok());
+    if untracked.is_some_and(|tree| tree.get_path(file).is_ok()) {
+        return Ok(format!("{}^3", stash));
+    }
+
+    Err(format!("{} has no {}", stash, file.display()).into())
+}
@@ -1,17 +1,38 @@
-use super::OpTrait;
-use crate::{git::diff::PatchMode, items::TargetData, state::State, term::Term, Action};
-use std::{ffi::OsString, process::Command, rc::Rc};
+use super::{stage, OpTrait};
+use crate::{
+    git,
+    git::diff::{self, PatchMode},
+    items::TargetData,
+    state::State,
+    term::Term,
+    Action,
+};
+use std::{ffi::OsString, rc::Rc};
 
 pub(crate) struct Unstage;
 impl OpTrait for Unstage {
     fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
         let action = match target.cloned() {
             Some(TargetData::AllStaged) => unstage_staged(),
+            // Taking the new file out alone would leave the old one removed from the index
+            Some(TargetData::Delta(d)) if d.status == git2::Delta::Renamed => {
+                unstage_patch(diff::encode(&d.format_patch()), &["--reverse"])
+            }
             Some(TargetData::Delta(d)) => unstage_file(d.new_file.into()),
-            Some(TargetData::Hunk(h)) => unstage_patch(h.format_patch().into_bytes()),
-            Some(TargetData::HunkLine(h, i)) => unstage_line(
-                h.format_line_patch(i..(i + 1), PatchMode::Reverse)
-                    .into_bytes(),
+            Some(TargetData::Hunk(h)) => {
+                unstage_patch(diff::encode(&h.format_patch()), &["--reverse"])
+            }
+            Some(TargetData::HunkLine(h, i)) if !h.is_change(i) => stage::no_change("unstage"),
+            Some(TargetData::HunkLine(h, i)) => unstage_patch(
+                diff::encode(&stage::line_patch(&h, i, PatchMode::Reverse)),
+                &["--reverse", "--recount"],
+            ),
+            Some(TargetData::HunkLines(h, lines)) if !h.has_change(lines.clone()) => {
+                stage::no_selected_change("unstage")
+            }
+            Some(TargetData::HunkLines(h, lines)) => unstage_patch(
+                diff::encode(&h.format_line_patch(lines, PatchMode::Reverse)),
+                &["--reverse", "--recount"],
             ),
             _ => return None,
         };
@@ -29,7 +50,7 @@ impl OpTrait for Unstage {
 
 fn unstage_staged() -> Action {
     Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
+        let mut cmd = git::command();
         cmd.args(["reset", "HEAD", "--"]);
 
         state.close_menu();
@@ -39,31 +60,59 @@ fn unstage_staged() -> Action {
 
 fn unstage_file(file: OsString) -> Action {
     Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["restore", "--staged"]);
-        cmd.arg(&file);
+        let mut cmd = git::command();
+        git::version::unstage_paths(&mut cmd, &[&file]);
 
         state.close_menu();
         state.run_cmd(term, &[], cmd)
     })
 }
 
-fn unstage_patch(input: Vec<u8>) -> Action {
+/// Applies the patch of staged changes backwards to the index, the way they're staged.
+fn unstage_patch(input: Vec<u8>, args: &'static [&'static str]) -> Action {
     Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["apply", "--cached", "--reverse"]);
+        let mut cmd = git::command();
+        cmd.args(["apply", "--cached"]).args(args);
 
         state.close_menu();
-        state.run_cmd(term, &input, cmd)
+        state
+            .run_cmd(term, &input, cmd)
+            .map_err(|error| stage::explain_apply_error(state, &input, args, error))
     })
 }
 
-fn unstage_line(input: Vec<u8>) -> Action {
-    Rc::new(move |state: &mut State, term: &mut Term| {
-        let mut cmd = Command::new("git");
-        cmd.args(["apply", "--cached", "--reverse", "--recount"]);
+/// Edits the patch of staged changes to a file, or of one staged hunk, then unstages what's left of it.
+pub(crate) struct EditAndUnstage;
+impl OpTrait for EditAndUnstage {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target.cloned() {
+            Some(TargetData::Delta(delta)) => Some(Rc::new(move |state, term| {
+                if delta.hunks.is_empty() {
+                    return Err(format!("No hunks to edit in {}", delta.new_file.display()).into());
+                }
 
-        state.close_menu();
-        state.run_cmd(term, &input, cmd)
-    })
+                state.close_menu();
+                stage::edit_patch(state, term, &delta, delta.format_patch(), true)
+            })),
+            Some(TargetData::Hunk(hunk)) => Some(Rc::new(move |state, term| {
+                let delta = state
+                    .screen()
+                    .delta_of(&hunk)
+                    .cloned()
+                    .ok_or("The file of this hunk isn't shown")?;
+
+                state.close_menu();
+                stage::edit_patch(state, term, &delta, hunk.format_patch(), true)
+            })),
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "Edit patch and unstage".into()
+    }
 }
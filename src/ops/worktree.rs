@@ -0,0 +1,108 @@
+use super::{create_prompt, create_y_n_prompt, selected_rev, set_prompt, Action, OpTrait};
+use crate::{git, items::TargetData, screen, state::State, term::Term, Res};
+use git2::Repository;
+use std::{path::PathBuf, rc::Rc};
+
+pub(crate) struct ShowWorktrees;
+impl OpTrait for ShowWorktrees {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(Rc::new(|state: &mut State, term: &mut Term| {
+            state.close_menu();
+            state.screens.push(screen::worktrees::create(
+                Rc::clone(&state.config),
+                Rc::clone(&state.repo),
+                term.size()?,
+            )?);
+            Ok(())
+        }))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "list".into()
+    }
+}
+
+pub(crate) struct WorktreeAdd;
+impl OpTrait for WorktreeAdd {
+    fn get_action(&self, _target: Option<&TargetData>) -> Option<Action> {
+        Some(create_prompt("Worktree path", worktree_add, true))
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "add".into()
+    }
+}
+
+fn worktree_add(state: &mut State, _term: &mut Term, path: &str) -> Res<()> {
+    if path.is_empty() {
+        return Err("Worktree path can't be empty".into());
+    }
+
+    let path = path.to_string();
+    state.close_menu();
+
+    set_prompt(
+        state,
+        "Worktree branch",
+        Box::new(move |state, term, branch| {
+            let mut cmd = git::command();
+            cmd.args(["worktree", "add", &path]);
+            if !branch.is_empty() {
+                cmd.arg(branch);
+            }
+
+            state.run_cmd(term, &[], cmd)
+        }),
+        Box::new(selected_rev),
+        false,
+    );
+
+    Ok(())
+}
+
+pub(crate) struct WorktreeRemove;
+impl OpTrait for WorktreeRemove {
+    fn get_action(&self, target: Option<&TargetData>) -> Option<Action> {
+        match target {
+            Some(TargetData::Worktree(path)) => {
+                let path = path.clone();
+                let remove: Action = Rc::new(move |state: &mut State, term: &mut Term| {
+                    let mut cmd = git::command();
+                    cmd.args(["worktree", "remove"]);
+                    cmd.arg(&path);
+
+                    state.close_menu();
+                    state.run_cmd(term, &[], cmd)
+                });
+
+                Some(create_y_n_prompt(remove, "Really remove worktree?"))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_target_op(&self) -> bool {
+        true
+    }
+
+    fn display(&self, _state: &State) -> String {
+        "remove".into()
+    }
+}
+
+/// Switches gitu over to the repository checked out at `path`.
+pub(crate) fn open_worktree(path: PathBuf) -> Option<Action> {
+    Some(Rc::new(move |state: &mut State, term: &mut Term| {
+        let repo = Rc::new(Repository::open(&path)?);
+
+        state.close_menu();
+        state.screens = vec![screen::status::create(
+            Rc::clone(&state.config),
+            Rc::clone(&repo),
+            term.size()?,
+            Rc::clone(&state.diff_options),
+        )?];
+        state.repo = repo;
+        Ok(())
+    }))
+}
@@ -0,0 +1,151 @@
+use crate::{cmd_log::CmdLogEntry, state};
+use ratatui::{
+    style::{Style, Stylize},
+    text::Line,
+};
+use std::{
+    io::{self, Read},
+    mem,
+    process::Child,
+    sync::{Arc, Mutex, RwLock},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A command running in the background.
+/// Its output is read on separate threads as it's written, so it can't fill up the pipes
+/// and stall the command, and so git's progress can be shown meanwhile.
+pub(crate) struct PendingCmd {
+    pub child: Child,
+    pub log_entry: Arc<RwLock<CmdLogEntry>>,
+    stdout: Output,
+    stderr: Output,
+    started_at: Instant,
+}
+
+struct Output {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    reader: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl PendingCmd {
+    pub(crate) fn new(mut child: Child, log_entry: Arc<RwLock<CmdLogEntry>>) -> Self {
+        Self {
+            stdout: Output::read(child.stdout.take().expect("stdout is piped")),
+            stderr: Output::read(child.stderr.take().expect("stderr is piped")),
+            child,
+            log_entry,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Waits for the command to close its output, and returns stderr followed by stdout.
+    pub(crate) fn take_output(&mut self) -> io::Result<Vec<u8>> {
+        let mut out = self.stderr.take()?;
+        out.extend(self.stdout.take()?);
+        Ok(out)
+    }
+
+    /// A spinner and the command, followed by the last progress it reported, if any.
+    pub(crate) fn status_line(&self) -> Line<'static> {
+        let command = state::short_command(&self.log_entry.read().unwrap().args);
+        let frame = self.started_at.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+        let spinner = SPINNER[frame as usize % SPINNER.len()];
+        let progress = last_progress(&self.stderr.bytes.lock().unwrap());
+
+        Line::styled(
+            match progress {
+                Some(progress) => format!("{spinner} {command}: {progress}"),
+                None => format!("{spinner} {command}"),
+            },
+            Style::new().dim(),
+        )
+    }
+}
+
+impl Output {
+    fn read(mut pipe: impl Read + Send + 'static) -> Self {
+        let bytes = Arc::new(Mutex::new(vec![]));
+        let buffer = Arc::clone(&bytes);
+
+        let reader = thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => return Ok(()),
+                    Ok(read) => buffer.lock().unwrap().extend_from_slice(&chunk[..read]),
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                    Err(error) => return Err(error),
+                }
+            }
+        });
+
+        Self {
+            bytes,
+            reader: Some(reader),
+        }
+    }
+
+    fn take(&mut self) -> io::Result<Vec<u8>> {
+        if let Some(reader) = self.reader.take() {
+            reader.join().expect("Output reader panicked")?;
+        }
+
+        Ok(mem::take(&mut *self.bytes.lock().unwrap()))
+    }
+}
+
+/// Git redraws progress like `Receiving objects:  50% (1/2)` in place, ending each update with `\r`.
+fn last_progress(stderr: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(stderr)
+        .rsplit(['\r', '\n'])
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Keeps only the final state of lines that were redrawn in place, as a terminal would show them.
+pub(crate) fn collapse_progress(out: &str) -> String {
+    out.split_inclusive('\n')
+        .map(|line| {
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text, "\n"),
+                None => (line, ""),
+            };
+
+            let text = text
+                .rsplit('\r')
+                .find(|update| !update.trim().is_empty())
+                .unwrap_or_default();
+
+            format!("{text}{newline}")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collapse_progress, last_progress};
+
+    #[test]
+    fn progress_lines() {
+        let stderr = b"remote: Enumerating objects: 4, done.\n\
+            Receiving objects:  25% (1/4)\rReceiving objects:  50% (2/4)\r";
+        assert_eq!(
+            last_progress(stderr).as_deref(),
+            Some("Receiving objects:  50% (2/4)")
+        );
+        assert_eq!(last_progress(b""), None);
+
+        assert_eq!(
+            collapse_progress(
+                "Counting objects:  50% (2/4)\rCounting objects: 100% (4/4), done.\n\
+                To /tmp/remote.git\n"
+            ),
+            "Counting objects: 100% (4/4), done.\nTo /tmp/remote.git\n"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use super::Res;
-use crate::ops::Action;
+use crate::{ops::Action, state::State, term::Term};
 use ratatui::{backend::Backend, Terminal};
-use std::borrow::Cow;
+use std::{borrow::Cow, rc::Rc};
 use tui_prompts::{State as _, TextState};
 
 pub(crate) struct PromptData {
@@ -9,9 +9,16 @@ pub(crate) struct PromptData {
     pub(crate) update_fn: Action,
 }
 
+/// Goes on in the editor with what was typed into a prompt.
+pub(crate) type EditorFn = Rc<dyn Fn(&mut State, &mut Term, &str) -> Res<()>>;
+
 pub(crate) struct Prompt {
     pub(crate) data: Option<PromptData>,
     pub(crate) state: TextState<'static>,
+    /// What <tab> completes the last word of the input with, if anything.
+    pub(crate) completions: Vec<String>,
+    /// What <ctrl+o> continues the input in, if anything. Should it fail, the prompt is back as it was.
+    pub(crate) editor_fn: Option<EditorFn>,
 }
 
 impl Prompt {
@@ -19,6 +26,8 @@ impl Prompt {
         Prompt {
             data: None,
             state: TextState::new(),
+            completions: vec![],
+            editor_fn: None,
         }
     }
 
@@ -30,7 +39,47 @@ impl Prompt {
     pub(crate) fn reset<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Res<()> {
         self.data = None;
         self.state = TextState::new();
+        self.completions.clear();
+        self.editor_fn = None;
         terminal.hide_cursor()?;
         Ok(())
     }
+
+    /// Puts `input` back into the prompt, with the cursor at its end.
+    pub(crate) fn restore_input(&mut self, input: String) {
+        *self.state.position_mut() = input.chars().count();
+        *self.state.value_mut() = input;
+    }
+
+    /// Completes the last word of the input, as far as all matching completions agree.
+    /// Words are separated by spaces or dots, so both `main feat` and `main..feat` complete.
+    pub(crate) fn complete(&mut self) {
+        let value = self.state.value();
+        let word_start = value.rfind([' ', '.']).map_or(0, |i| i + 1);
+        let word = &value[word_start..];
+
+        let mut matches = self
+            .completions
+            .iter()
+            .filter(|completion| completion.starts_with(word));
+
+        let Some(first) = matches.next() else {
+            return;
+        };
+
+        let common = matches.fold(first.as_str(), |common, completion| {
+            let len = common
+                .chars()
+                .zip(completion.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum();
+
+            &common[..len]
+        });
+
+        let completed = format!("{}{}", &value[..word_start], common);
+        *self.state.position_mut() = completed.chars().count();
+        *self.state.value_mut() = completed;
+    }
 }
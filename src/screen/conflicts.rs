@@ -0,0 +1,100 @@
+use std::{fs, iter, path::PathBuf, rc::Rc};
+
+use crate::{
+    config::Config,
+    git::conflict::ConflictHunk,
+    items::{self, Item, TargetData},
+    Res,
+};
+use git2::Repository;
+use ratatui::{layout::Size, text::Line};
+
+use super::Screen;
+
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    path: PathBuf,
+) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let content = fs::read_to_string(repo.workdir().expect("No workdir").join(&path))?;
+            let hunks = ConflictHunk::parse(&content)?;
+
+            let resolved = hunks.is_empty().then(|| Item {
+                id: "conflicts_resolved".into(),
+                display: Line::raw("No conflicts"),
+                depth: 1,
+                unselectable: true,
+                ..Default::default()
+            });
+
+            Ok(iter::once(Item {
+                id: "conflicts_header".into(),
+                display: Line::styled(
+                    format!("Conflicts in {}", path.to_string_lossy()),
+                    &style.section_header,
+                ),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(resolved)
+            .chain(hunks.iter().enumerate().flat_map(|(i, hunk)| {
+                let target_data = TargetData::Conflict(path.clone(), i);
+
+                let sides = iter::once(("ours", format!("Ours ({})", hunk.ours_label), &hunk.ours))
+                    .chain(
+                        hunk.base
+                            .iter()
+                            .map(|(label, lines)| ("base", format!("Base ({})", label), lines)),
+                    )
+                    .chain(iter::once((
+                        "theirs",
+                        format!("Theirs ({})", hunk.theirs_label),
+                        &hunk.theirs,
+                    )));
+
+                [
+                    items::blank_line(),
+                    Item {
+                        id: format!("conflict_{}", i).into(),
+                        display: Line::styled(
+                            format!("Conflict at line {}", hunk.start + 1),
+                            &style.hunk_header,
+                        ),
+                        section: true,
+                        depth: 1,
+                        target_data: Some(target_data.clone()),
+                        ..Default::default()
+                    },
+                ]
+                .into_iter()
+                .chain(sides.flat_map(move |(side, header, lines)| {
+                    let target_data = target_data.clone();
+                    iter::once(Item {
+                        id: format!("conflict_{}_{}", i, side).into(),
+                        display: Line::styled(header, &style.file_header),
+                        section: true,
+                        depth: 2,
+                        target_data: Some(target_data.clone()),
+                        ..Default::default()
+                    })
+                    .chain(lines.iter().map(move |line| Item {
+                        display: Line::raw(line.trim_end_matches(['\n', '\r']).to_string()),
+                        depth: 3,
+                        unselectable: true,
+                        target_data: Some(target_data.clone()),
+                        ..Default::default()
+                    }))
+                }))
+                .collect::<Vec<_>>()
+            }))
+            .collect())
+        }),
+    )
+}
@@ -0,0 +1,128 @@
+use super::Screen;
+use crate::{
+    config::{Config, StyleConfig},
+    git::{
+        self,
+        diff::{Diff, Hunk},
+    },
+    items::{self, Item},
+    Res,
+};
+use git2::Repository;
+use ratatui::{
+    layout::Size,
+    text::{Line, Span, Text},
+};
+use std::{iter, rc::Rc};
+
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    from: String,
+    to: String,
+) -> Res<Screen> {
+    create_with_diff(config, size, move || {
+        let range = format!("{from}..{to}");
+        Ok((range.clone(), git::diff_refs(&repo, &from, &to)?))
+    })
+}
+
+/// What `HEAD` changed since it branched off `target`, like a pull request into `target` is diffed.
+/// The merge base is found again on every refresh, as `HEAD` or `target` may have moved.
+pub(crate) fn create_merge_base(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    target: String,
+) -> Res<Screen> {
+    create_with_diff(config, size, move || {
+        let merge_base = git::merge_base(&repo, &target)?.to_string();
+        let diff = git::diff_refs(&repo, &merge_base, "HEAD")?;
+        Ok((
+            format!("{target}...HEAD (merge base {})", &merge_base[..7]),
+            diff,
+        ))
+    })
+}
+
+fn create_with_diff(
+    config: Rc<Config>,
+    size: Size,
+    diff: impl Fn() -> Res<(String, Diff)> + 'static,
+) -> Res<Screen> {
+    Ok(Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let (range, diff) = diff()?;
+            let diff = styled(style, diff);
+
+            Ok(iter::once(Item {
+                id: format!("diff_refs_{range}").into(),
+                display: Line::styled(format!("Diff {range}"), &style.section_header),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(diff.deltas.is_empty().then(|| Item {
+                display: Line::raw("No differences"),
+                depth: 1,
+                unselectable: true,
+                ..Default::default()
+            }))
+            .chain([items::blank_line()])
+            .chain(items::create_diff_items(
+                Rc::clone(&config),
+                &diff,
+                &0,
+                false,
+            ))
+            .collect())
+        }),
+    )?
+    .read_only())
+}
+
+/// Colors the lines of a parsed diff by whether they're added or removed.
+fn styled(style: &StyleConfig, mut diff: Diff) -> Diff {
+    for delta in &mut diff.deltas {
+        for hunk in &mut delta.hunks {
+            let lines = hunk
+                .content
+                .lines
+                .iter()
+                .map(|line| {
+                    let text = line.to_string();
+                    let (marker, rest) =
+                        text.split_at(text.chars().next().map_or(0, char::len_utf8));
+
+                    let (marker_style, rest_style) = match marker {
+                        "-" => (
+                            &style.diff_highlight.tag_old,
+                            &style.diff_highlight.unchanged_old,
+                        ),
+                        "+" => (
+                            &style.diff_highlight.tag_new,
+                            &style.diff_highlight.unchanged_new,
+                        ),
+                        _ => return Line::raw(text),
+                    };
+
+                    Line::from(vec![
+                        Span::styled(marker.to_string(), marker_style),
+                        Span::styled(rest.to_string(), rest_style),
+                    ])
+                })
+                .collect::<Vec<_>>();
+
+            *hunk = Rc::new(Hunk {
+                content: Text::from(lines),
+                ..Hunk::clone(hunk)
+            });
+        }
+    }
+
+    diff
+}
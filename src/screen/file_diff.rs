@@ -0,0 +1,74 @@
+use super::Screen;
+use crate::{
+    config::Config,
+    git::{self, diff_options::DiffOptions},
+    items::{self, Item},
+    Res,
+};
+use git2::Repository;
+use ratatui::{layout::Size, text::Line};
+use std::{cell::RefCell, iter, path::PathBuf, rc::Rc};
+
+/// The unstaged, or staged, changes to a single file (or all of them), like `git diff [--staged] [-- <file>]`.
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    diff_options: Rc<RefCell<DiffOptions>>,
+    file: Option<PathBuf>,
+    staged: bool,
+) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let mut opts = diff_options.borrow().clone();
+            if let Some(file) = &file {
+                opts = opts.pathspec(file.clone());
+            }
+
+            let diff = if staged {
+                git::diff_staged(&config, &repo, &opts)?
+            } else {
+                git::diff_unstaged(&config, &repo, &opts)?
+            };
+
+            let header = format!(
+                "{} changes{}{}",
+                if staged { "Staged" } else { "Unstaged" },
+                file.as_ref()
+                    .map(|file| format!(" to {}", file.display()))
+                    .unwrap_or_default(),
+                opts.shown_algorithm()
+                    .map(|algorithm| format!(" ({} diff)", algorithm))
+                    .unwrap_or_default()
+            );
+
+            Ok(iter::once(Item {
+                id: match &file {
+                    Some(file) => format!("file_diff_{}", file.display()).into(),
+                    None => "file_diff".into(),
+                },
+                display: Line::styled(header, &style.section_header),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(diff.deltas.is_empty().then(|| Item {
+                display: Line::raw("No changes"),
+                depth: 1,
+                unselectable: true,
+                ..Default::default()
+            }))
+            .chain([items::blank_line()])
+            .chain(items::create_diff_items(
+                Rc::clone(&config),
+                &diff,
+                &0,
+                false,
+            ))
+            .collect::<Vec<_>>())
+        }),
+    )
+}
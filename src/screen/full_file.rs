@@ -0,0 +1,47 @@
+use std::{iter, rc::Rc};
+
+use crate::{
+    config::Config,
+    git::diff::Delta,
+    items::{self, Item},
+    Res,
+};
+use git2::Repository;
+use ratatui::{layout::Size, text::Line};
+
+use super::Screen;
+
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    delta: Delta,
+) -> Res<Screen> {
+    let content = delta.read_new_version(&repo)?;
+
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+
+            Ok(iter::once(Item {
+                id: "full_file_header".into(),
+                display: Line::styled(
+                    format!("Full version of {}", delta.new_file.to_string_lossy()),
+                    &style.section_header,
+                ),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(items::create_full_file_items(
+                Rc::clone(&config),
+                &delta,
+                &content,
+            ))
+            .collect())
+        }),
+    )
+    .map(Screen::read_only)
+}
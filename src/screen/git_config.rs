@@ -0,0 +1,101 @@
+use std::rc::Rc;
+
+use super::Screen;
+use crate::{
+    config::Config,
+    git::{self, config_list::ConfigEntry},
+    items::{Item, TargetData},
+    Res,
+};
+use git2::Repository;
+use ratatui::{
+    layout::Size,
+    text::{Line, Span},
+};
+
+/// The scopes git reads settings from, in order, and whether they're shown even when empty.
+const SCOPES: &[(&str, &str, bool)] = &[
+    ("system", "System", true),
+    ("global", "Global", true),
+    ("local", "Local", true),
+    ("worktree", "Worktree", false),
+    ("command", "Command line", false),
+];
+
+pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let entries = git::config_list(&repo)?;
+
+            Ok(SCOPES
+                .iter()
+                .flat_map(|&(scope, name, always_shown)| {
+                    let in_scope = entries
+                        .iter()
+                        .filter(|entry| entry.scope == scope)
+                        .collect::<Vec<_>>();
+
+                    if in_scope.is_empty() && !always_shown {
+                        return vec![];
+                    }
+
+                    create_scope_items(&config, scope, name, &in_scope)
+                })
+                .collect())
+        }),
+    )
+}
+
+/// A section for the scope, with each file that sets something in it, and what it sets.
+fn create_scope_items(
+    config: &Config,
+    scope: &str,
+    name: &str,
+    entries: &[&ConfigEntry],
+) -> Vec<Item> {
+    let style = &config.style;
+    let mut items = vec![Item {
+        id: format!("config_{}", scope).into(),
+        display: Line::styled(name.to_string(), &style.section_header),
+        section: true,
+        depth: 0,
+        target_data: Some(TargetData::ConfigScope(scope.to_string())),
+        ..Default::default()
+    }];
+
+    let mut origin = None;
+    for (i, entry) in entries.iter().enumerate() {
+        if origin != Some(&entry.origin) {
+            origin = Some(&entry.origin);
+            items.push(Item {
+                id: format!("config_{}_{}", scope, entry.origin).into(),
+                display: Line::styled(entry.file().to_string(), &style.file_header),
+                section: true,
+                depth: 1,
+                target_data: Some(TargetData::ConfigScope(scope.to_string())),
+                ..Default::default()
+            });
+        }
+
+        let mut spans = vec![
+            Span::raw(entry.key.clone()),
+            Span::raw(" = "),
+            Span::raw(entry.value.replace('\n', "\\n")),
+        ];
+        if entry.read_only_reason().is_some() {
+            spans.push(Span::raw(" (read-only)"));
+        }
+
+        items.push(Item {
+            id: format!("config_{}_{}_{}_{}", scope, entry.origin, i, entry.key).into(),
+            display: Line::from(spans),
+            depth: 2,
+            target_data: Some(TargetData::ConfigEntry((*entry).clone())),
+            ..Default::default()
+        });
+    }
+
+    items
+}
@@ -0,0 +1,60 @@
+use super::Screen;
+use crate::{config::Config, items::Item, menu::Menu, Res};
+use ratatui::{
+    layout::Size,
+    text::{Line, Span},
+};
+use std::{iter, rc::Rc};
+use unicode_width::UnicodeWidthStr;
+
+/// The keys bound to one op, like `k/p/<up>`, and what it does.
+pub(crate) struct KeybindingEntry {
+    pub(crate) keys: String,
+    pub(crate) description: String,
+}
+
+/// Every keybinding in the config, a section per menu they're in, to look through and search.
+pub(crate) fn create(
+    config: Rc<Config>,
+    size: Size,
+    menus: Vec<(Menu, Vec<KeybindingEntry>)>,
+) -> Res<Screen> {
+    let keys_width = menus
+        .iter()
+        .flat_map(|(_, entries)| entries)
+        .map(|entry| entry.keys.width())
+        .max()
+        .unwrap_or(0);
+
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+
+            Ok(menus
+                .iter()
+                .flat_map(|(menu, entries)| {
+                    let header = Item {
+                        id: format!("keybindings_{menu:?}").into(),
+                        display: Line::styled(menu.to_string(), &style.section_header),
+                        section: true,
+                        depth: 0,
+                        ..Default::default()
+                    };
+
+                    iter::once(header).chain(entries.iter().map(move |entry| Item {
+                        id: format!("keybindings_{menu:?}_{}", entry.keys).into(),
+                        display: Line::from(vec![
+                            Span::styled(format!("{:keys_width$}", entry.keys), &style.hotkey),
+                            Span::raw(format!(" {}", entry.description)),
+                        ]),
+                        depth: 1,
+                        ..Default::default()
+                    }))
+                })
+                .collect())
+        }),
+    )
+    .map(Screen::read_only)
+}
@@ -1,9 +1,12 @@
 use super::Screen;
-use crate::{config::Config, items::log, Res};
+use crate::{
+    config::Config,
+    items::{log, Item, LogFilter},
+    Res,
+};
 use git2::{Oid, Repository};
-use ratatui::layout::Size;
-use regex::Regex;
-use std::rc::Rc;
+use ratatui::{layout::Size, text::Line};
+use std::{cell::RefCell, rc::Rc};
 
 pub(crate) fn create(
     config: Rc<Config>,
@@ -11,11 +14,27 @@ pub(crate) fn create(
     size: Size,
     limit: usize,
     rev: Option<Oid>,
-    msg_regex: Option<Regex>,
+    filter: Rc<RefCell<LogFilter>>,
 ) -> Res<Screen> {
     Screen::new(
         Rc::clone(&config),
         size,
-        Box::new(move || log(&config, &repo, limit, rev, msg_regex.clone())),
+        Box::new(move || {
+            let filter = filter.borrow();
+            let header = (!filter.is_empty()).then(|| Item {
+                id: "log_filter".into(),
+                display: Line::styled(
+                    format!("Filtered by {}", filter.describe()),
+                    &config.style.section_header,
+                ),
+                unselectable: true,
+                ..Default::default()
+            });
+
+            Ok(header
+                .into_iter()
+                .chain(log(&config, &repo, limit, rev, &filter)?)
+                .collect())
+        }),
     )
 }
@@ -1,16 +1,40 @@
 use ratatui::prelude::*;
 
-use crate::{config::Config, items::TargetData, Res};
+use crate::{
+    config::Config,
+    git::diff::{Delta, Hunk},
+    items::{self, TargetData},
+    timing, Res,
+};
 
 use super::Item;
-use std::{borrow::Cow, collections::HashSet, rc::Rc};
-
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use unicode_width::UnicodeWidthChar;
+
+pub(crate) mod conflicts;
+pub(crate) mod diff_refs;
+pub(crate) mod file_diff;
+pub(crate) mod full_file;
+pub(crate) mod git_config;
+pub(crate) mod keybindings;
 pub(crate) mod log;
+pub(crate) mod output;
+pub(crate) mod preview_patch;
+pub(crate) mod range_diff;
 pub(crate) mod show;
+pub(crate) mod show_index;
 pub(crate) mod show_refs;
 pub(crate) mod status;
+pub(crate) mod worktrees;
 
 const BOTTOM_CONTEXT_LINES: usize = 2;
+const H_SCROLL_STEP: usize = 8;
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) enum NavMode {
@@ -19,15 +43,87 @@ pub(crate) enum NavMode {
     IncludeHunkLines,
 }
 
+/// Identifies the selected item across refreshes, during which hunk headers and ids may change.
+struct Selection {
+    /// The id of the enclosing top-level section, e.g. "unstaged_changes".
+    section: Option<Cow<'static, str>>,
+    key: SelectionKey,
+}
+
+enum SelectionKey {
+    Hunk(Rc<Hunk>),
+    HunkLine(Rc<Hunk>, usize),
+    Delta(PathBuf),
+    Id(Cow<'static, str>),
+}
+
+impl Selection {
+    fn of(items: &[Item], item_i: usize) -> Option<Self> {
+        let item = &items[item_i];
+        let key = match &item.target_data {
+            Some(TargetData::Hunk(hunk)) => SelectionKey::Hunk(Rc::clone(hunk)),
+            Some(TargetData::HunkLine(hunk, line_i)) => {
+                SelectionKey::HunkLine(Rc::clone(hunk), *line_i)
+            }
+            Some(TargetData::Delta(delta)) => SelectionKey::Delta(delta.new_file.clone()),
+            _ if !item.id.is_empty() => SelectionKey::Id(item.id.clone()),
+            _ => return None,
+        };
+
+        Some(Self {
+            section: items[..=item_i]
+                .iter()
+                .rev()
+                .find(|item| item.section && item.depth == 0)
+                .map(|item| item.id.clone()),
+            key,
+        })
+    }
+
+    fn matches(&self, item: &Item) -> bool {
+        match (&self.key, &item.target_data) {
+            (SelectionKey::Hunk(hunk), Some(TargetData::Hunk(other))) => hunk.content_eq(other),
+            (SelectionKey::HunkLine(hunk, line_i), Some(TargetData::HunkLine(other, other_i))) => {
+                line_i == other_i && hunk.content_eq(other)
+            }
+            (SelectionKey::Delta(path), Some(TargetData::Delta(other))) => path == &other.new_file,
+            (SelectionKey::Id(id), _) => id == &item.id,
+            _ => false,
+        }
+    }
+
+    /// The index of the item this selection is of, if it's still there.
+    fn find(&self, items: &[Item]) -> Option<usize> {
+        let mut section = None;
+
+        items.iter().position(|item| {
+            if item.section && item.depth == 0 {
+                section = Some(&item.id);
+            }
+
+            section == self.section.as_ref() && self.matches(item)
+        })
+    }
+}
+
 pub(crate) struct Screen {
     pub(crate) size: Size,
     cursor: usize,
     scroll: usize,
+    /// Columns that hunk lines are scrolled to the right by, when they don't fit.
+    h_scroll: usize,
     config: Rc<Config>,
     refresh_items: Box<dyn Fn() -> Res<Vec<Item>>>,
     items: Vec<Item>,
     line_index: Vec<usize>,
     collapsed: HashSet<Cow<'static, str>>,
+    raw_patch: bool,
+    wrap: bool,
+    read_only: bool,
+    /// Where a selection of lines in a hunk was started, its other end being the cursor.
+    line_selection: Option<(Rc<Hunk>, usize)>,
+    /// Whether the next update moves on from the selected item, once it's gone.
+    advance: bool,
 }
 
 impl Screen {
@@ -47,12 +143,18 @@ impl Screen {
         let mut screen = Self {
             cursor: 0,
             scroll: 0,
+            h_scroll: 0,
             size,
-            config,
+            config: Rc::clone(&config),
             refresh_items,
             items: vec![],
             line_index: vec![],
             collapsed,
+            raw_patch: false,
+            wrap: config.diff.wrap.enabled,
+            read_only: false,
+            line_selection: None,
+            advance: false,
         };
 
         screen.update()?;
@@ -91,17 +193,75 @@ impl Screen {
     }
 
     pub(crate) fn select_next(&mut self, nav_mode: NavMode) {
-        self.cursor = self.find_next(nav_mode);
+        let previous = self.cursor;
+        self.cursor = self.find_next(self.line_selection_nav_mode(nav_mode));
+        self.keep_in_line_selection_hunk(previous);
         self.scroll_fit_end();
         self.scroll_fit_start();
     }
 
+    /// Starts selecting lines from the hunk line at the cursor, or stops selecting.
+    pub(crate) fn toggle_line_selection(&mut self) -> Res<()> {
+        if self.line_selection.take().is_some() {
+            return Ok(());
+        }
+
+        match &self.get_selected_item().target_data {
+            Some(TargetData::HunkLine(hunk, i)) => {
+                self.line_selection = Some((Rc::clone(hunk), *i));
+                Ok(())
+            }
+            _ => Err("Move to a line of a hunk to select lines from".into()),
+        }
+    }
+
+    /// Stops selecting lines, returning whether there was a selection.
+    pub(crate) fn clear_line_selection(&mut self) -> bool {
+        self.line_selection.take().is_some()
+    }
+
+    /// The lines from where the selection was started to the cursor, which doesn't leave the hunk.
+    pub(crate) fn selected_lines(&self) -> Option<(Rc<Hunk>, Range<usize>)> {
+        let (hunk, start) = self.line_selection.as_ref()?;
+        match &self.get_selected_item().target_data {
+            Some(TargetData::HunkLine(other, i)) if Rc::ptr_eq(hunk, other) => {
+                Some((Rc::clone(hunk), *start.min(i)..(*start.max(i) + 1)))
+            }
+            _ => None,
+        }
+    }
+
+    /// While selecting lines, the cursor moves line by line.
+    fn line_selection_nav_mode(&self, nav_mode: NavMode) -> NavMode {
+        match self.line_selection {
+            Some(_) => NavMode::IncludeHunkLines,
+            None => nav_mode,
+        }
+    }
+
+    /// Moves the cursor back to `previous` if it left the hunk that lines are selected in,
+    /// as a selection spanning hunks would need a patch for each.
+    fn keep_in_line_selection_hunk(&mut self, previous: usize) {
+        let Some((hunk, _)) = &self.line_selection else {
+            return;
+        };
+
+        let in_hunk = matches!(
+            &self.get_selected_item().target_data,
+            Some(TargetData::HunkLine(other, _)) if Rc::ptr_eq(hunk, other)
+        );
+        if !in_hunk {
+            self.cursor = previous;
+        }
+    }
+
     fn scroll_fit_start(&mut self) {
         if self.items.is_empty() {
             return;
         }
 
-        let top = self.cursor.saturating_sub(self.get_selected_item().depth);
+        let above = self.get_selected_item().depth.max(self.scrolloff());
+        let top = self.cursor.saturating_sub(above);
         if top < self.scroll {
             self.scroll = top;
         }
@@ -114,11 +274,17 @@ impl Screen {
 
         let depth = self.get_selected_item().depth;
 
-        let last = BOTTOM_CONTEXT_LINES
-            + (self.cursor..self.line_index.len())
-                .take_while(|&line_i| line_i == self.cursor || depth < self.at_line(line_i).depth)
-                .last()
-                .unwrap();
+        let item_end = (self.cursor..self.line_index.len())
+            .take_while(|&line_i| line_i == self.cursor || depth < self.at_line(line_i).depth)
+            .last()
+            .unwrap();
+
+        // The margin can't go past the last line, context lines are shown regardless
+        let below = self
+            .scrolloff()
+            .min(self.line_index.len() - 1 - item_end)
+            .max(BOTTOM_CONTEXT_LINES);
+        let last = item_end + below;
 
         let end_line = self.size.height.saturating_sub(1) as usize;
         if last > end_line + self.scroll {
@@ -126,6 +292,12 @@ impl Screen {
         }
     }
 
+    /// The `ui.scrolloff` lines to keep around the selection, leaving room for it in the middle.
+    fn scrolloff(&self) -> usize {
+        let height = self.size.height as usize;
+        self.config.ui.scrolloff.min(height.saturating_sub(1) / 2)
+    }
+
     pub(crate) fn find_next(&mut self, nav_mode: NavMode) -> usize {
         (self.cursor..self.line_index.len())
             .skip(1)
@@ -151,7 +323,9 @@ impl Screen {
     }
 
     pub(crate) fn select_previous(&mut self, nav_mode: NavMode) {
-        self.cursor = self.find_previous(nav_mode);
+        let previous = self.cursor;
+        self.cursor = self.find_previous(self.line_selection_nav_mode(nav_mode));
+        self.keep_in_line_selection_hunk(previous);
         self.scroll_fit_start();
     }
 
@@ -178,7 +352,7 @@ impl Screen {
                 .copied()
                 .enumerate()
                 .map(|(line, _)| (line + 1).saturating_sub(half_screen))
-                .last()
+                .next_back()
                 .unwrap_or(0),
         );
 
@@ -186,6 +360,33 @@ impl Screen {
         self.update_cursor(nav_mode);
     }
 
+    pub(crate) fn scroll_left(&mut self) {
+        self.h_scroll = self.h_scroll.saturating_sub(H_SCROLL_STEP);
+    }
+
+    pub(crate) fn scroll_right(&mut self) {
+        self.h_scroll = (self.h_scroll + H_SCROLL_STEP).min(self.max_h_scroll());
+    }
+
+    pub(crate) fn scroll_line_start(&mut self) {
+        self.h_scroll = 0;
+    }
+
+    pub(crate) fn scroll_line_end(&mut self) {
+        self.h_scroll = self.max_h_scroll();
+    }
+
+    /// How far the longest hunk line in view can scroll, until its end is visible.
+    fn max_h_scroll(&self) -> usize {
+        let width = (self.size.width as usize).saturating_sub(1);
+
+        self.line_views(self.size)
+            .filter(|line| is_hunk_line(line.item))
+            .map(|line| line.display.width().saturating_sub(width))
+            .max()
+            .unwrap_or(0)
+    }
+
     pub(crate) fn toggle_section(&mut self) {
         let selected = &self.items[self.line_index[self.cursor]];
 
@@ -200,14 +401,322 @@ impl Screen {
         self.update_line_index();
     }
 
+    /// Selects line `line` of the new version of `file` (or of any file), expanding what it's folded into.
+    /// When the line is outside of every hunk, the nearest changed line is selected instead.
+    /// Returns the new file line number that got selected, if the diff has any lines.
+    pub(crate) fn select_new_line(&mut self, file: Option<&Path>, line: u32) -> Option<u32> {
+        let mut nearest: Option<(u32, &Rc<Hunk>, usize, u32)> = None;
+
+        let hunks = self
+            .items
+            .iter()
+            .filter_map(|item| match &item.target_data {
+                Some(TargetData::Hunk(hunk)) if file.is_none_or(|file| file == hunk.new_file) => {
+                    Some(hunk)
+                }
+                _ => None,
+            });
+
+        'hunks: for hunk in hunks {
+            // Removed lines are placed at the new line they'd come before
+            let mut next_new = hunk.new_start;
+
+            for (line_i, (_old, new)) in hunk.line_numbers().into_iter().enumerate() {
+                let position = new.unwrap_or(next_new);
+                if let Some(new) = new {
+                    next_new = new + 1;
+                }
+
+                if new == Some(line) {
+                    nearest = Some((0, hunk, line_i, line));
+                    break 'hunks;
+                }
+
+                let distance = position.abs_diff(line);
+                if hunk.is_change(line_i) && nearest.is_none_or(|(d, ..)| distance < d) {
+                    nearest = Some((distance, hunk, line_i, position));
+                }
+            }
+        }
+
+        let (_, hunk, line_i, selected) = nearest?;
+        let hunk = Rc::clone(hunk);
+        self.select_hunk(&hunk, Some(line_i)).then_some(selected)
+    }
+
+    /// Selects `hunk`, or line `line_i` of it, expanding what it's folded into.
+    /// Returns whether the hunk is on this screen.
+    pub(crate) fn select_hunk(&mut self, hunk: &Rc<Hunk>, line_i: Option<usize>) -> bool {
+        let Some(item_i) = self.items.iter().position(|item| match &item.target_data {
+            Some(TargetData::Hunk(other)) => line_i.is_none() && Rc::ptr_eq(hunk, other),
+            Some(TargetData::HunkLine(other, other_i)) => {
+                line_i == Some(*other_i) && Rc::ptr_eq(hunk, other)
+            }
+            _ => false,
+        }) else {
+            return false;
+        };
+
+        let mut depth = self.items[item_i].depth;
+        for item in self.items[..item_i].iter().rev() {
+            if item.depth < depth {
+                depth = item.depth;
+                self.collapsed.remove(&item.id);
+            }
+        }
+
+        self.update_line_index();
+        let Some(line) = self.line_index.iter().position(|&i| i == item_i) else {
+            return false;
+        };
+
+        self.cursor = line;
+        self.scroll_fit_end();
+        self.scroll_fit_start();
+        true
+    }
+
+    /// The index of the stash this is the view of, from the target of its header.
+    pub(crate) fn shown_stash(&self) -> Option<usize> {
+        self.items.iter().find_map(|item| match &item.target_data {
+            Some(TargetData::Stash { id, .. }) if item.section && item.depth == 0 => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// The file diff that `hunk` is part of.
+    pub(crate) fn delta_of(&self, hunk: &Rc<Hunk>) -> Option<&Delta> {
+        self.items.iter().find_map(|item| match &item.target_data {
+            Some(TargetData::Delta(delta))
+                if delta.hunks.iter().any(|other| Rc::ptr_eq(hunk, other)) =>
+            {
+                Some(delta)
+            }
+            _ => None,
+        })
+    }
+
+    /// Selects the next line that shows `query`, or the previous one going `backwards`, wrapping around
+    /// at the ends. Like Vim's `smartcase`, case only matters when the query has uppercase letters.
+    /// Returns whether any line matched.
+    pub(crate) fn search(&mut self, query: &str, backwards: bool) -> bool {
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        let query = match ignore_case {
+            true => query.to_lowercase(),
+            false => query.to_string(),
+        };
+
+        let len = self.line_index.len();
+        let matched = (1..=len)
+            .map(|offset| match backwards {
+                true => (self.cursor + len - offset) % len,
+                false => (self.cursor + offset) % len,
+            })
+            .filter(|&line_i| {
+                let text = self.items[self.line_index[line_i]].display.to_string();
+                match ignore_case {
+                    true => text.to_lowercase().contains(&query),
+                    false => text.contains(&query),
+                }
+            })
+            // Wrapped lines can't be selected themselves, but the line they continue can
+            .filter_map(|line_i| {
+                (0..=line_i)
+                    .rev()
+                    .find(|&line_i| !self.items[self.line_index[line_i]].unselectable)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(&first) = matched.first() else {
+            return false;
+        };
+
+        self.cursor = matched
+            .into_iter()
+            .find(|&line_i| line_i != self.cursor)
+            .unwrap_or(first);
+        self.scroll_to_cursor();
+        true
+    }
+
+    /// Disables staging and discarding, for diffs that aren't of the working tree or index.
+    pub(crate) fn read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// How many lines are shown, expanded sections included.
+    pub(crate) fn line_count(&self) -> usize {
+        self.line_index.len()
+    }
+
+    pub(crate) fn is_raw_patch(&self) -> bool {
+        self.raw_patch
+    }
+
+    pub(crate) fn toggle_raw_patch(&mut self) -> Res<()> {
+        self.raw_patch = !self.raw_patch;
+        self.update()
+    }
+
+    pub(crate) fn is_wrapped(&self) -> bool {
+        self.wrap
+    }
+
+    pub(crate) fn toggle_wrap(&mut self) -> Res<()> {
+        self.wrap = !self.wrap;
+        self.update()
+    }
+
+    /// Lines are drawn one column in, leaving room for the cursor.
+    fn wrap_width(&self) -> usize {
+        (self.size.width as usize).saturating_sub(1)
+    }
+
+    pub(crate) fn resize(&mut self, size: Size) -> Res<()> {
+        let wraps = self.wrap || self.items.iter().any(|item| item.word_wrap);
+        let rewrap = wraps && size.width != self.size.width;
+        self.size = size;
+
+        if rewrap {
+            self.update()?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn expand_all(&mut self) {
+        let ids = self.section_ids();
+        self.collapsed.retain(|id| !ids.contains(id));
+        self.update_line_index_keeping_selection();
+    }
+
+    pub(crate) fn collapse_all(&mut self) {
+        let ids = self.section_ids();
+        self.collapsed.extend(ids);
+        self.update_line_index_keeping_selection();
+    }
+
+    fn section_ids(&self) -> HashSet<Cow<'static, str>> {
+        self.items
+            .iter()
+            .filter(|item| item.section)
+            .map(|item| item.id.clone())
+            .collect()
+    }
+
+    /// Moves the cursor to the selected item, or to the section it got folded into.
+    fn update_line_index_keeping_selection(&mut self) {
+        let selected = self.line_index.get(self.cursor).copied();
+        self.update_line_index();
+
+        if let Some(selected) = selected {
+            self.cursor = self
+                .line_index
+                .iter()
+                .rposition(|&item_i| item_i <= selected)
+                .unwrap_or(0);
+        }
+
+        self.scroll_fit_start();
+    }
+
+    /// Has the next update select what came after the selected item if that's gone, like after staging it.
+    pub(crate) fn advance_on_next_update(&mut self) {
+        self.advance = true;
+    }
+
+    /// The next hunk or file shown after the selected item, in the same section. From a hunk,
+    /// the first hunk of an expanded file is picked over its header.
+    fn next_to_advance_to(&self) -> Option<Selection> {
+        let &selected_i = self.line_index.get(self.cursor)?;
+        let selected = &self.items[selected_i];
+        let from_hunk = matches!(selected.target_data, Some(TargetData::Hunk(_)));
+
+        let mut candidates = self.line_index[self.cursor + 1..]
+            .iter()
+            .map(|&item_i| (item_i, &self.items[item_i]))
+            .skip_while(|(_, item)| item.depth > selected.depth)
+            .take_while(|(_, item)| !(item.section && item.depth == 0))
+            .filter(|(_, item)| {
+                matches!(
+                    item.target_data,
+                    Some(TargetData::Hunk(_) | TargetData::Delta(_) | TargetData::File(_))
+                )
+            });
+
+        let (mut next_i, next) = candidates.next()?;
+        if from_hunk && matches!(next.target_data, Some(TargetData::Delta(_))) {
+            if let Some((hunk_i, _)) = candidates.next().filter(|(_, item)| {
+                item.depth > next.depth && matches!(item.target_data, Some(TargetData::Hunk(_)))
+            }) {
+                next_i = hunk_i;
+            }
+        }
+
+        Selection::of(&self.items, next_i)
+    }
+
     pub(crate) fn update(&mut self) -> Res<()> {
+        // The hunks are new after a refresh, and the lines to select likely changed
+        self.line_selection = None;
         let nav_mode = self.selected_item_nav_mode();
-        self.items = (self.refresh_items)()?;
+        let selection = self
+            .line_index
+            .get(self.cursor)
+            .and_then(|&item_i| Selection::of(&self.items, item_i));
+        let advance_to = std::mem::take(&mut self.advance)
+            .then(|| self.next_to_advance_to())
+            .flatten();
+
+        self.items = timing::measure(
+            "Refreshing items",
+            &self.refresh_items,
+            |items| match items {
+                Ok(items) => format!("{} items", items.len()),
+                Err(_) => "failed".into(),
+            },
+        )?;
+        if self.raw_patch {
+            self.items = items::raw_patch(std::mem::take(&mut self.items));
+        }
+        if self.wrap {
+            self.items = items::wrap_hunk_lines(std::mem::take(&mut self.items), self.wrap_width());
+        }
+        self.items = items::wrap_words(std::mem::take(&mut self.items), self.wrap_width());
         self.update_line_index();
-        self.update_cursor(nav_mode);
+
+        // An item that's still there hasn't been staged after all
+        let reselected = selection
+            .and_then(|selection| selection.find(&self.items))
+            .or_else(|| advance_to.and_then(|selection| selection.find(&self.items)))
+            .and_then(|item_i| self.line_index.iter().position(|&i| i == item_i));
+
+        match reselected {
+            Some(line_i) => {
+                self.cursor = line_i;
+                self.scroll_to_cursor();
+            }
+            // When the selected item is gone, whatever took its place is selected.
+            None => self.update_cursor(nav_mode),
+        }
+
         Ok(())
     }
 
+    fn scroll_to_cursor(&mut self) {
+        let height = self.size.height as usize;
+        if self.cursor < self.scroll || self.scroll + height <= self.cursor {
+            self.scroll = self.cursor.saturating_sub(height / 2);
+        }
+    }
+
     fn update_cursor(&mut self, nav_mode: NavMode) {
         self.clamp_cursor();
         if self.is_cursor_off_screen() {
@@ -284,7 +793,7 @@ impl Screen {
         &self.items[self.line_index[self.cursor]]
     }
 
-    fn line_views(&self, area: Size) -> impl Iterator<Item = LineView> {
+    fn line_views(&self, area: Size) -> impl Iterator<Item = LineView<'_>> {
         let scan_start = self.scroll.min(self.cursor);
         let scan_end = (self.scroll + area.height as usize).min(self.line_index.len());
         let scan_highlight_range = scan_start..(scan_end);
@@ -311,6 +820,34 @@ impl Screen {
     }
 }
 
+fn is_hunk_line(item: &Item) -> bool {
+    matches!(item.target_data, Some(TargetData::HunkLine(..)))
+}
+
+/// Drops `offset` columns from the line, keeping its first span (the `+`/`-`) in place.
+fn scrolled_hunk_line<'a>(line: &Line<'a>, offset: usize) -> Line<'a> {
+    let mut spans = line.spans.iter();
+    let mut scrolled = spans.next().into_iter().cloned().collect::<Vec<_>>();
+    let mut skipped = 0;
+
+    for span in spans {
+        let mut visible = String::new();
+        for c in span.content.chars() {
+            if skipped < offset {
+                skipped += c.width().unwrap_or(0);
+            } else {
+                visible.push(c);
+            }
+        }
+
+        if !visible.is_empty() {
+            scrolled.push(Span::styled(visible, span.style));
+        }
+    }
+
+    Line::from(scrolled)
+}
+
 struct LineView<'a> {
     item_index: usize,
     item: &'a Item,
@@ -321,6 +858,8 @@ struct LineView<'a> {
 impl Widget for &Screen {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let style = &self.config.style;
+        let h_scroll = self.h_scroll.min(self.max_h_scroll());
+        let selected_lines = self.selected_lines();
 
         for (line_index, line) in self.line_views(area.as_size()).enumerate() {
             let line_area = Rect {
@@ -344,12 +883,27 @@ impl Widget for &Screen {
                 }
             }
 
-            line.display.render(indented_line_area, buf);
-            let overflow = line.display.width() > line_area.width as usize;
+            if let (Some((hunk, lines)), Some(TargetData::HunkLine(other, i))) =
+                (&selected_lines, &line.item.target_data)
+            {
+                if Rc::ptr_eq(hunk, other) && lines.contains(i) {
+                    buf.set_style(line_area, &style.line_selection);
+                }
+            }
+
+            let scrolled;
+            let display = if h_scroll > 0 && is_hunk_line(line.item) {
+                scrolled = scrolled_hunk_line(line.display, h_scroll);
+                &scrolled
+            } else {
+                line.display
+            };
+
+            display.render(indented_line_area, buf);
+            let overflow = display.width() > line_area.width as usize;
 
-            if self.is_collapsed(line.item) && line.display.width() > 0 || overflow {
-                let line_end =
-                    (indented_line_area.x + line.display.width() as u16).min(area.width - 1);
+            if self.is_collapsed(line.item) && display.width() > 0 || overflow {
+                let line_end = (indented_line_area.x + display.width() as u16).min(area.width - 1);
                 buf[(line_end, line_index as u16)].set_char('…');
             }
 
@@ -361,3 +915,75 @@ impl Widget for &Screen {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Selection;
+    use crate::{config, git::diff, items, Item};
+    use std::rc::Rc;
+
+    const BEFORE: &str = "diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+-one
++ONE
+ two
+@@ -10,2 +10,2 @@
+ ten
+-eleven
++ELEVEN
+";
+
+    const AFTER: &str = "diff --git a/file b/file
+index 3333333..2222222 100644
+--- a/file
++++ b/file
+@@ -10,2 +10,2 @@
+ ten
+-eleven
++ELEVEN
+";
+
+    fn items(patch: &str) -> Vec<Item> {
+        let config = Rc::new(config::init_test_config().unwrap());
+        let diff = diff::parse(patch).unwrap();
+
+        std::iter::once(Item {
+            id: "unstaged_changes".into(),
+            section: true,
+            ..Default::default()
+        })
+        .chain(items::create_diff_items(config, &diff, &1, false))
+        .collect()
+    }
+
+    #[test]
+    fn reselect_moved_hunk() {
+        let before = items(BEFORE);
+        let after = items(AFTER);
+
+        // The section, the delta, the first hunk and its 3 lines, then the second hunk
+        let selection = Selection::of(&before, 6).unwrap();
+        assert_eq!(selection.find(&after), Some(2));
+
+        let line_selection = Selection::of(&before, 9).unwrap();
+        assert_eq!(line_selection.find(&after), Some(5));
+    }
+
+    #[test]
+    fn removed_hunk_is_not_found() {
+        let selection = Selection::of(&items(BEFORE), 2).unwrap();
+        assert_eq!(selection.find(&items(AFTER)), None);
+    }
+
+    #[test]
+    fn reselect_only_within_section() {
+        let mut after = items(AFTER);
+        after[0].id = "staged_changes".into();
+
+        let selection = Selection::of(&items(BEFORE), 6).unwrap();
+        assert_eq!(selection.find(&after), None);
+    }
+}
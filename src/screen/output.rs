@@ -0,0 +1,26 @@
+use super::Screen;
+use crate::{config::Config, items::Item, Res};
+use ratatui::{layout::Size, text::Line};
+use std::rc::Rc;
+
+/// A pager for the output of commands, to scroll, search and wrap in place of an external one.
+pub(crate) fn create(config: Rc<Config>, size: Size, lines: Vec<Line<'static>>) -> Res<Screen> {
+    Screen::new(
+        config,
+        size,
+        Box::new(move || {
+            Ok(lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| Item {
+                    id: format!("output_line_{}", i).into(),
+                    display: line.clone(),
+                    depth: 0,
+                    soft_wrap: true,
+                    ..Default::default()
+                })
+                .collect())
+        }),
+    )
+    .map(Screen::read_only)
+}
@@ -0,0 +1,53 @@
+use super::Screen;
+use crate::{
+    config::{Config, StyleConfig},
+    git::diff,
+    items::Item,
+    Res,
+};
+use ratatui::{layout::Size, style::Style, text::Line};
+use std::rc::Rc;
+
+/// A read-only view of a patch, exactly as it would be sent to `git apply`.
+pub(crate) fn create(config: Rc<Config>, size: Size, patch: String) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let mut in_header = true;
+
+            Ok(patch
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    in_header &= !line.starts_with("@@");
+
+                    Item {
+                        id: format!("patch_line_{}", i).into(),
+                        display: Line::styled(
+                            diff::display_escaped(line),
+                            line_style(style, line, in_header),
+                        ),
+                        depth: 0,
+                        ..Default::default()
+                    }
+                })
+                .collect())
+        }),
+    )
+}
+
+fn line_style(style: &StyleConfig, line: &str, in_header: bool) -> Style {
+    if in_header {
+        (&style.file_header).into()
+    } else if line.starts_with("@@") {
+        (&style.hunk_header).into()
+    } else if line.starts_with('+') {
+        (&style.diff_highlight.tag_new).into()
+    } else if line.starts_with('-') {
+        (&style.diff_highlight.tag_old).into()
+    } else {
+        Style::new()
+    }
+}
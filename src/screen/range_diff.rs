@@ -0,0 +1,105 @@
+use super::Screen;
+use crate::{
+    config::{Config, StyleConfig},
+    git::diff::{CommitPair, PairStatus, RangeDiff},
+    items::{Item, TargetData},
+    Res,
+};
+use ratatui::{
+    layout::Size,
+    style::Style,
+    text::{Line, Span},
+};
+use std::{iter, rc::Rc};
+
+pub(crate) fn create(config: Rc<Config>, size: Size, range_diff: RangeDiff) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+
+            if range_diff.pairs.is_empty() {
+                return Ok(vec![Item {
+                    id: "no_commits".into(),
+                    display: Line::styled("No commits to compare", &style.section_header),
+                    unselectable: true,
+                    ..Default::default()
+                }]);
+            }
+
+            Ok(range_diff
+                .pairs
+                .iter()
+                .enumerate()
+                .flat_map(|(i, pair)| pair_items(style, i, pair))
+                .collect())
+        }),
+    )
+}
+
+fn pair_items<'a>(
+    style: &'a StyleConfig,
+    i: usize,
+    pair: &'a CommitPair,
+) -> impl Iterator<Item = Item> + 'a {
+    let status = match pair.status {
+        PairStatus::Unchanged => "=",
+        PairStatus::Changed => "!",
+        PairStatus::Removed => "<",
+        PairStatus::Added => ">",
+    };
+
+    let commit = |commit: &Option<(u32, String)>| match commit {
+        Some((position, hash)) => Line::from(vec![
+            Span::raw(format!("{}: ", position)),
+            Span::styled(hash.clone(), &style.hash),
+        ]),
+        None => Line::raw("-:"),
+    };
+
+    let mut display = commit(&pair.old);
+    display.push_span(format!(" {} ", status));
+    display.extend(commit(&pair.new));
+    display.push_span(format!(" {}", pair.subject));
+
+    let hash = pair
+        .new
+        .as_ref()
+        .or(pair.old.as_ref())
+        .map(|(_, hash)| hash);
+
+    iter::once(Item {
+        id: format!("range_diff_pair_{}", i).into(),
+        display,
+        section: !pair.hunks.is_empty(),
+        depth: 0,
+        target_data: hash.map(|hash| TargetData::Commit(hash.clone())),
+        ..Default::default()
+    })
+    .chain(pair.hunks.iter().enumerate().flat_map(move |(j, hunk)| {
+        iter::once(Item {
+            id: format!("range_diff_pair_{}_hunk_{}", i, j).into(),
+            display: Line::styled(format!("@@ {}", hunk.header), &style.hunk_header),
+            section: true,
+            depth: 1,
+            ..Default::default()
+        })
+        .chain(hunk.lines.iter().map(move |line| Item {
+            display: Line::styled(line.replace('\t', "    "), line_style(style, line)),
+            depth: 2,
+            unselectable: true,
+            ..Default::default()
+        }))
+    }))
+}
+
+fn line_style(style: &StyleConfig, line: &str) -> Style {
+    if line.starts_with('+') {
+        (&style.diff_highlight.tag_new).into()
+    } else if line.starts_with('-') {
+        (&style.diff_highlight.tag_old).into()
+    } else {
+        Style::new()
+    }
+}
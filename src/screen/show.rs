@@ -2,14 +2,15 @@ use std::{iter, rc::Rc};
 
 use crate::{
     config::Config,
-    git,
-    items::{self, Item},
+    git::{self, commit::CommitMessage, diff::Diff},
+    items::{self, Item, TargetData},
     Res,
 };
 use git2::Repository;
 use ratatui::{
     layout::Size,
-    text::{Line, Text},
+    style::Style,
+    text::{Line, Span, Text},
 };
 
 use super::Screen;
@@ -19,6 +20,41 @@ pub(crate) fn create(
     repo: Rc<Repository>,
     size: Size,
     reference: String,
+) -> Res<Screen> {
+    create_with_diff(config, repo, size, reference, None, git::show)
+}
+
+/// Like `create`, with the untracked files a stash has too. The stash is the target of its header,
+/// see `Screen::shown_stash`.
+pub(crate) fn create_stash(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    id: usize,
+    commit: String,
+) -> Res<Screen> {
+    let stash = TargetData::Stash {
+        commit: commit.clone(),
+        id,
+    };
+
+    create_with_diff(
+        config,
+        repo,
+        size,
+        commit,
+        Some(stash),
+        |_config, repo, stash| git::stash_show(repo, stash),
+    )
+}
+
+fn create_with_diff(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    reference: String,
+    header_target: Option<TargetData>,
+    diff: fn(&Config, &Repository, &str) -> Res<Diff>,
 ) -> Res<Screen> {
     Screen::new(
         Rc::clone(&config),
@@ -26,14 +62,17 @@ pub(crate) fn create(
         Box::new(move || {
             let style = &config.style;
             let commit = git::show_summary(repo.as_ref(), &reference)?;
-            let show = git::show(&config, repo.as_ref(), &reference)?;
+            let show = diff(&config, repo.as_ref(), &reference)?;
             let details = Text::from(commit.details).lines;
+            let message = CommitMessage::parse(&commit.message);
+            let notes = notes_lines(&config, &repo, &commit.hash)?;
 
             Ok(iter::once(Item {
                 id: format!("commit_section_{}", commit.hash).into(),
                 display: Line::styled(format!("commit {}", commit.hash), &style.section_header),
                 section: true,
                 depth: 0,
+                target_data: header_target.clone(),
                 ..Default::default()
             })
             .chain(details.into_iter().map(|line| Item {
@@ -43,6 +82,23 @@ pub(crate) fn create(
                 unselectable: true,
                 ..Default::default()
             }))
+            .chain(message_lines(&config, &message).map(|line| Item {
+                id: format!("commit_{}", commit.hash).into(),
+                display: line,
+                depth: 1,
+                unselectable: true,
+                word_wrap: true,
+                ..Default::default()
+            }))
+            .chain(trailer_items(&config, &message, &commit.hash))
+            .chain(notes.into_iter().map(|line| Item {
+                id: format!("commit_{}", commit.hash).into(),
+                display: line,
+                depth: 1,
+                unselectable: true,
+                word_wrap: true,
+                ..Default::default()
+            }))
             .chain([items::blank_line()])
             .chain(items::create_diff_items(
                 Rc::clone(&config),
@@ -54,3 +110,99 @@ pub(crate) fn create(
         }),
     )
 }
+
+/// Past this, subjects get truncated in one-line logs and forge UIs.
+const SUBJECT_MAX_CHARS: usize = 50;
+
+/// The subject and body indented like `git show` does, with a blank line before the body.
+fn message_lines<'a>(
+    config: &'a Config,
+    message: &CommitMessage,
+) -> impl Iterator<Item = Line<'static>> + 'a {
+    let style = &config.style;
+    let CommitMessage { subject, body, .. } = message;
+
+    let split = subject
+        .char_indices()
+        .nth(SUBJECT_MAX_CHARS)
+        .map(|(i, _)| i)
+        .unwrap_or(subject.len());
+    let (within, overflow) = subject.split_at(split);
+    let subject = Line::from(vec![
+        Span::raw(format!("    {}", within)),
+        Span::styled(overflow.to_string(), &style.commit_subject_overflow),
+    ]);
+
+    let body = (!body.is_empty())
+        .then(|| {
+            iter::once(Line::raw("")).chain(
+                body.clone()
+                    .into_iter()
+                    .map(|line| Line::raw(format!("    {}", line))),
+            )
+        })
+        .into_iter()
+        .flatten();
+
+    iter::once(Line::raw("")).chain([subject]).chain(body)
+}
+
+/// The trailers after a blank line, each selectable to copy. Lines they're folded onto are part of them.
+fn trailer_items(config: &Config, message: &CommitMessage, hash: &str) -> Vec<Item> {
+    if message.trailers.is_empty() {
+        return vec![];
+    }
+
+    let style = Style::from(&config.style.commit_trailer);
+    let mut trailers = message.trailers().into_iter().enumerate();
+    let mut id = format!("commit_{}", hash);
+
+    iter::once(Item {
+        id: id.clone().into(),
+        depth: 1,
+        unselectable: true,
+        ..Default::default()
+    })
+    .chain(message.trailers.iter().map(|line| {
+        let display = Line::styled(format!("    {}", line), style);
+        let folded = line.starts_with([' ', '\t']);
+        let trailer = (!folded).then(|| trailers.next()).flatten();
+
+        if let Some((i, _)) = &trailer {
+            id = format!("commit_trailer_{}_{}", hash, i);
+        }
+
+        Item {
+            id: id.clone().into(),
+            display,
+            depth: 1,
+            unselectable: trailer.is_none(),
+            word_wrap: true,
+            target_data: trailer.map(|(_, (key, value))| TargetData::Trailer(key, value)),
+            ..Default::default()
+        }
+    }))
+    .collect()
+}
+
+/// The notes on the commit in each of `git.notes_refs`, headed like `git log` does.
+fn notes_lines(config: &Config, repo: &Repository, hash: &str) -> Res<Vec<Line<'static>>> {
+    let mut lines = vec![];
+
+    for notes_ref in &config.git.notes_refs {
+        let Some(notes) = git::notes(repo, notes_ref, hash)? else {
+            continue;
+        };
+
+        let header = match notes_ref.as_str() {
+            "commits" | "refs/notes/commits" => "Notes:".to_string(),
+            _ => format!("Notes ({}):", notes_ref.trim_start_matches("refs/notes/")),
+        };
+
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(header, &config.style.section_header));
+        lines.extend(notes.lines().map(|line| Line::raw(format!("    {}", line))));
+    }
+
+    Ok(lines)
+}
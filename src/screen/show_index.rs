@@ -0,0 +1,55 @@
+use std::{iter, path::PathBuf, rc::Rc};
+
+use crate::{
+    config::Config,
+    git,
+    items::{self, Item},
+    Res,
+};
+use git2::Repository;
+use ratatui::{layout::Size, text::Line};
+
+use super::Screen;
+
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    path: PathBuf,
+) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let diff = git::diff_staged_file(&config, repo.as_ref(), &path)?;
+
+            let unchanged = diff.deltas.is_empty().then(|| Item {
+                id: "index_unchanged".into(),
+                display: Line::raw("No staged changes"),
+                depth: 1,
+                unselectable: true,
+                ..Default::default()
+            });
+
+            Ok(iter::once(Item {
+                id: "index_header".into(),
+                display: Line::styled(
+                    format!("Index version of {}", path.to_string_lossy()),
+                    &style.section_header,
+                ),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(unchanged)
+            .chain(items::create_diff_items(
+                Rc::clone(&config),
+                &diff,
+                &1,
+                false,
+            ))
+            .collect())
+        }),
+    )
+}
@@ -1,9 +1,13 @@
 use super::Screen;
 use crate::{
     config::Config,
-    git::{self, diff::Diff},
+    git::{
+        self,
+        diff::Diff,
+        diff_options::{DiffAlgorithm, DiffOptions},
+    },
     git2_opts,
-    items::{self, Item, TargetData},
+    items::{self, Item, LogFilter, TargetData},
     Res,
 };
 use git2::Repository;
@@ -11,14 +15,21 @@ use ratatui::{
     prelude::Size,
     text::{Line, Span},
 };
-use std::{path::PathBuf, rc::Rc};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use unicode_width::UnicodeWidthStr;
 
-pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Res<Screen> {
+pub(crate) fn create(
+    config: Rc<Config>,
+    repo: Rc<Repository>,
+    size: Size,
+    diff_options: Rc<RefCell<DiffOptions>>,
+) -> Res<Screen> {
     Screen::new(
         Rc::clone(&config),
         size,
         Box::new(move || {
             let style = &config.style;
+            let diff_options = diff_options.borrow();
             let statuses = repo.statuses(Some(&mut git2_opts::status(&repo)?))?;
 
             let untracked_files = statuses
@@ -33,8 +44,59 @@ pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Re
                 .map(|status| PathBuf::from(status.path().unwrap()))
                 .collect::<Vec<_>>();
 
-            let untracked = items_list(&config, untracked_files.clone());
-            let unmerged = items_list(&config, unmerged_files);
+            let shown = |section| {
+                diff_options.stat || config.status.section_order.iter().any(|s| s == section)
+            };
+            let unstaged = shown("unstaged_changes")
+                .then(|| git::diff_unstaged(&config, repo.as_ref(), &diff_options))
+                .transpose()?;
+            let staged = shown("staged_changes")
+                .then(|| git::diff_staged(&config, repo.as_ref(), &diff_options))
+                .transpose()?;
+
+            let mut sections = vec![];
+            if diff_options.stat {
+                sections.extend(diffstat_items(
+                    &config,
+                    unstaged.as_ref().unwrap(),
+                    staged.as_ref().unwrap(),
+                ));
+            }
+
+            for section in &config.status.section_order {
+                match section.as_str() {
+                    "untracked" => sections.extend(untracked_items(&config, &untracked_files)),
+                    "unmerged" => sections.extend(unmerged_items(&config, &unmerged_files)),
+                    "unstaged_changes" => sections.extend(create_status_section_items(
+                        Rc::clone(&config),
+                        "unstaged_changes",
+                        Some(TargetData::AllUnstaged),
+                        !config.status.expand_unstaged.enabled,
+                        unstaged.as_ref().unwrap(),
+                        diff_options.shown_algorithm(),
+                    )),
+                    "staged_changes" => sections.extend(create_status_section_items(
+                        Rc::clone(&config),
+                        "staged_changes",
+                        Some(TargetData::AllStaged),
+                        !config.status.expand_staged.enabled,
+                        staged.as_ref().unwrap(),
+                        diff_options.shown_algorithm(),
+                    )),
+                    "stashes" => sections.extend(create_stash_list_section_items(
+                        Rc::clone(&config),
+                        repo.as_ref(),
+                        "stashes",
+                    )),
+                    "recent_commits" => sections.extend(create_log_section_items(
+                        Rc::clone(&config),
+                        repo.as_ref(),
+                        "recent_commits",
+                    )),
+                    // Reported once on startup, see `unknown_sections`
+                    _ => (),
+                }
+            }
 
             let items = if let Some(rebase) = git::rebase_status(&repo)? {
                 vec![Item {
@@ -46,6 +108,22 @@ pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Re
                     ..Default::default()
                 }]
                 .into_iter()
+            } else if let Some(am) = git::am_status(&repo) {
+                vec![Item {
+                    id: "am_status".into(),
+                    display: Line::styled(
+                        match am.subject {
+                            Some(subject) => format!(
+                                "Applying patch {} of {}: {}",
+                                am.current, am.total, subject
+                            ),
+                            None => format!("Applying patch {} of {}", am.current, am.total),
+                        },
+                        &style.section_header,
+                    ),
+                    ..Default::default()
+                }]
+                .into_iter()
             } else if let Some(merge) = git::merge_status(&repo)? {
                 vec![Item {
                     id: "merge_status".into(),
@@ -66,62 +144,26 @@ pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Re
                     ..Default::default()
                 }]
                 .into_iter()
+            } else if let Some(bisect) = git::bisect_status(&repo)? {
+                vec![Item {
+                    id: "bisect_status".into(),
+                    display: Line::styled(
+                        match bisect.steps {
+                            Some(1) => format!("Bisecting {}, roughly 1 step left", bisect.start),
+                            Some(steps) => {
+                                format!("Bisecting {}, roughly {} steps left", bisect.start, steps)
+                            }
+                            None => format!("Bisecting {}", bisect.start),
+                        },
+                        &style.section_header,
+                    ),
+                    ..Default::default()
+                }]
+                .into_iter()
             } else {
                 branch_status_items(&config, &repo)?.into_iter()
             }
-            .chain(if untracked.is_empty() {
-                vec![]
-            } else {
-                vec![
-                    items::blank_line(),
-                    Item {
-                        id: "untracked".into(),
-                        display: Line::styled("Untracked files", &style.section_header),
-                        section: true,
-                        depth: 0,
-                        target_data: Some(TargetData::AllUntracked(untracked_files)),
-                        ..Default::default()
-                    },
-                ]
-            })
-            .chain(untracked)
-            .chain(if unmerged.is_empty() {
-                vec![]
-            } else {
-                vec![
-                    items::blank_line(),
-                    Item {
-                        id: "unmerged".into(),
-                        display: Line::styled("Unmerged", &style.section_header),
-                        section: true,
-                        depth: 0,
-                        ..Default::default()
-                    },
-                ]
-            })
-            .chain(unmerged)
-            .chain(create_status_section_items(
-                Rc::clone(&config),
-                "unstaged_changes",
-                Some(TargetData::AllUnstaged),
-                &git::diff_unstaged(&config, repo.as_ref())?,
-            ))
-            .chain(create_status_section_items(
-                Rc::clone(&config),
-                "staged_changes",
-                Some(TargetData::AllStaged),
-                &git::diff_staged(&config, repo.as_ref())?,
-            ))
-            .chain(create_stash_list_section_items(
-                Rc::clone(&config),
-                repo.as_ref(),
-                "stashes",
-            ))
-            .chain(create_log_section_items(
-                Rc::clone(&config),
-                repo.as_ref(),
-                "recent_commits",
-            ))
+            .chain(sections)
             .collect();
 
             Ok(items)
@@ -129,6 +171,69 @@ pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Re
     )
 }
 
+/// What `status.section_order` may list.
+const SECTIONS: [&str; 6] = [
+    "untracked",
+    "unmerged",
+    "unstaged_changes",
+    "staged_changes",
+    "stashes",
+    "recent_commits",
+];
+
+/// The entries of `status.section_order` that aren't sections, and so are left out.
+pub(crate) fn unknown_sections(config: &Config) -> Vec<&str> {
+    config
+        .status
+        .section_order
+        .iter()
+        .map(String::as_str)
+        .filter(|section| !SECTIONS.contains(section))
+        .collect()
+}
+
+fn untracked_items(config: &Config, untracked_files: &[PathBuf]) -> Vec<Item> {
+    if untracked_files.is_empty() {
+        return vec![];
+    }
+
+    [
+        items::blank_line(),
+        Item {
+            id: "untracked".into(),
+            display: Line::styled("Untracked files", &config.style.section_header),
+            section: true,
+            depth: 0,
+            target_data: Some(TargetData::AllUntracked(untracked_files.to_vec())),
+            default_collapsed: !config.status.expand_untracked.enabled,
+            ..Default::default()
+        },
+    ]
+    .into_iter()
+    .chain(items_list(config, untracked_files.to_vec()))
+    .collect()
+}
+
+fn unmerged_items(config: &Config, unmerged_files: &[PathBuf]) -> Vec<Item> {
+    if unmerged_files.is_empty() {
+        return vec![];
+    }
+
+    [
+        items::blank_line(),
+        Item {
+            id: "unmerged".into(),
+            display: Line::styled("Unmerged", &config.style.section_header),
+            section: true,
+            depth: 0,
+            ..Default::default()
+        },
+    ]
+    .into_iter()
+    .chain(items_list(config, unmerged_files.to_vec()))
+    .collect()
+}
+
 fn items_list(config: &Config, files: Vec<PathBuf>) -> Vec<Item> {
     let style = &config.style;
     files
@@ -217,11 +322,76 @@ fn branch_status_items(config: &Config, repo: &Repository) -> Res<Vec<Item>> {
     Ok(items)
 }
 
+/// `--stat` bars are at most this wide, and scaled down to fit when a file changes more lines.
+const STAT_BAR_WIDTH: usize = 40;
+
+/// Lines like `git diff --stat` prints for the unstaged and staged changes, with bars scaled
+/// to the file that changes the most.
+fn diffstat_items(config: &Config, unstaged: &Diff, staged: &Diff) -> Vec<Item> {
+    let files = unstaged
+        .deltas
+        .iter()
+        .map(|delta| ("unstaged", delta))
+        .chain(staged.deltas.iter().map(|delta| ("staged", delta)))
+        .map(|(kind, delta)| (kind, delta.new_file.to_string_lossy(), delta.stats()))
+        .collect::<Vec<_>>();
+
+    if files.is_empty() {
+        return vec![];
+    }
+
+    let style = &config.style;
+    let name_width = files
+        .iter()
+        .map(|(_, name, _)| name.width())
+        .max()
+        .unwrap_or(0);
+    let max_changes = files
+        .iter()
+        .map(|(.., stats)| stats.changes())
+        .max()
+        .unwrap_or(0);
+    let count_width = max_changes.to_string().len();
+
+    [
+        items::blank_line(),
+        Item {
+            id: "diffstat".into(),
+            display: Line::styled("Diffstat", &style.section_header),
+            section: true,
+            depth: 0,
+            ..Default::default()
+        },
+    ]
+    .into_iter()
+    .chain(files.into_iter().map(|(kind, name, stats)| {
+        let (added, removed) = stats.bar(max_changes, STAT_BAR_WIDTH);
+        let padding = " ".repeat(name_width - name.width());
+
+        Item {
+            id: format!("diffstat_{kind}_{name}").into(),
+            display: Line::from(vec![
+                format!("{:8}   ", kind).into(),
+                Span::styled(name.into_owned(), &style.file_header),
+                format!("{} | {:>count_width$} ", padding, stats.changes()).into(),
+                Span::styled("+".repeat(added), &style.diff_highlight.changed_new),
+                Span::styled("-".repeat(removed), &style.diff_highlight.changed_old),
+            ]),
+            depth: 1,
+            unselectable: true,
+            ..Default::default()
+        }
+    }))
+    .collect()
+}
+
 fn create_status_section_items<'a>(
     config: Rc<Config>,
     snake_case_header: &str,
     header_data: Option<TargetData>,
+    collapse_deltas: bool,
     diff: &'a Diff,
+    algorithm: Option<DiffAlgorithm>,
 ) -> impl Iterator<Item = Item> + 'a {
     let style = &config.style;
     if diff.deltas.is_empty() {
@@ -241,7 +411,11 @@ fn create_status_section_items<'a>(
                         capitalize(&snake_case_header.replace("_", " ")),
                         &style.section_header,
                     ),
-                    format!(" ({})", diff.deltas.len()).into(),
+                    match algorithm {
+                        Some(algorithm) => format!(" ({}, {} diff)", diff.deltas.len(), algorithm),
+                        None => format!(" ({})", diff.deltas.len()),
+                    }
+                    .into(),
                 ]),
                 section: true,
                 depth: 0,
@@ -251,7 +425,7 @@ fn create_status_section_items<'a>(
         ]
     }
     .into_iter()
-    .chain(items::create_diff_items(config, diff, &1, true))
+    .chain(items::create_diff_items(config, diff, &1, collapse_deltas))
 }
 
 fn capitalize(str: &str) -> String {
@@ -313,5 +487,5 @@ fn create_log_section_items<'a>(
         },
     ]
     .into_iter()
-    .chain(items::log(&config, repo, 10, None, None).unwrap())
+    .chain(items::log(&config, repo, 10, None, &LogFilter::default()).unwrap())
 }
@@ -0,0 +1,97 @@
+use std::{iter, path::Path, rc::Rc};
+
+use super::Screen;
+use crate::{
+    config::Config,
+    git,
+    items::{Item, TargetData},
+    Res,
+};
+use git2::Repository;
+use ratatui::{
+    layout::Size,
+    text::{Line, Span},
+};
+
+pub(crate) fn create(config: Rc<Config>, repo: Rc<Repository>, size: Size) -> Res<Screen> {
+    Screen::new(
+        Rc::clone(&config),
+        size,
+        Box::new(move || {
+            let style = &config.style;
+            let current = repo.workdir().expect("No workdir");
+            let worktrees = git::worktrees(&repo)?;
+            let main = worktrees.first().map(|main| main.path.clone());
+
+            Ok(iter::once(Item {
+                id: "worktrees".into(),
+                display: Line::styled("Worktrees".to_string(), &style.section_header),
+                section: true,
+                depth: 0,
+                ..Default::default()
+            })
+            .chain(worktrees.into_iter().map(|worktree| {
+                let kind = match (worktree.is_main, worktree.is_bare) {
+                    (true, true) => "bare",
+                    (true, false) => "main",
+                    (false, _) => "linked",
+                };
+
+                let mut spans = vec![
+                    Span::raw(if worktree.path == current { "* " } else { "  " }),
+                    Span::raw(format!(
+                        "{:8}   {}",
+                        kind,
+                        display_path(main.as_deref(), &worktree.path)
+                    )),
+                ];
+
+                if let Some(branch) = &worktree.branch {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(branch.clone(), &style.branch));
+                } else if let Some(head) = &worktree.head {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        head.get(..7).unwrap_or(head).to_string(),
+                        &style.hash,
+                    ));
+                }
+
+                if worktree.is_locked {
+                    spans.push(Span::raw(" (locked)"));
+                }
+
+                Item {
+                    id: worktree.path.to_string_lossy().to_string().into(),
+                    display: Line::from(spans),
+                    depth: 1,
+                    target_data: Some(TargetData::Worktree(worktree.path)),
+                    ..Default::default()
+                }
+            }))
+            .collect())
+        }),
+    )
+}
+
+/// Shows paths relative to the main worktree, as linked ones tend to live within or beside it.
+fn display_path(main: Option<&Path>, path: &Path) -> String {
+    let Some(main) = main else {
+        return path.to_string_lossy().to_string();
+    };
+
+    if let Ok(inside) = path.strip_prefix(main) {
+        if inside.as_os_str().is_empty() {
+            ".".into()
+        } else {
+            inside.to_string_lossy().to_string()
+        }
+    } else if let Some(beside) = main
+        .parent()
+        .and_then(|parent| path.strip_prefix(parent).ok())
+    {
+        Path::new("..").join(beside).to_string_lossy().to_string()
+    } else {
+        path.to_string_lossy().to_string()
+    }
+}
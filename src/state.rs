@@ -1,12 +1,11 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
-use std::io::Read;
 use std::ops::DerefMut;
-use std::process::Child;
+use std::path::Path;
 use std::process::Command;
 use std::process::Stdio;
 use std::rc::Rc;
-use std::sync::Arc;
-use std::sync::RwLock;
 
 use arboard::Clipboard;
 use crossterm::event;
@@ -19,18 +18,30 @@ use ratatui::layout::Size;
 use tui_prompts::State as _;
 use tui_prompts::Status;
 
+use crate::auto_fetch::AutoFetch;
 use crate::bindings::Bindings;
 use crate::cli;
 use crate::cmd_log::CmdLog;
 use crate::cmd_log::CmdLogEntry;
 use crate::config::Config;
+use crate::git;
+use crate::git::diff_options::DiffOptions;
+use crate::hooks::Hooks;
+use crate::items::LogFilter;
+use crate::items::TargetData;
+use crate::macros::Macros;
 use crate::menu::Menu;
 use crate::menu::PendingMenu;
+use crate::ops::blame::BlameKey;
 use crate::ops::Op;
+use crate::pending_cmd;
+use crate::pending_cmd::PendingCmd;
 use crate::prompt;
 use crate::screen;
 use crate::screen::Screen;
+use crate::status_line::StatusLine;
 use crate::term::Term;
+use crate::timing;
 use crate::ui;
 
 use super::Res;
@@ -39,15 +50,23 @@ pub(crate) struct State {
     pub repo: Rc<Repository>,
     pub config: Rc<Config>,
     pub bindings: Bindings,
-    pending_keys: Vec<(KeyModifiers, KeyCode)>,
+    pub pending_keys: Vec<(KeyModifiers, KeyCode)>,
     pub quit: bool,
     pub screens: Vec<Screen>,
     pub pending_menu: Option<PendingMenu>,
-    pub pending_cmd: Option<(Child, Arc<RwLock<CmdLogEntry>>)>,
+    pub pending_cmd: Option<PendingCmd>,
     enable_async_cmds: bool,
     pub current_cmd_log: CmdLog,
+    pub status_line: StatusLine,
     pub prompt: prompt::Prompt,
     pub clipboard: Option<Clipboard>,
+    pub diff_options: Rc<RefCell<DiffOptions>>,
+    pub blame_cache: HashMap<BlameKey, String>,
+    pub log_filter: Rc<RefCell<LogFilter>>,
+    pub macros: Macros,
+    pub last_search: Option<String>,
+    pub(crate) auto_fetch: AutoFetch,
+    pub(crate) hooks: Hooks,
 }
 
 impl State {
@@ -58,6 +77,17 @@ impl State {
         config: Rc<Config>,
         enable_async_cmds: bool,
     ) -> Res<Self> {
+        let saved_state = config
+            .state_path
+            .as_deref()
+            .map(crate::config::read_state)
+            .unwrap_or_default();
+        let diff_options = Rc::new(RefCell::new(
+            DiffOptions::new()
+                .algorithm(saved_state.diff_algorithm.unwrap_or(config.diff.algorithm))
+                .stat(config.status.diffstat.enabled),
+        ));
+        let log_filter = Rc::new(RefCell::new(LogFilter::default()));
         let screens = match args.command {
             Some(cli::Commands::Show { ref reference }) => {
                 vec![screen::show::create(
@@ -67,21 +97,57 @@ impl State {
                     reference.clone(),
                 )?]
             }
-            None => vec![screen::status::create(
-                Rc::clone(&config),
-                Rc::clone(&repo),
-                size,
-            )?],
+            Some(cli::Commands::Log { ref reference }) => {
+                let rev = match reference {
+                    Some(reference) => {
+                        Some(repo.revparse_single(reference)?.peel_to_commit()?.id())
+                    }
+                    None => None,
+                };
+
+                vec![screen::log::create(
+                    Rc::clone(&config),
+                    Rc::clone(&repo),
+                    size,
+                    u32::MAX as usize,
+                    rev,
+                    Rc::clone(&log_filter),
+                )?]
+            }
+            // Without a file or `--staged` there's nothing to narrow the status down to
+            Some(cli::Commands::Diff { staged, ref file }) if staged || file.is_some() => {
+                vec![screen::file_diff::create(
+                    Rc::clone(&config),
+                    Rc::clone(&repo),
+                    size,
+                    Rc::clone(&diff_options),
+                    file.as_ref()
+                        .map(|file| git::path_in_work_tree(&repo, file))
+                        .transpose()?,
+                    staged,
+                )?]
+            }
+            None | Some(cli::Commands::Status) | Some(cli::Commands::Diff { .. }) => {
+                vec![screen::status::create(
+                    Rc::clone(&config),
+                    Rc::clone(&repo),
+                    size,
+                    Rc::clone(&diff_options),
+                )?]
+            }
         };
 
         let bindings = Bindings::from(&config.bindings);
-        let pending_menu = root_menu(&config).map(PendingMenu::init);
+        let pending_menu = root_menu(&config).map(|menu| PendingMenu::init(menu, &config, &repo));
 
         let clipboard = Clipboard::new()
             .inspect_err(|e| log::warn!("Couldn't initialize clipboard: {}", e))
             .ok();
 
-        Ok(Self {
+        let unknown_sections = screen::status::unknown_sections(&config).join(", ");
+        let auto_fetch = AutoFetch::new(config.fetch.auto_interval);
+
+        let mut state = Self {
             repo,
             config,
             bindings,
@@ -92,45 +158,142 @@ impl State {
             pending_cmd: None,
             pending_menu,
             current_cmd_log: CmdLog::new(),
+            status_line: StatusLine::default(),
             prompt: prompt::Prompt::new(),
             clipboard,
-        })
+            diff_options,
+            blame_cache: HashMap::new(),
+            log_filter,
+            macros: Macros::default(),
+            last_search: None,
+            auto_fetch,
+            hooks: Hooks::new(),
+        };
+
+        if !unknown_sections.is_empty() {
+            log::warn!(
+                "Unknown sections in status.section_order: {}",
+                unknown_sections
+            );
+            state.display_error(format!(
+                "Unknown sections in status.section_order: {}",
+                unknown_sections
+            ));
+        }
+
+        Ok(state)
     }
 
     pub fn update(&mut self, term: &mut Term, events: &[Event]) -> Res<()> {
         for event in events {
-            match *event {
-                Event::Resize(w, h) => {
-                    for screen in self.screens.iter_mut() {
-                        screen.size = Size::new(w, h);
-                    }
+            self.handle_event(term, event)?;
+            self.replay_macro(term)?;
+        }
+
+        let handle_pending_cmd_result = self.handle_pending_cmd();
+        if handle_pending_cmd_result.is_err() {
+            self.hooks.cancel();
+        }
+        let pending_cmd_done = self
+            .handle_result(handle_pending_cmd_result)
+            .unwrap_or(true);
+
+        let handle_hooks_result = self.handle_hooks();
+        let hook_done = self.handle_result(handle_hooks_result).unwrap_or(true);
+
+        let handle_auto_fetch_result = self.handle_auto_fetch();
+        let auto_fetch_done = self
+            .handle_result(handle_auto_fetch_result)
+            .unwrap_or(false);
+
+        let status_cleared = self.status_line.clear_expired();
+
+        // Keeps the spinner of a running command going
+        let needs_redraw = !events.is_empty()
+            || pending_cmd_done
+            || auto_fetch_done
+            || hook_done
+            || status_cleared
+            || self.pending_cmd.is_some();
+
+        if let Some(screen) = self.screens.last().filter(|_| needs_redraw) {
+            let lines = format!("{} lines", screen.line_count());
+            timing::measure(
+                "Rendering",
+                || term.draw(|frame| ui::ui(frame, self)),
+                |_| lines,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, term: &mut Term, event: &Event) -> Res<()> {
+        match *event {
+            Event::Resize(w, h) => {
+                for screen in self.screens.iter_mut() {
+                    screen.resize(Size::new(w, h))?;
+                }
+            }
+            Event::Key(key) => {
+                if key.kind == KeyEventKind::Press {
+                    self.macros.record(key, self.prompt.state.is_focused());
                 }
-                Event::Key(key) => {
-                    if self.prompt.state.is_focused() {
-                        self.prompt.state.handle_key_event(key)
-                    } else if key.kind == KeyEventKind::Press {
-                        if self.pending_cmd.is_none() {
-                            self.current_cmd_log.clear();
-                        }
 
-                        self.handle_key_input(term, key)?;
+                if self.prompt.state.is_focused() {
+                    if key.code == KeyCode::Tab && !self.prompt.completions.is_empty() {
+                        if key.kind == KeyEventKind::Press {
+                            self.prompt.complete();
+                        }
+                    } else if key.code == KeyCode::Char('o')
+                        && key.modifiers == KeyModifiers::CONTROL
+                        && self.prompt.editor_fn.is_some()
+                    {
+                        if key.kind == KeyEventKind::Press {
+                            let result = self.continue_prompt_in_editor(term);
+                            self.handle_result(result);
+                        }
+                    } else {
+                        self.prompt.state.handle_key_event(key)
+                    }
+                } else if key.kind == KeyEventKind::Press {
+                    if self.pending_cmd.is_none() {
+                        self.current_cmd_log.clear();
                     }
+                    self.status_line.clear_info();
+
+                    self.handle_key_input(term, key)?;
                 }
-                _ => (),
             }
-
-            self.update_prompt(term)?;
+            _ => (),
         }
 
-        let handle_pending_cmd_result = self.handle_pending_cmd();
-        let pending_cmd_done = self
-            .handle_result(handle_pending_cmd_result)
-            .unwrap_or(true);
+        self.update_prompt(term)
+    }
+
+    /// Handles the keys of a macro being replayed as if they were typed. Stops at an error, or at a key
+    /// that goes elsewhere than when it was recorded, like a confirmation prompt that didn't show then.
+    fn replay_macro(&mut self, term: &mut Term) -> Res<()> {
+        while let Some(recorded) = self.macros.next_replayed() {
+            if self.pending_cmd.is_some() {
+                self.await_pending_cmd()?;
+                self.handle_pending_cmd()?;
+            }
+
+            if recorded.in_prompt != self.prompt.state.is_focused() {
+                self.macros.stop_replaying();
+                self.display_error(match recorded.in_prompt {
+                    true => "Stopped macro, a prompt it answered didn't show".into(),
+                    false => "Stopped macro at a prompt it didn't answer when recorded".into(),
+                });
+                break;
+            }
 
-        let needs_redraw = !events.is_empty() || pending_cmd_done;
+            self.handle_event(term, &Event::Key(recorded.key))?;
 
-        if needs_redraw && self.screens.last_mut().is_some() {
-            term.draw(|frame| ui::ui(frame, self))?;
+            if self.status_line.has_error() {
+                self.macros.stop_replaying();
+            }
         }
 
         Ok(())
@@ -138,6 +301,7 @@ impl State {
 
     fn update_prompt(&mut self, term: &mut Term) -> Res<()> {
         if self.prompt.state.status() == Status::Aborted {
+            self.hooks.cancel();
             self.unhide_menu();
             self.prompt.reset(term)?;
         } else if let Some(mut prompt_data) = self.prompt.data.take() {
@@ -145,19 +309,41 @@ impl State {
 
             match result {
                 Ok(()) => {
-                    if self.prompt.state.is_focused() {
+                    // The update_fn may have replaced it with a follow-up prompt
+                    if self.prompt.state.is_focused() && self.prompt.data.is_none() {
                         self.prompt.data = Some(prompt_data);
                     }
                 }
-                Err(error) => self
-                    .current_cmd_log
-                    .push(CmdLogEntry::Error(error.to_string())),
+                Err(error) => {
+                    self.hooks.cancel();
+                    self.display_error(error.to_string());
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Hands what was typed into the prompt to its `editor_fn`, and brings the prompt back should that fail,
+    /// like when the editor exits with an error.
+    fn continue_prompt_in_editor(&mut self, term: &mut Term) -> Res<()> {
+        let (Some(data), Some(editor_fn)) = (self.prompt.data.take(), self.prompt.editor_fn.take())
+        else {
+            return Ok(());
+        };
+        let input = self.prompt.state.value().to_string();
+        self.prompt.reset(term)?;
+
+        let result = editor_fn(self, term, &input);
+        if result.is_err() {
+            self.prompt.set(data);
+            self.prompt.restore_input(input);
+            self.prompt.editor_fn = Some(editor_fn);
+        }
+
+        result
+    }
+
     fn handle_key_input(&mut self, term: &mut Term, key: event::KeyEvent) -> Res<()> {
         let menu = match &self.pending_menu {
             None => Menu::Root,
@@ -186,10 +372,24 @@ impl State {
     }
 
     pub(crate) fn handle_op(&mut self, op: Op, term: &mut Term) -> Res<()> {
-        let target = self.screen().get_selected_item().target_data.as_ref();
+        if self.screen().is_read_only() && op.modifies_worktree() {
+            self.display_error("Not available in a read-only view".into());
+            return Ok(());
+        }
+
+        let selected_lines = self
+            .screen()
+            .selected_lines()
+            .map(|(hunk, lines)| TargetData::HunkLines(hunk, lines));
+        let target =
+            selected_lines
+                .as_ref()
+                .or(self.screen().get_selected_item().target_data.as_ref());
         if let Some(mut action) = op.clone().implementation().get_action(target) {
             let result = Rc::get_mut(&mut action).unwrap()(self, term);
-            self.handle_result(result);
+            if self.handle_result(result).is_some() {
+                self.hooks.queue(&self.config, &op);
+            }
         }
 
         Ok(())
@@ -199,16 +399,15 @@ impl State {
         match result {
             Ok(value) => Some(value),
             Err(error) => {
-                self.current_cmd_log
-                    .push(CmdLogEntry::Error(error.to_string()));
-
+                self.display_error(error.to_string());
                 None
             }
         }
     }
 
     pub fn close_menu(&mut self) {
-        self.pending_menu = root_menu(&self.config).map(PendingMenu::init)
+        self.pending_menu =
+            root_menu(&self.config).map(|menu| PendingMenu::init(menu, &self.config, &self.repo))
     }
 
     pub fn screen_mut(&mut self) -> &mut Screen {
@@ -219,14 +418,14 @@ impl State {
         self.screens.last().expect("No screen")
     }
 
-    /// Displays an `Info` message to the CmdLog.
+    /// Displays an info message in the status line, until the next key press.
     pub fn display_info(&mut self, message: String) {
-        self.current_cmd_log.push(CmdLogEntry::Info(message));
+        self.status_line.info(message);
     }
 
-    /// Displays an `Error` message to the CmdLog.
+    /// Displays an error message in the status line, until dismissed.
     pub fn display_error(&mut self, error: String) {
-        self.current_cmd_log.push(CmdLogEntry::Error(error));
+        self.status_line.error(error);
     }
 
     /// Runs a `Command` and handles its output.
@@ -247,6 +446,11 @@ impl State {
 
         cmd.current_dir(self.repo.workdir().expect("No workdir"));
 
+        // Git only reports progress to a terminal unless asked to, shown in the status line meanwhile
+        if self.enable_async_cmds && reports_progress(&cmd) {
+            cmd.arg("--progress");
+        }
+
         cmd.stdin(Stdio::piped());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
@@ -259,7 +463,7 @@ impl State {
         use std::io::Write;
         child.stdin.take().unwrap().write_all(input)?;
 
-        self.pending_cmd = Some((child, log_entry));
+        self.pending_cmd = Some(PendingCmd::new(child, log_entry));
 
         if !self.enable_async_cmds {
             self.await_pending_cmd()?;
@@ -269,38 +473,75 @@ impl State {
     }
 
     fn await_pending_cmd(&mut self) -> Res<()> {
-        if let Some((child, _)) = &mut self.pending_cmd {
-            child.wait()?;
+        if let Some(pending) = &mut self.pending_cmd {
+            pending.child.wait()?;
         }
         Ok(())
     }
 
     /// Handles any pending_cmd in State without blocking. Returns `true` if a cmd was handled.
     pub fn handle_pending_cmd(&mut self) -> Res<bool> {
-        let Some((ref mut child, ref mut log_rwlock)) = self.pending_cmd else {
+        let Some(ref mut pending) = self.pending_cmd else {
             return Ok(false);
         };
 
-        let Some(status) = child.try_wait()? else {
+        let Some(status) = pending.child.try_wait()? else {
             return Ok(false);
         };
 
         log::debug!("pending cmd finished with {:?}", status);
 
-        let result = write_child_output_to_log(log_rwlock, child, status);
+        let result = write_child_output_to_log(pending, status);
         self.pending_cmd = None;
         self.screen_mut().update()?;
-        result?;
+        self.display_info(result?);
+
+        Ok(true)
+    }
+
+    /// Starts an auto-fetch when one is due and nothing else runs or is being typed,
+    /// and refreshes the screen once it's done. Returns `true` if one finished.
+    fn handle_auto_fetch(&mut self) -> Res<bool> {
+        if self.pending_cmd.is_none() && self.prompt.data.is_none() {
+            self.auto_fetch.start_if_due(&self.repo)?;
+        }
+
+        if self.auto_fetch.poll()? != Some(true) {
+            return Ok(false);
+        }
+
+        self.screen_mut().update()?;
+        if self.status_line.is_empty() {
+            self.status_line.info("Auto-fetched".into());
+        }
 
         Ok(true)
     }
 
+    /// Starts the hook of the last action once it's done, including any prompt or command it started,
+    /// and refreshes the screen after a hook finished. Returns `true` if one finished.
+    fn handle_hooks(&mut self) -> Res<bool> {
+        if self.pending_cmd.is_none() && self.prompt.data.is_none() {
+            self.hooks
+                .start_queued(&self.config, &self.repo, &mut self.current_cmd_log)?;
+        }
+
+        if !self.hooks.poll()? {
+            return Ok(false);
+        }
+
+        self.screen_mut().update()?;
+        Ok(true)
+    }
+
     pub fn run_cmd_interactive(&mut self, term: &mut Term, mut cmd: Command) -> Res<()> {
         if self.pending_cmd.is_some() {
             return Err("A command is already running".into());
         }
 
-        cmd.current_dir(self.repo.workdir().expect("No workdir"));
+        if cmd.get_current_dir().is_none() {
+            cmd.current_dir(self.repo.workdir().expect("No workdir"));
+        }
 
         cmd.stdin(Stdio::piped());
         let child = cmd.spawn()?;
@@ -332,6 +573,55 @@ impl State {
             .into());
         }
 
+        self.display_info(format!(
+            "{} succeeded",
+            short_command(&crate::cmd_log::command_args(&cmd))
+        ));
+        Ok(())
+    }
+
+    /// Runs a `Command` on the terminal itself, with gitu out of the way until it exits.
+    /// Lets git show its own progress and ask for credentials.
+    pub fn run_cmd_suspended(&mut self, term: &mut Term, mut cmd: Command) -> Res<()> {
+        if self.pending_cmd.is_some() {
+            return Err("A command is already running".into());
+        }
+
+        term.backend_mut().suspend()?;
+        let status = cmd.status();
+        term.backend_mut().resume()?;
+        term.clear()?;
+
+        let status = status?;
+        let log_entry = self.current_cmd_log.push_cmd_with_output(&cmd, "".into());
+
+        if !status.success() {
+            return Err(format!(
+                "{} failed: exited with code: {}",
+                short_command(&log_entry.read().unwrap().args),
+                status.code().map(|c| c.to_string()).unwrap_or_default()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Continues in another repository, like one that was just cloned.
+    pub fn open_repo(&mut self, term: &mut Term, path: &Path) -> Res<()> {
+        let repo = Rc::new(Repository::open(path)?);
+
+        self.screens = vec![screen::status::create(
+            Rc::clone(&self.config),
+            Rc::clone(&repo),
+            term.size()?,
+            Rc::clone(&self.diff_options),
+        )?];
+        self.repo = repo;
+        self.blame_cache.clear();
+        *self.log_filter.borrow_mut() = LogFilter::default();
+        self.close_menu();
+
         Ok(())
     }
 
@@ -357,49 +647,79 @@ pub(crate) fn root_menu(config: &Config) -> Option<Menu> {
 }
 
 fn write_child_output_to_log(
-    log_rwlock: &mut Arc<RwLock<CmdLogEntry>>,
-    child: &mut Child,
+    pending: &mut PendingCmd,
     status: std::process::ExitStatus,
-) -> Result<(), Box<dyn Error>> {
-    let mut log = log_rwlock.write().unwrap();
+) -> Result<String, Box<dyn Error>> {
+    drop(pending.child.stdin.take());
 
-    let CmdLogEntry::Cmd { args, out: out_log } = log.deref_mut() else {
-        unreachable!("pending_cmd is always CmdLogEntry::Cmd variant");
-    };
+    log::debug!("Reading output");
+    let out_bytes = pending
+        .take_output()
+        .map_err(|e| format!("Couldn't read cmd output: {}", e))?;
 
-    drop(child.stdin.take());
+    let mut log = pending.log_entry.write().unwrap();
+    let CmdLogEntry { args, out: out_log } = log.deref_mut();
 
-    let mut out_bytes = vec![];
-    log::debug!("Reading stderr");
+    let out_string = pending_cmd::collapse_progress(&String::from_utf8(out_bytes)?);
 
-    child
-        .stderr
-        .take()
-        .unwrap()
-        .read_to_end(&mut out_bytes)
-        .map_err(|e| format!("Couldn't read cmd output: {}", e))?;
-
-    child
-        .stdout
-        .take()
-        .unwrap()
-        .read_to_end(&mut out_bytes)
-        .map_err(|e| format!("Couldn't read cmd output: {}", e))?;
+    if !status.success() {
+        let reason = failure_reason(&out_string)
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                format!(
+                    "exited with code: {}",
+                    status
+                        .code()
+                        .map(|c| c.to_string())
+                        .unwrap_or("".to_string())
+                )
+            });
+
+        *out_log = Some(out_string.into());
+        return Err(format!("{} failed: {}", short_command(args), reason).into());
+    }
 
-    let out_string = String::from_utf8(out_bytes.clone())?;
     *out_log = Some(out_string.into());
+    Ok(format!("{} succeeded", short_command(args)))
+}
 
-    if !status.success() {
-        return Err(format!(
-            "'{}' exited with code: {}",
-            args,
-            status
-                .code()
-                .map(|c| c.to_string())
-                .unwrap_or("".to_string())
-        )
-        .into());
+/// Whether `cmd` is a git command that can report progress with `--progress`.
+fn reports_progress(cmd: &Command) -> bool {
+    let mut args = cmd.get_args();
+    while let Some(arg) = args.next() {
+        if arg == "-c" {
+            args.next();
+            continue;
+        }
+
+        return ["fetch", "pull", "push", "clone"].contains(&&*arg.to_string_lossy());
     }
 
-    Ok(())
+    false
+}
+
+/// The program and subcommand, e.g. `git checkout`, leaving out any `-c key=value` config overrides.
+pub(crate) fn short_command(args: &str) -> String {
+    let mut words = args.split(' ');
+    let program = words.next().unwrap_or_default();
+
+    let mut subcommand = words.next();
+    while subcommand == Some("-c") {
+        words.next();
+        subcommand = words.next();
+    }
+
+    [Some(program), subcommand]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The last line of output that isn't a hint, usually the most telling error.
+fn failure_reason(out: &str) -> Option<&str> {
+    out.lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("hint:"))
 }
@@ -0,0 +1,72 @@
+use crate::config::StyleConfig;
+use ratatui::{style::Style, text::Line};
+use std::time::{Duration, Instant};
+
+const INFO_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The result of the last action, shown at the bottom of the screen.
+/// Info clears after a timeout or on the next key press, errors remain until dismissed.
+#[derive(Default)]
+pub(crate) struct StatusLine {
+    message: Option<StatusMessage>,
+}
+
+enum StatusMessage {
+    Info(String, Instant),
+    Error(String),
+}
+
+impl StatusLine {
+    pub(crate) fn info(&mut self, message: String) {
+        self.message = Some(StatusMessage::Info(message, Instant::now()));
+    }
+
+    pub(crate) fn error(&mut self, message: String) {
+        self.message = Some(StatusMessage::Error(message));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.message.is_none()
+    }
+
+    pub(crate) fn has_error(&self) -> bool {
+        matches!(self.message, Some(StatusMessage::Error(_)))
+    }
+
+    pub(crate) fn dismiss(&mut self) {
+        self.message = None;
+    }
+
+    /// Clears any info message, returning whether one was cleared.
+    pub(crate) fn clear_info(&mut self) -> bool {
+        let is_info = matches!(self.message, Some(StatusMessage::Info(..)));
+        if is_info {
+            self.message = None;
+        }
+        is_info
+    }
+
+    /// Clears an info message that's been shown long enough, returning whether one was cleared.
+    pub(crate) fn clear_expired(&mut self) -> bool {
+        match self.message {
+            Some(StatusMessage::Info(_, shown_at)) if shown_at.elapsed() >= INFO_TIMEOUT => {
+                self.clear_info()
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn line(&self, style: &StyleConfig) -> Option<Line<'static>> {
+        match &self.message {
+            Some(StatusMessage::Info(message, _)) => Some(Line::styled(
+                message.clone(),
+                Style::from(&style.status_info),
+            )),
+            Some(StatusMessage::Error(message)) => Some(Line::styled(
+                message.clone(),
+                Style::from(&style.status_error),
+            )),
+            None => None,
+        }
+    }
+}
@@ -143,4 +143,27 @@ impl TermBackend {
             TermBackend::Test(_) => Ok(()),
         }
     }
+
+    /// Hands the terminal back as it was before gitu started, for a command to use.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        match self {
+            TermBackend::Crossterm(c) => {
+                c.execute(LeaveAlternateScreen)?;
+                c.show_cursor()?;
+                disable_raw_mode()
+            }
+            TermBackend::Test(_) => Ok(()),
+        }
+    }
+
+    pub fn resume(&mut self) -> io::Result<()> {
+        match self {
+            TermBackend::Crossterm(c) => {
+                enable_raw_mode()?;
+                c.execute(EnterAlternateScreen)?;
+                c.hide_cursor()
+            }
+            TermBackend::Test(_) => Ok(()),
+        }
+    }
 }
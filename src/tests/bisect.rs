@@ -0,0 +1,29 @@
+use super::*;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "first-file", "hello");
+    commit(ctx.dir.path(), "second-file", "hello");
+    commit(ctx.dir.path(), "third-file", "hello");
+    ctx
+}
+
+#[test]
+fn bisect_menu() {
+    snapshot!(setup(), "B");
+}
+
+#[test]
+fn bisect_start() {
+    snapshot!(setup(), "BBHEAD<enter>HEAD~3<enter>");
+}
+
+#[test]
+fn bisect_first_bad_commit() {
+    snapshot!(setup(), "BBHEAD<enter>HEAD~2<enter>Bb");
+}
+
+#[test]
+fn bisect_reset() {
+    snapshot!(setup(), "BBHEAD<enter>HEAD~3<enter>Br");
+}
@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn blame_removed_line() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j>L");
+}
+
+#[test]
+fn blame_added_line() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j>L");
+}
+
+#[test]
+fn copy_permalink_uncommitted_line() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j><alt+y>");
+}
+
+#[test]
+fn copy_permalink_unknown_forge() {
+    let ctx = TestContext::setup_init();
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            "git@git.example.com:org/repo.git",
+        ],
+    );
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><alt+y>");
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+fn setup_github() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    git(
+        ctx.dir.path(),
+        &["remote", "set-url", "origin", "git@github.com:org/repo.git"],
+    );
+    ctx
+}
+
+#[test]
+fn browse_menu() {
+    let ctx = setup_github();
+    snapshot!(ctx, "O");
+}
+
+#[test]
+fn browse_repo() {
+    let ctx = setup_github();
+    snapshot!(ctx, "Oo");
+}
+
+#[test]
+fn browse_branch() {
+    let ctx = setup_github();
+    snapshot!(ctx, "Ob");
+}
+
+#[test]
+fn browse_file() {
+    let ctx = setup_github();
+    commit(ctx.dir.path(), "firstfile", "testing\n");
+    fs::write(ctx.dir.child("firstfile"), "changed\n").unwrap();
+    snapshot!(ctx, "jjOf");
+}
+
+#[test]
+fn browse_commit() {
+    let ctx = setup_github();
+    commit(ctx.dir.path(), "firstfile", "testing\n");
+    snapshot!(ctx, "llOc");
+}
+
+#[test]
+fn browse_unknown_forge() {
+    let ctx = TestContext::setup_clone();
+    git(
+        ctx.dir.path(),
+        &[
+            "remote",
+            "set-url",
+            "origin",
+            "git@git.example.com:org/repo.git",
+        ],
+    );
+    snapshot!(ctx, "Oo");
+}
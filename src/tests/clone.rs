@@ -0,0 +1,58 @@
+use super::*;
+
+#[test]
+fn clone_menu() {
+    snapshot!(TestContext::setup_clone(), "C");
+}
+
+#[test]
+fn clone_shallow() {
+    let mut ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "second-file", "");
+    // Local clones ignore --depth, unless given as a URL
+    let from = format!("file://{}", ctx.dir.path().to_str().unwrap());
+    let into = ctx.remote_dir.child("cloned");
+
+    let mut state = ctx.init_state();
+    state
+        .update(
+            &mut ctx.term,
+            &keys(&format!(
+                "C-d1<enter>C{}<enter>{}<enter>",
+                from,
+                into.to_str().unwrap()
+            )),
+        )
+        .unwrap();
+
+    // Continues in the clone
+    assert_eq!(
+        state.repo.workdir().unwrap().canonicalize().unwrap(),
+        into.canonicalize().unwrap()
+    );
+    assert!(git2::Repository::open(into).unwrap().is_shallow());
+}
+
+#[test]
+fn clone_invalid_url() {
+    snapshot!(TestContext::setup_clone(), "CCnot a repository<enter>");
+}
+
+#[test]
+fn clone_into_non_empty_dir() {
+    let mut ctx = TestContext::setup_clone();
+    let keys = format!(
+        "CC{}<enter>{}<enter>",
+        ctx.remote_dir.path().to_str().unwrap(),
+        ctx.dir.path().to_str().unwrap()
+    );
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &crate::tests::keys(&keys))
+        .unwrap();
+
+    // Not a snapshot, the length of the temp dir's path shifts what's shown
+    assert!(ctx
+        .redact_buffer()
+        .contains(" already exists and isn't empty"));
+}
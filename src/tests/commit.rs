@@ -32,3 +32,315 @@ fn commit_instant_fixup_stashes_changes_and_keeps_empty() {
 
     insta::assert_snapshot!(ctx.redact_buffer());
 }
+
+#[test]
+fn commit_menu_signoff_from_config() {
+    let mut ctx = TestContext::setup_init();
+    ctx.config().commit.signoff.enabled = true;
+
+    snapshot!(ctx, "c");
+}
+
+fn setup_amend() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file.txt", "initial\n");
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    ctx
+}
+
+#[test]
+fn commit_amend_signoff() {
+    let mut ctx = setup_amend();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("c-sa")).unwrap();
+
+    assert_eq!(
+        head_message(&ctx),
+        "add file.txt\n\nCommit body goes here\n\nSigned-off-by: Committer Name <committer@email.com>\n\n"
+    );
+}
+
+#[test]
+fn commit_amend_signoff_again() {
+    let mut ctx = setup_amend();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("c-sa")).unwrap();
+    state.update(&mut ctx.term, &keys("c-sa")).unwrap();
+
+    assert_eq!(
+        head_message(&ctx),
+        "add file.txt\n\nCommit body goes here\n\nSigned-off-by: Committer Name <committer@email.com>\n\n"
+    );
+}
+
+fn setup_others_commit() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file.txt", "initial\n");
+    fs::write(ctx.dir.child("file.txt"), "theirs\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    std::process::Command::new("git")
+        .args(["commit", "-m", "their change"])
+        .args(["--author", "Other Person <other@example.com>"])
+        .env("GIT_COMMITTER_NAME", "Other Committer")
+        .current_dir(ctx.dir.path())
+        .output()
+        .unwrap();
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    ctx
+}
+
+fn head_author_and_committer(ctx: &TestContext) -> String {
+    git(
+        ctx.dir.path(),
+        &["log", "-1", "--format=%an <%ae> / %cn <%ce>"],
+    )
+}
+
+fn head_message(ctx: &TestContext) -> String {
+    git(ctx.dir.path(), &["log", "-1", "--format=%B"])
+}
+
+#[test]
+fn commit_co_authored_prompt() {
+    snapshot!(setup_others_commit(), "co");
+}
+
+#[test]
+fn commit_co_authored() {
+    let mut ctx = setup_others_commit();
+    fs::write(ctx.dir.child("file.txt"), "paired\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("coother<enter>"))
+        .unwrap();
+
+    assert_eq!(
+        head_message(&ctx),
+        "Co-authored-by: Other Person <other@example.com>\n\n"
+    );
+}
+
+#[test]
+fn commit_co_authored_ambiguous() {
+    let mut ctx = setup_others_commit();
+    ctx.config()
+        .commit
+        .co_authors
+        .push("Another Person <another@example.com>".into());
+    snapshot!(ctx, "coperson<enter>");
+}
+
+#[test]
+fn commit_amend_co_authored_merges_trailers() {
+    let mut ctx = setup_others_commit();
+    ctx.config()
+        .commit
+        .co_authors
+        .push("Ada Lovelace <ada@example.com>".into());
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("cOada<enter>")).unwrap();
+    state
+        .update(&mut ctx.term, &keys("cOother, ada<enter>"))
+        .unwrap();
+
+    assert_eq!(
+        head_message(&ctx),
+        "their change\n\n\
+        Co-authored-by: Ada Lovelace <ada@example.com>\n\
+        Co-authored-by: Other Person <other@example.com>\n\n"
+    );
+}
+
+#[test]
+fn commit_amend_keeps_author() {
+    let mut ctx = setup_others_commit();
+    let mut state = ctx.init_state();
+    fs::write(ctx.dir.child("file.txt"), "amended\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+
+    state.update(&mut ctx.term, &keys("c")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    state.update(&mut ctx.term, &keys("a")).unwrap();
+
+    assert_eq!(
+        head_author_and_committer(&ctx),
+        "Other Person <other@example.com> / Committer Name <committer@email.com>\n"
+    );
+}
+
+#[test]
+fn commit_amend_reset_author() {
+    let mut ctx = setup_others_commit();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("c-R")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    state.update(&mut ctx.term, &keys("a")).unwrap();
+
+    assert_eq!(
+        head_author_and_committer(&ctx),
+        "Author Name <author@email.com> / Committer Name <committer@email.com>\n"
+    );
+}
+
+fn setup_scissors_commit() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file.txt", "initial\n");
+    fs::write(ctx.dir.child("file.txt"), "changed\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    ctx
+}
+
+#[test]
+fn commit_scissors_from_config() {
+    let ctx = setup_scissors_commit();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "commit.cleanup", "scissors"],
+    );
+
+    snapshot!(ctx, "c");
+}
+
+#[test]
+fn commit_scissors_template() {
+    let mut ctx = setup_scissors_commit();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("c-xc")).unwrap();
+
+    // Git hands the template to the editor (without its leading blank line), then gitu removes it
+    let template = fs::read_to_string(ctx.dir.child(".git/COMMIT_EDITMSG")).unwrap();
+    assert!(!ctx.dir.child(".git/GITU_COMMIT_TEMPLATE").exists());
+    assert!(template.starts_with("# ------------------------ >8 ------------------------\n"));
+    assert!(template.contains("diff --git a/file.txt b/file.txt\n"));
+    assert!(template.ends_with("-initial\n+changed\n"));
+    // Like git, with nothing written above the scissors line the commit is aborted
+    assert_eq!(
+        head_message(&ctx),
+        "add file.txt\n\nCommit body goes here\n\n"
+    );
+}
+
+#[test]
+fn commit_amend_scissors_strips_diff() {
+    let mut ctx = setup_scissors_commit();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("c-xa")).unwrap();
+
+    assert_eq!(
+        head_message(&ctx),
+        "add file.txt\n\nCommit body goes here\n\n"
+    );
+    assert_eq!(git(ctx.dir.path(), &["show", "HEAD:file.txt"]), "changed\n");
+}
+
+fn setup_trailing_whitespace() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file.txt", "initial\n");
+    fs::write(ctx.dir.child("file.txt"), "initial\nspaces  \nclean\ntab\t").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    ctx
+}
+
+#[test]
+fn commit_whitespace_cleaned_preview() {
+    let ctx = setup_trailing_whitespace();
+    snapshot!(ctx, "cw");
+}
+
+#[test]
+fn commit_whitespace_cleaned() {
+    let mut ctx = setup_trailing_whitespace();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("cwy")).unwrap();
+
+    assert_eq!(
+        git(ctx.dir.path(), &["show", ":file.txt"]),
+        "initial\nspaces\nclean\ntab"
+    );
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("file.txt")).unwrap(),
+        "initial\nspaces  \nclean\ntab\t"
+    );
+}
+
+#[test]
+fn commit_whitespace_cleaned_follows_core_whitespace() {
+    let mut ctx = setup_trailing_whitespace();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "core.whitespace", "-trailing-space"],
+    );
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("cw")).unwrap();
+
+    assert!(state.prompt.data.is_none());
+    assert_eq!(
+        git(ctx.dir.path(), &["show", ":file.txt"]),
+        "initial\nspaces  \nclean\ntab\t"
+    );
+}
+
+fn setup_staged() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file.txt", "initial\n");
+    fs::write(ctx.dir.child("file.txt"), "changed\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    ctx
+}
+
+#[test]
+fn quick_commit_prompt() {
+    snapshot!(setup_staged(), "cmAdd a feature");
+}
+
+#[test]
+fn quick_commit_with_body() {
+    let mut ctx = setup_staged();
+    let mut state = ctx.init_state();
+    state
+        .update(
+            &mut ctx.term,
+            &keys("cmAdd a feature\\n\\nIt does things<enter>"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        git(ctx.dir.path(), &["log", "-1", "--format=%B"]),
+        "Add a feature\n\nIt does things\n\n"
+    );
+}
+
+#[test]
+fn quick_commit_continued_in_editor() {
+    let mut ctx = setup_staged();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "core.editor", "echo 'More details' >>"],
+    );
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("cmAdd a feature<ctrl+o>"))
+        .unwrap();
+
+    assert_eq!(
+        git(ctx.dir.path(), &["log", "-1", "--format=%B"]),
+        "Add a feature\n\nMore details\n\n"
+    );
+}
+
+#[test]
+fn quick_commit_editor_failed_returns_to_prompt() {
+    let ctx = setup_staged();
+    run(ctx.dir.path(), &["git", "config", "core.editor", "false"]);
+    snapshot!(ctx, "cmAdd a feature<ctrl+o>");
+}
@@ -0,0 +1,52 @@
+use super::*;
+
+fn setup_conflict() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "new-file", "hello\n");
+
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "new-file", "hey\n");
+
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "new-file", "hi\n");
+
+    run(ctx.dir.path(), &["git", "merge", "other-branch"]);
+    ctx
+}
+
+#[test]
+fn show_conflicts() {
+    let ctx = setup_conflict();
+    snapshot!(ctx, "jje");
+}
+
+#[test]
+fn show_conflicts_diff3() {
+    let ctx = TestContext::setup_clone();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "merge.conflictStyle", "diff3"],
+    );
+    commit(ctx.dir.path(), "new-file", "hello\n");
+
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "new-file", "hey\n");
+
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "new-file", "hi\n");
+
+    run(ctx.dir.path(), &["git", "merge", "other-branch"]);
+    snapshot!(ctx, "jje");
+}
+
+#[test]
+fn accept_theirs() {
+    let ctx = setup_conflict();
+    snapshot!(ctx, "jjejt");
+}
+
+#[test]
+fn accept_both() {
+    let ctx = setup_conflict();
+    snapshot!(ctx, "jjeja<esc>");
+}
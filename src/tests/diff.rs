@@ -0,0 +1,299 @@
+use super::*;
+use crate::git::diff_options::DiffAlgorithm;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+    fs::write(ctx.dir.child("testfile"), "1\n2\n3\n4\nfive\n6\n7\n8\n9\n").unwrap();
+    ctx
+}
+
+#[test]
+fn diff_menu() {
+    snapshot!(setup(), "D");
+}
+
+#[test]
+fn diff_less_context() {
+    snapshot!(setup(), "jj<tab>D--q");
+}
+
+#[test]
+fn diff_more_context() {
+    snapshot!(setup(), "jj<tab>D+q");
+}
+
+#[test]
+fn diff_ignore_whitespace() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "testing\n");
+    fs::write(ctx.dir.child("testfile"), "testing  \n").unwrap();
+    snapshot!(ctx, "Dwq");
+}
+
+#[test]
+fn diff_ignore_whitespace_in_hunk() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "testing\nmore\n");
+    fs::write(ctx.dir.child("testfile"), "testing  \nmore!\n").unwrap();
+    snapshot!(ctx, "Dwqjj<tab>");
+}
+
+#[test]
+fn range_diff() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "1\n2\n3\n");
+    commit(ctx.dir.path(), "other-file", "1\n2\n3\n");
+    run(ctx.dir.path(), &["git", "branch", "old"]);
+    fs::write(ctx.dir.child("other-file"), "1\n2\n3\n4\n").unwrap();
+    run(
+        ctx.dir.path(),
+        &["git", "commit", "-a", "--amend", "--no-edit"],
+    );
+    commit(ctx.dir.path(), "new-file", "");
+    snapshot!(ctx, "Drold...main<enter>");
+}
+
+#[test]
+fn diff_raw_patch() {
+    snapshot!(setup(), "jj<tab>Dpq");
+}
+
+#[test]
+fn diff_raw_patch_toggled_back() {
+    snapshot!(setup(), "jj<tab>Dppq");
+}
+
+fn setup_branches() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["git", "checkout", "-b", "feature"]);
+    commit(ctx.dir.path(), "feature-file", "hello\n");
+    commit(ctx.dir.path(), "feature-file", "hello\nworld\n");
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    ctx
+}
+
+#[test]
+fn diff_refs() {
+    snapshot!(setup_branches(), "Ddmain..feature<enter>");
+}
+
+#[test]
+fn diff_refs_separated_by_space() {
+    snapshot!(setup_branches(), "Ddmain feature<enter>");
+}
+
+#[test]
+fn diff_refs_completion() {
+    snapshot!(setup_branches(), "Ddma<tab>..fe<tab>");
+}
+
+#[test]
+fn diff_refs_invalid_ref() {
+    snapshot!(setup_branches(), "Ddmain..nonexistent<enter>");
+}
+
+#[test]
+fn diff_refs_staging_disabled() {
+    snapshot!(setup_branches(), "Ddmain..feature<enter>jjs");
+}
+
+fn setup_diverged_branches() -> TestContext {
+    let ctx = setup_branches();
+    commit(ctx.dir.path(), "main-file", "only on main\n");
+    run(ctx.dir.path(), &["git", "checkout", "feature"]);
+    ctx
+}
+
+#[test]
+fn diff_merge_base() {
+    snapshot!(setup_diverged_branches(), "Dmmain<enter>");
+}
+
+#[test]
+fn diff_merge_base_completion() {
+    snapshot!(setup_diverged_branches(), "Dmma<tab>");
+}
+
+#[test]
+fn diff_merge_base_invalid_ref() {
+    snapshot!(setup_diverged_branches(), "Dmnonexistent<enter>");
+}
+
+fn setup_long_line() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "short\nold\n");
+    let long = "word ".repeat(40);
+    fs::write(ctx.dir.child("testfile"), format!("short\n{long}\n")).unwrap();
+    ctx
+}
+
+#[test]
+fn diff_long_line_cut_off() {
+    snapshot!(setup_long_line(), "jj<tab>");
+}
+
+#[test]
+fn diff_wrap() {
+    snapshot!(setup_long_line(), "jj<tab>Dlq");
+}
+
+#[test]
+fn diff_wrap_select_lines() {
+    snapshot!(setup_long_line(), "jj<tab>Dlq<ctrl+j><ctrl+j><ctrl+j>");
+}
+
+#[test]
+fn diff_wrap_config() {
+    let mut ctx = setup_long_line();
+    ctx.config().diff.wrap.enabled = true;
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn diff_over_max_line_bytes() {
+    let mut ctx = setup_long_line();
+    ctx.config().diff.max_line_bytes = 60;
+    snapshot!(ctx, "jj<tab>Dlq");
+}
+
+#[test]
+fn diff_huge_line() {
+    let ctx = TestContext::setup_init();
+    let huge = "word ".repeat(1_000_000);
+    commit(ctx.dir.path(), "bundle.min.js", &format!("{huge}old\n"));
+    fs::write(ctx.dir.child("bundle.min.js"), format!("new {huge}\n")).unwrap();
+    // Compared word by word, this wouldn't finish
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn diff_scroll_right() {
+    snapshot!(setup_long_line(), "jj<tab><right><right>");
+}
+
+#[test]
+fn diff_scroll_left() {
+    snapshot!(setup_long_line(), "jj<tab><right><right><left>");
+}
+
+#[test]
+fn diff_scroll_line_end() {
+    snapshot!(setup_long_line(), "jj<tab><end><right>");
+}
+
+#[test]
+fn diff_scroll_line_start() {
+    snapshot!(setup_long_line(), "jj<tab><end><home>");
+}
+
+fn setup_moved_line() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "code.rs", "}\n}\n}\nfn main() {\n}\n");
+    fs::write(ctx.dir.child("code.rs"), "}\n}\nfn main() {\n}\n}\n").unwrap();
+    ctx
+}
+
+#[test]
+fn diff_patience() {
+    snapshot!(setup_moved_line(), "jj<tab>");
+}
+
+#[test]
+fn diff_next_algorithm() {
+    snapshot!(setup_moved_line(), "jj<tab>Da");
+}
+
+#[test]
+fn diff_algorithm_saved() {
+    let mut ctx = setup_moved_line();
+    let state_dir = temp_dir::TempDir::new().unwrap();
+    let state_path = state_dir.child("state.toml");
+    ctx.config().state_path = Some(state_path.clone());
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("Da")).unwrap();
+    assert_eq!(
+        crate::config::read_state(&state_path).diff_algorithm,
+        Some(DiffAlgorithm::Myers)
+    );
+
+    // The next run starts with it
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn diff_algorithm_config() {
+    let mut ctx = setup_moved_line();
+    ctx.config().diff.algorithm = DiffAlgorithm::Myers;
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn stage_hunk_with_myers() {
+    let mut ctx = setup_moved_line();
+    ctx.config().diff.algorithm = DiffAlgorithm::Myers;
+    snapshot!(ctx, "jj<tab>js");
+}
+
+fn setup_full_file() -> TestContext {
+    let ctx = TestContext::setup_init();
+    let lines = (1..=16)
+        .map(|i| format!("line {}\n", i))
+        .collect::<Vec<_>>();
+    commit(ctx.dir.path(), "testfile", &lines.concat());
+
+    let mut changed = lines;
+    changed[1] = "changed 2\n".into();
+    changed.remove(11);
+    fs::write(ctx.dir.child("testfile"), changed.concat()).unwrap();
+    ctx
+}
+
+#[test]
+fn show_full_file() {
+    snapshot!(setup_full_file(), "jjv");
+}
+
+#[test]
+fn show_full_file_at_hunk() {
+    snapshot!(setup_full_file(), "jj<tab>jjv");
+}
+
+#[test]
+fn hunk_header_function_context() {
+    let ctx = TestContext::setup_init();
+    commit(
+        ctx.dir.path(),
+        "lib.rs",
+        "pub fn add(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    println!(\"adding\");\n    \
+        println!(\"{a} + {b}\");\n    println!(\"= {sum}\");\n    sum\n}\n",
+    );
+    fs::write(
+        ctx.dir.child("lib.rs"),
+        "pub fn add(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    println!(\"adding\");\n    \
+        println!(\"{a} + {b}\");\n    println!(\"= {sum}\");\n    sum + 0\n}\n",
+    )
+    .unwrap();
+    snapshot!(ctx, "jj<tab>");
+}
+
+fn setup_stat() -> TestContext {
+    let ctx = setup();
+    let lines = (1..=60).map(|i| format!("{}\n", i)).collect::<String>();
+    fs::write(ctx.dir.child("long-file"), lines).unwrap();
+    run(ctx.dir.path(), &["git", "add", "long-file"]);
+    ctx
+}
+
+#[test]
+fn diff_toggle_stat() {
+    snapshot!(setup_stat(), "Dsq");
+}
+
+#[test]
+fn diff_stat_config() {
+    let mut ctx = setup_stat();
+    ctx.config().status.diffstat.enabled = true;
+    snapshot!(ctx, "Dsq");
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::{Confirm, ConfirmWhen};
 
 #[test]
 pub(crate) fn discard_branch_confirm_prompt() {
@@ -70,6 +71,31 @@ pub(crate) fn discard_staged_file() {
     snapshot!(ctx, "jjKy");
 }
 
+#[test]
+pub(crate) fn discard_without_confirming() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().confirm.discard = Confirm::When(ConfirmWhen::Never);
+    commit(ctx.dir.path(), "file-one", "FOO\nBAR\n");
+    fs::write(ctx.dir.child("file-one"), "blahonga\n").unwrap();
+    snapshot!(ctx, "jjK");
+}
+
+#[test]
+pub(crate) fn clean_untracked_below_threshold() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().confirm.clean = Confirm::AboveFiles(2);
+    run(ctx.dir.path(), &["touch", "one", "two"]);
+    snapshot!(ctx, "jK");
+}
+
+#[test]
+pub(crate) fn clean_untracked_above_threshold() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().confirm.clean = Confirm::AboveFiles(2);
+    run(ctx.dir.path(), &["touch", "one", "two", "three"]);
+    snapshot!(ctx, "jK");
+}
+
 // FIXME Deleting branches doesn't work with the test-setup
 // #[test]
 // fn discard_branch() {
@@ -80,3 +106,29 @@ pub(crate) fn discard_staged_file() {
 //         .unwrap();
 //     insta::assert_snapshot!(ctx.redact_buffer());
 // }
+
+#[test]
+pub(crate) fn restore_file_prompt() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "file-one", "FOO\nBAR\n");
+    fs::write(ctx.dir.child("file-one"), "blahonga\n").unwrap();
+    snapshot!(ctx, "jjR");
+}
+
+#[test]
+pub(crate) fn restore_staged_and_unstaged_file() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "file-one", "FOO\nBAR\n");
+    fs::write(ctx.dir.child("file-one"), "staged\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file-one"]);
+    fs::write(ctx.dir.child("file-one"), "unstaged\n").unwrap();
+    snapshot!(ctx, "jjRy");
+}
+
+#[test]
+pub(crate) fn restore_added_file() {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["touch", "new-file"]);
+    run(ctx.dir.path(), &["git", "add", "new-file"]);
+    snapshot!(ctx, "jjRy");
+}
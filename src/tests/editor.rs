@@ -1,7 +1,8 @@
 use super::*;
 
-fn setup_scroll() -> (TestContext, crate::state::State) {
+fn setup_scroll(scrolloff: usize) -> (TestContext, crate::state::State) {
     let mut ctx = TestContext::setup_init();
+    ctx.config().ui.scrolloff = scrolloff;
     for file in ["file-1", "file-2", "file-3"] {
         commit(ctx.dir.path(), file, "");
         fs::write(
@@ -25,7 +26,7 @@ fn setup_scroll() -> (TestContext, crate::state::State) {
 
 #[test]
 fn scroll_down() {
-    let (mut ctx, mut state) = setup_scroll();
+    let (mut ctx, mut state) = setup_scroll(0);
 
     state.update(&mut ctx.term, &keys("<ctrl+d>")).unwrap();
 
@@ -34,7 +35,7 @@ fn scroll_down() {
 
 #[test]
 fn scroll_past_selection() {
-    let (mut ctx, mut state) = setup_scroll();
+    let (mut ctx, mut state) = setup_scroll(0);
 
     state
         .update(&mut ctx.term, &keys("<ctrl+d><ctrl+d><ctrl+d>"))
@@ -43,9 +44,37 @@ fn scroll_past_selection() {
     insta::assert_snapshot!(ctx.redact_buffer());
 }
 
+#[test]
+fn scrolloff_moving_down() {
+    let (mut ctx, mut state) = setup_scroll(5);
+    state.update(&mut ctx.term, &keys("jj")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn scrolloff_moving_up() {
+    let (mut ctx, mut state) = setup_scroll(5);
+    state.update(&mut ctx.term, &keys("jjjjjjjjkkkk")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn scrolloff_at_bottom() {
+    let (mut ctx, mut state) = setup_scroll(5);
+    state.update(&mut ctx.term, &keys("jjjjjjjjj")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn scrolloff_larger_than_screen() {
+    let (mut ctx, mut state) = setup_scroll(100);
+    state.update(&mut ctx.term, &keys("jj")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
 #[test]
 fn move_prev_sibling() {
-    let (mut ctx, mut state) = setup_scroll();
+    let (mut ctx, mut state) = setup_scroll(0);
     state
         .update(&mut ctx.term, &keys("<alt+k><alt+k>"))
         .unwrap();
@@ -54,14 +83,14 @@ fn move_prev_sibling() {
 
 #[test]
 fn move_next_sibling() {
-    let (mut ctx, mut state) = setup_scroll();
+    let (mut ctx, mut state) = setup_scroll(0);
     state.update(&mut ctx.term, &keys("<alt+j>")).unwrap();
     insta::assert_snapshot!(ctx.redact_buffer());
 }
 
 #[test]
 fn move_next_then_parent_section() {
-    let (mut ctx, mut state) = setup_scroll();
+    let (mut ctx, mut state) = setup_scroll(0);
     state
         .update(&mut ctx.term, &keys("<alt+j><alt+h>"))
         .unwrap();
@@ -77,3 +106,38 @@ fn exit_from_prompt_shows_menu() {
 fn re_enter_prompt_from_menu() {
     snapshot!(TestContext::setup_init(), "bb<esc>b");
 }
+
+fn setup_go_to_line() -> TestContext {
+    let ctx = TestContext::setup_init();
+    let lines = (1..=40)
+        .map(|i| format!("line {}\n", i))
+        .collect::<Vec<_>>();
+    commit(ctx.dir.path(), "file", &lines.concat());
+
+    let mut changed = lines;
+    changed[4] = "changed 5\n".into();
+    changed[29] = "changed 30\n".into();
+    fs::write(ctx.dir.child("file"), changed.concat()).unwrap();
+    ctx
+}
+
+#[test]
+fn go_to_line() {
+    let mut ctx = setup_go_to_line();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys(":31<enter>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn go_to_unchanged_line() {
+    let mut ctx = setup_go_to_line();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jj:18<enter>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn go_to_invalid_line() {
+    snapshot!(setup_go_to_line(), ":0<enter>");
+}
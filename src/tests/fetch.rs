@@ -1,4 +1,5 @@
 use super::*;
+use std::{thread, time::Duration};
 
 #[test]
 fn fetch_from_elsewhere_prompt() {
@@ -9,3 +10,24 @@ fn fetch_from_elsewhere_prompt() {
 fn fetch_from_elsewhere() {
     snapshot!(TestContext::setup_clone(), "feorigin<enter>");
 }
+
+#[test]
+fn auto_fetch() {
+    let mut ctx = TestContext::setup_clone();
+    clone_and_commit(&ctx.remote_dir, "remote-file", "hello");
+    ctx.config().fetch.auto_interval = 5;
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &[]).unwrap();
+    assert!(!state.auto_fetch.is_running());
+
+    state.auto_fetch.last_fetch -= Duration::from_secs(5 * 60);
+    while {
+        state.update(&mut ctx.term, &[]).unwrap();
+        state.auto_fetch.is_running()
+    } {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
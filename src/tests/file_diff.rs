@@ -0,0 +1,73 @@
+use super::*;
+use crate::cli::{Args, Commands};
+use git2::Repository;
+use std::path::PathBuf;
+
+fn diff_args(file: Option<PathBuf>, staged: bool) -> Args {
+    Args {
+        command: Some(Commands::Diff { staged, file }),
+        ..Default::default()
+    }
+}
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "changed", "one\n");
+    commit(ctx.dir.path(), "unchanged", "two\n");
+    fs::write(ctx.dir.child("changed"), "one\nmore\n").unwrap();
+    fs::write(ctx.dir.child("other"), "other\n").unwrap();
+    ctx
+}
+
+#[test]
+fn diff_of_file() {
+    let mut ctx = setup();
+    let repo = Repository::open(ctx.dir.path()).unwrap();
+    ctx.init_state_with_args(repo, &diff_args(Some(ctx.dir.child("changed")), false))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn staged_diff_of_file() {
+    let mut ctx = setup();
+    run(ctx.dir.path(), &["git", "add", "changed"]);
+    let repo = Repository::open(ctx.dir.path()).unwrap();
+    ctx.init_state_with_args(repo, &diff_args(Some(ctx.dir.child("changed")), true))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn staged_diff() {
+    let mut ctx = setup();
+    run(ctx.dir.path(), &["git", "add", "changed", "other"]);
+    let repo = Repository::open(ctx.dir.path()).unwrap();
+    ctx.init_state_with_args(repo, &diff_args(None, true))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn diff_of_unchanged_file() {
+    let mut ctx = setup();
+    let repo = Repository::open(ctx.dir.path()).unwrap();
+    ctx.init_state_with_args(repo, &diff_args(Some(ctx.dir.child("unchanged")), false))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn diff_of_file_outside_repo() {
+    let mut ctx = setup();
+    let repo = Repository::open(ctx.dir.path()).unwrap();
+    let outside = ctx.remote_dir.path().to_path_buf();
+    let Err(error) = ctx.init_state_with_args(repo, &diff_args(Some(outside.clone()), false))
+    else {
+        panic!("Expected an error");
+    };
+    assert_eq!(
+        error.to_string(),
+        format!("{} isn't a file in the repository", outside.display())
+    );
+}
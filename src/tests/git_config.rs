@@ -0,0 +1,76 @@
+use super::*;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_init();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "rebase.autosquash", "false"],
+    );
+    ctx
+}
+
+fn local_config(ctx: &TestContext, key: &str) -> String {
+    git(ctx.dir.path(), &["config", "--local", "--get", key])
+}
+
+#[test]
+fn config_menu() {
+    snapshot!(setup(), "G");
+}
+
+#[test]
+fn show_git_config() {
+    snapshot!(setup(), "GG");
+}
+
+#[test]
+fn config_set_prompt() {
+    snapshot!(setup(), "GGjjjjjjjjjjGs");
+}
+
+#[test]
+fn config_set() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state
+        .update(&mut ctx.term, &keys("GGjjjjjjjjjjGstrue<enter>"))
+        .unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(local_config(&ctx, "rebase.autosquash"), "true\n");
+}
+
+#[test]
+fn config_set_invalid_bool() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state
+        .update(&mut ctx.term, &keys("GGjjjjjjjjjjGsmaybe<enter>"))
+        .unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(local_config(&ctx, "rebase.autosquash"), "false\n");
+}
+
+#[test]
+fn config_set_read_only() {
+    snapshot!(setup(), "GGjjjjGs");
+}
+
+#[test]
+fn config_set_in_scope() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state
+        .update(
+            &mut ctx.term,
+            &keys("GGjjGsuser.email=ada@example.com<enter>"),
+        )
+        .unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(local_config(&ctx, "user.email"), "ada@example.com\n");
+}
@@ -68,6 +68,12 @@ pub fn set_env_vars() {
     env::set_var("GIT_COMMITTER_EMAIL", "committer@email.com");
     env::set_var("GIT_COMMITTER_DATE", "Sun Feb 18 14:00 2024 +0100");
     env::set_var("LC_ALL", "C");
+    // Tests that open an editor set `core.editor`, any other fails rather than waits for input
+    env::remove_var("GIT_EDITOR");
+    env::remove_var("VISUAL");
+    env::set_var("EDITOR", "false");
+    // So that opening pages in a browser does nothing
+    env::set_var("BROWSER", "true");
 }
 
 pub fn run(dir: &Path, cmd: &[&str]) -> String {
@@ -82,6 +88,27 @@ pub fn run(dir: &Path, cmd: &[&str]) -> String {
     .unwrap()
 }
 
+/// Runs git in `dir` and returns what it printed, to assert on the state of a repository.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    String::from_utf8(git_bytes(dir, args)).unwrap()
+}
+
+pub fn git_bytes(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap_or_else(|_| panic!("failed to execute git {:?}", args));
+
+    assert!(
+        out.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+    out.stdout
+}
+
 fn set_config(path: &Path) {
     run(path, &["git", "config", "user.email", "ci@example.com"]);
     run(path, &["git", "config", "user.name", "CI"]);
@@ -111,3 +138,14 @@ pub fn commit(dir: &Path, file_name: &str, contents: &str) {
     run(dir, &["git", "add", file_name]);
     run(dir, &["git", "commit", "-m", &message]);
 }
+
+/// Leaves one file of each kind that the status screen lists: `staged-file` and `unstaged-file`
+/// are committed and then changed, and `untracked-file` is new.
+pub fn make_changes(dir: &Path) {
+    commit(dir, "staged-file", "committed\n");
+    commit(dir, "unstaged-file", "committed\n");
+    fs::write(dir.join("staged-file"), "staged\n").expect("error writing to file");
+    run(dir, &["git", "add", "staged-file"]);
+    fs::write(dir.join("unstaged-file"), "unstaged\n").expect("error writing to file");
+    fs::write(dir.join("untracked-file"), "untracked\n").expect("error writing to file");
+}
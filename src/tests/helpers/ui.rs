@@ -5,6 +5,7 @@ use crate::{
     state::State,
     term::{Term, TermBackend},
     tests::helpers::RepoTestContext,
+    Res,
 };
 use crossterm::event::{Event, KeyEvent};
 use git2::Repository;
@@ -73,18 +74,25 @@ impl TestContext {
     }
 
     pub fn init_state_at_path(&mut self, path: PathBuf) -> State {
+        self.init_state_with_repo(Repository::open(path).unwrap())
+    }
+
+    pub fn init_state_with_repo(&mut self, repo: Repository) -> State {
+        self.init_state_with_args(repo, &Args::default()).unwrap()
+    }
+
+    pub fn init_state_with_args(&mut self, repo: Repository, args: &Args) -> Res<State> {
         let mut state = State::create(
-            Rc::new(Repository::open(path).unwrap()),
+            Rc::new(repo),
             self.size,
-            &Args::default(),
+            args,
             Rc::clone(&self.config),
             false,
-        )
-        .unwrap();
+        )?;
 
         // hack: Pass in an event just to force re-rendering
-        state.update(&mut self.term, &[Event::FocusGained]).unwrap();
-        state
+        state.update(&mut self.term, &[Event::FocusGained])?;
+        Ok(state)
     }
 
     pub fn redact_buffer(&self) -> String {
@@ -0,0 +1,62 @@
+use super::*;
+use crate::{ops::Op, state::State, term::Term};
+use std::{thread, time::Duration};
+
+fn await_hooks(state: &mut State, term: &mut Term) {
+    while {
+        state.update(term, &[]).unwrap();
+        state.hooks.is_running()
+    } {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn hook_after_stage() {
+    let mut ctx = TestContext::setup_clone();
+    fs::write(ctx.dir.child("new-file"), "hello\n").unwrap();
+    ctx.config().hooks.insert(
+        Op::Stage,
+        "printf '%s:%s' \"$GITU_ACTION\" \"$GITU_STAGED_PATHS\" > .git/hook-out".into(),
+    );
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("js")).unwrap();
+    await_hooks(&mut state, &mut ctx.term);
+
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child(".git/hook-out")).unwrap(),
+        "stage:new-file"
+    );
+}
+
+#[test]
+fn failing_hook() {
+    let mut ctx = TestContext::setup_clone();
+    fs::write(ctx.dir.child("new-file"), "hello\n").unwrap();
+    ctx.config()
+        .hooks
+        .insert(Op::Stage, "echo formatting failed; exit 3".into());
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("js")).unwrap();
+    await_hooks(&mut state, &mut ctx.term);
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn no_hook_after_failed_action() {
+    let mut ctx = TestContext::setup_clone();
+    fs::write(ctx.dir.child("new-file"), "hello\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "new-file"]);
+    ctx.config()
+        .hooks
+        .insert(Op::Commit, "touch .git/hook-out".into());
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("cc")).unwrap();
+    await_hooks(&mut state, &mut ctx.term);
+
+    assert!(!ctx.dir.child(".git/hook-out").exists());
+}
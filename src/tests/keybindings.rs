@@ -0,0 +1,19 @@
+use super::*;
+use crate::{menu::Menu, ops::Op};
+
+#[test]
+fn show_keybindings() {
+    snapshot!(TestContext::setup_init(), "?");
+}
+
+#[test]
+fn remapped_keybinding_listed() {
+    let mut ctx = TestContext::setup_init();
+    ctx.config()
+        .bindings
+        .get_mut(&Menu::Root)
+        .unwrap()
+        .insert(Op::ShowRefs, vec!["<ctrl+r>".into()]);
+
+    snapshot!(ctx, "?/Show Refs<enter>");
+}
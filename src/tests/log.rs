@@ -77,3 +77,53 @@ fn log_other_input() {
 fn log_other_invalid() {
     snapshot!(setup(), "lo <enter>");
 }
+
+fn setup_authors() -> TestContext {
+    let ctx = setup();
+    fs::write(ctx.dir.child("second commit"), "changed").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "commit",
+            "--author",
+            "Other Author <other@email.com>",
+            "-m",
+            "change second commit",
+        ],
+    );
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "commit",
+            "--allow-empty",
+            "--author",
+            "Other Author <other@email.com>",
+            "-m",
+            "empty commit",
+        ],
+    );
+    ctx
+}
+
+#[test]
+fn author_filter() {
+    snapshot!(setup_authors(), "l-AOther<enter>l");
+}
+
+#[test]
+fn path_filter() {
+    snapshot!(setup_authors(), "l--second commit<enter>l");
+}
+
+#[test]
+fn author_and_path_filter() {
+    snapshot!(setup_authors(), "l-AOther<enter>--second commit<enter>l");
+}
+
+#[test]
+fn clear_filters() {
+    snapshot!(setup_authors(), "l-AOther<enter>llx");
+}
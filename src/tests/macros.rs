@@ -0,0 +1,71 @@
+use super::*;
+use crate::config::Confirm;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["touch", "one", "two", "three"]);
+    ctx
+}
+
+#[test]
+fn record_macro_prompt() {
+    snapshot!(setup(), "M");
+}
+
+#[test]
+fn recording_macro() {
+    snapshot!(setup(), "Ma<enter>jj");
+}
+
+#[test]
+fn replay_macro() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("jjMa<enter>sM")).unwrap();
+    assert_eq!(
+        git(ctx.dir.path(), &["diff", "--cached", "--name-only"]),
+        "one\n"
+    );
+
+    state.update(&mut ctx.term, &keys("@2a<enter>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        git(ctx.dir.path(), &["diff", "--cached", "--name-only"]),
+        "one\nthree\ntwo\n"
+    );
+}
+
+#[test]
+fn replay_macro_prompt() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("Ma<enter>gM")).unwrap();
+    state.update(&mut ctx.term, &keys("@")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn replay_unknown_macro() {
+    snapshot!(setup(), "@b<enter>");
+}
+
+#[test]
+fn replay_macro_stops_at_new_prompt() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().confirm.clean = Confirm::AboveFiles(1);
+    run(ctx.dir.path(), &["touch", "one"]);
+    let mut state = ctx.init_state();
+
+    // Cleaning a single file doesn't ask
+    state.update(&mut ctx.term, &keys("jMa<enter>KgM")).unwrap();
+    assert!(!ctx.dir.child("one").exists());
+
+    run(ctx.dir.path(), &["touch", "two", "three"]);
+    state
+        .update(&mut ctx.term, &keys("gkkkkj@a<enter>"))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(ctx.dir.child("two").exists());
+}
@@ -3,22 +3,38 @@ use std::fs;
 #[macro_use]
 mod helpers;
 mod arg;
+mod bisect;
+mod blame;
+mod browse;
+mod clone;
 mod commit;
+mod conflict;
+mod diff;
 mod discard;
 mod editor;
 mod fetch;
+mod file_diff;
+mod git_config;
+mod hooks;
+mod keybindings;
 mod log;
+mod macros;
+mod notes;
+mod output;
+mod patch;
 mod pull;
 mod push;
 mod quit;
 mod rebase;
 mod remote;
 mod reset;
+mod sparse_checkout;
 mod stage;
 mod stash;
 mod unstage;
+mod worktree;
 
-use helpers::{clone_and_commit, commit, keys, run, TestContext};
+use helpers::{clone_and_commit, commit, git, git_bytes, keys, make_changes, run, TestContext};
 
 #[test]
 fn no_repo() {
@@ -62,6 +78,52 @@ fn unstaged_changes() {
     snapshot!(ctx, "jj<tab>");
 }
 
+#[test]
+fn carriage_return_inside_line() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "progress.log", "10%\r20%\r\n");
+    fs::write(ctx.dir.child("progress.log"), "10%\r20%\r\n10%\r100%\r\n")
+        .expect("error writing to file");
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn binary_image_without_graphics() {
+    let ctx = TestContext::setup_init();
+    fs::write(ctx.dir.child("logo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")
+        .expect("error writing to file");
+    run(ctx.dir.path(), &["git", "add", "."]);
+    snapshot!(ctx, "jj<tab>");
+}
+
+/// A binary file marked with the `diff` attribute is diffed as text, like `git diff --text`.
+fn setup_forced_text() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), ".gitattributes", "*.dat diff\n");
+    commit(ctx.dir.path(), "data.dat", "head\0er\nold\0\n");
+    fs::write(ctx.dir.child("data.dat"), b"head\0er\nnew\0\x01\xff\n").unwrap();
+    ctx
+}
+
+#[test]
+fn forced_text_binary_file() {
+    snapshot!(setup_forced_text(), "jj<tab>");
+}
+
+#[test]
+fn stage_forced_text_binary_file_line() {
+    let mut ctx = setup_forced_text();
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j>s"))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        git_bytes(ctx.dir.path(), &["show", ":data.dat"]),
+        b"head\0er\nold\0\nnew\0\x01\xff\n"
+    );
+}
+
 #[test]
 fn binary_file() {
     let ctx = TestContext::setup_init();
@@ -84,6 +146,119 @@ fn collapsed_sections_config() {
     snapshot!(ctx, "");
 }
 
+#[test]
+fn section_order_config() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().status.section_order = vec![
+        "staged_changes".into(),
+        "untracked".into(),
+        "unstaged_changes".into(),
+    ];
+    ctx.config().general.collapsed_sections = vec!["untracked".into()];
+    commit(ctx.dir.path(), "tracked", "one\n");
+    fs::write(ctx.dir.child("tracked"), "two\n").unwrap();
+    fs::write(ctx.dir.child("staged"), "").unwrap();
+    git(ctx.dir.path(), &["add", "staged"]);
+    fs::write(ctx.dir.child("untracked"), "").unwrap();
+
+    snapshot!(ctx, "");
+}
+
+#[test]
+fn section_order_unknown_section() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().status.section_order = vec!["recent_commits".into(), "unstagged".into()];
+
+    snapshot!(ctx, "");
+}
+
+#[test]
+fn status_expand_config() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().status.expand_unstaged.enabled = true;
+    ctx.config().status.expand_untracked.enabled = false;
+    commit(ctx.dir.path(), "testfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("testfile"), "test\ntesttest\n").unwrap();
+    fs::write(ctx.dir.child("untracked_file.txt"), "").unwrap();
+
+    snapshot!(ctx, "");
+}
+
+#[test]
+fn expand_all() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "testfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("testfile"), "test\ntesttest\n").unwrap();
+    fs::write(ctx.dir.child("untracked_file.txt"), "").unwrap();
+
+    snapshot!(ctx, "+");
+}
+
+#[test]
+fn collapse_all() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "testfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("testfile"), "test\ntesttest\n").unwrap();
+    fs::write(ctx.dir.child("untracked_file.txt"), "").unwrap();
+
+    snapshot!(ctx, "+jjjj<backtab>");
+}
+
+#[test]
+fn compact_whole_file_diffs() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().general.compact_whole_file_diffs.enabled = true;
+    fs::write(ctx.dir.child("new_file"), "one\ntwo\nthree\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn compact_whole_file_diffs_expand() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().general.compact_whole_file_diffs.enabled = true;
+    fs::write(ctx.dir.child("new_file"), "one\ntwo\nthree\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    snapshot!(ctx, "jj<tab>j<tab>");
+}
+
+fn lfs_pointer(oid: char, size: u64) -> String {
+    format!(
+        "version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n",
+        oid.to_string().repeat(64),
+        size
+    )
+}
+
+#[test]
+fn lfs_pointer_changed() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "model.bin", &lfs_pointer('a', 123_000_000));
+    fs::write(ctx.dir.child("model.bin"), lfs_pointer('b', 130_400_000)).unwrap();
+    fs::write(ctx.dir.child("new.bin"), lfs_pointer('c', 2_048)).unwrap();
+    run(ctx.dir.path(), &["git", "add", "new.bin"]);
+    snapshot!(ctx, "jj<tab>jjj<tab>");
+}
+
+#[test]
+fn executable_bit_toggled() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "run.sh", "echo hi\n");
+    commit(ctx.dir.path(), "tool.sh", "echo hi\n");
+    run(ctx.dir.path(), &["chmod", "+x", "run.sh", "tool.sh"]);
+    run(ctx.dir.path(), &["git", "add", "tool.sh"]);
+    run(ctx.dir.path(), &["chmod", "-x", "tool.sh"]);
+    snapshot!(ctx, "");
+}
+
+#[test]
+fn stage_executable_bit() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "run.sh", "echo hi\n");
+    run(ctx.dir.path(), &["chmod", "+x", "run.sh"]);
+    snapshot!(ctx, "jjs");
+}
+
 #[test]
 fn log() {
     let ctx = TestContext::setup_clone();
@@ -101,6 +276,50 @@ fn show() {
     snapshot!(ctx, "ll<enter>");
 }
 
+#[test]
+fn show_commit_message() {
+    let ctx = TestContext::setup_clone();
+    fs::write(ctx.dir.child("file"), "content\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file"]);
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "commit",
+            "-m",
+            "Add a file with a subject that goes on for longer than it should",
+            "-m",
+            "This paragraph of the body is long enough that it has to be wrapped at the width of the screen.",
+            "-m",
+            "A second paragraph.",
+            "--trailer",
+            "Co-authored-by: Some One <someone@example.com>",
+            "--signoff",
+        ],
+    );
+    snapshot!(ctx, "ll<enter>");
+}
+
+#[test]
+fn show_commit_trailer_selected() {
+    let ctx = TestContext::setup_clone();
+    fs::write(ctx.dir.child("file"), "content\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file"]);
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "commit",
+            "-m",
+            "Add a file",
+            "--trailer",
+            "Reviewed-by: A Reviewer <reviewer@example.com>",
+            "--signoff",
+        ],
+    );
+    snapshot!(ctx, "ll<enter>kk<alt+w>");
+}
+
 #[test]
 fn rebase_conflict() {
     let mut ctx = TestContext::setup_clone();
@@ -136,6 +355,100 @@ fn merge_conflict() {
     insta::assert_snapshot!(ctx.redact_buffer());
 }
 
+#[test]
+fn merge_conflict_separate_git_dir() {
+    let mut ctx = TestContext::setup_clone();
+    let git_dir = temp_dir::TempDir::new().unwrap();
+    // Leaves a `.git` file in the working tree, pointing to the git dir
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "init",
+            "--separate-git-dir",
+            git_dir.path().to_str().unwrap(),
+        ],
+    );
+    commit(ctx.dir.path(), "new-file", "hello");
+
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "new-file", "hey");
+
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "new-file", "hi");
+
+    run(ctx.dir.path(), &["git", "merge", "other-branch"]);
+
+    ctx.init_state();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn rebase_conflict_separate_git_dir() {
+    let mut ctx = TestContext::setup_clone();
+    let git_dir = temp_dir::TempDir::new().unwrap();
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "init",
+            "--separate-git-dir",
+            git_dir.path().to_str().unwrap(),
+        ],
+    );
+    commit(ctx.dir.path(), "new-file", "hello");
+
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "new-file", "hey");
+
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "new-file", "hi");
+
+    run(ctx.dir.path(), &["git", "checkout", "other-branch"]);
+    run(ctx.dir.path(), &["git", "rebase", "main"]);
+
+    ctx.init_state();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn git_dir_and_work_tree_from_env() {
+    let mut ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "dotfile", "hello\n");
+    fs::write(ctx.dir.child("dotfile"), "hey\n").unwrap();
+
+    // Like dotfiles kept in a home dir, with no `.git` in the working tree
+    let git_dir = temp_dir::TempDir::new().unwrap();
+    let dotfiles = git_dir.path().join("dotfiles.git");
+    fs::rename(ctx.dir.child(".git"), &dotfiles).unwrap();
+
+    let mut cmd = crate::git::command();
+    cmd.env("GIT_DIR", &dotfiles)
+        .env("GIT_WORK_TREE", ctx.dir.path())
+        .current_dir(ctx.dir.path());
+    let repo = crate::git::open_repo(cmd).unwrap();
+
+    assert_eq!(
+        repo.workdir().unwrap().canonicalize().unwrap(),
+        ctx.dir.path().canonicalize().unwrap()
+    );
+    ctx.init_state_with_repo(repo);
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn no_repo_without_git_dir() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let mut cmd = crate::git::command();
+    cmd.env("GIT_CEILING_DIRECTORIES", dir.path())
+        .current_dir(dir.path());
+
+    let Err(err) = crate::git::open_repo(cmd) else {
+        panic!("Found a repo in an empty dir");
+    };
+    assert_eq!(err.to_string(), "No .git found in the current directory");
+}
+
 #[test]
 fn revert_conflict() {
     let mut ctx = TestContext::setup_clone();
@@ -270,6 +583,31 @@ mod checkout {
     }
 }
 
+#[test]
+fn status_line_failed_command() {
+    snapshot!(TestContext::setup_clone(), "bbnonexistent<enter>");
+}
+
+#[test]
+fn status_line_error_remains() {
+    snapshot!(TestContext::setup_clone(), "bbnonexistent<enter>jk");
+}
+
+#[test]
+fn status_line_error_dismissed() {
+    snapshot!(
+        TestContext::setup_clone(),
+        "bbnonexistent<enter><backspace>"
+    );
+}
+
+#[test]
+fn status_line_info_cleared() {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["git", "branch", "asd"]);
+    snapshot!(ctx, "YjKnj");
+}
+
 #[test]
 fn updated_externally() {
     let mut ctx = TestContext::setup_init();
@@ -372,3 +710,39 @@ fn tab_diff() {
 
     insta::assert_snapshot!(ctx.redact_buffer());
 }
+
+fn setup_submodule() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            // Relative to origin, so that the commit doesn't depend on where the test runs
+            "./",
+            "test-submodule",
+        ],
+    );
+    run(ctx.dir.path(), &["git", "commit", "-m", "add submodule"]);
+    ctx
+}
+
+#[test]
+fn submodule_state() {
+    let ctx = setup_submodule();
+    let sub = ctx.dir.child("test-submodule");
+    commit(&sub, "new-in-sub", "new\n");
+    fs::write(sub.join("initial-file"), "changed\n").unwrap();
+    fs::write(sub.join("untracked"), "x\n").unwrap();
+    snapshot!(ctx, "");
+}
+
+#[test]
+fn submodule_untracked_only() {
+    let ctx = setup_submodule();
+    fs::write(ctx.dir.child("test-submodule").join("untracked"), "x\n").unwrap();
+    snapshot!(ctx, "");
+}
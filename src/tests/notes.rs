@@ -0,0 +1,72 @@
+use super::*;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "file", "content\n");
+    run(
+        ctx.dir.path(),
+        &["git", "notes", "add", "-m", "Reviewed, looks good"],
+    );
+    ctx
+}
+
+#[test]
+fn show_notes() {
+    snapshot!(setup(), "ll<enter>");
+}
+
+#[test]
+fn show_notes_of_several_refs() {
+    let mut ctx = setup();
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "notes",
+            "--ref",
+            "review",
+            "add",
+            "-m",
+            "Needs tests",
+        ],
+    );
+    ctx.config().git.notes_refs = vec!["commits".into(), "review".into()];
+    snapshot!(ctx, "ll<enter>");
+}
+
+#[test]
+fn edit_notes() {
+    let mut ctx = setup();
+    run(
+        ctx.dir.path(),
+        &["git", "config", "core.editor", "echo 'Approved' >"],
+    );
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("llT")).unwrap();
+
+    assert_eq!(git(ctx.dir.path(), &["notes", "show"]), "Approved\n");
+
+    state.update(&mut ctx.term, &keys("<enter>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn edit_notes_prompt_for_ref() {
+    let mut ctx = setup();
+    ctx.config().git.notes_refs = vec!["commits".into(), "review".into()];
+    run(
+        ctx.dir.path(),
+        &["git", "config", "core.editor", "echo 'Needs tests' >"],
+    );
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("llT")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+
+    state
+        .update(&mut ctx.term, &keys("<ctrl+u>review<enter>"))
+        .unwrap();
+    assert_eq!(
+        git(ctx.dir.path(), &["notes", "--ref", "review", "show"]),
+        "Needs tests\n"
+    );
+}
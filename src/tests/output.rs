@@ -0,0 +1,66 @@
+use super::*;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "first.txt", "one\n");
+    commit(ctx.dir.path(), "second.txt", "two\n");
+    ctx
+}
+
+#[test]
+fn show_git_output() {
+    snapshot!(setup(), "!log --format=%C(red)%s%n%b<enter>");
+}
+
+#[test]
+fn show_git_output_quoted_args() {
+    snapshot!(setup(), "!log \"--format=subject: %s\"<enter>");
+}
+
+#[test]
+fn show_git_output_failed() {
+    snapshot!(setup(), "!no-such-command<enter>");
+}
+
+#[test]
+fn search_output() {
+    snapshot!(setup(), "!log --format=%s%n%b<enter>/add first<enter>");
+}
+
+#[test]
+fn search_next_wraps_around() {
+    snapshot!(setup(), "!log --format=%s%n%b<enter>/body<enter>nnn");
+}
+
+#[test]
+fn search_previous() {
+    snapshot!(setup(), "!log --format=%s%n%b<enter>/body<enter>N");
+}
+
+#[test]
+fn search_no_match() {
+    snapshot!(setup(), "/nothing like this<enter>");
+}
+
+#[test]
+fn search_wrapped_output() {
+    let ctx = setup();
+    let long = format!("--format=%s{}end", "-".repeat(100));
+    snapshot!(ctx, &format!("!log {}<enter>Dlq/end<enter>", long));
+}
+
+#[test]
+fn show_cmd_log() {
+    let mut ctx = setup();
+    fs::write(ctx.dir.child("new.txt"), "new\n").unwrap();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("jsjju$")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn show_cmd_log_empty() {
+    snapshot!(setup(), "$");
+}
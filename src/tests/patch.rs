@@ -0,0 +1,190 @@
+use super::*;
+
+fn numbers() -> String {
+    (1..=30).map(|n| format!("{}\n", n)).collect()
+}
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    let numbers = numbers();
+    commit(ctx.dir.path(), "numbers.txt", &numbers);
+    fs::write(
+        ctx.dir.child("numbers.txt"),
+        numbers
+            .replace("\n2\n", "\ntwo\n")
+            .replace("\n25\n", "\ntwenty-five\n"),
+    )
+    .unwrap();
+    ctx
+}
+
+#[test]
+fn patch_menu() {
+    snapshot!(setup(), "p");
+}
+
+#[test]
+fn save_unstaged_patch() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("jps<enter>")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("unstaged.patch")).unwrap(),
+        git(ctx.dir.path(), &["diff"])
+    );
+}
+
+#[test]
+fn save_hunk_patch() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state
+        .update(&mut ctx.term, &keys("jj<tab>jjps<enter>"))
+        .unwrap();
+
+    let patch = fs::read_to_string(ctx.dir.child("numbers.txt.patch")).unwrap();
+    assert!(patch.contains("+twenty-five\n"));
+    assert!(!patch.contains("+two\n"));
+}
+
+#[test]
+fn save_commit_patch() {
+    let mut ctx = setup();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("llps<enter>")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    let saved = fs::read_dir(ctx.dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .find(|name| name.ends_with(".patch"))
+        .unwrap();
+    let patch = fs::read_to_string(ctx.dir.child(&saved)).unwrap();
+    assert!(patch.starts_with("From "));
+    assert!(patch.contains("Subject: [PATCH] add numbers.txt\n"));
+}
+
+#[test]
+fn save_patch_overwrite_prompt() {
+    let ctx = setup();
+    fs::write(ctx.dir.child("unstaged.patch"), "old\n").unwrap();
+    snapshot!(ctx, "jjjps<enter>");
+}
+
+#[test]
+fn apply_patch() {
+    let mut ctx = setup();
+    let patch = git(ctx.dir.path(), &["diff"]);
+    run(ctx.dir.path(), &["git", "checkout", "numbers.txt"]);
+    fs::write(ctx.dir.child("numbers.patch"), patch).unwrap();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("pa<enter>")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(fs::read_to_string(ctx.dir.child("numbers.txt"))
+        .unwrap()
+        .contains("twenty-five"));
+}
+
+#[test]
+fn apply_patch_with_rejects() {
+    let mut ctx = setup();
+    let patch = git(ctx.dir.path(), &["diff"]);
+    run(ctx.dir.path(), &["git", "checkout", "numbers.txt"]);
+    commit(
+        ctx.dir.path(),
+        "numbers.txt",
+        &numbers().replace("\n25\n", "\n25 changed\n"),
+    );
+    fs::write(ctx.dir.child("numbers.patch"), patch).unwrap();
+    let mut state = ctx.init_state();
+
+    state.update(&mut ctx.term, &keys("pa<enter>")).unwrap();
+    insta::assert_snapshot!("apply_patch_with_rejects_prompt", ctx.redact_buffer());
+
+    state.update(&mut ctx.term, &keys("y")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(ctx.dir.child("numbers.txt.rej").exists());
+}
+
+#[test]
+fn apply_missing_patch_file() {
+    snapshot!(setup(), "panothing.patch<enter>");
+}
+
+/// Stops `git am` at a patch that conflicts with what's been committed since.
+fn setup_am_conflict() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "file.txt", "original\n");
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "file.txt", "theirs\n");
+    run(
+        ctx.dir.path(),
+        &["git", "format-patch", "-1", "-o", ".git/patches"],
+    );
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "file.txt", "ours\n");
+    run(
+        ctx.dir.path(),
+        &["git", "am", "-3", ".git/patches/0001-modify-file.txt.patch"],
+    );
+    ctx
+}
+
+#[test]
+fn am_conflict() {
+    snapshot!(setup_am_conflict(), "");
+}
+
+#[test]
+fn am_abort() {
+    let mut ctx = setup_am_conflict();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("pA")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(!ctx.dir.child(".git/rebase-apply").exists());
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("file.txt")).unwrap(),
+        "ours\n"
+    );
+}
+
+#[test]
+fn am_skip() {
+    let mut ctx = setup_am_conflict();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("pS")).unwrap();
+
+    assert!(!ctx.dir.child(".git/rebase-apply").exists());
+    assert_eq!(
+        git(ctx.dir.path(), &["log", "-1", "--format=%s"]),
+        "modify file.txt\n"
+    );
+}
+
+#[test]
+fn am_continue() {
+    let mut ctx = setup_am_conflict();
+    fs::write(ctx.dir.child("file.txt"), "both\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file.txt"]);
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("pc")).unwrap();
+
+    assert!(!ctx.dir.child(".git/rebase-apply").exists());
+    assert_eq!(
+        git(ctx.dir.path(), &["show", "--format=", "HEAD:file.txt"]),
+        "both\n"
+    );
+}
+
+#[test]
+fn am_not_in_progress() {
+    snapshot!(setup(), "pc");
+}
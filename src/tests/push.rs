@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::{Confirm, ConfirmWhen};
 
 #[test]
 fn push_menu_no_remote_or_upstream_set() {
@@ -75,6 +76,21 @@ fn push_setup_push_remote() {
 fn force_push() {
     let ctx = TestContext::setup_clone();
     commit(ctx.dir.path(), "new-file", "");
+    snapshot!(ctx, "P-fuy");
+}
+
+#[test]
+fn force_push_confirm_prompt() {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "new-file", "");
+    snapshot!(ctx, "P-fu");
+}
+
+#[test]
+fn force_push_without_confirming() {
+    let mut ctx = TestContext::setup_clone();
+    ctx.config().confirm.force_push = Confirm::When(ConfirmWhen::Never);
+    commit(ctx.dir.path(), "new-file", "");
     snapshot!(ctx, "P-fu");
 }
 
@@ -100,5 +116,33 @@ fn push_elsewhere_prompt() {
 
 #[test]
 fn push_elsewhere() {
-    snapshot!(TestContext::setup_clone(), "Peorigin<enter>");
+    snapshot!(TestContext::setup_clone(), "Peorigin<enter><enter>");
+}
+
+#[test]
+fn push_elsewhere_branch_prompt() {
+    snapshot!(TestContext::setup_clone(), "Pe<enter>");
+}
+
+#[test]
+fn push_elsewhere_new_branch() {
+    snapshot!(TestContext::setup_clone(), "P-ue<enter>feature<enter>");
+}
+
+#[test]
+fn push_elsewhere_unknown_remote() {
+    snapshot!(TestContext::setup_clone(), "Penope<enter>");
+}
+
+#[test]
+fn push_menu_command_preview() {
+    let ctx = TestContext::setup_clone();
+    snapshot!(ctx, "P-f-t");
+}
+
+#[test]
+fn push_tags() {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["git", "tag", "v1.0"]);
+    snapshot!(ctx, "P-tu");
 }
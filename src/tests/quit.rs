@@ -29,3 +29,57 @@ pub(crate) fn confirm_quit() {
     let state = snapshot!(ctx, "qy");
     assert!(state.quit);
 }
+
+fn setup_merge_conflict() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    commit(ctx.dir.path(), "new-file", "hello");
+
+    run(ctx.dir.path(), &["git", "checkout", "-b", "other-branch"]);
+    commit(ctx.dir.path(), "new-file", "hey");
+
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    commit(ctx.dir.path(), "new-file", "hi");
+
+    run(ctx.dir.path(), &["git", "merge", "other-branch"]);
+    ctx
+}
+
+#[test]
+pub(crate) fn quit_in_progress_prompt() {
+    let state = snapshot!(setup_merge_conflict(), "q");
+    assert!(!state.quit);
+}
+
+#[test]
+pub(crate) fn quit_in_progress_cancel() {
+    let state = snapshot!(setup_merge_conflict(), "qn");
+    assert!(!state.quit);
+}
+
+#[test]
+pub(crate) fn quit_in_progress_leave() {
+    let mut ctx = setup_merge_conflict();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("qq")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(state.quit);
+    assert!(state.repo.path().join("MERGE_HEAD").exists());
+}
+
+#[test]
+pub(crate) fn quit_in_progress_abort() {
+    let mut ctx = setup_merge_conflict();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("qa")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(state.quit);
+    assert!(!state.repo.path().join("MERGE_HEAD").exists());
+}
+
+#[test]
+pub(crate) fn force_quit() {
+    let state = snapshot!(setup_merge_conflict(), "Q");
+    assert!(state.quit);
+}
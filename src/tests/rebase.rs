@@ -23,3 +23,82 @@ fn rebase_elsewhere_prompt() {
 fn rebase_elsewhere() {
     snapshot!(setup(), "remain<enter>");
 }
+
+fn setup_squash() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    commit(ctx.dir.path(), "a.txt", "a\n");
+    commit(ctx.dir.path(), "b.txt", "b\n");
+    commit(ctx.dir.path(), "c.txt", "c\n");
+    ctx
+}
+
+#[test]
+fn rebase_squash_into_parent() {
+    let mut ctx = setup_squash();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("llj")).unwrap();
+    state.update(&mut ctx.term, &keys("rs")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert!(!ctx.dir.child(".git/GITU_SQUASH_TODO").exists());
+    assert_eq!(
+        git(ctx.dir.path(), &["log", "-2", "--format=%B"]),
+        "add c.txt\n\nCommit body goes here\n\n\
+        add a.txt\n\nCommit body goes here\n\nadd b.txt\n\nCommit body goes here\n\n"
+    );
+    assert_eq!(
+        git(
+            ctx.dir.path(),
+            &["show", "--format=", "--name-only", "HEAD~"]
+        ),
+        "a.txt\nb.txt\n"
+    );
+}
+
+#[test]
+fn rebase_squash_message_aborted() {
+    let mut ctx = setup_squash();
+    run(ctx.dir.path(), &["git", "config", "core.editor", "false"]);
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("lljrs")).unwrap();
+
+    assert!(!ctx.dir.child(".git/GITU_SQUASH_TODO").exists());
+    run(ctx.dir.path(), &["git", "rebase", "--abort"]);
+    assert_eq!(git(ctx.dir.path(), &["rev-list", "--count", "HEAD"]), "4\n");
+}
+
+#[test]
+fn rebase_squash_pushed() {
+    snapshot!(setup_squash(), "lljjrs");
+}
+
+#[test]
+fn rebase_squash_into_root() {
+    let mut ctx = TestContext::setup_init();
+    run(ctx.dir.path(), &["git", "config", "core.editor", "true"]);
+    commit(ctx.dir.path(), "a.txt", "a\n");
+    commit(ctx.dir.path(), "b.txt", "b\n");
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("llrs")).unwrap();
+
+    assert_eq!(git(ctx.dir.path(), &["rev-list", "--count", "HEAD"]), "1\n");
+    assert_eq!(
+        git(
+            ctx.dir.path(),
+            &["show", "--format=", "--name-only", "HEAD"]
+        ),
+        "a.txt\nb.txt\n"
+    );
+}
+
+#[test]
+fn rebase_squash_merge() {
+    let ctx = setup_squash();
+    run(ctx.dir.path(), &["git", "checkout", "-b", "side", "HEAD~2"]);
+    commit(ctx.dir.path(), "side.txt", "side\n");
+    run(ctx.dir.path(), &["git", "checkout", "main"]);
+    run(ctx.dir.path(), &["git", "merge", "--no-edit", "side"]);
+
+    snapshot!(ctx, "llrs");
+}
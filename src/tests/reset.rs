@@ -28,5 +28,17 @@ pub(crate) fn reset_mixed() {
 
 #[test]
 fn reset_hard() {
-    snapshot!(setup(), "lljXh<enter>q");
+    snapshot!(setup(), "lljXh<enter>yq");
+}
+
+#[test]
+fn reset_hard_discarding_changes_prompt() {
+    let ctx = setup();
+    fs::write(ctx.dir.child("unwanted-file"), "changed\n").unwrap();
+    snapshot!(ctx, "lljXh<enter>");
+}
+
+#[test]
+fn reset_past_initial_commit() {
+    snapshot!(setup(), "XsHEAD~2<enter>");
 }
@@ -0,0 +1,36 @@
+use super::*;
+
+/// A sparse checkout of only `inside/`, with a change to `outside/file` staged regardless.
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    for dir in ["inside", "outside"] {
+        fs::create_dir(ctx.dir.child(dir)).unwrap();
+        commit(ctx.dir.path(), &format!("{dir}/file"), &format!("{dir}\n"));
+    }
+    run(ctx.dir.path(), &["git", "sparse-checkout", "set", "inside"]);
+
+    fs::write(ctx.dir.child(".git/new-content"), "outside changed\n").unwrap();
+    let blob = git(ctx.dir.path(), &["hash-object", "-w", ".git/new-content"]);
+    let cacheinfo = format!("100644,{},outside/file", blob.trim());
+    run(
+        ctx.dir.path(),
+        &["git", "update-index", "--cacheinfo", &cacheinfo],
+    );
+    run(
+        ctx.dir.path(),
+        &["git", "update-index", "--skip-worktree", "outside/file"],
+    );
+    ctx
+}
+
+#[test]
+fn staged_outside_sparse_checkout() {
+    let ctx = setup();
+    assert!(!ctx.dir.child("outside/file").exists());
+    snapshot!(ctx, "jj<tab>");
+}
+
+#[test]
+fn no_discard_outside_sparse_checkout() {
+    snapshot!(setup(), "jjK");
+}
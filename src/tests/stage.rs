@@ -46,6 +46,76 @@ fn stage_added_line() {
     snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j>s");
 }
 
+fn setup_line_selection() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file", "a\nb\nc\n");
+    fs::write(ctx.dir.child("file"), "A\nB\nC\n").unwrap();
+    ctx
+}
+
+#[test]
+fn select_lines() {
+    snapshot!(
+        setup_line_selection(),
+        "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+v>jjj"
+    );
+}
+
+#[test]
+fn select_lines_within_hunk() {
+    snapshot!(
+        setup_line_selection(),
+        "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+v>jjkkkkkkk"
+    );
+}
+
+#[test]
+fn stage_selected_lines() {
+    let mut ctx = setup_line_selection();
+    let mut state = ctx.init_state();
+
+    state
+        .update(
+            &mut ctx.term,
+            &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+v>jjjs"),
+        )
+        .unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(index_content(&ctx, "file"), b"a\nA\nB\n");
+}
+
+#[test]
+fn stage_after_selection_cancelled() {
+    let mut ctx = setup_line_selection();
+    let mut state = ctx.init_state();
+
+    state
+        .update(
+            &mut ctx.term,
+            &keys("jj<tab><ctrl+j><ctrl+j><ctrl+v>jj<esc>s"),
+        )
+        .unwrap();
+
+    assert_eq!(index_content(&ctx, "file"), b"a\nb\n");
+}
+
+#[test]
+fn stage_no_newline_marker() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing");
+    fs::write(ctx.dir.child("firstfile"), "weehooo").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><ctrl+j>s");
+}
+
+#[test]
+fn stage_removed_line_no_newline() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing");
+    fs::write(ctx.dir.child("firstfile"), "weehooo").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j>s");
+}
+
 #[test]
 fn stage_changes_crlf() {
     let ctx = TestContext::setup_init();
@@ -54,3 +124,249 @@ fn stage_changes_crlf() {
 
     snapshot!(ctx, "jj<tab>");
 }
+
+fn index_content(ctx: &TestContext, file: &str) -> Vec<u8> {
+    git_bytes(ctx.dir.path(), &["show", &format!(":{}", file)])
+}
+
+#[test]
+fn stage_hunk_crlf() {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "testfile", "testing\r\ntesttest\r\n");
+    fs::write(ctx.dir.child("testfile"), "test\r\ntesttest\r\n").unwrap();
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jj<tab>js")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(index_content(&ctx, "testfile"), b"test\r\ntesttest\r\n");
+}
+
+#[test]
+fn stage_hunk_autocrlf() {
+    let mut ctx = TestContext::setup_init();
+    run(ctx.dir.path(), &["git", "config", "core.autocrlf", "true"]);
+    commit(ctx.dir.path(), "testfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("testfile"), "test\r\ntesttest\r\n").unwrap();
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jj<tab>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    state.update(&mut ctx.term, &keys("js")).unwrap();
+    assert_eq!(index_content(&ctx, "testfile"), b"test\ntesttest\n");
+}
+
+#[test]
+fn stage_hunk_with_noprefix_config() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    run(ctx.dir.path(), &["git", "config", "diff.noprefix", "true"]);
+    fs::write(ctx.dir.child("firstfile"), "weehooo\ntesttest\n").unwrap();
+    snapshot!(ctx, "jj<tab>js");
+}
+
+#[test]
+fn stage_latin1_hunk() {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "latin1", "cafe\n");
+    fs::write(ctx.dir.child("latin1"), b"caf\xe9\n").unwrap();
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jj<tab>j")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    state.update(&mut ctx.term, &keys("s")).unwrap();
+
+    assert_eq!(index_content(&ctx, "latin1"), b"caf\xe9\n");
+}
+
+#[test]
+fn stage_added_line_no_newline() {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "old line");
+    fs::write(ctx.dir.child("firstfile"), "new line").unwrap();
+
+    let mut state = ctx.init_state();
+    state
+        .update(
+            &mut ctx.term,
+            &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j>s"),
+        )
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(index_content(&ctx, "firstfile"), b"old line\nnew line");
+}
+
+#[test]
+fn preview_hunk_patch() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab>j=");
+}
+
+#[test]
+fn preview_line_patch() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "weehooo\nblrergh\n").unwrap();
+    snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j>=");
+}
+
+#[test]
+fn show_index_version() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "staged\ntesttest\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "firstfile"]);
+    fs::write(ctx.dir.child("firstfile"), "unstaged\ntesttest\n").unwrap();
+    snapshot!(ctx, "jjI");
+}
+
+#[test]
+fn edit_and_stage_file() {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "testing\ntesttest\n");
+    fs::write(ctx.dir.child("firstfile"), "blahonga\ntesttest\n").unwrap();
+
+    run(
+        ctx.dir.path(),
+        &[
+            "git",
+            "config",
+            "core.editor",
+            "sed -i s/^+blahonga/+edited/",
+        ],
+    );
+    snapshot!(ctx, "jjE");
+}
+
+fn setup_stale_hunk() -> (TestContext, crate::state::State) {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file", "1\n2\n3\n4\n5\n6\n7\n");
+    fs::write(ctx.dir.child("file"), "1\n2\n3\nfour\n5\n6\n7\n").unwrap();
+    let state = ctx.init_state();
+
+    // Staged behind Gitu's back, so the displayed hunk no longer applies to the index
+    fs::write(ctx.dir.child("file"), "1\n2\n3\nFOUR\n5\n6\n7\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file"]);
+    fs::write(ctx.dir.child("file"), "1\n2\n3\nfour\n5\n6\n7\n").unwrap();
+
+    (ctx, state)
+}
+
+#[test]
+fn stage_stale_hunk() {
+    let (mut ctx, mut state) = setup_stale_hunk();
+    state.update(&mut ctx.term, &keys("jj<tab>js")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn stage_stale_hunk_3way() {
+    let (mut ctx, mut state) = setup_stale_hunk();
+    state.update(&mut ctx.term, &keys("jj<tab>jsy")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn stage_stale_hunk_3way_fallback() {
+    let mut ctx = TestContext::setup_init();
+    ctx.config().general.stage_3way_fallback.enabled = true;
+    commit(ctx.dir.path(), "file", "1\n2\n3\n4\n5\n6\n7\n8\n");
+    fs::write(ctx.dir.child("file"), "1\n2\n3\n4\nfive\n6\n7\n8\n").unwrap();
+    let mut state = ctx.init_state();
+
+    // Changes the hunk's context in the index, which a 3-way merge gets past
+    fs::write(ctx.dir.child("file"), "1\nTWO\n3\n4\n5\n6\n7\n8\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file"]);
+    fs::write(ctx.dir.child("file"), "1\nTWO\n3\n4\nfive\n6\n7\n8\n").unwrap();
+
+    state.update(&mut ctx.term, &keys("jj<tab>js")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+}
+
+#[test]
+fn stage_and_unstage_files() {
+    let mut ctx = TestContext::setup_clone();
+    make_changes(ctx.dir.path());
+
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jjjjsu")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+
+    assert_eq!(
+        git(ctx.dir.path(), &["status", "--short"]),
+        " M staged-file\nM  unstaged-file\n?? untracked-file\n"
+    );
+}
+
+#[test]
+fn stage_modified() {
+    let ctx = TestContext::setup_clone();
+    make_changes(ctx.dir.path());
+    snapshot!(ctx, "S");
+}
+
+#[test]
+fn stage_all() {
+    let ctx = TestContext::setup_clone();
+    make_changes(ctx.dir.path());
+    snapshot!(ctx, "A");
+}
+
+fn setup_hunks_in_two_files() -> TestContext {
+    let mut ctx = TestContext::setup_init();
+    ctx.config().status.expand_unstaged.enabled = true;
+    let lines = |changed: &[usize]| {
+        (1..=16)
+            .map(|i| match changed.contains(&i) {
+                true => format!("changed {}\n", i),
+                false => format!("{}\n", i),
+            })
+            .collect::<String>()
+    };
+    commit(ctx.dir.path(), "file-a", &lines(&[]));
+    commit(ctx.dir.path(), "file-b", &lines(&[]));
+
+    fs::write(ctx.dir.child("file-a"), lines(&[2, 14])).unwrap();
+    fs::write(ctx.dir.child("file-b"), lines(&[8])).unwrap();
+    ctx
+}
+
+#[test]
+fn advance_after_stage() {
+    let mut ctx = setup_hunks_in_two_files();
+    ctx.config().status.advance_after_stage.enabled = true;
+    // Stages the last hunk of file-a
+    snapshot!(ctx, "js");
+}
+
+#[test]
+fn advance_after_stage_disabled() {
+    let ctx = setup_hunks_in_two_files();
+    snapshot!(ctx, "js");
+}
+
+#[test]
+fn advance_after_stage_file() {
+    let mut ctx = setup_hunks_in_two_files();
+    ctx.config().status.advance_after_stage.enabled = true;
+    snapshot!(ctx, "ks");
+}
+
+#[test]
+fn stage_hunk_from_subdirectory_with_relative_diff() {
+    let mut ctx = TestContext::setup_init();
+    fs::create_dir(ctx.dir.child("dir")).unwrap();
+    commit(ctx.dir.path(), "dir/file", "one\n");
+    fs::write(ctx.dir.child("dir").join("file"), "one\ntwo\n").unwrap();
+    git(ctx.dir.path(), &["config", "diff.relative", "true"]);
+
+    // Opened like Gitu is when run from there
+    let mut cmd = crate::git::command();
+    cmd.current_dir(ctx.dir.child("dir"));
+    let mut state = ctx.init_state_with_repo(crate::git::open_repo(cmd).unwrap());
+
+    state.update(&mut ctx.term, &keys("jj<tab>js")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(git(ctx.dir.path(), &["show", ":dir/file"]), "one\ntwo\n");
+}
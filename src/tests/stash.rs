@@ -130,3 +130,95 @@ pub(crate) fn stash_drop() {
 pub(crate) fn stash_drop_default() {
     snapshot!(setup_two_stashes(), "zk<enter>");
 }
+
+#[test]
+pub(crate) fn show_stash_with_untracked() {
+    let ctx = setup();
+    git(
+        ctx.dir.path(),
+        &["stash", "--include-untracked", "-m", "both"],
+    );
+    snapshot!(ctx, "jj<enter>");
+}
+
+#[test]
+pub(crate) fn show_stash_without_untracked() {
+    let ctx = setup();
+    git(ctx.dir.path(), &["stash", "-m", "tracked"]);
+    snapshot!(ctx, "jjjj<enter>");
+}
+
+fn setup_stash_of_both() -> TestContext {
+    let ctx = setup();
+    git(
+        ctx.dir.path(),
+        &["stash", "--include-untracked", "-m", "both"],
+    );
+    ctx
+}
+
+#[test]
+pub(crate) fn stash_restore_file_from_view() {
+    let mut ctx = setup_stash_of_both();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jj<enter>jzf")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("file-two")).unwrap(),
+        "blahonga\n"
+    );
+    assert!(!ctx.dir.child("file-one").exists());
+}
+
+#[test]
+pub(crate) fn stash_restore_file_over_changes() {
+    let ctx = setup_stash_of_both();
+    fs::write(ctx.dir.child("file-one"), "changed\n").unwrap();
+    snapshot!(ctx, "jjjj<enter>kzf");
+}
+
+#[test]
+pub(crate) fn stash_restore_file_prompt() {
+    snapshot!(setup_stash_of_both(), "jjzf");
+}
+
+#[test]
+pub(crate) fn stash_restore_file() {
+    let mut ctx = setup_stash_of_both();
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("jjzffile-one<enter>"))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("file-one")).unwrap(),
+        "blahonga\n"
+    );
+}
+
+#[test]
+pub(crate) fn show_stash_from_subdirectory_with_relative_diff() {
+    let mut ctx = setup();
+    fs::create_dir(ctx.dir.child("dir")).unwrap();
+    commit(ctx.dir.path(), "dir/file-three", "");
+    fs::write(ctx.dir.child("dir").join("file-three"), "blahonga\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "."]);
+    git(ctx.dir.path(), &["stash", "-m", "relative"]);
+    git(ctx.dir.path(), &["config", "diff.relative", "true"]);
+
+    // Opened like Gitu is when run from there
+    let mut cmd = crate::git::command();
+    cmd.current_dir(ctx.dir.child("dir"));
+    let mut state = ctx.init_state_with_repo(crate::git::open_repo(cmd).unwrap());
+
+    state.update(&mut ctx.term, &keys("jj<enter>")).unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+
+    // The file is restored where it is in the work tree, not relative to `dir`
+    state.update(&mut ctx.term, &keys("kzf")).unwrap();
+    assert_eq!(
+        fs::read_to_string(ctx.dir.child("dir").join("file-three")).unwrap(),
+        "blahonga\n"
+    );
+    assert!(!ctx.dir.child("dir").join("dir").exists());
+}
@@ -25,3 +25,154 @@ fn unstage_added_line() {
     run(ctx.dir.path(), &["git", "add", "."]);
     snapshot!(ctx, "jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j>u");
 }
+
+fn setup_staged_hunk() -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "one\ntwo\nthree\n");
+    fs::write(ctx.dir.child("firstfile"), "ONE\ntwo\nTHREE\n").unwrap();
+    git(ctx.dir.path(), &["add", "."]);
+    ctx
+}
+
+#[test]
+fn unstage_added_line_of_hunk() {
+    let mut ctx = setup_staged_hunk();
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j>u"))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(git(ctx.dir.path(), &["show", ":firstfile"]), "two\nTHREE\n");
+}
+
+#[test]
+fn unstage_removed_line_of_hunk() {
+    let mut ctx = setup_staged_hunk();
+    let mut state = ctx.init_state();
+    state
+        .update(
+            &mut ctx.term,
+            &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j>u"),
+        )
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        git(ctx.dir.path(), &["show", ":firstfile"]),
+        "ONE\ntwo\nthree\nTHREE\n"
+    );
+}
+
+#[test]
+fn unstage_removed_line_no_newline() {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "old line");
+    fs::write(ctx.dir.child("firstfile"), "new line").unwrap();
+    git(ctx.dir.path(), &["add", "."]);
+
+    let mut state = ctx.init_state();
+    state
+        .update(&mut ctx.term, &keys("jj<tab><ctrl+j><ctrl+j>u"))
+        .unwrap();
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        git(ctx.dir.path(), &["show", ":firstfile"]),
+        "old line\nnew line"
+    );
+}
+
+#[test]
+fn unstage_selected_lines() {
+    let mut ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "file", "a\nb\nc\n");
+    fs::write(ctx.dir.child("file"), "A\nB\nC\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "file"]);
+    let mut state = ctx.init_state();
+
+    state
+        .update(
+            &mut ctx.term,
+            &keys("jj<tab><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+j><ctrl+v>ju"),
+        )
+        .unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(git(ctx.dir.path(), &["show", ":file"]), "C\n");
+}
+
+/// A file moved with `git mv` and edited, all staged.
+fn setup_rename() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    let lines = (1..=20).map(|i| format!("{i}\n")).collect::<String>();
+    commit(ctx.dir.path(), "old-name", &lines);
+    run(ctx.dir.path(), &["git", "mv", "old-name", "new-name"]);
+    fs::write(
+        ctx.dir.child("new-name"),
+        lines.replace("\n5\n", "\nfive\n"),
+    )
+    .unwrap();
+    run(ctx.dir.path(), &["git", "add", "new-name"]);
+    ctx
+}
+
+#[test]
+fn unstage_renamed_file() {
+    let mut ctx = setup_rename();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jju")).unwrap();
+
+    insta::assert_snapshot!(ctx.redact_buffer());
+    assert_eq!(
+        git(ctx.dir.path(), &["diff", "--cached", "--name-status"]),
+        ""
+    );
+    assert_eq!(
+        git(ctx.dir.path(), &["ls-files", "old-name", "new-name"]),
+        "old-name\n"
+    );
+}
+
+#[test]
+fn renamed_file_patch_applies() {
+    let mut ctx = setup_rename();
+    let mut state = ctx.init_state();
+    state.update(&mut ctx.term, &keys("jjps<enter>")).unwrap();
+
+    let patch = fs::read_to_string(ctx.dir.child("new-name.patch")).unwrap();
+    assert!(patch.contains("rename from old-name\nrename to new-name\n"));
+
+    run(ctx.dir.path(), &["git", "reset"]);
+    run(
+        ctx.dir.path(),
+        &["git", "apply", "--cached", "new-name.patch"],
+    );
+    assert_eq!(
+        git(ctx.dir.path(), &["diff", "--cached", "-M", "--name-status"]),
+        "R090\told-name\tnew-name\n"
+    );
+    assert_eq!(
+        git(ctx.dir.path(), &["show", ":new-name"]),
+        fs::read_to_string(ctx.dir.child("new-name")).unwrap()
+    );
+}
+
+fn setup_edit_and_unstage(editor: &str) -> TestContext {
+    let ctx = TestContext::setup_init();
+    commit(ctx.dir.path(), "firstfile", "a\nb\n");
+    fs::write(ctx.dir.child("firstfile"), "A\nB\n").unwrap();
+    run(ctx.dir.path(), &["git", "add", "firstfile"]);
+    run(ctx.dir.path(), &["git", "config", "core.editor", editor]);
+    ctx
+}
+
+#[test]
+fn edit_and_unstage_file() {
+    // Keeps the change to the second line staged, its new version becomes the context
+    let ctx = setup_edit_and_unstage("sed -i -e '/^-b$/d' -e 's/^+B$/ B/'");
+    snapshot!(ctx, "jjU");
+}
+
+#[test]
+fn edit_and_unstage_hunk_fails() {
+    let ctx = setup_edit_and_unstage("sed -i 's/^+B$/ X/'");
+    snapshot!(ctx, "jj<tab>jU");
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+fn setup() -> TestContext {
+    let ctx = TestContext::setup_clone();
+    run(
+        ctx.dir.path(),
+        &["git", "worktree", "add", "linked", "-b", "feature"],
+    );
+    ctx
+}
+
+#[test]
+fn worktree_menu() {
+    snapshot!(setup(), "%");
+}
+
+#[test]
+fn show_worktrees() {
+    snapshot!(setup(), "%%");
+}
+
+#[test]
+fn worktree_add() {
+    snapshot!(setup(), "%%%cother<enter><enter>");
+}
+
+#[test]
+fn worktree_remove_prompt() {
+    snapshot!(setup(), "%%jj%k");
+}
+
+#[test]
+fn worktree_remove() {
+    snapshot!(setup(), "%%jj%kyg");
+}
+
+#[test]
+fn open_worktree() {
+    snapshot!(setup(), "%%jj<enter>");
+}
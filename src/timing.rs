@@ -0,0 +1,86 @@
+use crate::git::diff::Diff;
+#[cfg(any(debug_assertions, feature = "debug-timing"))]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+#[cfg(any(debug_assertions, feature = "debug-timing"))]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns on logging of how long diffing and rendering take, see `cli::Args::debug_timing`.
+/// Release builds only measure anything with the `debug-timing` feature.
+pub(crate) fn enable() {
+    #[cfg(any(debug_assertions, feature = "debug-timing"))]
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs `f`, logging how long it took along with what `describe` says about its result.
+#[cfg(any(debug_assertions, feature = "debug-timing"))]
+pub(crate) fn measure<T>(
+    label: &str,
+    f: impl FnOnce() -> T,
+    describe: impl FnOnce(&T) -> String,
+) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    log::info!("{label} took {elapsed:?} ({})", describe(&result));
+    result
+}
+
+#[cfg(not(any(debug_assertions, feature = "debug-timing")))]
+pub(crate) fn measure<T>(
+    _label: &str,
+    f: impl FnOnce() -> T,
+    _describe: impl FnOnce(&T) -> String,
+) -> T {
+    f()
+}
+
+/// The size of a diff, like `2 deltas, 3 hunks, 40 lines`.
+pub(crate) fn diff_counts<E>(diff: &Result<Diff, E>) -> String {
+    let Ok(diff) = diff else {
+        return "failed".into();
+    };
+
+    let hunks = diff.deltas.iter().flat_map(|delta| &delta.hunks);
+    format!(
+        "{} deltas, {} hunks, {} lines",
+        diff.deltas.len(),
+        hunks.clone().count(),
+        hunks.map(|hunk| hunk.content.lines.len()).sum::<usize>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_counts;
+    use crate::git::diff;
+
+    #[test]
+    fn counts_of_diff() {
+        let patch = "\
+diff --git a/a b/a
+--- a/a
++++ b/a
+@@ -1 +1 @@
+-old
++new
+diff --git a/b b/b
+--- a/b
++++ b/b
+@@ -1,0 +2 @@
++added
+";
+        assert_eq!(
+            diff_counts(&diff::parse(patch)),
+            "2 deltas, 2 hunks, 3 lines"
+        );
+        assert_eq!(diff_counts(&Err::<diff::Diff, ()>(())), "failed");
+    }
+}
@@ -39,6 +39,21 @@ pub(crate) fn ui(frame: &mut Frame, state: &mut State) {
         None
     };
 
+    let status = match &state.pending_cmd {
+        Some(pending) => Some(pending.status_line()),
+        None => state.status_line.line(&state.config.style).or_else(|| {
+            // Like Vim, keeps showing that keys are being recorded
+            state.macros.recording().map(|register| {
+                Line::styled(format!("Recording macro {}", register), Style::new().dim())
+            })
+        }),
+    };
+
+    let maybe_status = status.map(|line| SizedWidget {
+        height: 1,
+        widget: Paragraph::new(line),
+    });
+
     let maybe_prompt = state.prompt.data.as_ref().map(|prompt_data| SizedWidget {
         height: 2,
         widget: TextPrompt::new(prompt_data.prompt_text.clone()).with_block(popup_block()),
@@ -65,6 +80,7 @@ pub(crate) fn ui(frame: &mut Frame, state: &mut State) {
             widget_height(&maybe_prompt),
             widget_height(&maybe_menu),
             widget_height(&maybe_log),
+            widget_height(&maybe_status),
         ],
     )
     .split(frame.area());
@@ -73,6 +89,7 @@ pub(crate) fn ui(frame: &mut Frame, state: &mut State) {
 
     maybe_render(maybe_menu, frame, layout[2]);
     maybe_render(maybe_log, frame, layout[3]);
+    maybe_render(maybe_status, frame, layout[4]);
 
     if let Some(prompt) = maybe_prompt {
         frame.render_stateful_widget(prompt, layout[1], &mut state.prompt.state);
@@ -77,6 +77,9 @@ impl<'a> MenuWidget<'a> {
             let target_binds = bindings
                 .list(&pending.menu)
                 .filter(|keybind| keybind.op.clone().implementation().is_target_op())
+                .filter(|keybind| {
+                    !(state.screen().is_read_only() && keybind.op.modifies_worktree())
+                })
                 .filter(|keybind| {
                     keybind
                         .op
@@ -130,6 +133,10 @@ impl<'a> MenuWidget<'a> {
             ]));
         }
 
+        if let Some(command) = pending.command_preview() {
+            right_column.push(Line::styled(format!("$ {}", command), &style.command));
+        }
+
         let widths = [
             col_width(&pending_binds_column),
             col_width(&menu_binds_column),